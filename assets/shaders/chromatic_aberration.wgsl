@@ -0,0 +1,27 @@
+// Splits the red and blue channels of the world camera's picture apart, further towards
+// the edges of the screen, for a moment after the player is hit.
+// See `src/game/post_processing.rs`.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct ChromaticAberration {
+    intensity: f32,
+    // Uniforms have to be 16 byte aligned on WebGL2.
+    _padding: vec3<f32>,
+}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> settings: ChromaticAberration;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let offset = (in.uv - 0.5) * 2.0 * settings.intensity;
+    let center = textureSample(screen_texture, texture_sampler, in.uv);
+    return vec4<f32>(
+        textureSample(screen_texture, texture_sampler, in.uv + offset).r,
+        center.g,
+        textureSample(screen_texture, texture_sampler, in.uv - offset).b,
+        center.a,
+    );
+}
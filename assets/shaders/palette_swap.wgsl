@@ -0,0 +1,46 @@
+// Draws a sprite frame from an index texture, with colors from a row of a palette lookup table,
+// and the sprite effects on top. See `src/game/palette.rs`.
+// `palette_swap_ui.wgsl` is the same for UI nodes.
+
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct PaletteParams {
+    rect: vec4<f32>,
+    frame: vec4<f32>,
+    tint: vec4<f32>,
+    flash: vec4<f32>,
+    outline: vec4<f32>,
+    row: u32,
+}
+
+@group(2) @binding(0) var<uniform> params: PaletteParams;
+@group(2) @binding(1) var index_texture: texture_2d<f32>;
+@group(2) @binding(2) var palette_texture: texture_2d<f32>;
+
+// Color of a pixel of the sheet in the palette, transparent outside the frame.
+fn pixel_color(pixel: vec2<f32>) -> vec4<f32> {
+    if any(pixel < params.frame.xy) || any(pixel >= params.frame.zw) {
+        return vec4(0.0);
+    }
+    let index = u32(round(textureLoad(index_texture, vec2<i32>(pixel), 0).r * 255.0));
+    return textureLoad(palette_texture, vec2<u32>(index, params.row), 0);
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let pixel = floor(params.rect.xy + mesh.uv * params.rect.zw);
+    let color = pixel_color(pixel) * params.tint;
+    if color.a == 0.0 {
+        if params.outline.a > 0.0 {
+            let neighbors = pixel_color(pixel + vec2(1.0, 0.0)).a
+                + pixel_color(pixel - vec2(1.0, 0.0)).a
+                + pixel_color(pixel + vec2(0.0, 1.0)).a
+                + pixel_color(pixel - vec2(0.0, 1.0)).a;
+            if neighbors > 0.0 {
+                return params.outline;
+            }
+        }
+        discard;
+    }
+    return vec4(mix(color.rgb, params.flash.rgb, params.flash.a), color.a);
+}
@@ -0,0 +1,72 @@
+// Particles simulated entirely on the GPU. See `src/game/particles.rs`.
+// The mesh has one quad per particle, whose vertices hold the quad's corner in x and y and
+// the particle's seed in z. Each particle loops through its lifetime, and starts again
+// from a new random spot every time, so the CPU only needs to update the time and gravity.
+
+#import bevy_sprite::mesh2d_functions::{get_world_from_local, mesh2d_position_local_to_clip}
+
+struct ParticleParams {
+    area: vec2<f32>,
+    velocity: vec2<f32>,
+    velocity_spread: vec2<f32>,
+    gravity: vec2<f32>,
+    size_start: vec2<f32>,
+    size_end: vec2<f32>,
+    color_start: vec4<f32>,
+    color_end: vec4<f32>,
+    lifetime: f32,
+    // Stops while the game is paused, unlike `globals.time`.
+    time: f32,
+}
+
+@group(2) @binding(0) var<uniform> params: ParticleParams;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+// Two pseudo-random numbers from 0 to 1.
+fn hash2(p: vec2<f32>) -> vec2<f32> {
+    let q = vec2(dot(p, vec2(127.1, 311.7)), dot(p, vec2(269.5, 183.3)));
+    return fract(sin(q) * 43758.5453);
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let seed = vertex.position.z;
+    let t = params.time / params.lifetime + seed;
+    let cycle = floor(t);
+    let progress = fract(t);
+    let age = progress * params.lifetime;
+
+    let spawn = (hash2(vec2(seed * 1000.0, cycle)) - 0.5) * params.area;
+    let velocity = params.velocity
+        + (hash2(vec2(cycle, seed * 1000.0)) - 0.5) * 2.0 * params.velocity_spread;
+    let center = spawn + velocity * age + 0.5 * params.gravity * age * age;
+    let size = mix(params.size_start, params.size_end, progress);
+
+    var out: VertexOutput;
+    let world_from_local = get_world_from_local(vertex.instance_index);
+    out.clip_position = mesh2d_position_local_to_clip(
+        world_from_local,
+        vec4(center + vertex.position.xy * size, 0.0, 1.0),
+    );
+    out.uv = vertex.uv;
+    out.color = mix(params.color_start, params.color_end, progress);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Soft round edges, which also work for streaks like rain.
+    let edge = 1.0 - smoothstep(0.3, 0.5, length(in.uv - 0.5));
+    return vec4(in.color.rgb, in.color.a * edge);
+}
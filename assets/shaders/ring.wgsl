@@ -0,0 +1,30 @@
+// A ring filled clockwise from the top, for UI nodes. See `src/ui/ring.rs`.
+
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+struct RingParams {
+    color: vec4<f32>,
+    background: vec4<f32>,
+    fraction: f32,
+    thickness: f32,
+}
+
+@group(1) @binding(0) var<uniform> params: RingParams;
+
+const TAU: f32 = 6.28318530718;
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    // From -1 to 1 across the node, with y pointing down.
+    let p = in.uv * 2.0 - 1.0;
+    let radius = length(p);
+    // Smooth edges about a pixel wide.
+    let aa = fwidth(radius);
+    let inner = 1.0 - params.thickness;
+    let coverage = (1.0 - smoothstep(1.0 - aa, 1.0, radius)) * smoothstep(inner - aa, inner, radius);
+
+    // 0 at the top, going clockwise up to 1.
+    let angle = fract(atan2(p.x, -p.y) / TAU + 1.0);
+    let color = select(params.background, params.color, angle < params.fraction);
+    return vec4(color.rgb, color.a * coverage);
+}
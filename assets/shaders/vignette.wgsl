@@ -0,0 +1,22 @@
+// A vignette that darkens and tints the edges of a full-screen UI node, for rewinding.
+// See `src/game/rewind.rs`.
+
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+struct VignetteParams {
+    color: vec4<f32>,
+    strength: f32,
+}
+
+@group(1) @binding(0) var<uniform> params: VignetteParams;
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    // From -1 to 1 across the node, with corners past 1.
+    let p = in.uv * 2.0 - 1.0;
+    let distance = length(p);
+    // Clear in the middle, closing in from the edges as the strength goes up.
+    let inner = mix(1.4, 0.5, params.strength);
+    let coverage = smoothstep(inner, inner + 0.6, distance) * params.strength;
+    return vec4(params.color.rgb, params.color.a * coverage);
+}
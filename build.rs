@@ -0,0 +1,98 @@
+//! Embeds build information shown on the about screen, and the manifest of asset hashes
+//! that `game::integrity` checks the shipped assets against.
+
+use std::{
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+fn main() {
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map_or("unknown".to_string(), |hash| hash.trim().to_string());
+    println!("cargo:rustc-env=BUILD_GIT_HASH={git_hash}");
+    println!("cargo:rustc-env=BUILD_DATE={}", build_date());
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    write_asset_manifest();
+}
+
+/// Writes `asset_manifest.txt` to the output directory, with a `<hash> <path>` line for
+/// every file in `assets`. Paths are relative to `assets`, with `/` as the separator.
+fn write_asset_manifest() {
+    let root = Path::new("assets");
+    let mut files = Vec::new();
+    collect_files(root, &mut files);
+    files.sort();
+    let mut manifest = String::new();
+    for file in files {
+        let Ok(bytes) = fs::read(&file) else {
+            continue;
+        };
+        let path = file
+            .strip_prefix(root)
+            .unwrap_or(&file)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let _ = writeln!(manifest, "{:016x} {path}", fnv1a(&bytes));
+    }
+    let out_dir = env::var("OUT_DIR").expect("cargo sets OUT_DIR for build scripts");
+    fs::write(Path::new(&out_dir).join("asset_manifest.txt"), manifest)
+        .expect("could not write the asset manifest");
+    println!("cargo:rerun-if-changed=assets");
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+/// 64-bit FNV-1a, which has to match `game::integrity::hash`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Today's date (UTC) as `YYYY-MM-DD`, without pulling in a date crate.
+fn build_date() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let days = (seconds / 86_400) as i64;
+    // Civil date from days since the epoch, see https://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
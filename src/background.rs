@@ -0,0 +1,165 @@
+//! Pausing the game while its window is unfocused or hidden, e.g. in a background tab.
+//! Virtual time stops, and a running game opens the pause menu,
+//! so nothing is missed and no CPU is spent while the player is away.
+//! Native builds can opt out with the "Run in background" setting.
+//!
+//! Separately, native builds keep playing audio while unfocused, like after alt-tabbing
+//! out of fullscreen, ducked or muted on the master bus as the "Audio when unfocused"
+//! setting says. The web build pauses all audio along with the game instead.
+
+use bevy::{
+    audio::Volume,
+    prelude::*,
+    window::{WindowFocused, WindowOccluded},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{screen::PlayingState, BoundedU8, GameSettings, LevelSetting};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(PreUpdate, (pause_in_background, apply_unfocused_audio));
+}
+
+/// What audio does while the window is unfocused: continue, duck or mute.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Eq, PartialEq, Reflect)]
+pub(crate) struct UnfocusedAudioSetting(pub(crate) BoundedU8<0, 2>);
+
+impl LevelSetting for UnfocusedAudioSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl Default for UnfocusedAudioSetting {
+    /// Muted, like when audio was paused along with the game.
+    fn default() -> Self {
+        Self::from_max()
+    }
+}
+
+/// Gain of the master bus while ducked for being unfocused.
+const UNFOCUSED_DUCK_GAIN: f32 = 0.3;
+
+impl UnfocusedAudioSetting {
+    /// What the master bus is multiplied by while unfocused.
+    pub(crate) fn gain(&self) -> f32 {
+        match self.0 .0 {
+            0 => 1.0,
+            1 => UNFOCUSED_DUCK_GAIN,
+            _ => 0.0,
+        }
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        match self.0 .0 {
+            0 => "Continue",
+            1 => "Duck",
+            _ => "Mute",
+        }
+        .to_string()
+    }
+}
+
+/// Where the window is, as far as the latest window events tell.
+#[derive(Default)]
+struct BackgroundState {
+    unfocused: bool,
+    occluded: bool,
+    /// Whether we paused the game, and have to resume it.
+    paused: bool,
+    /// Audio that was playing when we paused, which we have to resume.
+    paused_sinks: Vec<Entity>,
+}
+
+fn pause_in_background(
+    mut focus_events: EventReader<WindowFocused>,
+    mut occluded_events: EventReader<WindowOccluded>,
+    settings: Res<GameSettings>,
+    mut state: Local<BackgroundState>,
+    mut time: ResMut<Time<Virtual>>,
+    sink_query: Query<(Entity, &AudioSink)>,
+    playing_state: Option<Res<State<PlayingState>>>,
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+) {
+    for event in focus_events.read() {
+        state.unfocused = !event.focused;
+    }
+    for event in occluded_events.read() {
+        state.occluded = event.occluded;
+    }
+    // Browsers throttle background tabs anyway, which makes audio drift, so always pause there.
+    let may_pause = cfg!(target_family = "wasm") || !settings.run_in_background.is_on();
+    let should_pause = may_pause && (state.unfocused || state.occluded);
+    if should_pause == state.paused {
+        return;
+    }
+    state.paused = should_pause;
+
+    if should_pause {
+        info!("Pausing while in the background.");
+        time.pause();
+        // Native builds handle audio in `apply_unfocused_audio`.
+        if cfg!(target_family = "wasm") {
+            state.paused_sinks = sink_query
+                .iter()
+                .filter(|(_, sink)| !sink.is_paused())
+                .map(|(entity, sink)| {
+                    sink.pause();
+                    entity
+                })
+                .collect();
+        }
+        if playing_state.is_some_and(|playing| *playing.get() == PlayingState::Running) {
+            next_playing_state.set(PlayingState::Paused);
+        }
+    } else {
+        info!("Resuming in the foreground.");
+        time.unpause();
+        // Sounds that finished or were stopped in the meantime are gone, and skipped here.
+        for (_, sink) in sink_query.iter_many(state.paused_sinks.drain(..)) {
+            sink.play();
+        }
+    }
+}
+
+/// Scale the master bus for the window's focus, whenever either it or the settings change.
+/// Music follows right away, and sounds that are already playing are short enough to end.
+fn apply_unfocused_audio(
+    mut focus_events: EventReader<WindowFocused>,
+    mut unfocused: Local<bool>,
+    settings: Res<GameSettings>,
+    mut global_volume: ResMut<GlobalVolume>,
+) {
+    let mut focus_changed = false;
+    for event in focus_events.read() {
+        *unfocused = !event.focused;
+        focus_changed = true;
+    }
+    if !focus_changed && !settings.is_changed() {
+        return;
+    }
+    let gain = if *unfocused && cfg!(not(target_family = "wasm")) {
+        settings.unfocused_audio.gain()
+    } else {
+        1.0
+    };
+    let volume = Volume::from(&settings.global_volume_level).get() * gain;
+    if global_volume.volume.get() != volume {
+        global_volume.volume = Volume::new(volume);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unfocused_audio_continues_ducks_or_mutes() {
+        // Silent by default, like when audio was paused in the background.
+        assert_eq!(UnfocusedAudioSetting::default().gain(), 0.0);
+        assert_eq!(UnfocusedAudioSetting::default().name_display(), "Mute");
+        assert_eq!(UnfocusedAudioSetting::from_raw(0).gain(), 1.0);
+        let duck = UnfocusedAudioSetting::from_raw(1).gain();
+        assert!(duck > 0.0 && duck < 1.0);
+    }
+}
@@ -0,0 +1,53 @@
+//! Copying text to the system clipboard, with the same API on native and web.
+//! Native builds use `arboard`, web builds use the browser's clipboard API,
+//! which only allows copying in response to a click or key press.
+
+/// Put `text` on the clipboard, logging why if it can't be.
+pub fn copy(text: &str) {
+    backend::copy(text);
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod backend {
+    use std::sync::Mutex;
+
+    use arboard::Clipboard;
+    use bevy::prelude::*;
+
+    /// Kept around, since on Linux the copied text is gone once the clipboard is
+    /// dropped, unless a clipboard manager took it over.
+    static CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+
+    pub(super) fn copy(text: &str) {
+        let mut clipboard = CLIPBOARD.lock().unwrap_or_else(|e| e.into_inner());
+        if clipboard.is_none() {
+            *clipboard = Clipboard::new()
+                .inspect_err(|e| warn!("Could not open the clipboard: {e}"))
+                .ok();
+        }
+        if let Some(clipboard) = clipboard.as_mut() {
+            if let Err(e) = clipboard.set_text(text) {
+                warn!("Could not copy to the clipboard: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod backend {
+    use bevy::prelude::*;
+    use wasm_bindgen_futures::JsFuture;
+
+    pub(super) fn copy(text: &str) {
+        let Some(window) = web_sys::window() else {
+            warn!("No window, so nothing was copied to the clipboard.");
+            return;
+        };
+        let promise = window.navigator().clipboard().write_text(text);
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = JsFuture::from(promise).await {
+                warn!("Could not copy to the clipboard: {e:?}");
+            }
+        });
+    }
+}
@@ -0,0 +1,76 @@
+//! Console commands for inspecting the simulation checksums.
+
+use bevy::prelude::*;
+
+use super::console::{register_command, ConsoleCommand, ConsoleLog};
+use crate::{game::checksum::SimulationChecksums, screen::bug_report::BugReport, storage};
+
+pub(super) fn plugin(app: &mut App) {
+    register_command(
+        app,
+        "checksum",
+        "checksum [tick] - show the simulation checksum of a tick (default: latest)",
+    );
+    register_command(
+        app,
+        "diverge",
+        "diverge <report> - show where a saved bug report's checksums differ from this run's",
+    );
+    app.observe(handle_checksum_command);
+    app.observe(handle_diverge_command);
+}
+
+fn handle_checksum_command(
+    trigger: Trigger<ConsoleCommand>,
+    checksums: Res<SimulationChecksums>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let command = trigger.event();
+    if command.name != "checksum" {
+        return;
+    }
+    let ticks = checksums.ticks();
+    let tick = match command.args.first().map(|arg| arg.parse::<usize>()) {
+        Some(Ok(tick)) => tick,
+        Some(Err(_)) => {
+            log.print("Invalid tick, expected a number.");
+            return;
+        }
+        None => ticks.end().saturating_sub(1),
+    };
+    match ticks.get(tick) {
+        Some(checksum) => log.print(format!("Tick {tick}/{}: {checksum:016x}", ticks.end())),
+        None => log.print(format!(
+            "No checksum for tick {tick} (ticks {}..{} recorded).",
+            ticks.first_tick,
+            ticks.end()
+        )),
+    }
+}
+
+fn handle_diverge_command(
+    trigger: Trigger<ConsoleCommand>,
+    checksums: Res<SimulationChecksums>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let command = trigger.event();
+    if command.name != "diverge" {
+        return;
+    }
+    let Some(id) = command.args.first() else {
+        log.print("Expected the id of a saved bug report.");
+        return;
+    };
+    let Some(reported) = storage::load::<BugReport>(id).and_then(|report| report.checksums) else {
+        log.print(format!("No saved bug report {id} with checksums."));
+        return;
+    };
+    match checksums.ticks().first_divergence(&reported) {
+        Some(tick) => log.print(format!("Diverged from {id} at tick {tick}.")),
+        None => log.print(format!(
+            "No divergence from {id} in ticks {}..{}.",
+            reported.first_tick,
+            reported.end()
+        )),
+    }
+}
@@ -0,0 +1,198 @@
+//! A minimal text console, toggled with the backquote key.
+//! Other dev tools register commands with [`register_command`] and handle them
+//! by observing [`ConsoleCommand`].
+
+use bevy::{
+    input::{
+        common_conditions::input_just_pressed,
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::layers::Layer;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ConsoleCommands>();
+    app.init_resource::<ConsoleLog>();
+    app.init_resource::<ConsoleInput>();
+
+    app.add_systems(
+        Update,
+        (
+            toggle_console.run_if(input_just_pressed(KeyCode::Backquote)),
+            (read_console_input, update_console_text)
+                .chain()
+                .run_if(console_open),
+        )
+            .chain(),
+    );
+
+    register_command(app, "help", "help - list all commands");
+    register_command(app, "clear", "clear - clear the console output");
+    app.observe(handle_builtin_command);
+}
+
+/// Makes a command known to the console, so it shows up in `help` and is
+/// dispatched as a [`ConsoleCommand`] when entered.
+pub fn register_command(app: &mut App, name: &'static str, usage: &'static str) {
+    app.init_resource::<ConsoleCommands>();
+    app.world_mut()
+        .resource_mut::<ConsoleCommands>()
+        .0
+        .insert(name, usage);
+}
+
+/// Triggered when a registered command is entered.
+/// Observers should ignore commands with a different [`ConsoleCommand::name`].
+#[derive(Event, Debug)]
+pub struct ConsoleCommand {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+/// Usage strings of all registered commands, keyed by name.
+#[derive(Resource, Default)]
+struct ConsoleCommands(HashMap<&'static str, &'static str>);
+
+/// Output lines shown in the console.
+#[derive(Resource, Default)]
+pub struct ConsoleLog(Vec<String>);
+
+impl ConsoleLog {
+    const MAX_LINES: usize = 12;
+
+    /// Print a line to the console (and the regular log).
+    pub fn print(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        info!("[console] {line}");
+        self.0.push(line);
+        let overflow = self.0.len().saturating_sub(Self::MAX_LINES);
+        self.0.drain(..overflow);
+    }
+}
+
+#[derive(Resource, Default)]
+struct ConsoleInput(String);
+
+#[derive(Component)]
+struct ConsoleUi;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn console_open(console_query: Query<(), With<ConsoleUi>>) -> bool {
+    !console_query.is_empty()
+}
+
+fn toggle_console(mut commands: Commands, console_query: Query<Entity, With<ConsoleUi>>) {
+    if let Ok(entity) = console_query.get_single() {
+        commands.entity(entity).despawn_recursive();
+        return;
+    }
+    commands
+        .spawn((
+            Name::new("Dev Console"),
+            ConsoleUi,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(0.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+                z_index: Layer::DevOverlay.z_index(0),
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Dev Console Text"),
+                ConsoleText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+fn read_console_input(
+    mut commands: Commands,
+    mut keyboard_input: EventReader<KeyboardInput>,
+    mut input: ResMut<ConsoleInput>,
+    mut log: ResMut<ConsoleLog>,
+    registered: Res<ConsoleCommands>,
+) {
+    for event in keyboard_input.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(c) if c.as_str() != "`" => input.0.push_str(c),
+            Key::Space => input.0.push(' '),
+            Key::Backspace => {
+                input.0.pop();
+            }
+            Key::Enter => {
+                let line = std::mem::take(&mut input.0);
+                let mut words = line.split_whitespace().map(str::to_string);
+                let Some(name) = words.next() else {
+                    continue;
+                };
+                log.print(format!("> {line}"));
+                if registered.0.contains_key(name.as_str()) {
+                    commands.trigger(ConsoleCommand {
+                        name,
+                        args: words.collect(),
+                    });
+                } else {
+                    log.print(format!("Unknown command '{name}', try 'help'."));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn update_console_text(
+    input: Res<ConsoleInput>,
+    log: Res<ConsoleLog>,
+    mut text_query: Query<&mut Text, With<ConsoleText>>,
+    spawned_query: Query<(), Added<ConsoleText>>,
+) {
+    if !input.is_changed() && !log.is_changed() && spawned_query.is_empty() {
+        return;
+    }
+    for mut text in &mut text_query {
+        let mut value = log.0.join("\n");
+        value.push_str(&format!("\n> {}_", input.0));
+        text.sections[0].value = value;
+    }
+}
+
+fn handle_builtin_command(
+    trigger: Trigger<ConsoleCommand>,
+    registered: Res<ConsoleCommands>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    match trigger.event().name.as_str() {
+        "help" => {
+            let mut usages = registered.0.values().copied().collect::<Vec<_>>();
+            usages.sort();
+            for usage in usages {
+                log.print(usage);
+            }
+        }
+        "clear" => log.0.clear(),
+        _ => (),
+    }
+}
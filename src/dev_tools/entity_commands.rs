@@ -0,0 +1,183 @@
+//! Console commands for finding and inspecting entities, using the reflection
+//! registrations of our components:
+//! - `find name:Player` / `find Player` - list entities by name or by component.
+//! - `count Player` - count entities with a component.
+//! - `inspect 12v1` - print all reflected components of an entity.
+//! - `despawn 12v1` - despawn an entity and its children.
+
+use bevy::{
+    ecs::{component::ComponentId, world::EntityRef},
+    prelude::*,
+};
+
+use super::console::{register_command, ConsoleCommand, ConsoleLog};
+
+pub(super) fn plugin(app: &mut App) {
+    register_command(
+        app,
+        "find",
+        "find name:<text> | find <Component> - list matching entities",
+    );
+    register_command(
+        app,
+        "count",
+        "count <Component> - count entities with a component",
+    );
+    register_command(
+        app,
+        "inspect",
+        "inspect <entity> - print reflected components",
+    );
+    register_command(
+        app,
+        "despawn",
+        "despawn <entity> - despawn an entity recursively",
+    );
+    app.observe(handle_entity_command);
+}
+
+fn handle_entity_command(trigger: Trigger<ConsoleCommand>, mut commands: Commands) {
+    let ConsoleCommand { name, args } = trigger.event();
+    let Some(arg) = args.first().cloned() else {
+        if ["find", "count", "inspect", "despawn"].contains(&name.as_str()) {
+            commands.add(|world: &mut World| print(world, vec!["Missing argument.".into()]));
+        }
+        return;
+    };
+    // These commands need to look at arbitrary components, so they run with full world access.
+    match name.as_str() {
+        "find" => commands.add(move |world: &mut World| {
+            let lines = find(world, &arg);
+            print(world, lines);
+        }),
+        "count" => commands.add(move |world: &mut World| {
+            let line = match component_id(world, &arg) {
+                Ok(id) => format!("{} entities with {arg}.", entities_with(world, id).len()),
+                Err(e) => e,
+            };
+            print(world, vec![line]);
+        }),
+        "inspect" => commands.add(move |world: &mut World| {
+            let lines = match parse_entity(world, &arg) {
+                Ok(entity) => inspect(world, entity),
+                Err(e) => vec![e],
+            };
+            print(world, lines);
+        }),
+        "despawn" => commands.add(move |world: &mut World| {
+            let line = match parse_entity(world, &arg) {
+                Ok(entity) => {
+                    world.entity_mut(entity).despawn_recursive();
+                    format!("Despawned {entity}.")
+                }
+                Err(e) => e,
+            };
+            print(world, vec![line]);
+        }),
+        _ => (),
+    }
+}
+
+fn print(world: &mut World, lines: Vec<String>) {
+    let mut log = world.resource_mut::<ConsoleLog>();
+    for line in lines {
+        log.print(line);
+    }
+}
+
+/// Label an entity with its [`Name`], if it has one.
+fn describe(entity: EntityRef) -> String {
+    match entity.get::<Name>() {
+        Some(name) => format!("{} ({name})", entity.id()),
+        None => entity.id().to_string(),
+    }
+}
+
+fn find(world: &World, query: &str) -> Vec<String> {
+    let entities = match query.strip_prefix("name:") {
+        Some(name) => world
+            .iter_entities()
+            .filter(|entity| {
+                entity
+                    .get::<Name>()
+                    .is_some_and(|entity_name| entity_name.as_str().contains(name))
+            })
+            .map(|entity| entity.id())
+            .collect(),
+        None => match component_id(world, query) {
+            Ok(id) => entities_with(world, id),
+            Err(e) => return vec![e],
+        },
+    };
+    if entities.is_empty() {
+        return vec![format!("No entities match '{query}'.")];
+    }
+    entities
+        .into_iter()
+        .map(|entity| describe(world.entity(entity)))
+        .collect()
+}
+
+fn entities_with(world: &World, id: ComponentId) -> Vec<Entity> {
+    world
+        .iter_entities()
+        .filter(|entity| entity.contains_id(id))
+        .map(|entity| entity.id())
+        .collect()
+}
+
+/// Find a registered component by its short type name, e.g. `Player`.
+fn component_id(world: &World, name: &str) -> Result<ComponentId, String> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let registration = registry
+        .get_with_short_type_path(name)
+        .ok_or_else(|| format!("No registered type named '{name}'."))?;
+    world
+        .components()
+        .get_id(registration.type_id())
+        .ok_or_else(|| format!("'{name}' is not used as a component."))
+}
+
+/// Parse an entity as printed by the other commands (`12v1`), or just its index (`12`).
+fn parse_entity(world: &World, text: &str) -> Result<Entity, String> {
+    let (index, generation) = match text.split_once('v') {
+        Some((index, generation)) => (index, Some(generation)),
+        None => (text, None),
+    };
+    let index = index
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid entity '{text}'."))?;
+    let generation = generation
+        .map(|generation| generation.parse::<u32>())
+        .transpose()
+        .map_err(|_| format!("Invalid entity '{text}'."))?;
+    world
+        .iter_entities()
+        .map(|entity| entity.id())
+        .find(|entity| {
+            entity.index() == index
+                && (generation.is_none() || generation == Some(entity.generation()))
+        })
+        .ok_or_else(|| format!("Entity '{text}' does not exist."))
+}
+
+fn inspect(world: &World, entity: Entity) -> Vec<String> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let entity_ref = world.entity(entity);
+    let mut lines = vec![describe(entity_ref)];
+    for id in entity_ref.archetype().components() {
+        let Some(info) = world.components().get_info(id) else {
+            continue;
+        };
+        let reflected = info
+            .type_id()
+            .and_then(|type_id| registry.get_type_data::<ReflectComponent>(type_id))
+            .and_then(|reflect_component| reflect_component.reflect(entity_ref));
+        match reflected {
+            Some(value) => lines.push(format!("  {value:?}")),
+            // Not reflected, so only the name is known.
+            None => lines.push(format!("  {}", info.name())),
+        }
+    }
+    lines
+}
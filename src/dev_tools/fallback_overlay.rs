@@ -0,0 +1,58 @@
+//! Lists the assets that are standing in as fallbacks in the top right corner,
+//! so a missing file doesn't go unnoticed during development. See `game::fallback`.
+
+use bevy::prelude::*;
+
+use crate::{game::fallback::Fallbacks, layers::Layer};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        update_fallback_overlay.run_if(resource_changed::<Fallbacks>),
+    );
+}
+
+#[derive(Component)]
+struct FallbackOverlay;
+
+fn update_fallback_overlay(
+    mut commands: Commands,
+    fallbacks: Res<Fallbacks>,
+    overlay_query: Query<Entity, With<FallbackOverlay>>,
+) {
+    for entity in &overlay_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    if fallbacks.0.is_empty() {
+        return;
+    }
+    let mut lines = vec![format!("{} fallback assets:", fallbacks.0.len())];
+    lines.extend(
+        fallbacks
+            .0
+            .iter()
+            .map(|substitution| substitution.path.clone()),
+    );
+    commands.spawn((
+        Name::new("Fallback Overlay"),
+        FallbackOverlay,
+        TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(4.0),
+                right: Val::Px(4.0),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            z_index: Layer::DevOverlay.z_index(1),
+            ..TextBundle::from_section(
+                lines.join("\n"),
+                TextStyle {
+                    font_size: 14.0,
+                    color: Color::srgb(1.0, 0.0, 1.0),
+                    ..default()
+                },
+            )
+        },
+    ));
+}
@@ -0,0 +1,56 @@
+//! Console commands for changing the log filter at runtime,
+//! e.g. `log set crate::game=trace` while reproducing a bug.
+
+use bevy::{log::Level, prelude::*};
+
+use super::console::{register_command, ConsoleCommand, ConsoleLog};
+use crate::logging::LogFilter;
+
+pub(super) fn plugin(app: &mut App) {
+    register_command(
+        app,
+        "log",
+        "log show | log level <level> | log set <target=level> | log reset",
+    );
+    app.observe(handle_log_command);
+}
+
+fn handle_log_command(
+    trigger: Trigger<ConsoleCommand>,
+    log_filter: Option<ResMut<LogFilter>>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let command = trigger.event();
+    if command.name != "log" {
+        return;
+    }
+    let Some(mut log_filter) = log_filter else {
+        log.print("The log filter is not available.");
+        return;
+    };
+
+    match command.args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["show"] | [] => {
+            log.print(format!("Level: {}", log_filter.level()));
+            for directive in log_filter.directives() {
+                log.print(format!("  {directive}"));
+            }
+        }
+        ["level", level] => match level.parse::<Level>() {
+            Ok(level) => {
+                log_filter.set_level(level);
+                log.print(format!("Log level set to {level}."));
+            }
+            Err(_) => log.print(format!("Unknown log level '{level}'.")),
+        },
+        ["set", directive] => match log_filter.set_directive(directive) {
+            Ok(()) => log.print(format!("Added directive '{directive}'.")),
+            Err(e) => log.print(e),
+        },
+        ["reset"] => {
+            log_filter.clear_directives();
+            log.print("Cleared all log directives.");
+        }
+        _ => log.print("Invalid arguments, try 'help'."),
+    }
+}
@@ -1,5 +1,11 @@
 //! Development tools for the game. This plugin is only enabled in dev builds.
 
+mod checksum_commands;
+mod console;
+mod entity_commands;
+mod fallback_overlay;
+mod log_commands;
+
 use bevy::{dev_tools::states::log_transitions, prelude::*};
 
 use crate::screen::Screen;
@@ -7,4 +13,12 @@ use crate::screen::Screen;
 pub(super) fn plugin(app: &mut App) {
     // Print state transitions in dev builds
     app.add_systems(Update, log_transitions::<Screen>);
+
+    app.add_plugins((
+        console::plugin,
+        checksum_commands::plugin,
+        entity_commands::plugin,
+        fallback_overlay::plugin,
+        log_commands::plugin,
+    ));
 }
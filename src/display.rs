@@ -0,0 +1,469 @@
+//! Display settings, applied to the primary window whenever [`GameSettings`] change.
+//!
+//! Fullscreen goes to whichever monitor the window is on, so going fullscreen on the
+//! monitor chosen with the [`MonitorSetting`] first moves the window there in windowed mode,
+//! and switches the mode on the next frame.
+
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    window_placement::{MonitorInfo, Monitors},
+    BinaryAdjustment, BoundedU8, GameSettings, LevelSetting,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DisplaySettings>();
+    app.add_systems(
+        Update,
+        (
+            apply_display_settings.run_if(resource_changed::<GameSettings>),
+            apply_pending_window_mode.run_if(resource_exists::<PendingWindowMode>),
+        ),
+    );
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Reflect)]
+pub(crate) struct DisplaySettings {
+    pub(crate) fullscreen: ToggleSetting,
+    pub(crate) vsync: ToggleSetting,
+    /// Only used in windowed mode.
+    pub(crate) resolution: ResolutionSetting,
+    /// Render the world at the art's resolution, see `game::pixel_canvas`.
+    #[serde(default)]
+    pub(crate) pixel_perfect: ToggleSetting,
+    #[serde(default)]
+    pub(crate) quality: QualitySetting,
+    /// How the pixel perfect canvas is scaled up to the window.
+    #[serde(default)]
+    pub(crate) upscaling: UpscalingSetting,
+    /// How much of the world the camera shows, see `game::camera`.
+    #[serde(default)]
+    pub(crate) view_size: ViewSizeSetting,
+    /// How particles are drawn, see `game::particles`.
+    #[serde(default)]
+    pub(crate) particles: ParticleSetting,
+    /// Draw moving entities between fixed ticks, see `game::interpolation`.
+    #[serde(default = "ToggleSetting::from_max")]
+    pub(crate) motion_smoothing: ToggleSetting,
+    /// Which monitor fullscreen goes to.
+    #[serde(default)]
+    pub(crate) monitor: MonitorSetting,
+    #[serde(default)]
+    pub(crate) effects: EffectSettings,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            fullscreen: default(),
+            vsync: ToggleSetting::from_max(),
+            resolution: default(),
+            pixel_perfect: default(),
+            quality: default(),
+            upscaling: default(),
+            view_size: default(),
+            particles: default(),
+            motion_smoothing: ToggleSetting::from_max(),
+            monitor: default(),
+            effects: default(),
+        }
+    }
+}
+
+/// Post-processing on the world camera, see `game::post_processing`.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Reflect)]
+#[serde(default)]
+pub(crate) struct EffectSettings {
+    /// Glow around bright colors.
+    pub(crate) bloom: ToggleSetting,
+    /// Darken the edges of the screen while the player's health is low.
+    pub(crate) low_health_vignette: ToggleSetting,
+    /// Split the colors apart for a moment when the player is hit.
+    pub(crate) hit_aberration: ToggleSetting,
+}
+
+impl Default for EffectSettings {
+    fn default() -> Self {
+        Self {
+            bloom: ToggleSetting::from_max(),
+            low_health_vignette: ToggleSetting::from_max(),
+            hit_aberration: ToggleSetting::from_max(),
+        }
+    }
+}
+
+/// An on/off setting, adjusted with the same controls as the level settings.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Default, Eq, PartialEq, Reflect)]
+pub(crate) struct ToggleSetting(pub(crate) BoundedU8<0, 1>);
+
+impl LevelSetting for ToggleSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl ToggleSetting {
+    pub(crate) fn is_on(&self) -> bool {
+        self.0 .0 == 1
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        if self.is_on() { "On" } else { "Off" }.to_string()
+    }
+}
+
+/// Graphics quality. Low skips purely decorative effects, like the menu backdrop.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Eq, PartialEq, Reflect)]
+pub(crate) struct QualitySetting(pub(crate) BoundedU8<0, 1>);
+
+impl Default for QualitySetting {
+    fn default() -> Self {
+        Self::from_max()
+    }
+}
+
+impl LevelSetting for QualitySetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl QualitySetting {
+    pub(crate) fn is_high(&self) -> bool {
+        self.0 .0 == 1
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        if self.is_high() { "High" } else { "Low" }.to_string()
+    }
+}
+
+/// Crisp upscaling scales by whole numbers with sharp pixels, leaving black bars around
+/// the picture. Smooth upscaling fills the window as far as it can, blending pixels.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Default, Eq, PartialEq, Reflect)]
+pub(crate) struct UpscalingSetting(pub(crate) BoundedU8<0, 1>);
+
+impl LevelSetting for UpscalingSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl UpscalingSetting {
+    pub(crate) fn is_crisp(&self) -> bool {
+        self.0 .0 == 0
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        if self.is_crisp() { "Crisp" } else { "Smooth" }.to_string()
+    }
+}
+
+/// How much of the world is visible, from 50% to 150% of the default view,
+/// for screens where the default shows too much or too little of it.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Eq, PartialEq, Reflect)]
+pub(crate) struct ViewSizeSetting(pub(crate) BoundedU8<0, 10>);
+
+impl Default for ViewSizeSetting {
+    fn default() -> Self {
+        Self(5.into())
+    }
+}
+
+impl LevelSetting for ViewSizeSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl ViewSizeSetting {
+    /// What the camera's scale is multiplied by.
+    pub(crate) fn scale(&self) -> f32 {
+        (5 + self.0 .0) as f32 / 10.0
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        format!("{}%", (self.scale() * 100.0).round())
+    }
+}
+
+/// Particle quality. Low and medium draw every particle as a sprite, with low drawing a
+/// quarter of them. High lets dense effects use the GPU backend. Low by default on the web,
+/// where the GPU backend is slow or unsupported on many devices.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Eq, PartialEq, Reflect)]
+pub(crate) struct ParticleSetting(pub(crate) BoundedU8<0, 2>);
+
+impl Default for ParticleSetting {
+    fn default() -> Self {
+        if cfg!(target_family = "wasm") {
+            Self::from_raw(0)
+        } else {
+            Self::from_max()
+        }
+    }
+}
+
+impl LevelSetting for ParticleSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl ParticleSetting {
+    pub(crate) fn allows_gpu(&self) -> bool {
+        self.0 .0 == 2
+    }
+
+    /// How many of a preset's particles the CPU backend draws.
+    pub(crate) fn count_factor(&self) -> f32 {
+        if self.0 .0 == 0 {
+            0.25
+        } else {
+            1.0
+        }
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        ["Low", "Medium", "High"][self.0 .0 as usize].to_string()
+    }
+}
+
+/// Window sizes to choose from, in logical pixels.
+const RESOLUTIONS: [(f32, f32); 5] = [
+    (1280.0, 720.0),
+    (1600.0, 900.0),
+    (1920.0, 1080.0),
+    (2560.0, 1440.0),
+    (3840.0, 2160.0),
+];
+
+/// An index into [`RESOLUTIONS`].
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Default, Eq, PartialEq, Reflect)]
+pub(crate) struct ResolutionSetting(pub(crate) BoundedU8<0, 4>);
+
+impl LevelSetting for ResolutionSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl ResolutionSetting {
+    pub(crate) fn size(&self) -> (f32, f32) {
+        RESOLUTIONS[self.0 .0 as usize]
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        let (width, height) = self.size();
+        format!("{width}x{height}")
+    }
+}
+
+/// A monitor that fullscreen can go to.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MonitorChoice {
+    /// What the system calls it, which is what gets saved.
+    pub(crate) name: String,
+    /// Top left corner and size in physical pixels.
+    pub(crate) position: IVec2,
+    pub(crate) size: UVec2,
+}
+
+impl MonitorChoice {
+    /// The monitors that can be chosen, from left to right. Unnamed ones can't be saved.
+    pub(crate) fn all<'a>(monitors: impl IntoIterator<Item = &'a MonitorInfo>) -> Vec<Self> {
+        let mut choices = monitors
+            .into_iter()
+            .filter_map(|monitor| {
+                Some(Self {
+                    name: monitor.name.clone()?,
+                    position: monitor.area.position,
+                    size: monitor.area.size,
+                })
+            })
+            .collect::<Vec<_>>();
+        choices.sort_by_key(|choice| (choice.position.x, choice.position.y));
+        choices
+    }
+}
+
+/// The name of the monitor to go fullscreen on,
+/// or none to stay on the one the window is on.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, Reflect)]
+pub(crate) struct MonitorSetting(pub(crate) Option<String>);
+
+impl MonitorSetting {
+    /// The chosen monitor, if it is still connected.
+    pub(crate) fn find<'a>(&self, choices: &'a [MonitorChoice]) -> Option<&'a MonitorChoice> {
+        let name = self.0.as_ref()?;
+        choices.iter().find(|choice| &choice.name == name)
+    }
+
+    /// The next or previous choice, with the window's own monitor before the others.
+    /// Stops at either end, like the level settings.
+    pub(crate) fn stepped(&self, adjustment: BinaryAdjustment, choices: &[MonitorChoice]) -> Self {
+        // 0 is the window's own monitor, and a disconnected one starts from there too.
+        let index = self
+            .find(choices)
+            .and_then(|found| choices.iter().position(|choice| choice == found))
+            .map_or(0, |index| index + 1);
+        let index = match adjustment {
+            BinaryAdjustment::Up => (index + 1).min(choices.len()),
+            BinaryAdjustment::Down => index.saturating_sub(1),
+        };
+        Self(
+            index
+                .checked_sub(1)
+                .map(|index| choices[index].name.clone()),
+        )
+    }
+
+    /// Like "DELL U2720Q (3840x2160)".
+    pub(crate) fn name_display(&self, choices: &[MonitorChoice]) -> String {
+        match (&self.0, self.find(choices)) {
+            (None, _) => "Current".to_string(),
+            (Some(_), Some(choice)) => {
+                format!("{} ({}x{})", choice.name, choice.size.x, choice.size.y)
+            }
+            (Some(name), None) => format!("{name} (disconnected)"),
+        }
+    }
+}
+
+/// The mode to switch the window to once it was moved to the chosen monitor.
+#[derive(Resource, Debug)]
+struct PendingWindowMode(WindowMode);
+
+/// Move the window to `monitor` in windowed mode, going fullscreen there on the next frame.
+pub(crate) fn go_fullscreen_on(
+    commands: &mut Commands,
+    window: &mut Window,
+    monitor: &MonitorChoice,
+) {
+    info!("Going fullscreen on {}.", monitor.name);
+    window.mode = WindowMode::Windowed;
+    window.position = WindowPosition::At(monitor.position);
+    commands.insert_resource(PendingWindowMode(WindowMode::BorderlessFullscreen));
+}
+
+fn apply_pending_window_mode(
+    mut commands: Commands,
+    pending: Res<PendingWindowMode>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    commands.remove_resource::<PendingWindowMode>();
+    for mut window in &mut window_query {
+        window.mode = pending.0;
+    }
+}
+
+/// The display settings that [`apply_display_settings`] applies to the window.
+/// The rest are graphics options, which other systems read as they need them.
+#[derive(Debug, Clone, PartialEq)]
+struct WindowOptions {
+    fullscreen: ToggleSetting,
+    vsync: ToggleSetting,
+    resolution: ResolutionSetting,
+    monitor: MonitorSetting,
+}
+
+impl From<&DisplaySettings> for WindowOptions {
+    fn from(display: &DisplaySettings) -> Self {
+        Self {
+            fullscreen: display.fullscreen.clone(),
+            vsync: display.vsync.clone(),
+            resolution: display.resolution.clone(),
+            monitor: display.monitor.clone(),
+        }
+    }
+}
+
+fn apply_display_settings(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    mut applied: Local<Option<WindowOptions>>,
+    monitors: Monitors,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let options = WindowOptions::from(&settings.display);
+    // Only touch the window when these settings change, so it can still be resized by hand.
+    if applied.as_ref() == Some(&options) {
+        return;
+    }
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+    // At startup, the size the window was left at wins over the resolution setting,
+    // see `window_placement`. Later, only a new resolution or leaving fullscreen resizes it.
+    let resize = applied
+        .as_ref()
+        .map_or(settings.window.size.is_none(), |applied| {
+            applied.resolution != options.resolution || applied.fullscreen != options.fullscreen
+        });
+    // Only moved when going fullscreen or choosing another monitor, so windows dragged
+    // to another monitor while fullscreen aren't pulled back.
+    let moving = !applied
+        .as_ref()
+        .is_some_and(|applied| applied.fullscreen.is_on() && applied.monitor == options.monitor);
+
+    let choices = MonitorChoice::all(&monitors.all());
+    let monitor = options.monitor.find(&choices);
+    match monitor {
+        Some(monitor) if options.fullscreen.is_on() && moving => {
+            go_fullscreen_on(&mut commands, &mut window, monitor);
+        }
+        _ => {
+            commands.remove_resource::<PendingWindowMode>();
+            window.mode = if options.fullscreen.is_on() {
+                WindowMode::BorderlessFullscreen
+            } else {
+                WindowMode::Windowed
+            };
+        }
+    }
+    window.present_mode = if options.vsync.is_on() {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    // The web build always fits the canvas to the page instead.
+    if !options.fullscreen.is_on() && resize && cfg!(not(target_family = "wasm")) {
+        let (width, height) = options.resolution.size();
+        window.resolution.set(width, height);
+    }
+    *applied = Some(options);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fullscreen_monitor_steps_through_connected_monitors() {
+        let monitor = |name: &str, x| MonitorChoice {
+            name: name.to_string(),
+            position: IVec2::new(x, 0),
+            size: UVec2::new(1920, 1080),
+        };
+        let monitors = [monitor("Left", -1920), monitor("Right", 0)];
+        let current = MonitorSetting::default();
+        assert_eq!(current.name_display(&monitors), "Current");
+        assert_eq!(current.stepped(BinaryAdjustment::Down, &monitors), current);
+
+        let left = current.stepped(BinaryAdjustment::Up, &monitors);
+        assert_eq!(left.name_display(&monitors), "Left (1920x1080)");
+        let right = left.stepped(BinaryAdjustment::Up, &monitors);
+        assert_eq!(right.find(&monitors), Some(&monitors[1]));
+        assert_eq!(right.stepped(BinaryAdjustment::Up, &monitors), right);
+        assert_eq!(right.stepped(BinaryAdjustment::Down, &monitors), left);
+
+        // A monitor that was unplugged is kept, until another one is chosen.
+        let gone = MonitorSetting(Some("Gone".to_string()));
+        assert_eq!(gone.find(&monitors), None);
+        assert_eq!(gone.name_display(&monitors), "Gone (disconnected)");
+        assert_eq!(gone.stepped(BinaryAdjustment::Up, &monitors), left);
+    }
+}
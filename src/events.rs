@@ -0,0 +1,219 @@
+//! Cross-cutting gameplay events, declared and registered in one place.
+//!
+//! These are buffered events that any number of systems may read.
+//! Requests with exactly one handler (like [`PlaySfx`](crate::game::audio::sfx::PlaySfx))
+//! are observer triggers instead and live next to their observer.
+//!
+//! # Ordering
+//!
+//! All events here follow the same rule, so readers never miss an event or see it a frame late:
+//! - Write them in [`AppSet::Update`] (or earlier).
+//! - Read them in [`AppSet::HandleEvents`], which runs after [`AppSet::Update`].
+//!
+//! Gameplay runs on fixed ticks, with the same sets in `FixedUpdate`, so the rule holds
+//! within a tick. Events from a tick can also be read in `Update` (like for damage numbers):
+//! a frame's fixed ticks run before `Update`, and events are only cleared on frames
+//! after a tick ran, so none are missed.
+//!
+//! Events that are handled outside of these sets document their exception below.
+
+use bevy::prelude::*;
+
+#[allow(unused_imports)] // for doc links
+use crate::AppSet;
+use crate::{game::health::DamageType, screen::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<CollisionEvent>();
+    app.add_event::<CutsceneEnded>();
+    app.add_event::<DamageEvent>();
+    app.add_event::<DamageTaken>();
+    app.add_event::<DeathEvent>();
+    app.add_event::<DialogueNodeReached>();
+    app.add_event::<DialogueEnded>();
+    app.add_event::<PickupEvent>();
+    app.add_event::<ObjectiveProgress>();
+    app.add_event::<OnCycleAdvance>();
+    app.add_event::<PhaseChanged>();
+    app.add_event::<ScoreEvent>();
+    app.add_event::<ScreenRequest>();
+    app.add_event::<ShakeEvent>();
+    app.add_event::<WaveStarted>();
+    app.add_event::<WaveEnded>();
+}
+
+/// Two colliders overlap, see `game::collision`. Sent every fixed tick while they do.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionEvent {
+    /// The lower of the two entities.
+    pub a: Entity,
+    pub b: Entity,
+    /// Whether they didn't overlap the tick before.
+    pub started: bool,
+}
+
+impl CollisionEvent {
+    /// The entity that matches `predicate` and the other one, in that order,
+    /// if either of them matches.
+    pub fn either(&self, predicate: impl Fn(Entity) -> bool) -> Option<(Entity, Entity)> {
+        if predicate(self.a) {
+            Some((self.a, self.b))
+        } else if predicate(self.b) {
+            Some((self.b, self.a))
+        } else {
+            None
+        }
+    }
+}
+
+/// An entity should take damage, before its resistances, see `game::health`.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageType,
+    /// How much it wears down the target's poise, see `game::stagger`.
+    pub poise: f32,
+    /// The entity that caused the damage, if any.
+    pub source: Option<Entity>,
+    /// Whether this is a tick of a status effect, see `game::status`.
+    pub from_status: bool,
+}
+
+/// An entity lost health, after its resistances.
+///
+/// Sent in [`AppSet::HandleEvents`] when damage is applied, so read it after
+/// `game::health::ApplyDamage`.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct DamageTaken {
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageType,
+    pub poise: f32,
+    /// The entity that caused the damage, if any.
+    pub source: Option<Entity>,
+    pub from_status: bool,
+}
+
+/// An entity's health ran out, see `game::health`.
+///
+/// Sent in [`AppSet::HandleEvents`] when damage is applied. Read it in
+/// `game::health::DeathReactions`, while the entity is still around.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    /// The source of the damage that killed it, if any.
+    pub killer: Option<Entity>,
+}
+
+/// A line of dialogue was reached, including the first, see `game::dialogue`.
+///
+/// Sent when dialogue is started or continued, which happens in `Update` while the game
+/// waits in `PlayingState::Dialogue`, outside of the usual sets. Read it in `Update`.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct DialogueNodeReached {
+    pub dialogue: String,
+    pub node: String,
+}
+
+/// A dialogue ended, by running out of lines or being skipped, see `game::dialogue`.
+/// Sent like [`DialogueNodeReached`].
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct DialogueEnded {
+    pub dialogue: String,
+}
+
+/// A cutscene ended, by running out of steps or being skipped, see `game::cutscene`.
+///
+/// Sent while the cutscene runs in [`AppSet::Update`], or when it is skipped or started
+/// without existing, which happens in observers. Read it in `Update`.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct CutsceneEnded {
+    pub cutscene: String,
+    pub skipped: bool,
+}
+
+/// An entity collected a pickup.
+///
+/// Sent when collisions are handled, so read it after
+/// `game::collision::CollisionReactions`.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PickupEvent {
+    pub collector: Entity,
+    pub pickup: Entity,
+}
+
+/// Something happened that level objectives count towards, see `game::objectives`.
+///
+/// Also sent while handling events, like for deaths, so read it after
+/// `game::health::DeathReactions`.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum ObjectiveProgress {
+    EnemyKilled,
+    CycleSurvived,
+    /// Where the player is, sent every tick while a level has a place left to reach.
+    PlayerAt(Vec2),
+}
+
+/// The game completed a whole cycle, see `game::cycle`.
+/// Sent along with the [`PhaseChanged`] to the cycle's first phase.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OnCycleAdvance {
+    /// How many full cycles have been completed, including this one.
+    pub completed: u32,
+}
+
+/// The game advanced to a new phase of its cycle, see `game::cycle`.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseChanged {
+    /// How many full cycles have been completed.
+    pub cycle: u32,
+    /// Index of the new phase within the cycle.
+    pub phase: u32,
+}
+
+/// Points were scored, to be multiplied by the combo, see `game::score`.
+///
+/// Scoring often reacts to other events, so these may also be written in
+/// [`AppSet::HandleEvents`]. They are applied to the score in [`AppSet::Update`],
+/// on the same tick or the next, so none are missed either way.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreEvent {
+    pub amount: u64,
+    /// The entity that scored the points, if any.
+    pub source: Option<Entity>,
+}
+
+/// The camera should shake, e.g. because of an impact.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct ShakeEvent {
+    /// Added to the camera's trauma, which goes from 0 to 1.
+    pub trauma: f32,
+}
+
+/// A wave of enemies started, see `game::spawn::wave`.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaveStarted {
+    /// The wave's number, counting from 1.
+    pub wave: u32,
+    /// How many enemies it will spawn.
+    pub enemies: u32,
+}
+
+/// A wave of enemies was defeated or ran out of time, see `game::spawn::wave`.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaveEnded {
+    pub wave: u32,
+}
+
+/// Something wants to switch to another [`Screen`].
+/// This is the only way screens should be changed, see `screen::arbiter`.
+///
+/// Unlike the other events, these may be written from any schedule, and are
+/// applied during `PreUpdate` of the next frame, before state transitions run.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub enum ScreenRequest {
+    To(Screen),
+    /// Return to the screen we came from.
+    Back,
+}
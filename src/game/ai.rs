@@ -0,0 +1,290 @@
+//! Enemy behavior, as a small state machine per enemy in its [`AiBrain`].
+//!
+//! Enemies wander until the player comes within sight, chase them, and lunge at them
+//! once close enough, with a cooldown between lunges, which [`Upgrades`] may lengthen.
+//! Badly hurt enemies flee instead.
+//! How far they see, how fast they lunge and when they flee is [`AiTuning`], which
+//! comes from the enemy's definition in its waves file, see `spawn::wave`.
+//!
+//! Brains think on fixed ticks, in [`AppSet::RecordInput`] like the player's input, and
+//! steer through the enemy's [`MovementController`]. Staggered enemies don't think.
+//! Chasing enemies with a [`PathFollow`] go around walls on the way, see `navigation`.
+//! Wandering draws from [`GameRng::spawning`], which is also only drawn from on fixed ticks.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{
+    health::Health,
+    movement::MovementController,
+    navigation::{PathFollow, Pathfinding},
+    rewind::is_rewinding,
+    rng::GameRng,
+    spawn::player::Player,
+    stagger::Staggered,
+    upgrades::Upgrades,
+};
+use crate::{screen::PlayingState, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<AiBrain>();
+    app.add_systems(
+        FixedUpdate,
+        think
+            .in_set(AppSet::RecordInput)
+            .before(Pathfinding)
+            .run_if(in_state(PlayingState::Running).and_then(not(is_rewinding))),
+    );
+}
+
+/// What an enemy is up to.
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AiState {
+    /// Amble around in random directions.
+    #[default]
+    Wander,
+    /// Head straight for the player.
+    Chase,
+    /// Lunge at where the player was when the attack started.
+    Attack,
+    /// Run away from the player.
+    Flee,
+}
+
+/// How an enemy behaves. Missing fields in RON take their defaults.
+#[derive(Reflect, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct AiTuning {
+    /// How close the player has to be to be chased, in pixels.
+    pub sight_range: f32,
+    /// How far the player has to get to be lost again, in pixels.
+    pub give_up_range: f32,
+    /// How close the player has to be to lunge at, in pixels.
+    pub attack_range: f32,
+    /// Seconds a lunge lasts.
+    pub attack_duration: f32,
+    /// Seconds between lunges.
+    pub attack_cooldown: f32,
+    /// Speed while lunging, as a multiple of the enemy's speed.
+    pub attack_speed: f32,
+    /// Speed while wandering, as a multiple of the enemy's speed.
+    pub wander_speed: f32,
+    /// Seconds between picking a new direction to wander in.
+    pub wander_interval: f32,
+    /// The fraction of health at or below which the enemy flees, or 0 to never flee.
+    pub flee_health: f32,
+}
+
+impl Default for AiTuning {
+    fn default() -> Self {
+        Self {
+            sight_range: 600.0,
+            give_up_range: 900.0,
+            attack_range: 150.0,
+            attack_duration: 0.4,
+            attack_cooldown: 1.5,
+            attack_speed: 2.5,
+            wander_speed: 0.4,
+            wander_interval: 2.0,
+            flee_health: 0.0,
+        }
+    }
+}
+
+/// The state machine driving an enemy. Needs a [`MovementController`] to steer.
+#[derive(Component, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct AiBrain {
+    pub state: AiState,
+    pub tuning: AiTuning,
+    /// Seconds since entering the current state.
+    pub elapsed: f32,
+    /// Seconds until the next lunge is allowed.
+    pub cooldown: f32,
+    /// Where the brain is steering, with a length of at most 1 before speed multipliers.
+    direction: Vec2,
+}
+
+impl AiBrain {
+    pub fn new(tuning: AiTuning) -> Self {
+        Self {
+            state: AiState::Wander,
+            tuning,
+            elapsed: 0.0,
+            cooldown: 0.0,
+            direction: Vec2::ZERO,
+        }
+    }
+
+    /// The state to be in, given the distance to the player, if there is one,
+    /// and the fraction of health left.
+    pub fn next_state(&self, distance: Option<f32>, health: f32) -> AiState {
+        let tuning = &self.tuning;
+        let Some(distance) = distance else {
+            return AiState::Wander;
+        };
+        let in_sight = match self.state {
+            AiState::Wander => distance <= tuning.sight_range,
+            // Once noticed, the player has to get further away to be lost.
+            AiState::Chase | AiState::Attack | AiState::Flee => distance <= tuning.give_up_range,
+        };
+        if !in_sight {
+            return AiState::Wander;
+        }
+        if health <= tuning.flee_health {
+            return AiState::Flee;
+        }
+        match self.state {
+            // A lunge is seen through.
+            AiState::Attack if self.elapsed < tuning.attack_duration => AiState::Attack,
+            _ if distance <= tuning.attack_range && self.cooldown <= 0.0 => AiState::Attack,
+            _ => AiState::Chase,
+        }
+    }
+
+    fn enter(&mut self, state: AiState) {
+        if self.state == AiState::Attack {
+            self.cooldown = self.tuning.attack_cooldown;
+        }
+        self.state = state;
+        self.elapsed = 0.0;
+    }
+}
+
+fn think(
+    time: Res<Time>,
+    upgrades: Res<Upgrades>,
+    mut rng: ResMut<GameRng>,
+    player_query: Query<&Transform, With<Player>>,
+    mut brain_query: Query<
+        (
+            &mut AiBrain,
+            &mut MovementController,
+            &Transform,
+            Option<&Health>,
+            Option<&mut PathFollow>,
+        ),
+        Without<Staggered>,
+    >,
+) {
+    let dt = time.delta_seconds();
+    let player = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.xy());
+    for (mut brain, mut controller, transform, health, path) in &mut brain_query {
+        brain.elapsed += dt;
+        // Counting down slower is the same as a longer cooldown.
+        brain.cooldown = (brain.cooldown - dt / upgrades.enemy_cooldown.max(0.01)).max(0.0);
+
+        let position = transform.translation.xy();
+        let distance = player.map(|player| player.distance(position));
+        let health = health.map_or(1.0, Health::fraction);
+        let next = brain.next_state(distance, health);
+        let entered = next != brain.state;
+        if entered {
+            brain.enter(next);
+        }
+
+        let toward_player =
+            player.map_or(Vec2::ZERO, |player| (player - position).normalize_or_zero());
+        let speed = match brain.state {
+            AiState::Wander => {
+                let stopped = brain.direction == Vec2::ZERO;
+                if entered || stopped || brain.elapsed >= brain.tuning.wander_interval {
+                    brain.elapsed = 0.0;
+                    let angle = rng.spawning().gen_range(0.0..TAU);
+                    brain.direction = Vec2::from_angle(angle);
+                }
+                brain.tuning.wander_speed
+            }
+            AiState::Chase => {
+                brain.direction = toward_player;
+                1.0
+            }
+            AiState::Attack => {
+                // Committed to the direction it lunged in.
+                if entered {
+                    brain.direction = toward_player;
+                }
+                brain.tuning.attack_speed
+            }
+            AiState::Flee => {
+                brain.direction = -toward_player;
+                1.0
+            }
+        };
+        controller.0 = brain.direction * speed;
+
+        // Only chasing goes around walls; lunges and fleeing are headlong.
+        if let Some(mut path) = path {
+            let target = player.filter(|_| brain.state == AiState::Chase);
+            if path.target != target {
+                path.target = target;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ai_brains_chase_lunge_and_flee() {
+        let tuning = AiTuning {
+            flee_health: 0.25,
+            ..default()
+        };
+        let mut brain = AiBrain::new(tuning);
+        assert_eq!(brain.next_state(None, 1.0), AiState::Wander);
+        assert_eq!(
+            brain.next_state(Some(tuning.sight_range + 1.0), 1.0),
+            AiState::Wander
+        );
+        assert_eq!(
+            brain.next_state(Some(tuning.sight_range), 1.0),
+            AiState::Chase
+        );
+
+        // Once chasing, the player is only lost further away.
+        brain.state = AiState::Chase;
+        assert_eq!(
+            brain.next_state(Some(tuning.sight_range + 1.0), 1.0),
+            AiState::Chase
+        );
+        assert_eq!(
+            brain.next_state(Some(tuning.give_up_range + 1.0), 1.0),
+            AiState::Wander
+        );
+        assert_eq!(
+            brain.next_state(Some(tuning.attack_range), 1.0),
+            AiState::Attack
+        );
+        brain.cooldown = 1.0;
+        assert_eq!(
+            brain.next_state(Some(tuning.attack_range), 1.0),
+            AiState::Chase
+        );
+
+        // Lunges are seen through, even if the player gets out of range.
+        brain.state = AiState::Attack;
+        assert_eq!(
+            brain.next_state(Some(tuning.sight_range), 1.0),
+            AiState::Attack
+        );
+        brain.elapsed = tuning.attack_duration;
+        assert_eq!(
+            brain.next_state(Some(tuning.sight_range), 1.0),
+            AiState::Chase
+        );
+
+        assert_eq!(
+            brain.next_state(Some(tuning.attack_range), 0.2),
+            AiState::Flee
+        );
+    }
+}
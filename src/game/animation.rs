@@ -1,4 +1,6 @@
-//! Player sprite animation.
+//! Sprite sheet animation: [`SpriteAnimation`] plays frames from a texture atlas, and an
+//! [`AnimationController`] picks which clip plays from the entity's [`AnimationState`],
+//! so gameplay code only sets states like running or being hit.
 //! This is based on multiple examples and may be very different for your game.
 //! - [Sprite flipping](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_flipping.rs)
 //! - [Sprite animation](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_animation.rs)
@@ -6,156 +8,368 @@
 
 use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 
-use super::{audio::sfx::PlaySfx, movement::MovementController};
-use crate::AppSet;
+use super::audio::sfx::PlaySfx;
+use crate::{screen::PlayingState, AppSet};
 
 pub(super) fn plugin(app: &mut App) {
-    // Animate and play sound effects based on controls.
-    app.register_type::<PlayerAnimation>();
+    app.register_type::<(AnimationController, SpriteAnimation)>();
     app.add_systems(
         Update,
         (
-            update_animation_timer.in_set(AppSet::TickTimers),
-            (
-                update_animation_movement,
-                update_animation_atlas,
-                trigger_step_sfx,
-            )
+            (play_animation_controllers, tick_sprite_animations)
                 .chain()
-                .in_set(AppSet::Update),
-        ),
+                .in_set(AppSet::TickTimers),
+            trigger_step_sfx.in_set(AppSet::Update),
+        )
+            .run_if(in_state(PlayingState::Running)),
     );
 }
 
-/// Update the sprite direction and animation state (idling/walking).
-fn update_animation_movement(
-    mut player_query: Query<(&MovementController, &mut Sprite, &mut PlayerAnimation)>,
+/// Switch to the clip for each controller's state, after its interrupt finishes.
+fn play_animation_controllers(
+    mut commands: Commands,
+    mut controller_query: Query<(
+        Entity,
+        &mut AnimationController,
+        Option<&mut SpriteAnimation>,
+    )>,
 ) {
-    for (controller, mut sprite, mut animation) in &mut player_query {
-        let dx = controller.0.x;
-        if dx != 0.0 {
-            sprite.flip_x = dx < 0.0;
+    for (entity, mut controller, animation) in &mut controller_query {
+        if animation
+            .as_ref()
+            .is_some_and(|animation| animation.is_finished())
+        {
+            controller.finish_interrupt();
         }
-
-        let animation_state = if controller.0 == Vec2::ZERO {
-            PlayerAnimationState::Idling
-        } else {
-            PlayerAnimationState::Walking
+        let Some(clip) = controller.next_clip() else {
+            continue;
         };
-        animation.update_state(animation_state);
-    }
-}
-
-/// Update the animation timer.
-fn update_animation_timer(time: Res<Time>, mut query: Query<&mut PlayerAnimation>) {
-    for mut animation in &mut query {
-        animation.update_timer(time.delta());
+        match animation {
+            Some(mut animation) => *animation = clip,
+            None => {
+                commands.entity(entity).insert(clip);
+            }
+        }
     }
 }
 
-/// Update the texture atlas to reflect changes in the animation.
-fn update_animation_atlas(mut query: Query<(&PlayerAnimation, &mut TextureAtlas)>) {
-    for (animation, mut atlas) in &mut query {
-        if animation.changed() {
-            atlas.index = animation.get_atlas_index();
+/// Advance sprite animations and their atlases, and trigger [`AnimationFinished`]
+/// on those that just finished.
+fn tick_sprite_animations(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut animation_query: Query<(Entity, &mut SpriteAnimation, &mut TextureAtlas)>,
+) {
+    for (entity, mut animation, mut atlas) in &mut animation_query {
+        if animation.tick(time.delta()) {
+            commands.trigger_targets(AnimationFinished, entity);
+        }
+        if atlas.index != animation.atlas_index() {
+            atlas.index = animation.atlas_index();
         }
     }
 }
 
-/// If the player is moving, play a step sound effect synchronized with the animation.
-fn trigger_step_sfx(mut commands: Commands, mut step_query: Query<&PlayerAnimation>) {
-    for animation in &mut step_query {
-        if animation.state == PlayerAnimationState::Walking
+/// If the player is running, play a step sound effect synchronized with the animation.
+fn trigger_step_sfx(
+    mut commands: Commands,
+    step_query: Query<(&AnimationController, &SpriteAnimation)>,
+) {
+    for (controller, animation) in &step_query {
+        if controller.current() == AnimationState::Run
             && animation.changed()
-            && (animation.frame == 2 || animation.frame == 5)
+            && (animation.frame() == 2 || animation.frame() == 5)
         {
             commands.trigger(PlaySfx::RandomStep);
         }
     }
 }
 
-/// Component that tracks player's animation state.
-/// It is tightly bound to the texture atlas we use.
-#[derive(Component, Reflect)]
-#[reflect(Component)]
-pub struct PlayerAnimation {
-    timer: Timer,
-    frame: usize,
-    state: PlayerAnimationState,
+/// What an animated entity is doing, which picks the clip its [`AnimationController`] plays.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AnimationState {
+    #[default]
+    Idle,
+    Run,
+    Hit,
+    Stagger,
+    Death,
 }
 
-#[derive(Reflect, PartialEq)]
-pub enum PlayerAnimationState {
-    Idling,
-    Walking,
+/// Plays a clip for each [`AnimationState`], as a [`SpriteAnimation`] on the same entity.
+///
+/// The state set with [`set_state`](Self::set_state) plays until it changes.
+/// An [`interrupt`](Self::interrupt) plays its clip once on top of that, and then goes back.
+/// States without a clip keep playing the previous clip.
+#[derive(Component, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct AnimationController {
+    clips: HashMap<AnimationState, SpriteAnimation>,
+    state: AnimationState,
+    interrupt: Option<AnimationState>,
+    /// The clip that is playing, if it has been started.
+    playing: Option<AnimationState>,
+    /// Transitions that are not allowed, from the first state to the second.
+    blocked: Vec<(AnimationState, AnimationState)>,
+    /// States that can't be left.
+    finals: Vec<AnimationState>,
 }
 
-impl PlayerAnimation {
-    /// The number of idle frames.
-    const IDLE_FRAMES: usize = 2;
-    /// The duration of each idle frame.
-    const IDLE_INTERVAL: Duration = Duration::from_millis(500);
-
-    fn idling() -> Self {
+impl AnimationController {
+    /// A controller that starts idling with the `idle` clip.
+    pub fn new(idle: SpriteAnimation) -> Self {
         Self {
-            timer: Timer::new(Self::IDLE_INTERVAL, TimerMode::Repeating),
-            frame: 0,
-            state: PlayerAnimationState::Idling,
+            clips: HashMap::from([(AnimationState::Idle, idle)]),
+            state: AnimationState::Idle,
+            interrupt: None,
+            playing: None,
+            blocked: Vec::new(),
+            finals: Vec::new(),
         }
     }
 
-    /// The number of walking frames.
-    const WALKING_FRAMES: usize = 6;
-    /// The duration of each walking frame.
-    const WALKING_INTERVAL: Duration = Duration::from_millis(50);
+    pub fn with_clip(mut self, state: AnimationState, clip: SpriteAnimation) -> Self {
+        self.clips.insert(state, clip);
+        self
+    }
+
+    /// Don't go from `from` to `to`, for example to keep staggering through a hit.
+    pub fn with_blocked(mut self, from: AnimationState, to: AnimationState) -> Self {
+        self.blocked.push((from, to));
+        self
+    }
+
+    /// Never leave `state` once it starts, like death.
+    pub fn with_final(mut self, state: AnimationState) -> Self {
+        self.finals.push(state);
+        self
+    }
+
+    /// Whether the rules allow going from `from` to `to`.
+    pub fn allows(&self, from: AnimationState, to: AnimationState) -> bool {
+        from == to || (!self.finals.contains(&from) && !self.blocked.contains(&(from, to)))
+    }
+
+    /// The state whose clip should be playing.
+    pub fn current(&self) -> AnimationState {
+        self.interrupt.unwrap_or(self.state)
+    }
+
+    /// Play `state` until another one is set, if the rules allow it.
+    /// An interrupt that is playing finishes first, unless `state` is final.
+    pub fn set_state(&mut self, state: AnimationState) {
+        if self.state == state || !self.allows(self.current(), state) {
+            return;
+        }
+        self.state = state;
+        if self.finals.contains(&state) {
+            self.interrupt = None;
+        }
+    }
+
+    /// Play `state` once, then go back to the current state, if the rules allow it.
+    /// Restarts the interrupt if it is already playing.
+    pub fn interrupt(&mut self, state: AnimationState) {
+        if self.allows(self.current(), state) {
+            self.interrupt = Some(state);
+            self.playing = None;
+        }
+    }
 
-    fn walking() -> Self {
+    /// The interrupt's clip ran out, so go back to the current state.
+    fn finish_interrupt(&mut self) {
+        if self.interrupt.is_some() && self.playing == self.interrupt {
+            self.interrupt = None;
+        }
+    }
+
+    /// The clip to start, if the state changed since the last one started.
+    pub fn next_clip(&mut self) -> Option<SpriteAnimation> {
+        let current = self.current();
+        if self.playing == Some(current) {
+            return None;
+        }
+        let clip = self.clips.get(&current)?.clone();
+        self.playing = Some(current);
+        Some(if self.interrupt.is_some() {
+            clip.with_mode(AnimationMode::Once)
+        } else {
+            clip
+        })
+    }
+}
+
+/// What a [`SpriteAnimation`] does after its last frame.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnimationMode {
+    /// Start again from the first frame.
+    #[default]
+    Loop,
+    /// Play backwards to the first frame, then forwards again.
+    PingPong,
+    /// Stay on the last frame and trigger [`AnimationFinished`].
+    Once,
+}
+
+/// Plays a run of consecutive frames from the entity's [`TextureAtlas`].
+#[derive(Component, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct SpriteAnimation {
+    /// The atlas index of the first frame.
+    pub first: usize,
+    /// The number of frames.
+    pub frames: usize,
+    pub mode: AnimationMode,
+    timer: Timer,
+    frame: usize,
+    /// Whether a [`AnimationMode::PingPong`] animation is playing backwards.
+    reversed: bool,
+    /// Whether the frame changed on the last tick.
+    changed: bool,
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    /// A looping animation of `frames` frames starting at `first`, at `fps` frames per second.
+    pub fn new(first: usize, frames: usize, fps: f32) -> Self {
         Self {
-            timer: Timer::new(Self::WALKING_INTERVAL, TimerMode::Repeating),
+            first,
+            frames: frames.max(1),
+            mode: AnimationMode::Loop,
+            timer: Timer::from_seconds(1.0 / fps.max(f32::EPSILON), TimerMode::Repeating),
             frame: 0,
-            state: PlayerAnimationState::Walking,
+            reversed: false,
+            changed: false,
+            finished: false,
         }
     }
 
-    pub fn new() -> Self {
-        Self::idling()
+    pub fn with_mode(mut self, mode: AnimationMode) -> Self {
+        self.mode = mode;
+        self
     }
 
-    /// Update animation timers.
-    pub fn update_timer(&mut self, delta: Duration) {
+    /// Advance by `delta`, returning whether a [`AnimationMode::Once`] animation just finished.
+    pub fn tick(&mut self, delta: Duration) -> bool {
+        self.changed = false;
+        if self.finished {
+            return false;
+        }
         self.timer.tick(delta);
-        if !self.timer.finished() {
-            return;
+        let frame = self.frame;
+        for _ in 0..self.timer.times_finished_this_tick() {
+            self.step();
+            if self.finished {
+                break;
+            }
         }
-        self.frame = (self.frame + 1)
-            % match self.state {
-                PlayerAnimationState::Idling => Self::IDLE_FRAMES,
-                PlayerAnimationState::Walking => Self::WALKING_FRAMES,
-            };
+        self.changed = self.frame != frame;
+        self.finished
     }
 
-    /// Update animation state if it changes.
-    pub fn update_state(&mut self, state: PlayerAnimationState) {
-        if self.state != state {
-            match state {
-                PlayerAnimationState::Idling => *self = Self::idling(),
-                PlayerAnimationState::Walking => *self = Self::walking(),
+    fn step(&mut self) {
+        let last = self.frames - 1;
+        match self.mode {
+            AnimationMode::Loop => self.frame = (self.frame + 1) % self.frames,
+            AnimationMode::Once if self.frame < last => self.frame += 1,
+            AnimationMode::Once => self.finished = true,
+            AnimationMode::PingPong if last == 0 => {}
+            AnimationMode::PingPong => {
+                if self.frame == 0 {
+                    self.reversed = false;
+                } else if self.frame == last {
+                    self.reversed = true;
+                }
+                if self.reversed {
+                    self.frame -= 1;
+                } else {
+                    self.frame += 1;
+                }
             }
         }
     }
 
-    /// Whether animation changed this tick.
+    /// The current frame, counting from 0.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Whether the frame changed on the last tick.
     pub fn changed(&self) -> bool {
-        self.timer.finished()
+        self.changed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
     }
 
     /// Return sprite index in the atlas.
-    pub fn get_atlas_index(&self) -> usize {
-        match self.state {
-            PlayerAnimationState::Idling => self.frame,
-            PlayerAnimationState::Walking => 6 + self.frame,
+    pub fn atlas_index(&self) -> usize {
+        self.first + self.frame
+    }
+}
+
+/// Triggered on an entity when its [`AnimationMode::Once`] animation finishes,
+/// for example to go back to idling after an attack:
+/// `.observe(|trigger: Trigger<AnimationFinished>, mut commands: Commands| ...)`.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationFinished;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sprite_animations_follow_their_mode() {
+        let mut looping = SpriteAnimation::new(4, 3, 10.0);
+        // A tenth of a second isn't exact as a float, so tick by what the timer ended up with.
+        let frame = looping.timer.duration();
+        let mut once = SpriteAnimation::new(4, 3, 10.0).with_mode(AnimationMode::Once);
+        let mut ping_pong = SpriteAnimation::new(4, 3, 10.0).with_mode(AnimationMode::PingPong);
+        let mut frames = (vec![], vec![], vec![]);
+        for _ in 0..4 {
+            assert!(!looping.tick(frame));
+            assert!(!ping_pong.tick(frame));
+            frames.0.push(looping.atlas_index());
+            frames.2.push(ping_pong.atlas_index());
         }
+        for _ in 0..2 {
+            assert!(!once.tick(frame));
+            frames.1.push(once.atlas_index());
+        }
+        assert_eq!(frames, (vec![5, 6, 4, 5], vec![5, 6], vec![5, 6, 5, 4]));
+
+        // One long frame can finish the animation, but only once.
+        let mut once = SpriteAnimation::new(0, 3, 10.0).with_mode(AnimationMode::Once);
+        assert!(once.tick(frame * 5));
+        assert!(once.is_finished());
+        assert_eq!(once.frame(), 2);
+        assert!(!once.tick(frame));
+    }
+
+    #[test]
+    fn animation_controllers_return_from_interrupts_but_not_death() {
+        let mut controller = AnimationController::new(SpriteAnimation::new(0, 2, 10.0))
+            .with_clip(AnimationState::Run, SpriteAnimation::new(2, 4, 10.0))
+            .with_clip(AnimationState::Hit, SpriteAnimation::new(6, 1, 10.0))
+            .with_final(AnimationState::Death);
+        assert_eq!(controller.next_clip().map(|clip| clip.first), Some(0));
+        assert_eq!(controller.next_clip(), None);
+
+        controller.set_state(AnimationState::Run);
+        controller.interrupt(AnimationState::Hit);
+        let hit = controller.next_clip().unwrap();
+        assert_eq!((hit.first, hit.mode), (6, AnimationMode::Once));
+        assert_eq!(controller.current(), AnimationState::Hit);
+
+        // Dying cancels the hit, and there is no death clip, so the hit clip keeps playing.
+        controller.set_state(AnimationState::Death);
+        assert_eq!(controller.current(), AnimationState::Death);
+        assert_eq!(controller.next_clip(), None);
+        controller.set_state(AnimationState::Idle);
+        controller.interrupt(AnimationState::Hit);
+        assert_eq!(controller.current(), AnimationState::Death);
     }
 }
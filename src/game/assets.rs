@@ -1,46 +1,159 @@
+//! Typed catalogs of the game's assets. The loading screen inserts them, so every asset
+//! starts loading at once, and waits until they are ready. Systems take the catalog
+//! they need as a resource and get strongly-typed handles from it, instead of loading
+//! paths themselves.
+//!
+//! Each path is written down exactly once, in the `FromWorld` implementation of its
+//! catalog, so a missing file is reported on the loading screen instead of showing up
+//! as a missing sprite or sound later on.
+
 use bevy::{
+    asset::{LoadState, RecursiveDependencyLoadState},
     prelude::*,
     render::texture::{ImageLoaderSettings, ImageSampler},
     utils::HashMap,
 };
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::{
+    cutscene::CutsceneData,
+    fallback::Fallbacks,
+    spawn::{level::LevelData, wave::WaveData},
+};
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<HandleMap<ImageKey>>();
-    app.init_resource::<HandleMap<ImageKey>>();
+    app.register_type::<(ImageAssets, AudioAssets, FontAssets, LevelAssets)>();
+}
+
+/// A resource of asset handles, which the loading screen can report progress for.
+pub trait AssetCatalog: Resource {
+    fn handles(&self) -> Vec<UntypedHandle>;
+
+    /// How far along each of these assets is, including what they depend on.
+    fn reports(&self, asset_server: &AssetServer, fallbacks: &Fallbacks) -> Vec<AssetReport> {
+        self.handles()
+            .into_iter()
+            .map(|handle| {
+                let status = match asset_server.get_recursive_dependency_load_state(handle.id()) {
+                    Some(RecursiveDependencyLoadState::Loaded) => AssetStatus::Loaded,
+                    Some(RecursiveDependencyLoadState::Failed) => {
+                        let error = match asset_server.get_load_state(handle.id()) {
+                            Some(LoadState::Failed(error)) => error.to_string(),
+                            _ => "Something it depends on failed to load".to_string(),
+                        };
+                        if fallbacks.contains(handle.id()) {
+                            AssetStatus::Fallback(error)
+                        } else {
+                            AssetStatus::Failed(error)
+                        }
+                    }
+                    _ => AssetStatus::Loading,
+                };
+                AssetReport {
+                    path: handle
+                        .path()
+                        .map_or_else(|| format!("{:?}", handle.id()), ToString::to_string),
+                    status,
+                }
+            })
+            .collect()
+    }
+
+    /// How many of these assets are done loading, and which ones failed to.
+    fn load_progress(&self, asset_server: &AssetServer, fallbacks: &Fallbacks) -> LoadProgress {
+        let mut progress = LoadProgress::default();
+        for report in self.reports(asset_server, fallbacks) {
+            progress.total += 1;
+            match report.status {
+                AssetStatus::Loaded | AssetStatus::Fallback(_) => progress.done += 1,
+                AssetStatus::Failed(_) => progress.failed.push(report.path),
+                AssetStatus::Loading => (),
+            }
+        }
+        progress
+    }
+}
 
-    app.register_type::<HandleMap<SfxKey>>();
-    app.init_resource::<HandleMap<SfxKey>>();
+/// How far along loading an asset is, named by its path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetReport {
+    pub path: String,
+    pub status: AssetStatus,
+}
 
-    app.register_type::<HandleMap<SoundtrackKey>>();
-    app.init_resource::<HandleMap<SoundtrackKey>>();
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetStatus {
+    Loading,
+    Loaded,
+    /// Failed to load, with the error saying why.
+    Failed(String),
+    /// Failed to load like [`AssetStatus::Failed`], but was replaced with a fallback,
+    /// see `game::fallback`.
+    Fallback(String),
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
-pub enum ImageKey {
-    Ducky,
+/// Number of assets done loading out of a total. Add them up to combine catalogs.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LoadProgress {
+    pub done: usize,
+    pub total: usize,
+    /// Paths of the assets that failed to load.
+    pub failed: Vec<String>,
 }
 
-impl AssetKey for ImageKey {
-    type Asset = Image;
+impl LoadProgress {
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done == self.total
+    }
 }
 
-impl FromWorld for HandleMap<ImageKey> {
+impl std::ops::Add for LoadProgress {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.done += other.done;
+        self.total += other.total;
+        self.failed.extend(other.failed);
+        self
+    }
+}
+
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct ImageAssets {
+    /// A 6x2 sheet of 32x32 frames, see `spawn::player::ducky_atlas_layout`.
+    pub ducky: Handle<Image>,
+}
+
+impl FromWorld for ImageAssets {
     fn from_world(world: &mut World) -> Self {
         let asset_server = world.resource::<AssetServer>();
-        [(
-            ImageKey::Ducky,
-            asset_server.load_with_settings(
+        Self {
+            ducky: asset_server.load_with_settings(
                 "images/ducky.png",
                 |settings: &mut ImageLoaderSettings| {
                     settings.sampler = ImageSampler::nearest();
                 },
             ),
-        )]
-        .into()
+        }
+    }
+}
+
+impl AssetCatalog for ImageAssets {
+    fn handles(&self) -> Vec<UntypedHandle> {
+        vec![self.ducky.clone().untyped()]
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Deserialize, Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect)]
 pub enum SfxKey {
     ButtonHover,
     ButtonPress,
@@ -48,80 +161,188 @@ pub enum SfxKey {
     Step2,
     Step3,
     Step4,
+    Pickup,
+    FocusMoved,
+    EdgeReached,
+    InvalidAction,
+    TabSwitched,
 }
 
-impl AssetKey for SfxKey {
-    type Asset = AudioSource;
-}
+impl SfxKey {
+    const ALL: [Self; 11] = [
+        Self::ButtonHover,
+        Self::ButtonPress,
+        Self::Step1,
+        Self::Step2,
+        Self::Step3,
+        Self::Step4,
+        Self::Pickup,
+        Self::FocusMoved,
+        Self::EdgeReached,
+        Self::InvalidAction,
+        Self::TabSwitched,
+    ];
 
-impl FromWorld for HandleMap<SfxKey> {
-    fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
-        [
-            (
-                SfxKey::ButtonHover,
-                asset_server.load("audio/sfx/button_hover.ogg"),
-            ),
-            (
-                SfxKey::ButtonPress,
-                asset_server.load("audio/sfx/button_press.ogg"),
-            ),
-            (SfxKey::Step1, asset_server.load("audio/sfx/step1.ogg")),
-            (SfxKey::Step2, asset_server.load("audio/sfx/step2.ogg")),
-            (SfxKey::Step3, asset_server.load("audio/sfx/step3.ogg")),
-            (SfxKey::Step4, asset_server.load("audio/sfx/step4.ogg")),
-        ]
-        .into()
+    fn path(self) -> &'static str {
+        match self {
+            Self::ButtonHover => "audio/sfx/button_hover.ogg",
+            Self::ButtonPress => "audio/sfx/button_press.ogg",
+            Self::Step1 => "audio/sfx/step1.ogg",
+            Self::Step2 => "audio/sfx/step2.ogg",
+            Self::Step3 => "audio/sfx/step3.ogg",
+            Self::Step4 => "audio/sfx/step4.ogg",
+            // The button press, pitched up, until there is a sound of its own.
+            Self::Pickup => "audio/sfx/button_press.ogg",
+            // UI cues are told apart by pitch, see `audio::sfx`.
+            Self::FocusMoved | Self::EdgeReached => "audio/sfx/button_hover.ogg",
+            Self::InvalidAction | Self::TabSwitched => "audio/sfx/button_press.ogg",
+        }
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect)]
 pub enum SoundtrackKey {
     Credits,
     Gameplay,
+    Endless,
+}
+
+impl SoundtrackKey {
+    const ALL: [Self; 3] = [Self::Credits, Self::Gameplay, Self::Endless];
+
+    fn path(self) -> &'static str {
+        match self {
+            Self::Credits => "audio/soundtracks/Monkeys Spinning Monkeys.ogg",
+            Self::Gameplay => "audio/soundtracks/Fluffing A Duck.ogg",
+            Self::Endless => "audio/soundtracks/Kevin MacLeod - Satiate.mp3",
+        }
+    }
+}
+
+/// Sound effects and soundtracks. The keys stay around so sounds can be named in events.
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct AudioAssets {
+    sfx: HashMap<SfxKey, Handle<AudioSource>>,
+    soundtracks: HashMap<SoundtrackKey, Handle<AudioSource>>,
 }
 
-impl AssetKey for SoundtrackKey {
-    type Asset = AudioSource;
+impl AudioAssets {
+    pub fn sfx(&self, key: SfxKey) -> Handle<AudioSource> {
+        self.sfx[&key].clone_weak()
+    }
+
+    pub fn soundtrack(&self, key: SoundtrackKey) -> Handle<AudioSource> {
+        self.soundtracks[&key].clone_weak()
+    }
 }
 
-impl FromWorld for HandleMap<SoundtrackKey> {
+impl FromWorld for AudioAssets {
     fn from_world(world: &mut World) -> Self {
         let asset_server = world.resource::<AssetServer>();
-        [
-            (
-                SoundtrackKey::Credits,
-                asset_server.load("audio/soundtracks/Monkeys Spinning Monkeys.ogg"),
-            ),
-            (
-                SoundtrackKey::Gameplay,
-                asset_server.load("audio/soundtracks/Fluffing A Duck.ogg"),
-            ),
-        ]
-        .into()
+        // Every key has a path, so the lookups above can't miss.
+        Self {
+            sfx: SfxKey::ALL
+                .map(|key| (key, asset_server.load(key.path())))
+                .into(),
+            soundtracks: SoundtrackKey::ALL
+                .map(|key| (key, asset_server.load(key.path())))
+                .into(),
+        }
+    }
+}
+
+impl AssetCatalog for AudioAssets {
+    fn handles(&self) -> Vec<UntypedHandle> {
+        self.sfx
+            .values()
+            .chain(self.soundtracks.values())
+            .map(|handle| handle.clone().untyped())
+            .collect()
     }
 }
 
-pub trait AssetKey: Sized {
-    type Asset: Asset;
+/// Parse a catalog embedded with `include_str!`, or log why it couldn't be and return an
+/// empty one.
+///
+/// Small catalogs that are looked up by id, like the items and the skins, are embedded
+/// instead of loaded like the assets above. Plugins insert them as plain resources while
+/// the app is built, so screens and tests can read them without waiting on the loading
+/// screen, at the cost of hot reloading. Each one has a test that parses it, so a broken
+/// file fails the tests instead of shipping an empty catalog.
+pub fn parse_embedded<T: DeserializeOwned + Default>(source: &str, what: &str) -> T {
+    ron::from_str(source)
+        .inspect_err(|e| error!("Could not parse the {what}: {e}"))
+        .unwrap_or_default()
 }
 
-#[derive(Resource, Reflect, Deref, DerefMut)]
+/// Names of the levels, each in `assets/levels/<name>.level.ron`. Runs start in the first.
+pub const LEVELS: [&str; 2] = ["main", "grove"];
+
+#[derive(Resource, Debug, Reflect)]
 #[reflect(Resource)]
-pub struct HandleMap<K: AssetKey>(HashMap<K, Handle<K::Asset>>);
+pub struct LevelAssets {
+    pub levels: HashMap<String, Handle<LevelData>>,
+    pub waves: Handle<WaveData>,
+    pub cutscenes: Handle<CutsceneData>,
+}
 
-impl<K: AssetKey, T> From<T> for HandleMap<K>
-where
-    T: Into<HashMap<K, Handle<K::Asset>>>,
-{
-    fn from(value: T) -> Self {
-        Self(value.into())
+impl LevelAssets {
+    /// The level with this name, or the first level if there is none, like for a
+    /// level removed since the game was saved.
+    pub fn level(&self, name: &str) -> &Handle<LevelData> {
+        self.levels.get(name).unwrap_or(&self.levels[LEVELS[0]])
+    }
+}
+
+impl FromWorld for LevelAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            levels: LEVELS
+                .map(|name| {
+                    let path = format!("levels/{name}.level.ron");
+                    (name.to_string(), asset_server.load(path))
+                })
+                .into(),
+            waves: asset_server.load("waves/main.waves.ron"),
+            cutscenes: asset_server.load("cutscenes/main.cutscenes.ron"),
+        }
+    }
+}
+
+impl AssetCatalog for LevelAssets {
+    fn handles(&self) -> Vec<UntypedHandle> {
+        self.levels
+            .values()
+            .map(|handle| handle.clone().untyped())
+            .chain([
+                self.waves.clone().untyped(),
+                self.cutscenes.clone().untyped(),
+            ])
+            .collect()
+    }
+}
+
+/// Optional fonts. Text keeps the default font until these have loaded,
+/// or for good if they fail to.
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct FontAssets {
+    pub dyslexic: Handle<Font>,
+}
+
+impl FromWorld for FontAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            dyslexic: asset_server.load("fonts/OpenDyslexic-Regular.otf"),
+        }
     }
 }
 
-impl<K: AssetKey> HandleMap<K> {
-    pub fn all_loaded(&self, asset_server: &AssetServer) -> bool {
-        self.values()
-            .all(|x| asset_server.is_loaded_with_dependencies(x))
+impl AssetCatalog for FontAssets {
+    fn handles(&self) -> Vec<UntypedHandle> {
+        vec![self.dyslexic.clone().untyped()]
     }
 }
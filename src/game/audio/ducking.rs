@@ -0,0 +1,70 @@
+//! Lowers the music while priority sound effects play, so they can be heard clearly.
+//! Play a sound with [`PlaySfx::Priority`](super::sfx::PlaySfx::Priority) to duck the music.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::sfx::PlayingSfx;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<MusicDucking>();
+    app.add_systems(Update, update_ducking.in_set(DuckingSet));
+}
+
+/// Systems reading [`MusicDucking::gain`] should run after this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DuckingSet;
+
+/// How far and how fast the music is ducked.
+#[derive(Resource, Debug)]
+pub struct MusicDucking {
+    /// Gain applied to the music while fully ducked.
+    pub ducked_gain: f32,
+    /// Time to go from full volume to fully ducked.
+    pub attack: Duration,
+    /// Time to return from fully ducked to full volume.
+    pub release: Duration,
+    /// The current gain, from `ducked_gain` to 1.
+    gain: f32,
+}
+
+impl Default for MusicDucking {
+    fn default() -> Self {
+        Self {
+            ducked_gain: 0.35,
+            attack: Duration::from_millis(80),
+            release: Duration::from_millis(600),
+            gain: 1.0,
+        }
+    }
+}
+
+impl MusicDucking {
+    /// Gain to apply to the music right now.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+}
+
+fn update_ducking(
+    time: Res<Time>,
+    mut ducking: ResMut<MusicDucking>,
+    sfx_query: Query<&PlayingSfx>,
+) {
+    let ducked = sfx_query.iter().any(|sfx| sfx.priority);
+    let (target, duration) = if ducked {
+        (ducking.ducked_gain, ducking.attack)
+    } else {
+        (1.0, ducking.release)
+    };
+    // Move linearly, so attack and release take the same time however deep the duck is.
+    let step = (1.0 - ducking.ducked_gain) * time.delta_seconds()
+        / duration.as_secs_f32().max(f32::EPSILON);
+    let gain = ducking.gain;
+    ducking.gain = if gain > target {
+        (gain - step).max(target)
+    } else {
+        (gain + step).min(target)
+    };
+}
@@ -0,0 +1,91 @@
+//! Output levels of the music and sound effect buses, and the UI sub-bus of the latter,
+//! for the meters in the settings menu.
+//! Bevy doesn't expose the mixed samples, so the meters follow the gain of every playing
+//! sound rather than its waveform: they show what each volume setting controls,
+//! not how loud a particular moment of a track is.
+
+use bevy::prelude::*;
+
+use super::{
+    ducking::DuckingSet,
+    sfx::{PlayingSfx, SfxBus},
+    soundtrack::IsSoundtrack,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<AudioMeters>();
+    app.add_systems(Update, update_meters.after(DuckingSet));
+}
+
+/// Current levels of each bus, where `master` is everything that is played.
+#[derive(Resource, Debug, Default)]
+pub struct AudioMeters {
+    pub master: BusLevel,
+    pub soundtrack: BusLevel,
+    pub sfx: BusLevel,
+    /// The part of `sfx` that is UI sounds.
+    pub ui: BusLevel,
+}
+
+/// Level of one bus, as a fraction of the meter's range.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BusLevel {
+    /// Combined level of everything playing on the bus.
+    pub rms: f32,
+    /// Highest recent level, falling back slowly after a sound ends.
+    pub peak: f32,
+}
+
+/// Quietest level that still shows on a meter.
+const METER_MIN_DECIBELS: f32 = -48.0;
+/// How fast the peak marker falls, in meter fractions per second.
+const PEAK_FALL_RATE: f32 = 0.5;
+
+impl BusLevel {
+    fn update(&mut self, gains: impl Iterator<Item = f32>, delta: f32) {
+        // Uncorrelated sounds add up in power, not amplitude.
+        let power = gains.map(|gain| gain * gain).sum::<f32>();
+        self.rms = meter_fraction(power.sqrt());
+        self.peak = (self.peak - PEAK_FALL_RATE * delta).max(self.rms);
+    }
+}
+
+/// Map an amplitude onto the meter, evenly in decibels.
+fn meter_fraction(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        return 0.0;
+    }
+    let decibels = 20.0 * amplitude.log10();
+    (1.0 - decibels / METER_MIN_DECIBELS).clamp(0.0, 1.0)
+}
+
+fn update_meters(
+    time: Res<Time<Real>>,
+    mut meters: ResMut<AudioMeters>,
+    soundtrack_query: Query<&AudioSink, With<IsSoundtrack>>,
+    sfx_query: Query<(&AudioSink, &PlayingSfx)>,
+) {
+    // Real time, so the meters settle even while the game is paused.
+    let delta = time.delta_seconds();
+    // Sink volumes already include the global volume.
+    let gain = |sink: &AudioSink| {
+        if sink.is_paused() || sink.empty() {
+            0.0
+        } else {
+            sink.volume()
+        }
+    };
+    meters
+        .soundtrack
+        .update(soundtrack_query.iter().map(gain), delta);
+    let sfx_sinks = || sfx_query.iter().map(|(sink, _)| sink);
+    meters.sfx.update(sfx_sinks().map(gain), delta);
+    let ui_sinks = sfx_query
+        .iter()
+        .filter(|(_, playing)| playing.bus == SfxBus::Ui)
+        .map(|(sink, _)| sink);
+    meters.ui.update(ui_sinks.map(gain), delta);
+    meters
+        .master
+        .update(soundtrack_query.iter().chain(sfx_sinks()).map(gain), delta);
+}
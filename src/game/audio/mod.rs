@@ -1,8 +1,15 @@
+pub mod ducking;
+pub mod meter;
 pub mod sfx;
 pub mod soundtrack;
 
 use bevy::prelude::*;
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins((sfx::plugin, soundtrack::plugin));
+    app.add_plugins((
+        ducking::plugin,
+        meter::plugin,
+        sfx::plugin,
+        soundtrack::plugin,
+    ));
 }
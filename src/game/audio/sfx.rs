@@ -1,43 +1,243 @@
-use bevy::{audio::PlaybackMode, prelude::*};
-use rand::seq::SliceRandom;
+//! One-shot sound effects, played through [`PlaySfx`].
+//! Each sound gets a little random pitch variance so repeats don't sound mechanical,
+//! and a cap on how many copies can play at once, so bursts don't clip or pile up entities.
+//!
+//! Sounds of the menus are on the [`SfxBus::Ui`] sub-bus, whose volume is relative to
+//! the SFX volume. Navigation [`UiCue`]s each have a volume of their own on top of that.
 
-use crate::game::assets::{HandleMap, SfxKey};
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::game::{
+    assets::{AudioAssets, SfxKey},
+    rng::GameRng,
+};
 use crate::GameSettings;
 
 pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(PlayingSfx, SfxBus, UiCue)>();
     app.observe(play_sfx);
 }
 
 fn play_sfx(
     trigger: Trigger<PlaySfx>,
     mut commands: Commands,
-    sfx_handles: Res<HandleMap<SfxKey>>,
+    audio: Res<AudioAssets>,
     settings: Res<GameSettings>,
+    mut game_rng: ResMut<GameRng>,
+    playing_query: Query<&PlayingSfx>,
 ) {
-    let sfx_key = match trigger.event() {
-        PlaySfx::Key(key) => *key,
-        PlaySfx::RandomStep => random_step(),
+    let rng = game_rng.vfx();
+    let (sfx_key, priority) = match trigger.event() {
+        PlaySfx::Key(key) => (*key, false),
+        PlaySfx::Priority(key) => (*key, true),
+        PlaySfx::RandomStep => (random_step(rng), false),
     };
-    commands.spawn(AudioSourceBundle {
-        source: sfx_handles[&sfx_key].clone_weak(),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Despawn,
-            volume: (&settings.sfx_volume_level_relative).into(),
-            ..default()
+    let SfxPlayback {
+        bus,
+        max_instances,
+        pitch,
+        pitch_variance,
+    } = playback(sfx_key);
+    let playing = playing_query
+        .iter()
+        .filter(|playing| playing.key == sfx_key)
+        .count();
+    if playing >= max_instances {
+        return;
+    }
+    let speed = pitch + rng.gen_range(-pitch_variance..=pitch_variance);
+    let mut gain = Volume::from(&settings.sfx_volume_level_relative).get();
+    if bus == SfxBus::Ui {
+        gain *= settings.ui_volume_level_relative.gain();
+    }
+    if let Some(cue) = UiCue::of(sfx_key) {
+        gain *= settings.ui_cue_volumes.get(cue).gain();
+    }
+    if gain <= 0.0 {
+        return;
+    }
+    commands.spawn((
+        Name::new(format!("Sfx {sfx_key:?}")),
+        AudioSourceBundle {
+            source: audio.sfx(sfx_key),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new(gain),
+                speed,
+                ..default()
+            },
+        },
+        PlayingSfx {
+            key: sfx_key,
+            bus,
+            priority,
         },
-    });
+    ));
 }
 
 /// Trigger this event to play a single sound effect.
+/// The entity playing it despawns itself once the sound is done.
 #[derive(Event)]
 pub enum PlaySfx {
     Key(SfxKey),
+    /// An important sound, which lowers the music while it plays.
+    Priority(SfxKey),
     RandomStep,
 }
 
-fn random_step() -> SfxKey {
+/// Marks an entity playing a sound effect, to count copies of the same sound.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(super) struct PlayingSfx {
+    key: SfxKey,
+    pub(super) bus: SfxBus,
+    /// Whether the music is ducked while this plays.
+    pub(super) priority: bool,
+}
+
+/// Which volume setting applies to a sound, on top of the SFX volume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum SfxBus {
+    /// Just the SFX volume.
+    Gameplay,
+    /// The UI sounds volume as well.
+    Ui,
+}
+
+/// Sounds for navigating menus without looking, each with its own volume setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum UiCue {
+    /// Focus moved to another widget.
+    FocusMoved,
+    /// Focus wrapped around, or couldn't move any further.
+    EdgeReached,
+    /// Something couldn't be done, like turning a setting past its limit.
+    InvalidAction,
+    /// Another tab was selected.
+    TabSwitched,
+}
+
+impl UiCue {
+    pub const ALL: [Self; 4] = [
+        Self::FocusMoved,
+        Self::EdgeReached,
+        Self::InvalidAction,
+        Self::TabSwitched,
+    ];
+
+    pub fn key(self) -> SfxKey {
+        match self {
+            Self::FocusMoved => SfxKey::FocusMoved,
+            Self::EdgeReached => SfxKey::EdgeReached,
+            Self::InvalidAction => SfxKey::InvalidAction,
+            Self::TabSwitched => SfxKey::TabSwitched,
+        }
+    }
+
+    fn of(key: SfxKey) -> Option<Self> {
+        Self::ALL.into_iter().find(|cue| cue.key() == key)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::FocusMoved => "Focus moved",
+            Self::EdgeReached => "Edge reached",
+            Self::InvalidAction => "Invalid action",
+            Self::TabSwitched => "Tab switched",
+        }
+    }
+}
+
+struct SfxPlayback {
+    bus: SfxBus,
+    /// Further requests are dropped while this many copies are playing.
+    max_instances: usize,
+    /// Relative playback speed, and thereby pitch.
+    pitch: f32,
+    /// Largest relative change in playback speed, and thereby pitch.
+    pitch_variance: f32,
+}
+
+fn playback(key: SfxKey) -> SfxPlayback {
+    match key {
+        SfxKey::ButtonHover | SfxKey::ButtonPress => SfxPlayback {
+            bus: SfxBus::Ui,
+            max_instances: 2,
+            pitch: 1.0,
+            pitch_variance: 0.02,
+        },
+        SfxKey::Step1 | SfxKey::Step2 | SfxKey::Step3 | SfxKey::Step4 => SfxPlayback {
+            bus: SfxBus::Gameplay,
+            max_instances: 2,
+            pitch: 1.0,
+            pitch_variance: 0.1,
+        },
+        SfxKey::Pickup => SfxPlayback {
+            bus: SfxBus::Gameplay,
+            max_instances: 3,
+            pitch: 1.5,
+            pitch_variance: 0.15,
+        },
+        // Little variance, so each cue keeps a pitch of its own to tell it apart.
+        SfxKey::FocusMoved => SfxPlayback {
+            bus: SfxBus::Ui,
+            max_instances: 2,
+            pitch: 1.0,
+            pitch_variance: 0.02,
+        },
+        SfxKey::EdgeReached => SfxPlayback {
+            bus: SfxBus::Ui,
+            max_instances: 1,
+            pitch: 0.7,
+            pitch_variance: 0.0,
+        },
+        SfxKey::InvalidAction => SfxPlayback {
+            bus: SfxBus::Ui,
+            max_instances: 1,
+            pitch: 0.5,
+            pitch_variance: 0.0,
+        },
+        SfxKey::TabSwitched => SfxPlayback {
+            bus: SfxBus::Ui,
+            max_instances: 2,
+            pitch: 1.3,
+            pitch_variance: 0.02,
+        },
+    }
+}
+
+fn random_step(rng: &mut impl Rng) -> SfxKey {
     [SfxKey::Step1, SfxKey::Step2, SfxKey::Step3, SfxKey::Step4]
-        .choose(&mut rand::thread_rng())
+        .choose(rng)
         .copied()
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LevelSetting, VolumeSetting};
+
+    #[test]
+    fn ui_cues_have_their_own_sounds_and_volumes() {
+        assert_eq!(VolumeSetting::from_max().gain(), 1.0);
+        assert_eq!(VolumeSetting::from_raw(0).gain(), 0.0);
+        let keys = UiCue::ALL.map(UiCue::key);
+        for (i, key) in keys.iter().enumerate() {
+            assert!(!keys[..i].contains(key), "{key:?}");
+        }
+
+        // Cues start at full volume, and are set one at a time.
+        let mut volumes = GameSettings::default().ui_cue_volumes;
+        assert!(UiCue::ALL
+            .iter()
+            .all(|&cue| *volumes.get(cue) == VolumeSetting::from_max()));
+        *volumes.get_mut(UiCue::EdgeReached) = VolumeSetting::from_raw(30);
+        assert_eq!(*volumes.get(UiCue::EdgeReached).0, 30);
+        assert_eq!(*volumes.get(UiCue::FocusMoved), VolumeSetting::from_max());
+    }
+}
@@ -1,51 +1,122 @@
-use bevy::{audio::PlaybackMode, prelude::*};
+//! Looping background music. Screens request a track with [`SoundtrackCommand`],
+//! and the previous track is crossfaded into the new one.
 
-use crate::game::assets::{HandleMap, SoundtrackKey};
+use std::time::Duration;
+
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use super::ducking::{DuckingSet, MusicDucking};
+use crate::game::assets::{AudioAssets, SoundtrackKey};
 use crate::GameSettings;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<IsSoundtrack>();
-    app.observe(play_soundtrack);
+    app.init_resource::<SoundtrackCrossfade>();
+    app.observe(handle_soundtrack_command);
+    app.add_systems(Update, fade_soundtracks.after(DuckingSet));
+}
+
+/// Trigger this event to change the soundtrack.
+/// Soundtracks loop until another one is requested or they are stopped.
+#[derive(Event, Debug, Clone, Copy)]
+pub enum SoundtrackCommand {
+    /// Crossfade to this track. Does nothing if it is already playing.
+    Play(SoundtrackKey),
+    /// Fade out the current track.
+    Stop,
+}
+
+/// How long it takes to fade a soundtrack fully in or out.
+#[derive(Resource, Debug, Clone, Copy, Deref, DerefMut)]
+pub struct SoundtrackCrossfade(pub Duration);
+
+impl Default for SoundtrackCrossfade {
+    fn default() -> Self {
+        Self(Duration::from_millis(1500))
+    }
+}
+
+/// Marker component for the soundtrack entities so we can find them later.
+/// There can be several while crossfading, but at most one is fading in.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(super) struct IsSoundtrack {
+    key: SoundtrackKey,
+    /// Current loudness from 0 to 1, before the volume settings are applied.
+    fade: f32,
+    fading_in: bool,
 }
 
-fn play_soundtrack(
-    trigger: Trigger<PlaySoundtrack>,
+fn handle_soundtrack_command(
+    trigger: Trigger<SoundtrackCommand>,
     mut commands: Commands,
-    soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
-    soundtrack_query: Query<Entity, With<IsSoundtrack>>,
-    settings: Res<GameSettings>,
+    audio: Res<AudioAssets>,
+    mut soundtrack_query: Query<&mut IsSoundtrack>,
 ) {
-    for entity in &soundtrack_query {
-        commands.entity(entity).despawn_recursive();
+    let requested = match trigger.event() {
+        SoundtrackCommand::Play(key) => Some(*key),
+        SoundtrackCommand::Stop => None,
+    };
+    let mut already_playing = false;
+    for mut soundtrack in &mut soundtrack_query {
+        // A track that is fading out can come back without restarting.
+        let keep = Some(soundtrack.key) == requested;
+        soundtrack.fading_in = keep;
+        already_playing |= keep;
     }
 
-    let soundtrack_key = match trigger.event() {
-        PlaySoundtrack::Key(key) => *key,
-        PlaySoundtrack::Disable => return,
+    let Some(key) = requested.filter(|_| !already_playing) else {
+        return;
     };
     commands.spawn((
+        Name::new(format!("Soundtrack {key:?}")),
         AudioSourceBundle {
-            source: soundtrack_handles[&soundtrack_key].clone_weak(),
+            source: audio.soundtrack(key),
             settings: PlaybackSettings {
                 mode: PlaybackMode::Loop,
-                volume: (&settings.soundtrack_volume_level_relative).into(),
+                // Silent until `fade_soundtracks` takes over.
+                volume: Volume::ZERO,
                 ..default()
             },
         },
-        IsSoundtrack,
+        IsSoundtrack {
+            key,
+            fade: 0.0,
+            fading_in: true,
+        },
     ));
 }
 
-/// Trigger this event to play or disable the soundtrack.
-/// Playing a new soundtrack will overwrite the previous one.
-/// Soundtracks will loop.
-#[derive(Event)]
-pub enum PlaySoundtrack {
-    Key(SoundtrackKey),
-    Disable,
+fn fade_soundtracks(
+    mut commands: Commands,
+    time: Res<Time>,
+    crossfade: Res<SoundtrackCrossfade>,
+    settings: Res<GameSettings>,
+    global_volume: Res<GlobalVolume>,
+    ducking: Res<MusicDucking>,
+    mut soundtrack_query: Query<(Entity, &mut IsSoundtrack, Option<&AudioSink>)>,
+) {
+    let step = time.delta_seconds() / crossfade.as_secs_f32().max(f32::EPSILON);
+    // Sink volumes are absolute, so the global volume has to be applied here as well.
+    let volume = Volume::from(&settings.soundtrack_volume_level_relative).get()
+        * global_volume.volume.get()
+        * ducking.gain();
+    for (entity, mut soundtrack, sink) in &mut soundtrack_query {
+        soundtrack.fade = if soundtrack.fading_in {
+            (soundtrack.fade + step).min(1.0)
+        } else {
+            (soundtrack.fade - step).max(0.0)
+        };
+        if !soundtrack.fading_in && soundtrack.fade == 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // The sink is added once the track starts playing.
+        if let Some(sink) = sink {
+            sink.set_volume(soundtrack.fade * volume);
+        }
+    }
 }
-
-/// Marker component for the soundtrack entity so we can find it later.
-#[derive(Component, Reflect)]
-#[reflect(Component)]
-struct IsSoundtrack;
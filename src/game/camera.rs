@@ -0,0 +1,334 @@
+//! Camera behavior: following a target, zooming, and trauma-based shake.
+//!
+//! [`CameraFollow`] smoothly moves the camera towards its target once the target leaves
+//! a deadzone around the center of the screen, so small movements don't move the camera.
+//!
+//! Send a [`ShakeEvent`] to add trauma, which decays over time; the camera shakes with
+//! the square of the trauma, so small hits are subtle and big ones stand out.
+//! Disabled by the screen shake setting.
+//!
+//! [`CameraZoom`] is adjusted with the mouse wheel or the gamepad triggers during gameplay,
+//! and multiplied by the view size display setting. The result stays within the
+//! [`ViewLimits`] of the level being played.
+//! With the pixel perfect display setting, zoom and position snap to whole screen pixels
+//! per world pixel, so sprite art doesn't shimmer.
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    render::camera::ScalingMode,
+};
+
+use super::cutscene::ActiveCutscene;
+use crate::{events::ShakeEvent, screen::PlayingState, AppSet, GameSettings};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(WorldCamera, UiCamera, CameraFollow, CameraShake, CameraZoom)>();
+    app.init_resource::<ViewLimits>();
+    app.add_systems(
+        Update,
+        record_zoom_input
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(PlayingState::Running)),
+    );
+    // After `AppSet::Update`, so the camera sees where its target moved this frame.
+    // Zoom goes last, since pixel snapping has to see the final position.
+    // Cutscenes move the camera themselves.
+    app.add_systems(
+        Update,
+        (
+            follow_target.run_if(not(resource_exists::<ActiveCutscene>)),
+            add_trauma,
+            shake_camera,
+            apply_zoom,
+        )
+            .chain()
+            .in_set(AppSet::HandleEvents),
+    );
+}
+
+/// The camera that draws the game world. Following, shaking and zooming only move this one.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct WorldCamera;
+
+/// The camera that draws the UI, on [`Layer::ScreenUi`](crate::layers::Layer::ScreenUi).
+/// It never moves, so the HUD stays readable while the world camera shakes.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct UiCamera;
+
+/// Makes the camera it is on follow `target`.
+/// Removed again once the target is despawned.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct CameraFollow {
+    pub target: Entity,
+    /// Half the size of the area around the screen center the target can move in
+    /// without moving the camera.
+    pub deadzone: Vec2,
+    /// How quickly the camera catches up, as the fraction of the distance
+    /// left after one second is `exp(-smoothing)`.
+    pub smoothing: f32,
+    /// Fastest the camera can move, in pixels per second.
+    pub max_speed: f32,
+}
+
+impl CameraFollow {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            deadzone: Vec2::new(160.0, 90.0),
+            smoothing: 5.0,
+            max_speed: 1500.0,
+        }
+    }
+}
+
+fn follow_target(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut camera_query: Query<(Entity, &CameraFollow, &mut Transform, Option<&CameraShake>)>,
+    target_query: Query<&GlobalTransform>,
+) {
+    for (camera, follow, mut transform, shake) in &mut camera_query {
+        let Ok(target) = target_query.get(follow.target) else {
+            commands.entity(camera).remove::<CameraFollow>();
+            continue;
+        };
+        let target = target.translation().truncate();
+        // Follow from where the camera would be without shaking.
+        let center =
+            transform.translation.truncate() - shake.map_or(Vec2::ZERO, |shake| shake.offset);
+        // Just far enough to bring the target back to the edge of the deadzone.
+        let offset = target - center;
+        let goal = center + offset - offset.clamp(-follow.deadzone, follow.deadzone);
+        let smoothed = (goal - center) * (1.0 - (-follow.smoothing * time.delta_seconds()).exp());
+        let step = smoothed.clamp_length_max(follow.max_speed * time.delta_seconds());
+        transform.translation += step.extend(0.0);
+    }
+}
+
+/// Shakes the camera it is on, which is only ever the [`WorldCamera`].
+/// Only the offset it added is removed again, so other systems can still move the camera.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct CameraShake {
+    /// From 0 to 1.
+    trauma: f32,
+    offset: Vec2,
+    angle: f32,
+}
+
+/// Trauma lost per second.
+const TRAUMA_DECAY: f32 = 1.2;
+/// Offset in pixels and rotation in radians at full trauma.
+const MAX_OFFSET: f32 = 24.0;
+const MAX_ANGLE: f32 = 0.06;
+/// How fast the shake moves.
+const FREQUENCY: f32 = 25.0;
+
+fn add_trauma(
+    mut shake_events: EventReader<ShakeEvent>,
+    settings: Res<GameSettings>,
+    mut shake_query: Query<&mut CameraShake>,
+) {
+    let enabled = settings.screen_shake_enabled.is_on();
+    for event in shake_events.read() {
+        if !enabled {
+            continue;
+        }
+        for mut shake in &mut shake_query {
+            shake.trauma = (shake.trauma + event.trauma).clamp(0.0, 1.0);
+        }
+    }
+}
+
+fn shake_camera(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    mut shake_query: Query<(&mut CameraShake, &mut Transform)>,
+) {
+    let enabled = settings.screen_shake_enabled.is_on();
+    let t = time.elapsed_seconds() * FREQUENCY;
+    for (mut shake, mut transform) in &mut shake_query {
+        if shake.trauma == 0.0 && shake.offset == Vec2::ZERO {
+            continue;
+        }
+        // Undo last frame's shake.
+        transform.translation -= shake.offset.extend(0.0);
+        transform.rotate_z(-shake.angle);
+
+        shake.trauma = if enabled {
+            (shake.trauma - TRAUMA_DECAY * time.delta_seconds()).max(0.0)
+        } else {
+            0.0
+        };
+        let amount = shake.trauma * shake.trauma;
+        shake.offset = MAX_OFFSET * amount * Vec2::new(noise(t, 0.0), noise(t, 10.0));
+        shake.angle = MAX_ANGLE * amount * noise(t, 20.0);
+
+        transform.translation += shake.offset.extend(0.0);
+        transform.rotate_z(shake.angle);
+    }
+}
+
+/// Zoom of the camera it is on, as the scale of its orthographic projection.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct CameraZoom {
+    /// Larger is further out. Kept between [`MIN_ZOOM_SCALE`] and [`MAX_ZOOM_SCALE`].
+    pub scale: f32,
+    /// Offset added to snap to whole pixels, undone the next frame.
+    snap: Vec2,
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            snap: Vec2::ZERO,
+        }
+    }
+}
+
+/// The smallest and largest camera scale, set by the level being played.
+/// Designers use these to keep players from seeing too little or too much of a level.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct ViewLimits {
+    pub min_scale: f32,
+    pub max_scale: f32,
+}
+
+impl Default for ViewLimits {
+    fn default() -> Self {
+        Self {
+            min_scale: MIN_ZOOM_SCALE,
+            max_scale: MAX_ZOOM_SCALE,
+        }
+    }
+}
+
+impl ViewLimits {
+    /// The camera scale for a zoom and a view size setting.
+    pub fn scale(&self, zoom: f32, view_size: f32) -> f32 {
+        (zoom * view_size).clamp(self.min_scale, self.max_scale.max(self.min_scale))
+    }
+}
+
+const MIN_ZOOM_SCALE: f32 = 0.5;
+const MAX_ZOOM_SCALE: f32 = 2.0;
+/// Relative zoom per mouse wheel line.
+const WHEEL_ZOOM_STEP: f32 = 0.1;
+/// Mouse wheel pixels per line, for touchpads.
+const WHEEL_PIXELS_PER_LINE: f32 = 100.0;
+/// Relative zoom per second with a trigger fully pressed.
+const TRIGGER_ZOOM_SPEED: f32 = 1.0;
+
+fn record_zoom_input(
+    time: Res<Time>,
+    mut wheel_events: EventReader<MouseWheel>,
+    gamepads: Res<Gamepads>,
+    trigger_axes: Res<Axis<GamepadButton>>,
+    mut zoom_query: Query<&mut CameraZoom>,
+) {
+    // Positive zooms in.
+    let wheel = wheel_events
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / WHEEL_PIXELS_PER_LINE,
+        })
+        .sum::<f32>();
+    let trigger = |gamepad, button| {
+        trigger_axes
+            .get(GamepadButton::new(gamepad, button))
+            .unwrap_or_default()
+    };
+    let triggers = gamepads
+        .iter()
+        .map(|gamepad| {
+            trigger(gamepad, GamepadButtonType::RightTrigger2)
+                - trigger(gamepad, GamepadButtonType::LeftTrigger2)
+        })
+        .sum::<f32>();
+    if wheel == 0.0 && triggers == 0.0 {
+        return;
+    }
+    let factor = (1.0 - WHEEL_ZOOM_STEP).powf(wheel)
+        * (-triggers * TRIGGER_ZOOM_SPEED * time.delta_seconds()).exp();
+    for mut zoom in &mut zoom_query {
+        zoom.scale = (zoom.scale * factor).clamp(MIN_ZOOM_SCALE, MAX_ZOOM_SCALE);
+    }
+}
+
+fn apply_zoom(
+    settings: Res<GameSettings>,
+    limits: Res<ViewLimits>,
+    mut camera_query: Query<(&mut CameraZoom, &mut OrthographicProjection, &mut Transform)>,
+) {
+    let pixel_perfect = settings.display.pixel_perfect.is_on();
+    let view_size = settings.display.view_size.scale();
+    for (mut zoom, mut projection, mut transform) in &mut camera_query {
+        transform.translation -= zoom.snap.extend(0.0);
+        let scale = limits.scale(zoom.scale, view_size);
+        let scale = if pixel_perfect {
+            snap_scale(scale)
+        } else {
+            scale
+        };
+        if projection.scale != scale {
+            projection.scale = scale;
+        }
+        zoom.snap = if pixel_perfect {
+            // One pixel of the render target is `scale` world units,
+            // or more when rendering to the low-res pixel canvas.
+            let pixel = match projection.scaling_mode {
+                ScalingMode::WindowSize(pixels_per_unit) => scale / pixels_per_unit,
+                _ => scale,
+            };
+            let position = transform.translation.truncate();
+            (position / pixel).round() * pixel - position
+        } else {
+            Vec2::ZERO
+        };
+        transform.translation += zoom.snap.extend(0.0);
+    }
+}
+
+/// The closest scale at which world pixels are a whole number of screen pixels, or the
+/// other way around.
+fn snap_scale(scale: f32) -> f32 {
+    if scale < 1.0 {
+        1.0 / (1.0 / scale).round()
+    } else {
+        scale.round()
+    }
+}
+
+/// Smooth noise from -1 to 1, different for each `seed`.
+fn noise(t: f32, seed: f32) -> f32 {
+    0.5 * (t + seed).sin() + 0.3 * (2.3 * t + 1.7 * seed).sin() + 0.2 * (5.1 * t + 2.9 * seed).sin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{display::ViewSizeSetting, LevelSetting};
+
+    #[test]
+    fn view_size_stays_within_level_limits() {
+        let limits = ViewLimits {
+            min_scale: 0.75,
+            max_scale: 1.5,
+        };
+        assert_eq!(ViewSizeSetting::default().scale(), 1.0);
+        assert_eq!(limits.scale(1.0, ViewSizeSetting::default().scale()), 1.0);
+        assert_eq!(limits.scale(2.0, ViewSizeSetting::from_max().scale()), 1.5);
+        assert_eq!(
+            limits.scale(0.5, ViewSizeSetting::from_raw(0).scale()),
+            0.75
+        );
+    }
+}
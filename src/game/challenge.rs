@@ -0,0 +1,357 @@
+//! Rules a level can be played under for bonus objectives, declared in its file as
+//! [`ChallengeData`]: completing it within a time limit, without taking damage, or
+//! without some of the player's abilities.
+//!
+//! The [`Challenge`] of the level being played keeps track of which rules were broken,
+//! which the run's [`SaveGame`] keeps. Abilities a rule takes away can't be used at all,
+//! since [`ActionInput`](super::input::ActionInput) never reports them. Completing the level
+//! turns each rule into a [`BonusObjective`], kept or not, which the results screens list,
+//! and grants the challenge's rewards if every rule was kept.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    assets::LevelAssets,
+    cutscene::ActiveCutscene,
+    health::ApplyDamage,
+    input::Action,
+    inventory::{Inventory, ItemCatalog},
+    objectives::{LevelCompleted, Reward},
+    save::SaveGame,
+    spawn::{
+        level::{LevelData, SpawnLevel},
+        player::Player,
+    },
+    stats::format_time,
+};
+use crate::{
+    events::{DamageTaken, ScoreEvent},
+    screen::PlayingState,
+    ui::announcer::{Announce, Importance},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Challenge>();
+    app.observe(set_challenge);
+    app.observe(complete_challenge);
+    app.add_systems(
+        FixedUpdate,
+        (
+            tick_challenge
+                .in_set(AppSet::TickTimers)
+                .run_if(not(resource_exists::<ActiveCutscene>)),
+            enforce_rules
+                .in_set(AppSet::HandleEvents)
+                .after(ApplyDamage),
+        )
+            .chain()
+            .run_if(in_state(PlayingState::Running).and_then(has_rules)),
+    );
+}
+
+/// The rules of a level, as written in its file. Levels without any have no challenge.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ChallengeData {
+    pub rules: Vec<Rule>,
+    /// Granted when the level is completed with every rule kept.
+    pub rewards: Vec<Reward>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    /// Complete the level's objectives within this many seconds.
+    TimeLimit(f32),
+    /// Don't let the player take any damage.
+    NoDamage,
+    /// The player can't use this ability in the level.
+    Without(Ability),
+}
+
+impl Rule {
+    pub fn description(&self) -> String {
+        match self {
+            Rule::TimeLimit(seconds) => format!("Finish within {}", format_time(*seconds)),
+            Rule::NoDamage => "Take no damage".to_string(),
+            Rule::Without(ability) => format!("Finish without {}", ability.name()),
+        }
+    }
+}
+
+/// An action that a [`Rule::Without`] can take away. Moving, interacting and pausing
+/// can't be, since the level couldn't be played without them.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ability {
+    Sprint,
+    AdvanceCycle,
+    Rewind,
+    QuickActions,
+}
+
+impl Ability {
+    pub fn action(self) -> Action {
+        match self {
+            Ability::Sprint => Action::Sprint,
+            Ability::AdvanceCycle => Action::AdvanceCycle,
+            Ability::Rewind => Action::Rewind,
+            Ability::QuickActions => Action::QuickWheel,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Ability::Sprint => "sprinting",
+            Ability::AdvanceCycle => "advancing the cycle",
+            Ability::Rewind => "rewinding",
+            Ability::QuickActions => "quick actions",
+        }
+    }
+}
+
+/// How far along the challenge of the level being played is, as saved.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ChallengeProgress {
+    /// Seconds spent in the level, not counting pauses and cutscenes.
+    pub elapsed: f32,
+    /// Indices of the rules that were broken.
+    pub broken: Vec<usize>,
+}
+
+/// A rule of a completed level, and whether it was kept.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BonusObjective {
+    pub level: String,
+    pub description: String,
+    pub kept: bool,
+}
+
+impl BonusObjective {
+    /// A line of the results, like "[x] grove: Take no damage".
+    pub fn line(&self) -> String {
+        let mark = if self.kept { "[x]" } else { "[ ]" };
+        format!("{mark} {}: {}", self.level, self.description)
+    }
+}
+
+/// The rules of the level being played, set when it spawns.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct Challenge {
+    pub data: ChallengeData,
+    pub progress: ChallengeProgress,
+}
+
+impl Challenge {
+    /// The challenge of `level`, continuing from `progress`, like that of a [`SaveGame`].
+    pub fn new(level: &LevelData, progress: &ChallengeProgress) -> Self {
+        Self {
+            data: level.challenge.clone(),
+            progress: progress.clone(),
+        }
+    }
+
+    pub fn is_kept(&self, index: usize) -> bool {
+        !self.progress.broken.contains(&index)
+    }
+
+    /// Whether a rule takes `action` away.
+    pub fn restricts(&self, action: Action) -> bool {
+        self.data
+            .rules
+            .iter()
+            .any(|rule| matches!(rule, Rule::Without(ability) if ability.action() == action))
+    }
+
+    /// Break the rules that `damage_taken` and the time spent break,
+    /// returning the indices of those that weren't already.
+    pub fn check(&mut self, damage_taken: bool) -> Vec<usize> {
+        let mut broken = Vec::new();
+        for (index, rule) in self.data.rules.iter().enumerate() {
+            let breaks = match rule {
+                Rule::TimeLimit(seconds) => self.progress.elapsed > *seconds,
+                Rule::NoDamage => damage_taken,
+                // Enforced by the input instead.
+                Rule::Without(_) => false,
+            };
+            if breaks && !self.progress.broken.contains(&index) {
+                broken.push(index);
+            }
+        }
+        self.progress.broken.extend(&broken);
+        broken
+    }
+
+    /// A line of the HUD per rule, like "Bonus: Take no damage (failed)".
+    pub fn hud_lines(&self) -> Vec<String> {
+        self.data
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let description = rule.description();
+                match (rule, self.is_kept(index)) {
+                    (_, false) => format!("Bonus: {description} (failed)"),
+                    (Rule::TimeLimit(seconds), true) => format!(
+                        "Bonus: {description} ({} left)",
+                        format_time((seconds - self.progress.elapsed).max(0.0))
+                    ),
+                    (_, true) => format!("Bonus: {description}"),
+                }
+            })
+            .collect()
+    }
+
+    /// The level's rules as bonus objectives, kept if the level was completed.
+    pub fn bonus_objectives(&self, level: &str, completed: bool) -> Vec<BonusObjective> {
+        self.data
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| BonusObjective {
+                level: level.to_string(),
+                description: rule.description(),
+                kept: completed && self.is_kept(index),
+            })
+            .collect()
+    }
+}
+
+/// One line per bonus objective of the run, for the results screens. Those of the level
+/// being played weren't kept, since it wasn't completed.
+pub fn bonus_objective_lines(save: &SaveGame, challenge: &Challenge) -> Vec<String> {
+    save.bonus_objectives
+        .iter()
+        .cloned()
+        .chain(challenge.bonus_objectives(&save.level, false))
+        .map(|objective| objective.line())
+        .collect()
+}
+
+fn has_rules(challenge: Res<Challenge>) -> bool {
+    !challenge.data.rules.is_empty()
+}
+
+fn set_challenge(
+    _trigger: Trigger<SpawnLevel>,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<LevelData>>,
+    save: Res<SaveGame>,
+    mut challenge: ResMut<Challenge>,
+) {
+    *challenge = levels
+        .get(level_assets.level(&save.level))
+        .map(|level| Challenge::new(level, &save.challenge))
+        .unwrap_or_default();
+}
+
+fn tick_challenge(time: Res<Time>, mut challenge: ResMut<Challenge>) {
+    challenge.progress.elapsed += time.delta_seconds();
+}
+
+fn enforce_rules(
+    mut commands: Commands,
+    mut taken_events: EventReader<DamageTaken>,
+    mut challenge: ResMut<Challenge>,
+    mut save: ResMut<SaveGame>,
+    player_query: Query<(), With<Player>>,
+) {
+    let damage_taken = taken_events
+        .read()
+        .any(|event| player_query.contains(event.target));
+    for index in challenge.check(damage_taken) {
+        commands.trigger(Announce::new(
+            Importance::Normal,
+            format!(
+                "Bonus objective failed: {}",
+                challenge.data.rules[index].description()
+            ),
+        ));
+    }
+    save.challenge.clone_from(&challenge.progress);
+}
+
+/// Record the rules as bonus objectives, and reward keeping all of them.
+fn complete_challenge(
+    _trigger: Trigger<LevelCompleted>,
+    mut commands: Commands,
+    mut challenge: ResMut<Challenge>,
+    mut save: ResMut<SaveGame>,
+    catalog: Res<ItemCatalog>,
+    mut inventory: ResMut<Inventory>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    if challenge.data.rules.is_empty() {
+        return;
+    }
+    // Time could have run out this tick, after the rules were enforced.
+    challenge.check(false);
+    let objectives = challenge.bonus_objectives(&save.level, true);
+    if objectives.iter().all(|objective| objective.kept) {
+        for reward in &challenge.data.rewards {
+            reward.grant(&catalog, &mut inventory, &mut score_events);
+        }
+        commands.trigger(Announce::new(
+            Importance::Normal,
+            "Every bonus objective complete",
+        ));
+    }
+    save.bonus_objectives.extend(objectives);
+    // Done with, so the results don't list the level again.
+    *challenge = default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenge_rules_break_once_and_become_bonus_objectives() {
+        let level: LevelData =
+            ron::from_str(include_str!("../../assets/levels/grove.level.ron")).unwrap();
+        let mut challenge = Challenge::new(&level, &ChallengeProgress::default());
+        assert_eq!(
+            level.challenge.rules,
+            [
+                Rule::TimeLimit(150.0),
+                Rule::NoDamage,
+                Rule::Without(Ability::Rewind)
+            ]
+        );
+        assert!(challenge.restricts(Action::Rewind));
+        assert!(!challenge.restricts(Action::Sprint));
+        assert!(challenge.check(false).is_empty());
+
+        challenge.progress.elapsed = 30.0;
+        assert_eq!(challenge.check(true), vec![1]);
+        assert!(challenge.check(true).is_empty());
+        assert_eq!(
+            challenge.hud_lines(),
+            [
+                "Bonus: Finish within 2:30 (2:00 left)",
+                "Bonus: Take no damage (failed)",
+                "Bonus: Finish without rewinding",
+            ]
+        );
+        challenge.progress.elapsed = 151.0;
+        assert_eq!(challenge.check(false), vec![0]);
+
+        let kept = challenge
+            .bonus_objectives("grove", true)
+            .iter()
+            .map(|objective| objective.kept)
+            .collect::<Vec<_>>();
+        assert_eq!(kept, [false, false, true]);
+        // Unfinished levels keep none.
+        let save = SaveGame {
+            level: "grove".to_string(),
+            ..default()
+        };
+        assert_eq!(
+            bonus_objective_lines(&save, &challenge)[2],
+            "[ ] grove: Finish without rewinding"
+        );
+        assert!(Challenge::default().hud_lines().is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+//! Per-tick checksums of the simulation state, for tracking down desyncs.
+//! Each fixed tick hashes the transforms and health of all [`Checksummed`] entities,
+//! chained with the tick before. Every build records them, so players' bug reports carry
+//! them too, and only the last ten minutes or so are kept.
+//! Bug reports attach the checksums of the ticks their replay covers, which can later be
+//! compared against a local re-simulation with [`ChecksumRange::first_divergence`].
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::health::Health;
+use crate::screen::{PlayingState, Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Checksummed>();
+    app.init_resource::<SimulationChecksums>();
+    app.add_systems(OnEnter(Screen::Playing), reset_checksums);
+    app.add_systems(
+        FixedPostUpdate,
+        record_checksum.run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// How many ticks of checksums are kept, about ten minutes at the default 64 Hz.
+const MAX_TICKS: usize = 64 * 60 * 10;
+
+/// Marks an entity whose state is part of the simulation checksum.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct Checksummed;
+
+/// Checksums of consecutive ticks, counted from when [`Screen::Playing`] was entered.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ChecksumRange {
+    pub first_tick: usize,
+    pub checksums: Vec<u64>,
+}
+
+impl ChecksumRange {
+    /// The tick after the last one.
+    pub fn end(&self) -> usize {
+        self.first_tick + self.checksums.len()
+    }
+
+    pub fn get(&self, tick: usize) -> Option<u64> {
+        let index = tick.checked_sub(self.first_tick)?;
+        self.checksums.get(index).copied()
+    }
+
+    /// The last `ticks` of the range.
+    pub fn last(&self, ticks: usize) -> ChecksumRange {
+        let skipped = self.checksums.len().saturating_sub(ticks);
+        ChecksumRange {
+            first_tick: self.first_tick + skipped,
+            checksums: self.checksums[skipped..].to_vec(),
+        }
+    }
+
+    /// The first tick covered by both ranges at which they differ, if any.
+    /// Since each checksum is chained with the one before, a divergence persists
+    /// once it happens, which lets us binary search for it.
+    // Compared from the dev console.
+    #[cfg_attr(not(feature = "dev"), allow(dead_code))]
+    pub fn first_divergence(&self, other: &ChecksumRange) -> Option<usize> {
+        let start = self.first_tick.max(other.first_tick);
+        let end = self.end().min(other.end());
+        let (mut low, mut high) = (start, end.max(start));
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.get(mid) == other.get(mid) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        (low < end).then_some(low)
+    }
+}
+
+/// Checksums of the most recent fixed ticks, up to about ten minutes of them.
+#[derive(Resource, Debug, Default)]
+pub struct SimulationChecksums(ChecksumRange);
+
+impl SimulationChecksums {
+    pub fn ticks(&self) -> &ChecksumRange {
+        &self.0
+    }
+
+    fn push(&mut self, checksum: u64) {
+        let range = &mut self.0;
+        if range.checksums.len() == MAX_TICKS {
+            // Drop the older half at once, rather than shifting every tick.
+            range.checksums.drain(..MAX_TICKS / 2);
+            range.first_tick += MAX_TICKS / 2;
+        }
+        range.checksums.push(checksum);
+    }
+}
+
+fn reset_checksums(mut checksums: ResMut<SimulationChecksums>) {
+    *checksums = default();
+}
+
+fn record_checksum(
+    mut checksums: ResMut<SimulationChecksums>,
+    checksummed_query: Query<(&Transform, Option<&Health>), With<Checksummed>>,
+) {
+    // Query order isn't stable between runs, so combine sorted per-entity hashes.
+    let mut entity_hashes = checksummed_query
+        .iter()
+        .map(|(transform, health)| {
+            let mut hash = Fnv1a::default();
+            for value in transform
+                .translation
+                .to_array()
+                .into_iter()
+                .chain(transform.rotation.to_array())
+                .chain(health.map(|health| health.current))
+            {
+                hash.write(&value.to_bits().to_le_bytes());
+            }
+            hash.0
+        })
+        .collect::<Vec<_>>();
+    entity_hashes.sort_unstable();
+
+    let mut hash = Fnv1a::default();
+    if let Some(previous) = checksums.0.checksums.last() {
+        hash.write(&previous.to_le_bytes());
+    }
+    for entity_hash in entity_hashes {
+        hash.write(&entity_hash.to_le_bytes());
+    }
+    checksums.push(hash.0);
+}
+
+/// 64-bit FNV-1a, used instead of `std`'s hasher since its output must not
+/// change between platforms or compiler versions.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divergence_is_found_where_both_ranges_overlap() {
+        let local = ChecksumRange {
+            first_tick: 10,
+            checksums: vec![1, 2, 3, 4, 5, 6],
+        };
+        assert_eq!(local.end(), 16);
+        assert_eq!(local.get(12), Some(3));
+        assert_eq!(local.get(9), None);
+
+        let reported = ChecksumRange {
+            first_tick: 12,
+            checksums: vec![3, 4, 9, 9, 9, 9],
+        };
+        assert_eq!(local.first_divergence(&reported), Some(14));
+        assert_eq!(reported.first_divergence(&local), Some(14));
+        assert_eq!(local.first_divergence(&local.last(3)), None);
+        assert_eq!(local.last(3).first_tick, 13);
+        // Ranges that don't overlap can't diverge.
+        let later = ChecksumRange {
+            first_tick: 20,
+            checksums: vec![7],
+        };
+        assert_eq!(local.first_divergence(&later), None);
+    }
+}
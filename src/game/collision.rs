@@ -0,0 +1,353 @@
+//! Lightweight collision detection for gameplay, without a physics engine.
+//! Entities with a [`Collider`] and a [`CollisionLayer`] send a [`CollisionEvent`] every
+//! fixed tick they overlap something on a layer either of them collides with. Nothing is
+//! pushed apart; colliders only report overlaps, except that movement keeps them out of
+//! walls, see [`Collider::push_out_of_box`]. Overlaps are checked once everything
+//! has moved, at the start of [`AppSet::HandleEvents`], with the simulated transforms of
+//! colliders, which should be top-level entities.
+//!
+//! Colliders are sorted into a [`SpatialGrid`] first, so only colliders that share a
+//! cell are tested against each other.
+//!
+//! With the `physics` feature, contacts from the physics engine are sent instead,
+//! see `game::physics`.
+//!
+//! Reactions to collisions run in [`CollisionReactions`], within [`AppSet::HandleEvents`]
+//! of `FixedUpdate`:
+//! contact damage (see `game::health`), collecting pickups, and entering trigger zones.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use super::{
+    inventory::{Inventory, ItemCatalog, Pickup},
+    spawn::player::Player,
+};
+use crate::{
+    events::{CollisionEvent, PickupEvent},
+    screen::PlayingState,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Collider, CollisionLayer, TriggerZone)>();
+    app.configure_sets(FixedUpdate, CollisionReactions.in_set(AppSet::HandleEvents));
+    app.add_systems(
+        FixedUpdate,
+        (collect_pickups, enter_trigger_zones)
+            .in_set(CollisionReactions)
+            .run_if(in_state(PlayingState::Running)),
+    );
+
+    #[cfg(not(feature = "physics"))]
+    {
+        app.init_resource::<SpatialGrid>();
+        app.init_resource::<Contacts>();
+        app.add_systems(
+            FixedUpdate,
+            detect_collisions
+                .in_set(AppSet::HandleEvents)
+                .before(CollisionReactions)
+                .run_if(in_state(PlayingState::Running)),
+        );
+    }
+}
+
+/// Systems that react to [`CollisionEvent`]s. They may send other events,
+/// whose readers should run after this set.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollisionReactions;
+
+/// The shape of an entity for collisions, centered on its translation.
+/// Sizes are in world units, regardless of the entity's scale.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum Collider {
+    /// An axis-aligned box.
+    Aabb {
+        half_size: Vec2,
+    },
+    Circle {
+        radius: f32,
+    },
+}
+
+impl Collider {
+    /// Half the size of the box around the shape.
+    pub fn half_extents(&self) -> Vec2 {
+        match *self {
+            Self::Aabb { half_size } => half_size,
+            Self::Circle { radius } => Vec2::splat(radius),
+        }
+    }
+
+    /// Whether this shape at `position` overlaps `other` at `other_position`.
+    /// Shapes that only touch at their edges don't overlap.
+    pub fn overlaps(&self, position: Vec2, other: &Self, other_position: Vec2) -> bool {
+        let offset = other_position - position;
+        match (*self, *other) {
+            (Self::Aabb { half_size: a }, Self::Aabb { half_size: b }) => {
+                let gap = offset.abs() - (a + b);
+                gap.x < 0.0 && gap.y < 0.0
+            }
+            (Self::Circle { radius: a }, Self::Circle { radius: b }) => {
+                offset.length_squared() < (a + b) * (a + b)
+            }
+            (Self::Aabb { half_size }, Self::Circle { radius }) => {
+                // The point of the box closest to the circle's center.
+                let closest = offset.clamp(-half_size, half_size);
+                (offset - closest).length_squared() < radius * radius
+            }
+            (Self::Circle { .. }, Self::Aabb { .. }) => {
+                other.overlaps(other_position, self, position)
+            }
+        }
+    }
+
+    /// How far this shape at `position` has to move to stop overlapping a box at
+    /// `box_center`, along the shortest way out, or `None` if they don't overlap.
+    pub fn push_out_of_box(
+        &self,
+        position: Vec2,
+        box_center: Vec2,
+        half_size: Vec2,
+    ) -> Option<Vec2> {
+        let offset = position - box_center;
+        if let Self::Circle { radius } = *self {
+            let closest = offset.clamp(-half_size, half_size);
+            let outside = offset - closest;
+            // A center outside the box is pushed straight away from its closest point.
+            if outside != Vec2::ZERO {
+                let distance = outside.length();
+                return (distance < radius).then(|| outside / distance * (radius - distance));
+            }
+        }
+        // Otherwise the shape is pushed out of the nearest side, like a box.
+        let reach = half_size + self.half_extents();
+        let depth = reach - offset.abs();
+        if depth.x <= 0.0 || depth.y <= 0.0 {
+            return None;
+        }
+        let sign = Vec2::new(
+            if offset.x < 0.0 { -1.0 } else { 1.0 },
+            if offset.y < 0.0 { -1.0 } else { 1.0 },
+        );
+        Some(if depth.x < depth.y {
+            Vec2::new(depth.x * sign.x, 0.0)
+        } else {
+            Vec2::new(0.0, depth.y * sign.y)
+        })
+    }
+}
+
+/// Which layers an entity is on, and which layers it collides with, as bit masks.
+/// Two entities collide if either one's filter includes a layer the other is a member of.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct CollisionLayer {
+    pub member: u32,
+    pub filter: u32,
+}
+
+impl CollisionLayer {
+    pub const PLAYER: u32 = 1 << 0;
+    pub const ENEMY: u32 = 1 << 1;
+    pub const PICKUP: u32 = 1 << 2;
+    pub const TRIGGER: u32 = 1 << 3;
+
+    pub const fn new(member: u32, filter: u32) -> Self {
+        Self { member, filter }
+    }
+
+    pub fn interacts_with(&self, other: &Self) -> bool {
+        self.filter & other.member != 0 || other.filter & self.member != 0
+    }
+}
+
+/// An area that triggers [`TriggerEntered`] on itself when the player walks into it.
+/// Give it a [`Collider`] and [`CollisionLayer::TRIGGER`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct TriggerZone;
+
+/// Triggered on a [`TriggerZone`] when the player enters it.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerEntered {
+    pub player: Entity,
+}
+
+/// Side length of the cells in the [`SpatialGrid`], in world units.
+/// About the size of the player, so most colliders cover only a few cells.
+const CELL_SIZE: f32 = 256.0;
+
+/// The colliders in each cell of a uniform grid, rebuilt every tick.
+#[derive(Resource, Debug, Default)]
+pub struct SpatialGrid {
+    cells: HashMap<IVec2, Vec<Entity>>,
+}
+
+impl SpatialGrid {
+    pub fn clear(&mut self) {
+        // Keeps the allocations of cells used last frame, and drops the rest.
+        self.cells.retain(|_, entities| {
+            let used = !entities.is_empty();
+            entities.clear();
+            used
+        });
+    }
+
+    /// Add an entity to every cell that the box around it touches.
+    pub fn insert(&mut self, entity: Entity, position: Vec2, half_extents: Vec2) {
+        let min = ((position - half_extents) / CELL_SIZE).floor().as_ivec2();
+        let max = ((position + half_extents) / CELL_SIZE).floor().as_ivec2();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(entity);
+            }
+        }
+    }
+
+    /// Every pair of entities that share a cell, once, with the lower entity first.
+    pub fn candidate_pairs(&self) -> HashSet<(Entity, Entity)> {
+        let mut pairs = HashSet::new();
+        for entities in self.cells.values() {
+            for (i, &a) in entities.iter().enumerate() {
+                for &b in &entities[i + 1..] {
+                    pairs.insert(if a < b { (a, b) } else { (b, a) });
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// Pairs that overlapped last frame, to tell which collisions just started.
+#[cfg(not(feature = "physics"))]
+#[derive(Resource, Debug, Default)]
+struct Contacts(HashSet<(Entity, Entity)>);
+
+#[cfg(not(feature = "physics"))]
+fn detect_collisions(
+    mut grid: ResMut<SpatialGrid>,
+    mut contacts: ResMut<Contacts>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    collider_query: Query<(Entity, &Collider, &CollisionLayer, &Transform)>,
+) {
+    grid.clear();
+    for (entity, collider, _, transform) in &collider_query {
+        grid.insert(
+            entity,
+            transform.translation.truncate(),
+            collider.half_extents(),
+        );
+    }
+
+    let mut overlapping = HashSet::new();
+    for (a, b) in grid.candidate_pairs() {
+        let Ok([(_, collider_a, layer_a, transform_a), (_, collider_b, layer_b, transform_b)]) =
+            collider_query.get_many([a, b])
+        else {
+            continue;
+        };
+        if !layer_a.interacts_with(layer_b)
+            || !collider_a.overlaps(
+                transform_a.translation.truncate(),
+                collider_b,
+                transform_b.translation.truncate(),
+            )
+        {
+            continue;
+        }
+        overlapping.insert((a, b));
+        collision_events.send(CollisionEvent {
+            a,
+            b,
+            started: !contacts.0.contains(&(a, b)),
+        });
+    }
+    contacts.0 = overlapping;
+}
+
+fn collect_pickups(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut pickup_events: EventWriter<PickupEvent>,
+    catalog: Res<ItemCatalog>,
+    inventory: Res<Inventory>,
+    player_query: Query<(), With<Player>>,
+    layer_query: Query<&CollisionLayer>,
+    item_query: Query<&Pickup>,
+) {
+    for event in collision_events.read() {
+        let Some((collector, pickup)) = event.either(|entity| player_query.contains(entity)) else {
+            continue;
+        };
+        let is_pickup = layer_query
+            .get(pickup)
+            .is_ok_and(|layer| layer.member & CollisionLayer::PICKUP != 0);
+        // Items that don't fit the inventory are left lying around.
+        let fits = item_query.get(pickup).map_or(true, |item| {
+            catalog
+                .get(&item.item)
+                .is_some_and(|item| inventory.room_for(item) > 0)
+        });
+        if is_pickup && fits {
+            pickup_events.send(PickupEvent { collector, pickup });
+        }
+    }
+}
+
+fn enter_trigger_zones(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    player_query: Query<(), With<Player>>,
+    zone_query: Query<(), With<TriggerZone>>,
+) {
+    for event in collision_events.read().filter(|event| event.started) {
+        let Some((player, zone)) = event.either(|entity| player_query.contains(entity)) else {
+            continue;
+        };
+        if zone_query.contains(zone) {
+            commands.trigger_targets(TriggerEntered { player }, zone);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colliders_overlap_by_shape() {
+        let square = Collider::Aabb {
+            half_size: Vec2::splat(10.0),
+        };
+        let circle = Collider::Circle { radius: 5.0 };
+        assert!(square.overlaps(Vec2::ZERO, &square, Vec2::new(19.0, 0.0)));
+        assert!(!square.overlaps(Vec2::ZERO, &square, Vec2::new(20.0, 0.0)));
+        assert!(square.overlaps(Vec2::ZERO, &circle, Vec2::new(14.0, 0.0)));
+        // Near the corner, the circle misses even though the boxes around both overlap.
+        assert!(!circle.overlaps(Vec2::new(14.0, 14.0), &square, Vec2::ZERO));
+        assert!(circle.overlaps(Vec2::ZERO, &circle, Vec2::new(6.0, 6.0)));
+    }
+
+    #[test]
+    fn spatial_grid_pairs_neighbours_once() {
+        let [a, b, c] = [1, 2, 3].map(Entity::from_raw);
+        let mut grid = SpatialGrid::default();
+        // `a` spans two cells, both shared with `b`.
+        grid.insert(a, Vec2::new(256.0, 0.0), Vec2::splat(10.0));
+        grid.insert(b, Vec2::new(256.0, 0.0), Vec2::splat(20.0));
+        grid.insert(c, Vec2::new(5000.0, 0.0), Vec2::splat(10.0));
+        let pairs = grid.candidate_pairs();
+        assert_eq!(pairs.len(), 1);
+        assert!(pairs.contains(&(a, b)));
+
+        let layer = |member, filter| CollisionLayer::new(member, filter);
+        let player = layer(CollisionLayer::PLAYER, CollisionLayer::PICKUP);
+        assert!(player.interacts_with(&layer(CollisionLayer::PICKUP, 0)));
+        assert!(!player.interacts_with(&layer(CollisionLayer::ENEMY, 0)));
+        assert!(player.interacts_with(&layer(CollisionLayer::ENEMY, CollisionLayer::PLAYER)));
+    }
+}
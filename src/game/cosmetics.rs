@@ -0,0 +1,151 @@
+//! Player skins, defined in `assets/cosmetics/skins.ron` and picked on the customization
+//! screen. Some skins have to be unlocked first, through an achievement or a cheat code.
+//! The selected and unlocked skins are kept in the player's [`Profile`], and the selected
+//! skin's palette is applied to the player when it spawns.
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    assets::parse_embedded,
+    palette::{Palette, PaletteLibrary},
+    profile::Profile,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(SkinCatalog::load());
+    app.add_systems(Startup, register_skin_palettes);
+    app.observe(unlock_achievement_skins);
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Skin {
+    /// Stays the same when the name changes, since it is persisted.
+    pub id: String,
+    pub name: String,
+    /// Replacements for the ducky's colors.
+    #[serde(default)]
+    pub palette: Palette,
+    #[serde(default)]
+    pub unlock: Unlock,
+}
+
+impl Skin {
+    /// Id of the skin's palette in the [`PaletteLibrary`].
+    pub fn palette_id(&self) -> String {
+        format!("skin/{}", self.id)
+    }
+}
+
+/// How a skin becomes available.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub enum Unlock {
+    #[default]
+    Always,
+    /// Unlocked by an [`AchievementUnlocked`] event with this name.
+    Achievement(String),
+    /// Unlocked by typing this on the customization screen.
+    Code(String),
+}
+
+/// All skins from `assets/cosmetics/skins.ron`. The first one is the default.
+#[derive(Resource, Debug)]
+pub struct SkinCatalog(pub Vec<Skin>);
+
+impl SkinCatalog {
+    const SOURCE: &'static str = include_str!("../../assets/cosmetics/skins.ron");
+
+    pub fn load() -> Self {
+        let skins: Vec<Skin> = parse_embedded(Self::SOURCE, "skins");
+        if skins.is_empty() {
+            // There always has to be something to select.
+            return Self(vec![Skin {
+                id: "classic".to_string(),
+                name: "Classic".to_string(),
+                palette: Palette::default(),
+                unlock: Unlock::Always,
+            }]);
+        }
+        Self(skins)
+    }
+
+    /// The selected skin, or the default one if it is gone or locked.
+    pub fn selected(&self, cosmetics: &Cosmetics) -> &Skin {
+        self.0
+            .iter()
+            .find(|skin| {
+                cosmetics.selected.as_ref() == Some(&skin.id) && cosmetics.is_unlocked(skin)
+            })
+            .unwrap_or(&self.0[0])
+    }
+
+    /// The locked skins whose code `typed` ends with.
+    pub fn matching_code<'a>(&'a self, typed: &'a str) -> impl Iterator<Item = &'a Skin> {
+        let typed = typed.to_lowercase();
+        self.0.iter().filter(move |skin| match &skin.unlock {
+            Unlock::Code(code) => !code.is_empty() && typed.ends_with(&code.to_lowercase()),
+            _ => false,
+        })
+    }
+}
+
+/// The player's skin choices, part of their [`Profile`].
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct Cosmetics {
+    /// Id of the selected skin, if one was ever picked.
+    pub selected: Option<String>,
+    /// Ids of skins that were unlocked.
+    pub unlocked: HashSet<String>,
+}
+
+impl Cosmetics {
+    pub fn is_unlocked(&self, skin: &Skin) -> bool {
+        skin.unlock == Unlock::Always || self.unlocked.contains(&skin.id)
+    }
+
+    /// Returns whether the skin was locked before.
+    pub fn unlock(&mut self, skin: &Skin) -> bool {
+        !self.is_unlocked(skin) && self.unlocked.insert(skin.id.clone())
+    }
+}
+
+/// Trigger this when the player earns an achievement, to unlock the skins tied to it.
+#[derive(Event, Debug)]
+pub struct AchievementUnlocked(pub String);
+
+fn unlock_achievement_skins(
+    trigger: Trigger<AchievementUnlocked>,
+    catalog: Res<SkinCatalog>,
+    mut profile: ResMut<Profile>,
+) {
+    let achievement = &trigger.event().0;
+    for skin in &catalog.0 {
+        if skin.unlock == Unlock::Achievement(achievement.clone()) && profile.cosmetics.unlock(skin)
+        {
+            info!(
+                "Unlocked skin {} with achievement {achievement}.",
+                skin.name
+            );
+        }
+    }
+}
+
+fn register_skin_palettes(catalog: Res<SkinCatalog>, mut library: ResMut<PaletteLibrary>) {
+    for skin in &catalog.0 {
+        library.insert(skin.palette_id(), skin.palette.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skins_parse_with_an_unlocked_default() {
+        let skins = SkinCatalog::load();
+        assert!(skins.0.len() > 1);
+        assert!(Cosmetics::default().is_unlocked(&skins.0[0]));
+        assert_eq!(skins.selected(&Cosmetics::default()).id, skins.0[0].id);
+    }
+}
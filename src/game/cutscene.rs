@@ -0,0 +1,439 @@
+//! Scripted sequences like the intro and the ending, as timelines of [`CutsceneStep`]s.
+//!
+//! Cutscenes are a [`CutsceneData`] asset, written in RON in
+//! `assets/cutscenes/main.cutscenes.ron` and loaded with the levels, so the loading screen
+//! reports a broken file. In dev builds, saving it while playing takes effect right away.
+//!
+//! Trigger [`PlayCutscene`] to start one. Its steps run one after the other while the game
+//! is running, and a [`CutsceneEnded`] is sent once they are done. While it plays, player
+//! input is ignored, since [`AppSet::RecordInput`] only runs without an [`ActiveCutscene`],
+//! and the camera stops following the player. `screen::cutscene` letterboxes the screen and
+//! offers to skip it, which triggers [`SkipCutscene`].
+//!
+//! New runs open with the `intro` cutscene, once per save, and completing the objectives
+//! of the last level plays the `ending`, after which the run is over.
+
+use std::fmt;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use serde::Deserialize;
+
+use super::{
+    assets::{LevelAssets, SfxKey},
+    audio::sfx::PlaySfx,
+    camera::WorldCamera,
+    dialogue::{ActiveDialogue, StartDialogue},
+    mode::GameMode,
+    movement::MovementController,
+    mutators::Modifiers,
+    save::SaveGame,
+    spawn::{
+        player::Player,
+        wave::{spawn_enemy, Difficulty, WaveData},
+    },
+};
+use crate::{
+    events::{CutsceneEnded, ScreenRequest},
+    screen::{PlayingState, Screen},
+    ui::tween::Ease,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<CutsceneData>();
+    app.init_asset_loader::<CutsceneDataLoader>();
+    app.configure_sets(
+        Update,
+        AppSet::RecordInput.run_if(not(resource_exists::<ActiveCutscene>)),
+    );
+    app.observe(play_cutscene);
+    app.observe(skip_cutscene);
+    app.add_systems(OnEnter(PlayingState::Running), start_intro);
+    app.add_systems(OnExit(Screen::Playing), stop_cutscene);
+    app.add_systems(
+        Update,
+        (
+            run_cutscene.in_set(AppSet::Update).run_if(
+                in_state(PlayingState::Running).and_then(resource_exists::<ActiveCutscene>),
+            ),
+            end_run.in_set(AppSet::HandleEvents),
+        ),
+    );
+}
+
+/// Id of the cutscene that new runs open with.
+const INTRO: &str = "intro";
+/// Id of the cutscene that plays once the last level is done.
+pub const ENDING: &str = "ending";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Cutscene {
+    pub id: String,
+    pub steps: Vec<CutsceneStep>,
+}
+
+/// Something a cutscene does, before going on to its next step.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum CutsceneStep {
+    /// Pan the camera to `to` over this many seconds.
+    MoveCamera { to: CameraTarget, seconds: f32 },
+    /// Spawn an enemy from the waves file, like a wave would.
+    SpawnEnemy { enemy: String, position: Vec2 },
+    /// Open the dialogue with this id, and wait until it ends.
+    Dialogue(String),
+    /// Wait this many seconds.
+    Wait(f32),
+    /// Play a sound effect, lowering the music while it plays.
+    Sound(SfxKey),
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CameraTarget {
+    /// A position in the level, in pixels.
+    Position(Vec2),
+    /// Where the player is when the step starts.
+    Player,
+}
+
+/// All cutscenes, as described by their RON file.
+#[derive(Asset, TypePath, Deserialize, Debug, Default)]
+#[serde(transparent)]
+pub struct CutsceneData(pub Vec<Cutscene>);
+
+impl CutsceneData {
+    pub fn get(&self, id: &str) -> Option<&Cutscene> {
+        self.0.iter().find(|cutscene| cutscene.id == id)
+    }
+}
+
+/// The loaded [`CutsceneData`], for systems that look cutscenes up by id.
+#[derive(SystemParam)]
+struct Cutscenes<'w> {
+    level_assets: Res<'w, LevelAssets>,
+    data: Res<'w, Assets<CutsceneData>>,
+}
+
+impl Cutscenes<'_> {
+    /// The cutscene with this id. The loading screen waits for the cutscenes, so they are
+    /// only missing if a hot reload failed.
+    fn get(&self, id: &str) -> Option<&Cutscene> {
+        self.data.get(&self.level_assets.cutscenes)?.get(id)
+    }
+}
+
+#[derive(Default)]
+struct CutsceneDataLoader;
+
+/// Why a cutscenes file couldn't be loaded.
+#[derive(Debug)]
+enum CutsceneDataLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for CutsceneDataLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read cutscenes: {error}"),
+            Self::Ron(error) => write!(f, "could not parse cutscenes: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for CutsceneDataLoaderError {}
+
+impl From<std::io::Error> for CutsceneDataLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for CutsceneDataLoaderError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        Self::Ron(error)
+    }
+}
+
+impl AssetLoader for CutsceneDataLoader {
+    type Asset = CutsceneData;
+    type Settings = ();
+    type Error = CutsceneDataLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<CutsceneData, CutsceneDataLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cutscenes.ron"]
+    }
+}
+
+/// Play the cutscene with this id from its first step.
+#[derive(Event, Debug, Clone)]
+pub struct PlayCutscene(pub String);
+
+/// End the [`ActiveCutscene`] early, only doing what its remaining steps leave behind.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SkipCutscene;
+
+/// The cutscene being played, and how far along it is.
+/// Only exists while a cutscene plays.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ActiveCutscene {
+    pub cutscene: String,
+    /// Index of the current step.
+    pub step: usize,
+    /// Whether the current step was started.
+    started: bool,
+    /// Seconds since the current step started.
+    elapsed: f32,
+    /// Where a camera move started, and where it got to.
+    camera_from: Vec2,
+    camera_at: Vec2,
+}
+
+impl ActiveCutscene {
+    fn new(cutscene: &str) -> Self {
+        Self {
+            cutscene: cutscene.to_string(),
+            step: 0,
+            started: false,
+            elapsed: 0.0,
+            camera_from: Vec2::ZERO,
+            camera_at: Vec2::ZERO,
+        }
+    }
+}
+
+fn play_cutscene(
+    trigger: Trigger<PlayCutscene>,
+    mut commands: Commands,
+    cutscenes: Cutscenes,
+    mut end_events: EventWriter<CutsceneEnded>,
+    mut controller_query: Query<&mut MovementController, With<Player>>,
+) {
+    let id = &trigger.event().0;
+    if cutscenes.get(id).is_none() {
+        // Ended right away, so whatever waits for it isn't stuck.
+        warn!("There is no cutscene {id} to play.");
+        end_events.send(CutsceneEnded {
+            cutscene: id.clone(),
+            skipped: false,
+        });
+        return;
+    }
+    commands.insert_resource(ActiveCutscene::new(id));
+    // Input stops being recorded, so don't keep moving the way the player last did.
+    for mut controller in &mut controller_query {
+        controller.0 = Vec2::ZERO;
+    }
+}
+
+/// The resources steps need to spawn enemies with.
+#[derive(SystemParam)]
+struct EnemySpawner<'w> {
+    level_assets: Res<'w, LevelAssets>,
+    waves: Res<'w, Assets<WaveData>>,
+    difficulty: Res<'w, Difficulty>,
+    modifiers: Res<'w, Modifiers>,
+}
+
+impl EnemySpawner<'_> {
+    fn spawn(&self, commands: &mut Commands, name: &str, position: Vec2) {
+        let Some(enemy) = self
+            .waves
+            .get(&self.level_assets.waves)
+            .and_then(|data| data.enemies.get(name))
+        else {
+            warn!("A cutscene has an unknown enemy \"{name}\", skipping it.");
+            return;
+        };
+        // Not part of any wave, so waves don't wait for it.
+        spawn_enemy(
+            commands,
+            enemy,
+            0,
+            self.difficulty.0,
+            &self.modifiers,
+            position,
+        );
+    }
+}
+
+fn run_cutscene(
+    mut commands: Commands,
+    time: Res<Time>,
+    cutscenes: Cutscenes,
+    mut active: ResMut<ActiveCutscene>,
+    dialogue: Option<Res<ActiveDialogue>>,
+    spawner: EnemySpawner,
+    mut end_events: EventWriter<CutsceneEnded>,
+    mut camera_query: Query<&mut Transform, With<WorldCamera>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+) {
+    let steps = cutscenes
+        .get(&active.cutscene)
+        .map_or(&[][..], |cutscene| &cutscene.steps);
+    // Steps that are done right away don't hold up the next one.
+    while let Some(step) = steps.get(active.step) {
+        if active.started {
+            active.elapsed += time.delta_seconds();
+        } else {
+            active.started = true;
+            active.elapsed = 0.0;
+            match step {
+                CutsceneStep::MoveCamera { .. } => {
+                    let at = camera_query
+                        .get_single()
+                        .map_or(Vec2::ZERO, |transform| transform.translation.xy());
+                    active.camera_from = at;
+                    active.camera_at = at;
+                }
+                CutsceneStep::SpawnEnemy { enemy, position } => {
+                    spawner.spawn(&mut commands, enemy, *position);
+                }
+                CutsceneStep::Dialogue(id) => {
+                    commands.trigger(StartDialogue(id.clone()));
+                }
+                CutsceneStep::Wait(_) => (),
+                CutsceneStep::Sound(key) => {
+                    commands.trigger(PlaySfx::Priority(*key));
+                }
+            }
+        }
+
+        let done = match step {
+            CutsceneStep::MoveCamera { to, seconds } => {
+                let to = match to {
+                    CameraTarget::Position(position) => *position,
+                    CameraTarget::Player => player_query
+                        .get_single()
+                        .map_or(active.camera_from, |transform| transform.translation().xy()),
+                };
+                let fraction = if *seconds > 0.0 {
+                    (active.elapsed / seconds).min(1.0)
+                } else {
+                    1.0
+                };
+                let position = active.camera_from.lerp(to, Ease::OutCubic.apply(fraction));
+                // Moved by the difference, so the camera's shake offset stays intact.
+                for mut transform in &mut camera_query {
+                    transform.translation += (position - active.camera_at).extend(0.0);
+                }
+                active.camera_at = position;
+                fraction >= 1.0
+            }
+            // The dialogue only starts once the trigger above is applied.
+            CutsceneStep::Dialogue(_) => active.elapsed > 0.0 && dialogue.is_none(),
+            CutsceneStep::Wait(seconds) => active.elapsed >= *seconds,
+            CutsceneStep::SpawnEnemy { .. } | CutsceneStep::Sound(_) => true,
+        };
+        if !done {
+            return;
+        }
+        active.step += 1;
+        active.started = false;
+    }
+
+    commands.remove_resource::<ActiveCutscene>();
+    end_events.send(CutsceneEnded {
+        cutscene: active.cutscene.clone(),
+        skipped: false,
+    });
+}
+
+fn skip_cutscene(
+    _trigger: Trigger<SkipCutscene>,
+    mut commands: Commands,
+    cutscenes: Cutscenes,
+    active: Option<Res<ActiveCutscene>>,
+    spawner: EnemySpawner,
+    mut end_events: EventWriter<CutsceneEnded>,
+) {
+    let Some(active) = active else {
+        return;
+    };
+    let steps = cutscenes
+        .get(&active.cutscene)
+        .map_or(&[][..], |cutscene| &cutscene.steps);
+    // The run carries on with what was spawned, so that still happens. A step that
+    // already started did its part.
+    let first = active.step + usize::from(active.started);
+    for step in steps.iter().skip(first) {
+        if let CutsceneStep::SpawnEnemy { enemy, position } = step {
+            spawner.spawn(&mut commands, enemy, *position);
+        }
+    }
+    commands.remove_resource::<ActiveCutscene>();
+    end_events.send(CutsceneEnded {
+        cutscene: active.cutscene.clone(),
+        skipped: true,
+    });
+}
+
+fn stop_cutscene(mut commands: Commands) {
+    commands.remove_resource::<ActiveCutscene>();
+}
+
+/// Play the intro on the first time a save is played, and remember it was.
+fn start_intro(mut commands: Commands, cutscenes: Cutscenes, mut save: ResMut<SaveGame>) {
+    if cutscenes.get(INTRO).is_some() && save.unlocks.insert(format!("cutscene_{INTRO}")) {
+        commands.trigger(PlayCutscene(INTRO.to_string()));
+    }
+}
+
+/// Go to the results once the ending is over, the same as when the player dies.
+fn end_run(
+    mut end_events: EventReader<CutsceneEnded>,
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mode: Res<GameMode>,
+) {
+    if end_events.read().any(|event| event.cutscene == ENDING) {
+        screen_requests.send(ScreenRequest::To(mode.results_screen()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::dialogue::DialogueCatalog;
+
+    #[test]
+    fn cutscenes_refer_to_existing_dialogue_and_enemies() {
+        let data: CutsceneData =
+            ron::from_str(include_str!("../../assets/cutscenes/main.cutscenes.ron")).unwrap();
+        let dialogue = DialogueCatalog::load();
+        let waves: WaveData =
+            ron::from_str(include_str!("../../assets/waves/main.waves.ron")).unwrap();
+        assert!(data.get("intro").is_some());
+        assert!(data.get(ENDING).is_some());
+        for cutscene in &data.0 {
+            assert!(!cutscene.steps.is_empty(), "{}", cutscene.id);
+            for step in &cutscene.steps {
+                match step {
+                    CutsceneStep::Dialogue(id) => {
+                        assert!(dialogue.get(id).is_some(), "{} {id}", cutscene.id);
+                    }
+                    CutsceneStep::SpawnEnemy { enemy, .. } => {
+                        assert!(waves.enemies.contains_key(enemy), "{} {enemy}", cutscene.id);
+                    }
+                    CutsceneStep::MoveCamera { seconds, .. } | CutsceneStep::Wait(seconds) => {
+                        assert!(*seconds >= 0.0, "{}", cutscene.id);
+                    }
+                    CutsceneStep::Sound(_) => (),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! The game's cycle, like day and night, tides or the phases of the moon.
+//! A cycle is a loop of named phases, set up per level with [`CycleParameters`].
+//! The current one is the [`CyclePhase`] resource, which advances on a timer,
+//! when the player presses [`Action::AdvanceCycle`], or both.
+//!
+//! Each step sends [`PhaseChanged`], and completing a whole cycle also sends
+//! [`OnCycleAdvance`].
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    cosmetics::AchievementUnlocked,
+    input::{Action, ActionInput},
+};
+use crate::{
+    events::{OnCycleAdvance, PhaseChanged},
+    screen::{PlayingState, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CyclePhase>();
+    app.add_systems(
+        FixedUpdate,
+        (
+            tick_cycle.in_set(AppSet::TickTimers),
+            unlock_cycle_achievements.in_set(AppSet::HandleEvents),
+        )
+            .run_if(in_state(PlayingState::Running).and_then(resource_exists::<CyclePhase>)),
+    );
+    app.add_systems(
+        Update,
+        advance_cycle_on_action
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(PlayingState::Running).and_then(resource_exists::<CyclePhase>)),
+    );
+    app.add_systems(OnExit(Screen::Playing), remove_cycle);
+}
+
+/// How a level's cycle goes.
+#[derive(Deserialize, Reflect, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct CycleParameters {
+    /// The names of the phases, in order.
+    pub phases: Vec<String>,
+    /// Seconds each phase lasts, or 0 to only advance on player action.
+    pub phase_duration: f32,
+    /// Whether the player can advance to the next phase early.
+    pub advance_on_action: bool,
+}
+
+impl Default for CycleParameters {
+    fn default() -> Self {
+        Self {
+            phases: ["Day", "Dusk", "Night", "Dawn"].map(String::from).into(),
+            phase_duration: 30.0,
+            advance_on_action: true,
+        }
+    }
+}
+
+/// Where the game is in its cycle. Only exists while playing.
+#[derive(Resource, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Resource)]
+pub struct CyclePhase {
+    /// How many full cycles have been completed.
+    pub cycle: u32,
+    /// Index of the current phase within the cycle.
+    pub phase: u32,
+    /// Seconds into the current phase.
+    elapsed: f32,
+    parameters: CycleParameters,
+}
+
+impl CyclePhase {
+    pub fn new(parameters: CycleParameters) -> Self {
+        Self {
+            cycle: 0,
+            phase: 0,
+            elapsed: 0.0,
+            parameters,
+        }
+    }
+
+    /// Continue from a saved cycle and phase.
+    pub fn resumed(mut self, cycle: u32, phase: u32) -> Self {
+        self.cycle = cycle;
+        self.phase = phase % self.phase_count();
+        self
+    }
+
+    pub fn phase_count(&self) -> u32 {
+        (self.parameters.phases.len() as u32).max(1)
+    }
+
+    /// The name of the current phase.
+    pub fn phase_name(&self) -> &str {
+        self.parameters
+            .phases
+            .get(self.phase as usize)
+            .map_or("", String::as_str)
+    }
+
+    /// Whether the player can advance the cycle with [`Action::AdvanceCycle`].
+    pub fn advances_on_action(&self) -> bool {
+        self.parameters.advance_on_action
+    }
+
+    /// How far the current phase is, from 0 to 1. Stays at 0 without a timer.
+    pub fn phase_progress(&self) -> f32 {
+        if self.parameters.phase_duration > 0.0 {
+            (self.elapsed / self.parameters.phase_duration).min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// How far the current cycle is, from 0 to 1.
+    pub fn cycle_progress(&self) -> f32 {
+        (self.phase as f32 + self.phase_progress()) / self.phase_count() as f32
+    }
+
+    /// Go to the next phase, returning whether that completed a cycle.
+    pub fn advance(&mut self) -> bool {
+        self.elapsed = 0.0;
+        self.phase += 1;
+        if self.phase < self.phase_count() {
+            return false;
+        }
+        self.phase = 0;
+        self.cycle += 1;
+        true
+    }
+
+    /// Advance the timer by `dt` seconds, returning how many phases passed.
+    pub fn tick(&mut self, dt: f32) -> u32 {
+        let duration = self.parameters.phase_duration;
+        if duration <= 0.0 {
+            return 0;
+        }
+        self.elapsed += dt;
+        let mut passed = 0;
+        while self.elapsed >= duration {
+            let left = self.elapsed - duration;
+            self.advance();
+            self.elapsed = left;
+            passed += 1;
+        }
+        passed
+    }
+}
+
+/// Send the events for the phase the cycle just advanced to.
+fn announce_phase(
+    cycle: &CyclePhase,
+    completed_cycle: bool,
+    phase_events: &mut EventWriter<PhaseChanged>,
+    cycle_events: &mut EventWriter<OnCycleAdvance>,
+) {
+    if completed_cycle {
+        cycle_events.send(OnCycleAdvance {
+            completed: cycle.cycle,
+        });
+    }
+    phase_events.send(PhaseChanged {
+        cycle: cycle.cycle,
+        phase: cycle.phase,
+    });
+}
+
+fn tick_cycle(
+    time: Res<Time>,
+    mut cycle: ResMut<CyclePhase>,
+    mut phase_events: EventWriter<PhaseChanged>,
+    mut cycle_events: EventWriter<OnCycleAdvance>,
+) {
+    let before = cycle.cycle;
+    let passed = cycle.tick(time.delta_seconds());
+    if passed > 0 {
+        // A long frame may skip phases, which only announces where it ended up.
+        announce_phase(
+            &cycle,
+            cycle.cycle > before,
+            &mut phase_events,
+            &mut cycle_events,
+        );
+    }
+}
+
+fn advance_cycle_on_action(
+    actions: ActionInput,
+    mut cycle: ResMut<CyclePhase>,
+    mut phase_events: EventWriter<PhaseChanged>,
+    mut cycle_events: EventWriter<OnCycleAdvance>,
+) {
+    if cycle.advances_on_action() && actions.just_pressed(Action::AdvanceCycle) {
+        let completed = cycle.advance();
+        announce_phase(&cycle, completed, &mut phase_events, &mut cycle_events);
+    }
+}
+
+fn unlock_cycle_achievements(
+    mut commands: Commands,
+    mut cycle_events: EventReader<OnCycleAdvance>,
+) {
+    for event in cycle_events.read() {
+        if event.completed == 1 {
+            commands.trigger(AchievementUnlocked("first_cycle".to_string()));
+        }
+    }
+}
+
+fn remove_cycle(mut commands: Commands) {
+    commands.remove_resource::<CyclePhase>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_advance_through_their_phases() {
+        let mut cycle = CyclePhase::new(CycleParameters {
+            phases: vec!["Day".to_string(), "Night".to_string()],
+            phase_duration: 10.0,
+            advance_on_action: true,
+        });
+        assert_eq!(cycle.tick(5.0), 0);
+        assert_eq!(cycle.cycle_progress(), 0.25);
+        assert_eq!(cycle.tick(10.0), 1);
+        assert_eq!(cycle.phase_name(), "Night");
+        assert_eq!(cycle.cycle_progress(), 0.75);
+        // Advancing early restarts the phase timer.
+        assert!(cycle.advance());
+        assert_eq!((cycle.cycle, cycle.phase), (1, 0));
+        assert_eq!(cycle.cycle_progress(), 0.0);
+
+        let resumed = CyclePhase::new(CycleParameters::default()).resumed(3, 6);
+        assert_eq!((resumed.cycle, resumed.phase_name()), (3, "Night"));
+    }
+}
@@ -0,0 +1,100 @@
+//! Numbers that pop up where damage is taken, colored by its [`DamageType`](super::health::DamageType),
+//! then float up and fade out.
+
+use bevy::prelude::*;
+
+use crate::{
+    events::DamageTaken,
+    layers::{Layer, OnLayer},
+    screen::{PlayingState, Screen},
+    ui::palette::GameplayPalette,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DamageNumber>();
+    app.add_systems(
+        Update,
+        (
+            float_damage_numbers.in_set(AppSet::Update),
+            spawn_damage_numbers.in_set(AppSet::HandleEvents),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct DamageNumber {
+    color: Color,
+    age: f32,
+}
+
+/// Seconds a number stays up.
+const LIFETIME: f32 = 0.8;
+/// How fast numbers float up, in world units per second.
+const RISE_SPEED: f32 = 120.0;
+/// How far above the entity's center numbers start.
+const OFFSET: f32 = 96.0;
+const FONT_SIZE: f32 = 48.0;
+/// Ticks of status effects are smaller, so hits stand out.
+const STATUS_FONT_SIZE: f32 = 32.0;
+
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    mut taken_events: EventReader<DamageTaken>,
+    palette: Res<GameplayPalette>,
+    transform_query: Query<&GlobalTransform>,
+) {
+    for event in taken_events.read() {
+        let Ok(transform) = transform_query.get(event.target) else {
+            continue;
+        };
+        let color = palette.color(event.kind.color());
+        let font_size = if event.from_status {
+            STATUS_FONT_SIZE
+        } else {
+            FONT_SIZE
+        };
+        commands.spawn((
+            Name::new("Damage Number"),
+            DamageNumber { color, age: 0.0 },
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("{}", event.amount.round().max(1.0)),
+                    TextStyle {
+                        font_size,
+                        color,
+                        ..default()
+                    },
+                ),
+                transform: Transform::from_translation(
+                    (transform.translation().truncate() + Vec2::Y * OFFSET).extend(0.0),
+                ),
+                ..default()
+            },
+            OnLayer::new(Layer::WorldUi),
+            StateScoped(Screen::Playing),
+        ));
+    }
+}
+
+fn float_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut number_query: Query<(Entity, &mut DamageNumber, &mut Transform, &mut Text)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut number, mut transform, mut text) in &mut number_query {
+        number.age += dt;
+        if number.age >= LIFETIME {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.translation.y += RISE_SPEED * dt;
+        let alpha = 1.0 - number.age / LIFETIME;
+        for section in &mut text.sections {
+            section.style.color = number.color.with_alpha(alpha);
+        }
+    }
+}
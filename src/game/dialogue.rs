@@ -0,0 +1,193 @@
+//! Conversations and narration, as trees of lines defined in `assets/dialogue/dialogue.ron`.
+//!
+//! Trigger [`StartDialogue`] to open one. The game waits in [`PlayingState::Dialogue`]
+//! while it lasts, with its lines in a dialogue box, see `screen::dialogue`, which triggers
+//! [`ContinueDialogue`] to move through them. Reaching a line sends a
+//! [`DialogueNodeReached`] and the end sends a [`DialogueEnded`], so gameplay can wait for a
+//! conversation or react to where it went, like cutscenes do, see `cutscene`.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::assets::parse_embedded;
+use crate::{
+    events::{DialogueEnded, DialogueNodeReached},
+    screen::PlayingState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(DialogueCatalog::load());
+    app.observe(start_dialogue);
+    app.observe(continue_dialogue);
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Dialogue {
+    /// Stays the same when the lines change, since it is saved.
+    pub id: String,
+    /// The lines, starting with the first.
+    pub nodes: Vec<DialogueNode>,
+}
+
+impl Dialogue {
+    pub fn node(&self, id: &str) -> Option<&DialogueNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+}
+
+/// A line of dialogue.
+#[derive(Deserialize, Debug, Clone)]
+pub struct DialogueNode {
+    pub id: String,
+    pub speaker: String,
+    pub text: String,
+    /// The node that follows, or none to end the dialogue.
+    /// Not used when there are choices.
+    #[serde(default)]
+    pub next: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+}
+
+/// An answer the player can pick, leading to its own node.
+#[derive(Deserialize, Debug, Clone)]
+pub struct DialogueChoice {
+    pub text: String,
+    /// The node it leads to, or none to end the dialogue.
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+/// All dialogues from `assets/dialogue/dialogue.ron`.
+#[derive(Resource, Debug, Default)]
+pub struct DialogueCatalog(pub Vec<Dialogue>);
+
+impl DialogueCatalog {
+    const SOURCE: &'static str = include_str!("../../assets/dialogue/dialogue.ron");
+
+    pub fn load() -> Self {
+        Self(parse_embedded(Self::SOURCE, "dialogue"))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Dialogue> {
+        self.0.iter().find(|dialogue| dialogue.id == id)
+    }
+}
+
+/// Open the dialogue with this id at its first line, pausing the game.
+#[derive(Event, Debug, Clone)]
+pub struct StartDialogue(pub String);
+
+/// Go to the line with this id in the [`ActiveDialogue`], or end it with `None`.
+#[derive(Event, Debug, Clone)]
+pub struct ContinueDialogue(pub Option<String>);
+
+/// The dialogue being shown, and the id of its current line.
+/// Only exists while a dialogue is open.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct ActiveDialogue {
+    pub dialogue: String,
+    pub node: String,
+}
+
+impl ActiveDialogue {
+    /// The current line, unless the catalog changed since.
+    pub fn current<'a>(&self, catalog: &'a DialogueCatalog) -> Option<&'a DialogueNode> {
+        catalog.get(&self.dialogue)?.node(&self.node)
+    }
+}
+
+fn start_dialogue(
+    trigger: Trigger<StartDialogue>,
+    mut commands: Commands,
+    catalog: Res<DialogueCatalog>,
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+    mut node_events: EventWriter<DialogueNodeReached>,
+) {
+    let id = &trigger.event().0;
+    let Some(first) = catalog.get(id).and_then(|dialogue| dialogue.nodes.first()) else {
+        warn!("There is no dialogue {id} to start.");
+        return;
+    };
+    commands.insert_resource(ActiveDialogue {
+        dialogue: id.clone(),
+        node: first.id.clone(),
+    });
+    next_playing_state.set(PlayingState::Dialogue);
+    node_events.send(DialogueNodeReached {
+        dialogue: id.clone(),
+        node: first.id.clone(),
+    });
+}
+
+fn continue_dialogue(
+    trigger: Trigger<ContinueDialogue>,
+    mut commands: Commands,
+    catalog: Res<DialogueCatalog>,
+    active: Option<ResMut<ActiveDialogue>>,
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+    mut node_events: EventWriter<DialogueNodeReached>,
+    mut end_events: EventWriter<DialogueEnded>,
+) {
+    let Some(mut active) = active else {
+        return;
+    };
+    let next = trigger.event().0.as_deref().filter(|&next| {
+        let exists = catalog
+            .get(&active.dialogue)
+            .is_some_and(|dialogue| dialogue.node(next).is_some());
+        if !exists {
+            warn!("Dialogue {} has no node {next}.", active.dialogue);
+        }
+        exists
+    });
+    if let Some(next) = next {
+        active.node = next.to_string();
+        node_events.send(DialogueNodeReached {
+            dialogue: active.dialogue.clone(),
+            node: active.node.clone(),
+        });
+        return;
+    }
+    commands.remove_resource::<ActiveDialogue>();
+    next_playing_state.set(PlayingState::Running);
+    end_events.send(DialogueEnded {
+        dialogue: active.dialogue.clone(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::typewriter::Typewriter;
+
+    #[test]
+    fn dialogue_nodes_lead_to_existing_nodes() {
+        let catalog = DialogueCatalog::load();
+        assert!(catalog.get("intro").is_some());
+        for dialogue in &catalog.0 {
+            assert!(!dialogue.nodes.is_empty(), "{}", dialogue.id);
+            for node in &dialogue.nodes {
+                let nexts = node
+                    .choices
+                    .iter()
+                    .map(|choice| &choice.next)
+                    .chain([&node.next]);
+                for next in nexts.flatten() {
+                    assert!(dialogue.node(next).is_some(), "{} {next}", dialogue.id);
+                }
+            }
+        }
+
+        // Characters are revealed whole, however many bytes they take.
+        let mut typewriter = Typewriter::default();
+        typewriter.set("Ké ké!");
+        assert_eq!(typewriter.visible(), "");
+        typewriter.step(2.5 / 45.0);
+        assert_eq!(typewriter.visible(), "Ké");
+        assert!(!typewriter.is_finished());
+        typewriter.finish();
+        assert_eq!(typewriter.visible(), "Ké ké!");
+        assert!(typewriter.is_finished());
+    }
+}
@@ -0,0 +1,179 @@
+//! Stand-ins for images, sounds and fonts that failed to load, so a missing file shows up
+//! as a magenta square or silence instead of stopping the game.
+//!
+//! The fallbacks are made in code, except for the font, which is Bevy's embedded default.
+//! Each substitution is logged and kept in [`Fallbacks`], which the loading screen counts as
+//! loaded and dev builds list in a corner of the screen. Loading the real file later,
+//! like with the loading screen's retry button, replaces the fallback again.
+
+use bevy::{
+    asset::{AssetLoadFailedEvent, TrackAssets, UntypedAssetId},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Fallbacks>();
+    // Right after the asset server reports the failure, so nothing sees it missing.
+    app.add_systems(
+        PreUpdate,
+        (
+            substitute_failed::<Image>,
+            substitute_failed::<AudioSource>,
+            substitute_failed::<Font>,
+            forget_loaded::<Image>,
+            forget_loaded::<AudioSource>,
+            forget_loaded::<Font>,
+        )
+            .after(TrackAssets),
+    );
+}
+
+/// An asset that failed to load, and was replaced with its fallback.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Substitution {
+    pub id: UntypedAssetId,
+    pub path: String,
+    /// Why it failed to load.
+    pub error: String,
+}
+
+/// Every asset that is currently a fallback.
+#[derive(Resource, Debug, Default)]
+pub struct Fallbacks(pub Vec<Substitution>);
+
+impl Fallbacks {
+    pub fn contains(&self, id: impl Into<UntypedAssetId>) -> bool {
+        let id = id.into();
+        self.0.iter().any(|substitution| substitution.id == id)
+    }
+}
+
+/// An asset type with a stand-in for files that fail to load.
+trait Fallback: Asset + Sized {
+    /// `None` if there is nothing to stand in with.
+    fn fallback(assets: &Assets<Self>) -> Option<Self>;
+}
+
+impl Fallback for Image {
+    /// A single magenta pixel, which stands out wherever it is stretched to.
+    fn fallback(_assets: &Assets<Self>) -> Option<Self> {
+        Some(Image::new_fill(
+            Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255, 0, 255, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        ))
+    }
+}
+
+impl Fallback for AudioSource {
+    fn fallback(_assets: &Assets<Self>) -> Option<Self> {
+        Some(AudioSource {
+            bytes: silent_wav().into(),
+        })
+    }
+}
+
+impl Fallback for Font {
+    /// A copy of the default font, which is missing in builds without Bevy's `default_font`.
+    fn fallback(assets: &Assets<Self>) -> Option<Self> {
+        assets.get(&Handle::<Self>::default()).cloned()
+    }
+}
+
+/// A tenth of a second of 16 bit mono silence, as a WAV file.
+pub fn silent_wav() -> Vec<u8> {
+    const SAMPLE_RATE: u32 = 8000;
+    let data_size = SAMPLE_RATE / 10 * 2;
+    let mut wav = Vec::with_capacity(44 + data_size as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    // Format chunk size, PCM, one channel.
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    // Bytes per second, bytes per sample and bits per sample.
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    wav.resize(44 + data_size as usize, 0);
+    wav
+}
+
+fn substitute_failed<A: Fallback>(
+    mut failed_events: EventReader<AssetLoadFailedEvent<A>>,
+    mut assets: ResMut<Assets<A>>,
+    mut fallbacks: ResMut<Fallbacks>,
+) {
+    for event in failed_events.read() {
+        let Some(fallback) = A::fallback(&assets) else {
+            warn!(
+                "Failed to load {}, and there is no fallback for it.",
+                event.path
+            );
+            continue;
+        };
+        warn!(
+            "Failed to load {}, using a fallback instead: {}",
+            event.path, event.error
+        );
+        assets.insert(event.id, fallback);
+        let id = event.id.untyped();
+        fallbacks.0.retain(|substitution| substitution.id != id);
+        fallbacks.0.push(Substitution {
+            id,
+            path: event.path.to_string(),
+            error: event.error.to_string(),
+        });
+    }
+}
+
+/// Drop fallbacks whose real asset loaded after all.
+fn forget_loaded<A: Asset>(
+    mut asset_events: EventReader<AssetEvent<A>>,
+    mut fallbacks: ResMut<Fallbacks>,
+) {
+    for event in asset_events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } = event {
+            let id = id.untyped();
+            if let Some(substitution) = fallbacks
+                .0
+                .iter()
+                .find(|substitution| substitution.id == id)
+            {
+                info!("Loaded {}, replacing its fallback.", substitution.path);
+                fallbacks.0.retain(|substitution| substitution.id != id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_sound_is_a_whole_wav_file() {
+        let wav = silent_wav();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        let riff_size = u32::from_le_bytes(wav[4..8].try_into().unwrap());
+        let data_size = u32::from_le_bytes(wav[40..44].try_into().unwrap());
+        assert_eq!(riff_size as usize, wav.len() - 8);
+        assert_eq!(data_size as usize, wav.len() - 44);
+        assert!(wav[44..].iter().all(|&sample| sample == 0));
+    }
+}
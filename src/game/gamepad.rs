@@ -0,0 +1,268 @@
+//! Gamepad input: button bindings for [`Action`]s, the left stick for movement,
+//! button names for each controller layout, and rumble feedback scaled by the rumble setting.
+
+use std::time::Duration;
+
+use bevy::{
+    input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use super::input::Action;
+use crate::{display::ToggleSetting, BoundedU8, GameSettings, LevelSetting};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(GamepadBindings, RumbleSetting, GamepadLayoutSetting)>();
+    app.init_resource::<GamepadBindings>();
+    app.observe(rumble);
+}
+
+/// Gamepad buttons bound to each [`Action`].
+#[derive(Resource, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Resource)]
+pub struct GamepadBindings(HashMap<Action, GamepadButtonType>);
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        Self(
+            [
+                (Action::MoveUp, GamepadButtonType::DPadUp),
+                (Action::MoveDown, GamepadButtonType::DPadDown),
+                (Action::MoveLeft, GamepadButtonType::DPadLeft),
+                (Action::MoveRight, GamepadButtonType::DPadRight),
+                (Action::Sprint, GamepadButtonType::LeftThumb),
+                (Action::AdvanceCycle, GamepadButtonType::West),
+                (Action::Rewind, GamepadButtonType::LeftTrigger),
+                (Action::QuickWheel, GamepadButtonType::North),
+                (Action::Interact, GamepadButtonType::South),
+                (Action::Pause, GamepadButtonType::Start),
+            ]
+            .into(),
+        )
+    }
+}
+
+impl GamepadBindings {
+    pub fn get(&self, action: Action) -> Option<GamepadButtonType> {
+        self.0.get(&action).copied()
+    }
+}
+
+/// Stick deflection below which input is ignored, to hide stick drift.
+const STICK_DEADZONE: f32 = 0.2;
+
+/// The most deflected left stick of all connected gamepads, rescaled so that
+/// it starts at zero at the edge of the deadzone.
+pub fn left_stick(gamepads: &Gamepads, axes: &Axis<GamepadAxis>) -> Vec2 {
+    let stick = gamepads
+        .iter()
+        .map(|gamepad| {
+            Vec2::new(
+                axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                    .unwrap_or_default(),
+                axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                    .unwrap_or_default(),
+            )
+        })
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .unwrap_or_default();
+    let length = stick.length();
+    if length < STICK_DEADZONE {
+        return Vec2::ZERO;
+    }
+    stick / length * ((length - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)).min(1.0)
+}
+
+/// Controller families, which differ in how their face buttons are labeled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadLayout {
+    Generic,
+    Xbox,
+    PlayStation,
+    Switch,
+}
+
+impl GamepadLayout {
+    /// Guess the layout from the name the gamepad reports.
+    fn detect(name: &str) -> Self {
+        let name = name.to_lowercase();
+        let contains_any = |words: &[&str]| words.iter().any(|word| name.contains(word));
+        if contains_any(&["xbox", "x-box", "xinput"]) {
+            GamepadLayout::Xbox
+        } else if contains_any(&[
+            "playstation",
+            "dualshock",
+            "dualsense",
+            "ps3",
+            "ps4",
+            "ps5",
+            // What Sony controllers report themselves as on some platforms.
+            "wireless controller",
+        ]) {
+            GamepadLayout::PlayStation
+        } else if contains_any(&["nintendo", "switch", "pro controller", "joy-con"]) {
+            GamepadLayout::Switch
+        } else {
+            GamepadLayout::Generic
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            GamepadLayout::Generic => "Generic",
+            GamepadLayout::Xbox => "Xbox",
+            GamepadLayout::PlayStation => "PlayStation",
+            GamepadLayout::Switch => "Switch",
+        }
+    }
+
+    /// The label printed on `button` for this layout.
+    pub fn button_name(self, button: GamepadButtonType) -> String {
+        use GamepadButtonType as B;
+        use GamepadLayout as L;
+        let name = match (self, button) {
+            (L::Xbox, B::South) => "A",
+            (L::Xbox, B::East) => "B",
+            (L::Xbox, B::West) => "X",
+            (L::Xbox, B::North) => "Y",
+            (L::Xbox, B::LeftTrigger) => "LB",
+            (L::Xbox, B::RightTrigger) => "RB",
+            (L::Xbox, B::LeftTrigger2) => "LT",
+            (L::Xbox, B::RightTrigger2) => "RT",
+            (L::Xbox, B::Start) => "Menu",
+            (L::Xbox, B::Select) => "View",
+            (L::PlayStation, B::South) => "Cross",
+            (L::PlayStation, B::East) => "Circle",
+            (L::PlayStation, B::West) => "Square",
+            (L::PlayStation, B::North) => "Triangle",
+            (L::PlayStation, B::LeftTrigger) => "L1",
+            (L::PlayStation, B::RightTrigger) => "R1",
+            (L::PlayStation, B::LeftTrigger2) => "L2",
+            (L::PlayStation, B::RightTrigger2) => "R2",
+            (L::PlayStation, B::Start) => "Options",
+            (L::PlayStation, B::Select) => "Share",
+            // Nintendo swaps the labels of the face buttons.
+            (L::Switch, B::South) => "B",
+            (L::Switch, B::East) => "A",
+            (L::Switch, B::West) => "Y",
+            (L::Switch, B::North) => "X",
+            (L::Switch, B::LeftTrigger) => "L",
+            (L::Switch, B::RightTrigger) => "R",
+            (L::Switch, B::LeftTrigger2) => "ZL",
+            (L::Switch, B::RightTrigger2) => "ZR",
+            (L::Switch, B::Start) => "+",
+            (L::Switch, B::Select) => "-",
+            (_, B::DPadUp) => "D-pad up",
+            (_, B::DPadDown) => "D-pad down",
+            (_, B::DPadLeft) => "D-pad left",
+            (_, B::DPadRight) => "D-pad right",
+            _ => return format!("{button:?}"),
+        };
+        name.to_string()
+    }
+}
+
+/// Which [`GamepadLayout`] to name buttons by, where 0 detects it from the connected gamepad.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Default, Eq, PartialEq, Reflect)]
+pub(crate) struct GamepadLayoutSetting(pub(crate) BoundedU8<0, 4>);
+
+impl LevelSetting for GamepadLayoutSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl GamepadLayoutSetting {
+    fn preset(&self) -> Option<GamepadLayout> {
+        match self.0 .0 {
+            0 => None,
+            1 => Some(GamepadLayout::Generic),
+            2 => Some(GamepadLayout::Xbox),
+            3 => Some(GamepadLayout::PlayStation),
+            _ => Some(GamepadLayout::Switch),
+        }
+    }
+
+    /// The chosen layout, or the layout of the first connected gamepad.
+    pub(crate) fn layout(&self, gamepads: &Gamepads) -> GamepadLayout {
+        self.preset().unwrap_or_else(|| {
+            gamepads
+                .iter()
+                .find_map(|gamepad| gamepads.name(gamepad))
+                .map_or(GamepadLayout::Generic, GamepadLayout::detect)
+        })
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        self.preset()
+            .map_or("Automatic", GamepadLayout::name)
+            .to_string()
+    }
+}
+
+/// The button that presses focused UI widgets. With `swap_confirm`, it is the east button,
+/// as is the convention on Nintendo consoles.
+pub(crate) fn confirm_button(swap_confirm: &ToggleSetting) -> GamepadButtonType {
+    if swap_confirm.is_on() {
+        GamepadButtonType::East
+    } else {
+        GamepadButtonType::South
+    }
+}
+
+/// Rumble strength, where 0 disables rumble entirely.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Eq, PartialEq, Reflect)]
+pub(crate) struct RumbleSetting(pub(crate) BoundedU8<0, 10>);
+
+impl LevelSetting for RumbleSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+/// Trigger this event to rumble all connected gamepads.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Rumble {
+    /// Strength between 0 and 1, before the rumble setting is applied.
+    pub strength: f32,
+    pub duration: Duration,
+}
+
+impl Rumble {
+    /// A short, sharp rumble for taking a hit.
+    pub const HIT: Self = Self {
+        strength: 0.8,
+        duration: Duration::from_millis(200),
+    };
+    /// A light tick for picking something up.
+    pub const PICKUP: Self = Self {
+        strength: 0.3,
+        duration: Duration::from_millis(80),
+    };
+}
+
+fn rumble(
+    trigger: Trigger<Rumble>,
+    settings: Res<GameSettings>,
+    gamepads: Res<Gamepads>,
+    mut rumble_requests: EventWriter<GamepadRumbleRequest>,
+) {
+    let Rumble { strength, duration } = *trigger.event();
+    let strength = strength * settings.rumble_level.fraction();
+    if strength <= 0.0 {
+        return;
+    }
+    for gamepad in gamepads.iter() {
+        rumble_requests.send(GamepadRumbleRequest::Add {
+            gamepad,
+            duration,
+            intensity: GamepadRumbleIntensity {
+                // The weak motor gives most of the high-frequency "buzz".
+                strong_motor: strength,
+                weak_motor: strength * 0.5,
+            },
+        });
+    }
+}
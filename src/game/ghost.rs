@@ -0,0 +1,246 @@
+//! Ghosts of the player's fastest run through each level.
+//!
+//! While a level is played from its start, the player's path is recorded a [`Snapshot`]
+//! per tick, and rewinding takes ticks back off it. Completing the level in fewer ticks
+//! than the stored ghost replaces it, encoded with [`encode_snapshots`] so that a whole run
+//! only takes a few kilobytes of storage. The next time the level spawns, a translucent
+//! ducky runs the stored path alongside the player.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    assets::ImageAssets,
+    interpolation::InterpolatedTransform,
+    objectives::LevelCompleted,
+    rewind::{is_rewinding, Rewind, RewindHistory, Snapshot},
+    save::SaveGame,
+    snapshot_codec::{decode_snapshots, encode_snapshots},
+    spawn::{
+        level::SpawnLevel,
+        player::{ducky_atlas_layout, Player},
+    },
+};
+use crate::{
+    layers::{Layer, OnLayer, LAYER_DEPTH},
+    screen::{PlayingState, Screen},
+    storage, AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(storage::load::<Ghosts>(GHOSTS_KEY).unwrap_or_default());
+    app.init_resource::<GhostRecording>();
+    app.register_type::<Ghost>();
+    app.observe(start_ghost);
+    app.observe(keep_fastest_run);
+    app.add_systems(
+        FixedUpdate,
+        run_ghosts
+            .in_set(AppSet::Update)
+            .run_if(in_state(PlayingState::Running).and_then(not(is_rewinding))),
+    );
+    // After the rewind history is recorded or played back.
+    app.add_systems(
+        FixedLast,
+        record_ghost.run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// Key under which [`Ghosts`] are persisted.
+const GHOSTS_KEY: &str = "ghosts";
+
+/// The fastest run through each level, by level name.
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Ghosts(HashMap<String, StoredGhost>);
+
+/// A run's path, as stored.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoredGhost {
+    /// How many ticks the run took, to compare runs without decoding them.
+    pub ticks: usize,
+    /// Snapshots from `snapshot_codec`, in base64.
+    pub snapshots: String,
+}
+
+impl StoredGhost {
+    pub fn new(snapshots: &[Snapshot]) -> Self {
+        Self {
+            ticks: snapshots.len(),
+            snapshots: BASE64.encode(encode_snapshots(snapshots)),
+        }
+    }
+
+    /// The snapshots, or `None` if they were stored by an older encoding.
+    pub fn decode(&self) -> Option<Vec<Snapshot>> {
+        let bytes = BASE64.decode(&self.snapshots).ok()?;
+        decode_snapshots(&bytes)
+    }
+}
+
+/// The player's path through the level being played, if it was played from its start.
+#[derive(Resource, Debug, Default)]
+struct GhostRecording {
+    snapshots: Option<Vec<Snapshot>>,
+    /// How long the player's rewind history was at the end of the last tick.
+    history_len: usize,
+}
+
+/// Runs a stored path, a snapshot per tick, and vanishes at its end.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Ghost {
+    snapshots: Vec<Snapshot>,
+    tick: usize,
+}
+
+fn start_ghost(
+    _trigger: Trigger<SpawnLevel>,
+    mut commands: Commands,
+    save: Res<SaveGame>,
+    ghosts: Res<Ghosts>,
+    images: Res<ImageAssets>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut recording: ResMut<GhostRecording>,
+    ghost_query: Query<Entity, With<Ghost>>,
+) {
+    for entity in &ghost_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    // A run continued from a save didn't start where the ghost did.
+    *recording = GhostRecording {
+        snapshots: save.player_position.is_none().then(Vec::new),
+        history_len: 0,
+    };
+
+    let Some(stored) = ghosts.0.get(&save.level) else {
+        return;
+    };
+    let Some(snapshots) = stored.decode() else {
+        warn!("Could not decode the ghost of level {}.", save.level);
+        return;
+    };
+    let Some(start) = snapshots.first() else {
+        return;
+    };
+    commands.spawn((
+        Name::new("Ghost"),
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(1.0, 1.0, 1.0, 0.4),
+                ..default()
+            },
+            texture: images.ducky.clone_weak(),
+            transform: Transform::from_translation(start.position.extend(0.0))
+                .with_scale(Vec2::splat(8.0).extend(1.0)),
+            ..default()
+        },
+        TextureAtlas {
+            layout: texture_atlas_layouts.add(ducky_atlas_layout()),
+            index: 0,
+        },
+        Ghost { snapshots, tick: 0 },
+        InterpolatedTransform::default(),
+        // Behind the player, who is on the world layer.
+        OnLayer::new(Layer::Background).with_order(LAYER_DEPTH - 1.0),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn record_ghost(
+    time: Res<Time>,
+    rewind: Res<Rewind>,
+    mut recording: ResMut<GhostRecording>,
+    player_query: Query<(&Transform, &RewindHistory), With<Player>>,
+) {
+    let Ok((transform, history)) = player_query.get_single() else {
+        return;
+    };
+    let last_history_len = std::mem::replace(&mut recording.history_len, history.len());
+    let Some(snapshots) = &mut recording.snapshots else {
+        return;
+    };
+    if rewind.active {
+        // Take back as many ticks as rewinding took off the player's history.
+        let taken_back = last_history_len.saturating_sub(history.len());
+        snapshots.truncate(snapshots.len().saturating_sub(taken_back));
+        return;
+    }
+    let position = transform.translation.xy();
+    let dt = time.delta_seconds();
+    let velocity = match snapshots.last() {
+        Some(previous) if dt > 0.0 => (position - previous.position) / dt,
+        _ => Vec2::ZERO,
+    };
+    snapshots.push(Snapshot {
+        position,
+        velocity,
+        health: None,
+    });
+}
+
+fn run_ghosts(
+    mut commands: Commands,
+    mut ghost_query: Query<(Entity, &mut Ghost, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut ghost, mut transform, mut sprite) in &mut ghost_query {
+        let Some(snapshot) = ghost.snapshots.get(ghost.tick).copied() else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        ghost.tick += 1;
+        transform.translation = snapshot.position.extend(transform.translation.z);
+        if snapshot.velocity.x != 0.0 {
+            sprite.flip_x = snapshot.velocity.x < 0.0;
+        }
+    }
+}
+
+/// Store the run as the level's ghost, if it was faster than the one before.
+fn keep_fastest_run(
+    _trigger: Trigger<LevelCompleted>,
+    save: Res<SaveGame>,
+    mut recording: ResMut<GhostRecording>,
+    mut ghosts: ResMut<Ghosts>,
+) {
+    let Some(snapshots) = recording.snapshots.take() else {
+        return;
+    };
+    let faster = match ghosts.0.get(&save.level) {
+        Some(ghost) => snapshots.len() < ghost.ticks,
+        None => true,
+    };
+    if faster && !snapshots.is_empty() {
+        ghosts
+            .0
+            .insert(save.level.clone(), StoredGhost::new(&snapshots));
+        storage::save(GHOSTS_KEY, &*ghosts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_ghosts_decode_to_their_run() {
+        let snapshots = (0..600)
+            .map(|tick| Snapshot {
+                position: Vec2::new(tick as f32 * 6.5, 100.0),
+                velocity: Vec2::new(416.0, 0.0),
+                health: None,
+            })
+            .collect::<Vec<_>>();
+        let stored = StoredGhost::new(&snapshots);
+        assert_eq!(stored.ticks, 600);
+        // Steady motion costs next to nothing.
+        assert!(stored.snapshots.len() < 400, "{}", stored.snapshots.len());
+        assert_eq!(stored.decode(), Some(snapshots));
+
+        let broken = StoredGhost {
+            ticks: 1,
+            snapshots: "not base64!".to_string(),
+        };
+        assert_eq!(broken.decode(), None);
+    }
+}
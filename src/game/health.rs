@@ -0,0 +1,337 @@
+//! Health, damage and death. Send a [`DamageEvent`] to hurt an entity with [`Health`].
+//! Each hit flashes the entity, and can make it briefly [`Invulnerable`] so that one
+//! touch doesn't deal damage every tick.
+//!
+//! Damage has a [`DamageType`], which [`Resistances`] scale. The damage that is dealt after
+//! that is sent as a [`DamageTaken`] event, for damage numbers and status effects.
+//!
+//! When health runs out, a [`DeathEvent`] is sent and the entity is despawned,
+//! except for the player, whose death ends the run with the game over screen.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    animation::{AnimationController, AnimationState},
+    collision::CollisionReactions,
+    gamepad::Rumble,
+    mode::GameMode,
+    spawn::player::Player,
+    sprite_effects::HitFlash,
+    upgrades::Upgrades,
+};
+use crate::{
+    events::{CollisionEvent, DamageEvent, DamageTaken, DeathEvent, ScreenRequest, ShakeEvent},
+    screen::PlayingState,
+    ui::announcer::{Announce, Importance},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Health, Damage, Invulnerable, Resistances)>();
+    app.configure_sets(
+        FixedUpdate,
+        (ApplyDamage, DeathReactions)
+            .chain()
+            .in_set(AppSet::HandleEvents)
+            .after(CollisionReactions),
+    );
+    app.add_systems(
+        FixedUpdate,
+        (
+            tick_invulnerability.in_set(AppSet::TickTimers),
+            deal_contact_damage.in_set(CollisionReactions),
+            apply_damage.in_set(ApplyDamage),
+            handle_deaths
+                .in_set(AppSet::HandleEvents)
+                .after(DeathReactions),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// Applies [`DamageEvent`]s and sends [`DamageTaken`] and [`DeathEvent`]s,
+/// whose readers should run after this set.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ApplyDamage;
+
+/// Systems that react to [`DeathEvent`]s while the dead are still around.
+/// They are despawned right after this set.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeathReactions;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+    /// Seconds of invulnerability after taking a hit.
+    pub invulnerability: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            invulnerability: 0.0,
+        }
+    }
+
+    pub fn with_invulnerability(mut self, seconds: f32) -> Self {
+        self.invulnerability = seconds;
+        self
+    }
+
+    /// Lose up to `amount` health, returning how much was lost.
+    pub fn take(&mut self, amount: f32) -> f32 {
+        let taken = amount.max(0.0).min(self.current);
+        self.current -= taken;
+        taken
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    /// Health left, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 {
+            self.current / self.max
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Damage this entity deals to what it collides with, see `game::collision`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Damage {
+    pub amount: f32,
+    pub kind: DamageType,
+    /// Poise damage, which is higher for heavy attacks, see `game::stagger`.
+    pub poise: f32,
+}
+
+/// What kind of damage something deals, which [`Resistances`] scale.
+#[derive(Reflect, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Fire,
+    Poison,
+}
+
+impl DamageType {
+    /// The color of damage numbers for this type.
+    pub fn color(self) -> Color {
+        match self {
+            Self::Physical => Color::WHITE,
+            Self::Fire => Color::srgb(1.0, 0.55, 0.15),
+            Self::Poison => Color::srgb(0.55, 0.9, 0.25),
+        }
+    }
+}
+
+/// How much of each type of damage an entity takes: below 1 resists it, above 1 is
+/// vulnerable to it, and 0 is immune. Entities without this take all damage as it is.
+/// Usually comes from level data, like the player's.
+#[derive(Component, Reflect, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+#[serde(default)]
+pub struct Resistances {
+    pub physical: f32,
+    pub fire: f32,
+    pub poison: f32,
+}
+
+impl Default for Resistances {
+    fn default() -> Self {
+        Self {
+            physical: 1.0,
+            fire: 1.0,
+            poison: 1.0,
+        }
+    }
+}
+
+impl Resistances {
+    /// How much damage of type `kind` is multiplied by.
+    pub fn multiplier(&self, kind: DamageType) -> f32 {
+        match kind {
+            DamageType::Physical => self.physical,
+            DamageType::Fire => self.fire,
+            DamageType::Poison => self.poison,
+        }
+        .max(0.0)
+    }
+}
+
+/// Ignores damage until the timer runs out, after which it is removed.
+#[derive(Component, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Invulnerable(pub Timer);
+
+impl Invulnerable {
+    pub fn new(seconds: f32) -> Self {
+        Self(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+}
+
+/// How much trauma the camera gets when the player is hit.
+const PLAYER_HIT_TRAUMA: f32 = 0.4;
+
+/// Fraction of health below which the player is warned, through screen reader announcements.
+const LOW_HEALTH_FRACTION: f32 = 0.25;
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut invulnerable_query: Query<(Entity, &mut Invulnerable)>,
+) {
+    for (entity, mut invulnerable) in &mut invulnerable_query {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+fn deal_contact_damage(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut damage_events: EventWriter<DamageEvent>,
+    damage_query: Query<&Damage>,
+    health_query: Query<(), With<Health>>,
+) {
+    for event in collision_events.read() {
+        // Both may hurt each other.
+        for (source, target) in [(event.a, event.b), (event.b, event.a)] {
+            if let (Ok(damage), true) = (damage_query.get(source), health_query.contains(target)) {
+                damage_events.send(DamageEvent {
+                    target,
+                    amount: damage.amount,
+                    kind: damage.kind,
+                    poise: damage.poise,
+                    source: Some(source),
+                    from_status: false,
+                });
+            }
+        }
+    }
+}
+
+fn apply_damage(
+    mut commands: Commands,
+    upgrades: Res<Upgrades>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut taken_events: EventWriter<DamageTaken>,
+    mut death_events: EventWriter<DeathEvent>,
+    mut shake_events: EventWriter<ShakeEvent>,
+    mut health_query: Query<(
+        &mut Health,
+        Option<&Resistances>,
+        Option<&mut AnimationController>,
+        Has<Invulnerable>,
+        Has<Player>,
+        Has<Sprite>,
+    )>,
+) {
+    for event in damage_events.read() {
+        let Ok((mut health, resistances, animation, invulnerable, is_player, is_sprite)) =
+            health_query.get_mut(event.target)
+        else {
+            continue;
+        };
+        // Also skips the dead, who may get hit again before they are despawned.
+        // Status effects keep ticking through invulnerability, which only protects from hits.
+        if (invulnerable && !event.from_status) || health.is_dead() {
+            continue;
+        }
+        let mut multiplier =
+            resistances.map_or(1.0, |resistances| resistances.multiplier(event.kind));
+        if is_player {
+            multiplier *= upgrades.damage_taken;
+        }
+        let was_low = health.fraction() < LOW_HEALTH_FRACTION;
+        let amount = health.take(event.amount * multiplier);
+        if amount <= 0.0 {
+            continue;
+        }
+        if is_player && !was_low && health.fraction() < LOW_HEALTH_FRACTION && !health.is_dead() {
+            commands.trigger(Announce::new(Importance::Essential, "Low health"));
+        }
+        taken_events.send(DamageTaken {
+            target: event.target,
+            amount,
+            kind: event.kind,
+            poise: event.poise,
+            source: event.source,
+            from_status: event.from_status,
+        });
+
+        let mut entity = commands.entity(event.target);
+        if is_sprite {
+            // Restarts a flash that is still fading.
+            entity.insert(HitFlash::DAMAGE);
+        }
+        if let Some(mut animation) = animation {
+            if health.is_dead() {
+                animation.set_state(AnimationState::Death);
+            } else if !event.from_status {
+                animation.interrupt(AnimationState::Hit);
+            }
+        }
+        // Ticks of damage over time don't count as hits.
+        if !event.from_status {
+            if health.invulnerability > 0.0 {
+                entity.insert(Invulnerable::new(health.invulnerability));
+            }
+            if is_player {
+                commands.trigger(Rumble::HIT);
+                shake_events.send(ShakeEvent {
+                    trauma: PLAYER_HIT_TRAUMA,
+                });
+            }
+        }
+        if health.is_dead() {
+            death_events.send(DeathEvent {
+                entity: event.target,
+                killer: event.source,
+            });
+        }
+    }
+}
+
+fn handle_deaths(
+    mut commands: Commands,
+    mut death_events: EventReader<DeathEvent>,
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mode: Res<GameMode>,
+    player_query: Query<(), With<Player>>,
+) {
+    for event in death_events.read() {
+        if player_query.contains(event.entity) {
+            info!("The player died.");
+            screen_requests.send(ScreenRequest::To(mode.results_screen()));
+        } else if let Some(entity) = commands.get_entity(event.entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn health_never_drops_below_zero() {
+        let mut health = Health::new(30.0);
+        assert_eq!(health.take(-5.0), 0.0);
+        assert_eq!(health.take(20.0), 20.0);
+        assert!(!health.is_dead());
+        assert_eq!(health.take(20.0), 10.0);
+        assert!(health.is_dead());
+        assert_eq!(health.fraction(), 0.0);
+        assert_eq!(Health::new(0.0).fraction(), 0.0);
+    }
+}
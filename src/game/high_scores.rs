@@ -0,0 +1,172 @@
+//! The best scores reached on this device, persisted between sessions.
+//! Each [`ScoreCategory`], a mode with a set of mutators, has its own table.
+//! So does each week's challenge, see `weekly`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{mode::GameMode, weekly::IsoWeek};
+use crate::storage;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(storage::load::<HighScores>(HIGH_SCORES_KEY).unwrap_or_default());
+    app.add_systems(
+        Update,
+        save_high_scores.run_if(resource_changed::<HighScores>),
+    );
+}
+
+/// Key under which [`HighScores`] are persisted.
+const HIGH_SCORES_KEY: &str = "high_scores";
+
+/// The best scores of every mode, highest first.
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct HighScores(Vec<HighScore>);
+
+/// Fields default when missing, so older scores still load.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct HighScore {
+    /// The profile name at the time.
+    pub name: String,
+    pub score: u64,
+    pub mode: GameMode,
+    /// Ids of the run's mutators, sorted.
+    pub mutators: Vec<String>,
+    /// Whole seconds the run lasted.
+    pub survived: u32,
+    /// The week, for a weekly challenge.
+    pub week: Option<IsoWeek>,
+}
+
+impl HighScore {
+    pub fn category(&self) -> ScoreCategory {
+        ScoreCategory {
+            mode: self.mode,
+            mutators: self.mutators.clone(),
+            week: self.week,
+        }
+    }
+}
+
+/// Which table a score goes in: scores are only compared to ones of the same mode
+/// with the same mutators.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ScoreCategory {
+    pub mode: GameMode,
+    /// Ids of the mutators, sorted.
+    pub mutators: Vec<String>,
+    /// Weekly challenges are only compared within their week.
+    pub week: Option<IsoWeek>,
+}
+
+impl ScoreCategory {
+    fn contains(&self, entry: &HighScore) -> bool {
+        entry.mode == self.mode && entry.mutators == self.mutators && entry.week == self.week
+    }
+}
+
+impl HighScores {
+    /// How many scores are kept per category.
+    pub const MAX_ENTRIES: usize = 10;
+
+    /// The scores of `category`, highest first.
+    pub fn entries<'a>(
+        &'a self,
+        category: &'a ScoreCategory,
+    ) -> impl Iterator<Item = &'a HighScore> {
+        self.0.iter().filter(|entry| category.contains(entry))
+    }
+
+    /// Every category with scores, by mode, then by mutators, then by week.
+    pub fn categories(&self) -> Vec<ScoreCategory> {
+        let mut categories: Vec<_> = self.0.iter().map(HighScore::category).collect();
+        categories.sort();
+        categories.dedup();
+        categories
+    }
+
+    /// Add a score, returning its rank in its category's table from 0 if it made it in.
+    /// Ties go to the older score.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let category = entry.category();
+        let rank = self
+            .entries(&category)
+            .take_while(|other| other.score >= entry.score)
+            .count();
+        if rank >= Self::MAX_ENTRIES {
+            return None;
+        }
+        let index = self.0.partition_point(|other| other.score >= entry.score);
+        self.0.insert(index, entry);
+        // Drop whichever of the category's scores got pushed out of its table.
+        if let Some(index) = (0..self.0.len())
+            .filter(|&index| category.contains(&self.0[index]))
+            .nth(Self::MAX_ENTRIES)
+        {
+            self.0.remove(index);
+        }
+        Some(rank)
+    }
+}
+
+fn save_high_scores(high_scores: Res<HighScores>) {
+    storage::save(HIGH_SCORES_KEY, &*high_scores);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn high_scores_keep_the_best_in_order() {
+        let mut high_scores = HighScores::default();
+        let entry = |score| HighScore {
+            name: "Ducky".to_string(),
+            score,
+            ..default()
+        };
+        for score in 1..=HighScores::MAX_ENTRIES as u64 {
+            assert!(high_scores.insert(entry(score * 10)).is_some());
+        }
+        // Ties rank below the older score, and scores below the table are dropped.
+        assert_eq!(high_scores.insert(entry(50)), Some(6));
+        assert_eq!(high_scores.insert(entry(0)), None);
+        assert_eq!(high_scores.insert(entry(1000)), Some(0));
+        let scores = |high_scores: &HighScores, mode, mutators: &[&str]| {
+            let category = ScoreCategory {
+                mode,
+                mutators: mutators.iter().map(|id| id.to_string()).collect(),
+                week: None,
+            };
+            high_scores
+                .entries(&category)
+                .map(|entry| entry.score)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            scores(&high_scores, GameMode::Normal, &[]),
+            [1000, 100, 90, 80, 70, 60, 50, 50, 40, 30]
+        );
+
+        // Endless scores, and scores with mutators, have tables of their own.
+        let endless = HighScore {
+            mode: GameMode::Endless,
+            survived: 95,
+            ..entry(5)
+        };
+        assert_eq!(high_scores.insert(endless.clone()), Some(0));
+        let mutated = HighScore {
+            mutators: vec!["one_hit_death".to_string()],
+            ..endless
+        };
+        assert_eq!(high_scores.insert(mutated), Some(0));
+        assert_eq!(scores(&high_scores, GameMode::Endless, &[]), [5]);
+        assert_eq!(
+            scores(&high_scores, GameMode::Endless, &["one_hit_death"]),
+            [5]
+        );
+        assert_eq!(scores(&high_scores, GameMode::Normal, &[]).len(), 10);
+        assert_eq!(high_scores.categories().len(), 3);
+    }
+}
@@ -0,0 +1,639 @@
+//! Mapping from physical input to game [`Action`]s.
+//! Gameplay systems should use [`ActionInput`] instead of checking keys directly,
+//! so the player can rebind them on the controls screen and use a gamepad.
+//!
+//! Bindings are [`KeyCode`]s, which are physical key positions, so the default WASD
+//! bindings work on any layout (e.g. ZQSD on AZERTY). [`KeyboardLayout`] names keys
+//! by what is printed on them instead.
+//!
+//! Sustained actions like sprinting can be held or toggled, see [`ActionModes`].
+//! That is resolved here, so gameplay code only sees whether the action is active.
+//!
+//! All devices can be used at the same time. Each action merges its sources, and
+//! [`ActionSources`] remembers which device each action was last used with, so
+//! [`ActionPrompts`] can show the key or button the player is actually using for it.
+
+use bevy::{
+    ecs::system::SystemParam,
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+    ui::UiSystem,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    assets::parse_embedded,
+    challenge::Challenge,
+    gamepad::{left_stick, GamepadBindings},
+    touch::TouchInput,
+};
+use crate::GameSettings;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(KeyBindings, ActionModes, InputSource)>();
+    // Usually already inserted with the stored bindings by `AppPlugin`.
+    app.init_resource::<KeyBindings>();
+    app.init_resource::<ActionModes>();
+    app.init_resource::<ToggledActions>();
+    app.init_resource::<ActionSources>();
+    app.init_resource::<QueuedActions>();
+    app.init_resource::<KeyboardLayout>();
+    app.insert_resource(BindingPresets::load());
+    app.add_systems(PreUpdate, learn_keyboard_layout);
+    // After `UiSystem::Focus`, since touch buttons are read from UI interactions.
+    app.add_systems(
+        PreUpdate,
+        (update_toggled_actions, track_action_sources).after(UiSystem::Focus),
+    );
+    app.add_systems(PreUpdate, press_queued_actions);
+}
+
+/// Everything the player can do with a button.
+#[derive(Serialize, Deserialize, Reflect, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Sprint,
+    AdvanceCycle,
+    Rewind,
+    QuickWheel,
+    /// Advance dialogue, see `dialogue`.
+    Interact,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Sprint,
+        Action::AdvanceCycle,
+        Action::Rewind,
+        Action::QuickWheel,
+        Action::Interact,
+        Action::Pause,
+    ];
+
+    /// Actions that stay active while held, which can be set to toggle instead.
+    pub const SUSTAINED: [Action; 3] = [Action::Sprint, Action::Rewind, Action::QuickWheel];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Sprint => "Sprint",
+            Action::AdvanceCycle => "Advance cycle",
+            Action::Rewind => "Rewind",
+            Action::QuickWheel => "Quick actions",
+            Action::Interact => "Interact",
+            Action::Pause => "Pause",
+        }
+    }
+}
+
+/// Keys bound to each [`Action`], with a primary and a secondary slot.
+#[derive(Resource, Serialize, Deserialize, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Resource)]
+#[serde(from = "StoredKeyBindings")]
+pub struct KeyBindings(HashMap<Action, [Option<KeyCode>; KeyBindings::SLOTS]>);
+
+/// [`KeyBindings`] as they were saved, which may lack actions added since.
+#[derive(Deserialize)]
+struct StoredKeyBindings(HashMap<Action, [Option<KeyCode>; KeyBindings::SLOTS]>);
+
+impl From<StoredKeyBindings> for KeyBindings {
+    fn from(stored: StoredKeyBindings) -> Self {
+        let mut bindings = Self::default();
+        bindings.0.extend(stored.0);
+        bindings
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(
+            [
+                (
+                    Action::MoveUp,
+                    [Some(KeyCode::KeyW), Some(KeyCode::ArrowUp)],
+                ),
+                (
+                    Action::MoveDown,
+                    [Some(KeyCode::KeyS), Some(KeyCode::ArrowDown)],
+                ),
+                (
+                    Action::MoveLeft,
+                    [Some(KeyCode::KeyA), Some(KeyCode::ArrowLeft)],
+                ),
+                (
+                    Action::MoveRight,
+                    [Some(KeyCode::KeyD), Some(KeyCode::ArrowRight)],
+                ),
+                (Action::Sprint, [Some(KeyCode::ShiftLeft), None]),
+                (
+                    Action::AdvanceCycle,
+                    [Some(KeyCode::KeyE), Some(KeyCode::Space)],
+                ),
+                (Action::Rewind, [Some(KeyCode::KeyR), None]),
+                (Action::QuickWheel, [Some(KeyCode::KeyF), None]),
+                (
+                    Action::Interact,
+                    [Some(KeyCode::Enter), Some(KeyCode::KeyC)],
+                ),
+                (Action::Pause, [Some(KeyCode::Escape), None]),
+            ]
+            .into(),
+        )
+    }
+}
+
+impl KeyBindings {
+    pub const SLOTS: usize = 2;
+
+    /// The key in the given slot of `action`, if any.
+    pub fn get(&self, action: Action, slot: usize) -> Option<KeyCode> {
+        self.0.get(&action).and_then(|keys| keys[slot])
+    }
+
+    /// Bind `key` to the given slot of `action`, or clear the slot with `None`.
+    pub fn set(&mut self, action: Action, slot: usize, key: Option<KeyCode>) {
+        self.0.entry(action).or_default()[slot] = key;
+    }
+
+    /// The action and slot `key` is bound to, if any.
+    pub fn find(&self, key: KeyCode) -> Option<(Action, usize)> {
+        Action::ALL.into_iter().find_map(|action| {
+            (0..Self::SLOTS)
+                .find(|&slot| self.get(action, slot) == Some(key))
+                .map(|slot| (action, slot))
+        })
+    }
+
+    /// Actions without any key. Every action is required,
+    /// since keyboard players have no other way to trigger them.
+    pub fn unbound(&self) -> impl Iterator<Item = Action> + '_ {
+        Action::ALL
+            .into_iter()
+            .filter(|&action| self.keys(action).next().is_none())
+    }
+
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = KeyCode> + '_ {
+        self.0.get(&action).into_iter().flatten().flatten().copied()
+    }
+
+    pub fn pressed(&self, action: Action, input: &ButtonInput<KeyCode>) -> bool {
+        input.any_pressed(self.keys(action))
+    }
+
+    pub fn just_pressed(&self, action: Action, input: &ButtonInput<KeyCode>) -> bool {
+        input.any_just_pressed(self.keys(action))
+    }
+}
+
+/// Whether a sustained action is active while its input is held,
+/// or switched on and off by pressing it.
+#[derive(Serialize, Deserialize, Reflect, Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum ActionMode {
+    #[default]
+    Hold,
+    Toggle,
+}
+
+impl ActionMode {
+    pub fn name_display(self) -> String {
+        match self {
+            ActionMode::Hold => "Hold",
+            ActionMode::Toggle => "Toggle",
+        }
+        .to_string()
+    }
+}
+
+/// The [`ActionMode`] of each of the [`Action::SUSTAINED`] actions.
+/// Saved together with the key bindings.
+#[derive(Resource, Serialize, Deserialize, Reflect, Debug, Default, Clone, PartialEq)]
+#[reflect(Resource)]
+pub struct ActionModes(HashMap<Action, ActionMode>);
+
+impl ActionModes {
+    pub fn get(&self, action: Action) -> ActionMode {
+        self.0.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, action: Action, mode: ActionMode) {
+        self.0.insert(action, mode);
+    }
+}
+
+/// Toggle-mode actions that are currently switched on.
+#[derive(Resource, Debug, Default)]
+pub struct ToggledActions {
+    active: HashSet<Action>,
+    just_activated: HashSet<Action>,
+}
+
+fn update_toggled_actions(
+    input: RawActionInput,
+    modes: Res<ActionModes>,
+    mut toggled: ResMut<ToggledActions>,
+) {
+    let ToggledActions {
+        active,
+        just_activated,
+    } = &mut *toggled;
+    just_activated.clear();
+    for action in Action::SUSTAINED {
+        if modes.get(action) != ActionMode::Toggle {
+            // Switching back to hold shouldn't leave the action stuck on.
+            active.remove(&action);
+            continue;
+        }
+        if !input.just_pressed(action) {
+            continue;
+        }
+        if !active.remove(&action) {
+            active.insert(action);
+            just_activated.insert(action);
+        }
+    }
+}
+
+/// Actions pressed by the game instead of a device, like abilities used from the
+/// quick-action wheel. They are just pressed for the whole frame after being queued,
+/// so every reader sees them regardless of system order.
+#[derive(Resource, Debug, Default)]
+pub struct QueuedActions {
+    queued: HashSet<Action>,
+    pressed: HashSet<Action>,
+}
+
+impl QueuedActions {
+    /// Press `action` next frame.
+    pub fn press(&mut self, action: Action) {
+        self.queued.insert(action);
+    }
+}
+
+fn press_queued_actions(mut queued: ResMut<QueuedActions>) {
+    let QueuedActions { queued, pressed } = &mut *queued;
+    *pressed = std::mem::take(queued);
+}
+
+/// A named set of key bindings.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BindingPreset {
+    pub name: String,
+    pub bindings: KeyBindings,
+}
+
+/// The default bindings followed by the alternatives in `assets/bindings/presets.ron`.
+#[derive(Resource, Debug)]
+pub struct BindingPresets(pub Vec<BindingPreset>);
+
+impl BindingPresets {
+    /// Embedded instead of loaded as an asset, since the controls screen needs them right away.
+    const SOURCE: &'static str = include_str!("../../assets/bindings/presets.ron");
+
+    pub fn load() -> Self {
+        let default = BindingPreset {
+            name: "Default".to_string(),
+            bindings: KeyBindings::default(),
+        };
+        let alternatives: Vec<BindingPreset> = parse_embedded(Self::SOURCE, "binding presets");
+        Self(std::iter::once(default).chain(alternatives).collect())
+    }
+
+    /// The index of the preset that matches `bindings` exactly, if any.
+    pub fn position(&self, bindings: &KeyBindings) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|preset| preset.bindings == *bindings)
+    }
+}
+
+/// What is printed on each key in the player's keyboard layout.
+/// There is no portable way to ask for the layout, so it is learned from the characters
+/// that key presses produce, and keys that haven't been pressed yet are named as on QWERTY.
+#[derive(Resource, Debug, Default)]
+pub struct KeyboardLayout(HashMap<KeyCode, String>);
+
+impl KeyboardLayout {
+    /// A short, player-facing name for a key.
+    pub fn key_name(&self, key: KeyCode) -> String {
+        self.0
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| qwerty_key_name(key))
+    }
+}
+
+fn qwerty_key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    for prefix in ["Key", "Digit", "Arrow"] {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            if !rest.is_empty() {
+                return rest.to_string();
+            }
+        }
+    }
+    name
+}
+
+fn learn_keyboard_layout(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut layout: ResMut<KeyboardLayout>,
+) {
+    // Modifiers change the character, e.g. Shift+1 is `!` on QWERTY.
+    if keyboard.any_pressed([
+        KeyCode::ShiftLeft,
+        KeyCode::ShiftRight,
+        KeyCode::AltRight,
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+    ]) {
+        keyboard_events.clear();
+        return;
+    }
+    for event in keyboard_events.read() {
+        let Key::Character(character) = &event.logical_key else {
+            continue;
+        };
+        let label = character.to_uppercase();
+        if label.trim().is_empty() || layout.0.get(&event.key_code) == Some(&label) {
+            continue;
+        }
+        layout.0.insert(event.key_code, label);
+    }
+}
+
+/// Keyboard, gamepad and touch input, resolved to [`Action`]s through the bindings.
+/// Gameplay code should use this, which also applies the [`ActionModes`], and leaves out
+/// actions the level's [`Challenge`] takes away.
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    raw: RawActionInput<'w>,
+    modes: Res<'w, ActionModes>,
+    toggled: Res<'w, ToggledActions>,
+    queued: Res<'w, QueuedActions>,
+    challenge: Res<'w, Challenge>,
+}
+
+impl ActionInput<'_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        if self.challenge.restricts(action) {
+            return false;
+        }
+        match self.modes.get(action) {
+            ActionMode::Hold => self.raw.pressed(action),
+            ActionMode::Toggle => self.toggled.active.contains(&action),
+        }
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        if self.challenge.restricts(action) {
+            return false;
+        }
+        self.queued.pressed.contains(&action)
+            || match self.modes.get(action) {
+                ActionMode::Hold => self.raw.just_pressed(action),
+                ActionMode::Toggle => self.toggled.just_activated.contains(&action),
+            }
+    }
+
+    /// Movement intent from the move actions, the left stick and the virtual joystick,
+    /// with a length of at most 1.
+    pub fn movement(&self) -> Vec2 {
+        self.raw.movement()
+    }
+}
+
+/// A kind of device that actions can be triggered with.
+#[derive(Reflect, Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum InputSource {
+    #[default]
+    Keyboard,
+    Gamepad,
+    Touch,
+}
+
+impl InputSource {
+    const ALL: [InputSource; 3] = [
+        InputSource::Keyboard,
+        InputSource::Gamepad,
+        InputSource::Touch,
+    ];
+}
+
+/// The state of an action's inputs on one device.
+#[derive(Debug, Default, Clone, Copy)]
+struct SourceState {
+    pressed: bool,
+    just_pressed: bool,
+    /// Pressed since before this frame.
+    held: bool,
+}
+
+/// The [`InputSource`] each action was last used with.
+/// Tracked per action instead of globally, so that e.g. pausing with a gamepad
+/// doesn't change the prompts for moving with the keyboard.
+#[derive(Resource, Debug, Default)]
+pub struct ActionSources {
+    by_action: HashMap<Action, InputSource>,
+    /// The source any action was last used with, for actions that weren't used yet.
+    last: InputSource,
+}
+
+impl ActionSources {
+    pub fn get(&self, action: Action) -> InputSource {
+        self.by_action.get(&action).copied().unwrap_or(self.last)
+    }
+
+    fn insert(&mut self, action: Action, source: InputSource) {
+        self.by_action.insert(action, source);
+        self.last = source;
+    }
+}
+
+fn track_action_sources(input: RawActionInput, mut sources: ResMut<ActionSources>) {
+    for action in Action::ALL {
+        let source = InputSource::ALL
+            .into_iter()
+            .find(|&source| input.state(action, source).just_pressed);
+        if let Some(source) = source {
+            sources.insert(action, source);
+        }
+    }
+    // Analog movement counts as using the move actions.
+    let analog = [
+        (
+            InputSource::Gamepad,
+            left_stick(&input.gamepads, &input.gamepad_axes),
+        ),
+        (InputSource::Touch, input.touch.joystick),
+    ];
+    for (source, direction) in analog {
+        if direction == Vec2::ZERO {
+            continue;
+        }
+        for action in [
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::MoveLeft,
+            Action::MoveRight,
+        ] {
+            sources.insert(action, source);
+        }
+    }
+}
+
+/// Player-facing names for the input of each action, on the device it was last used with.
+#[derive(SystemParam)]
+pub struct ActionPrompts<'w> {
+    sources: Res<'w, ActionSources>,
+    key_bindings: Res<'w, KeyBindings>,
+    layout: Res<'w, KeyboardLayout>,
+    gamepad_bindings: Res<'w, GamepadBindings>,
+    gamepads: Res<'w, Gamepads>,
+    settings: Res<'w, GameSettings>,
+}
+
+impl ActionPrompts<'_> {
+    /// The device `action` was last used with, or would be used with next.
+    pub fn source(&self, action: Action) -> InputSource {
+        self.sources.get(action)
+    }
+
+    /// E.g. "Esc", "Menu" or "the on-screen button".
+    pub fn prompt(&self, action: Action) -> String {
+        let keyboard = || {
+            self.key_bindings
+                .keys(action)
+                .next()
+                .map_or("an unbound key".to_string(), |key| {
+                    self.layout.key_name(key)
+                })
+        };
+        match self.sources.get(action) {
+            InputSource::Keyboard => keyboard(),
+            InputSource::Gamepad => {
+                self.gamepad_bindings
+                    .get(action)
+                    .map_or_else(keyboard, |button| {
+                        self.settings
+                            .gamepad_layout
+                            .layout(&self.gamepads)
+                            .button_name(button)
+                    })
+            }
+            InputSource::Touch => "the on-screen button".to_string(),
+        }
+    }
+}
+
+/// Input resolved through the bindings, before applying the [`ActionModes`].
+#[derive(SystemParam)]
+pub struct RawActionInput<'w> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    key_bindings: Res<'w, KeyBindings>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+    gamepad_bindings: Res<'w, GamepadBindings>,
+    touch: Res<'w, TouchInput>,
+}
+
+impl RawActionInput<'_> {
+    fn bound_gamepad_buttons(&self, action: Action) -> impl Iterator<Item = GamepadButton> + '_ {
+        let button_type = self.gamepad_bindings.get(action);
+        self.gamepads.iter().filter_map(move |gamepad| {
+            button_type.map(|button_type| GamepadButton::new(gamepad, button_type))
+        })
+    }
+
+    fn state(&self, action: Action, source: InputSource) -> SourceState {
+        match source {
+            InputSource::Keyboard => SourceState {
+                pressed: self.key_bindings.pressed(action, &self.keyboard),
+                just_pressed: self.key_bindings.just_pressed(action, &self.keyboard),
+                held: self
+                    .key_bindings
+                    .keys(action)
+                    .any(|key| self.keyboard.pressed(key) && !self.keyboard.just_pressed(key)),
+            },
+            InputSource::Gamepad => SourceState {
+                pressed: self
+                    .gamepad_buttons
+                    .any_pressed(self.bound_gamepad_buttons(action)),
+                just_pressed: self
+                    .gamepad_buttons
+                    .any_just_pressed(self.bound_gamepad_buttons(action)),
+                held: self.bound_gamepad_buttons(action).any(|button| {
+                    self.gamepad_buttons.pressed(button)
+                        && !self.gamepad_buttons.just_pressed(button)
+                }),
+            },
+            InputSource::Touch => SourceState {
+                pressed: self.touch.pressed(action),
+                just_pressed: self.touch.just_pressed(action),
+                held: self.touch.pressed(action) && !self.touch.just_pressed(action),
+            },
+        }
+    }
+
+    /// Pressed on any device.
+    fn pressed(&self, action: Action) -> bool {
+        InputSource::ALL
+            .into_iter()
+            .any(|source| self.state(action, source).pressed)
+    }
+
+    /// Pressed on some device, while it wasn't already held on another one.
+    /// Pressing an action on a second device doesn't trigger it again.
+    fn just_pressed(&self, action: Action) -> bool {
+        let states = InputSource::ALL.map(|source| self.state(action, source));
+        states.iter().any(|state| state.just_pressed) && !states.iter().any(|state| state.held)
+    }
+
+    fn movement(&self) -> Vec2 {
+        let mut intent = Vec2::ZERO;
+        if self.pressed(Action::MoveUp) {
+            intent.y += 1.0;
+        }
+        if self.pressed(Action::MoveDown) {
+            intent.y -= 1.0;
+        }
+        if self.pressed(Action::MoveLeft) {
+            intent.x -= 1.0;
+        }
+        if self.pressed(Action::MoveRight) {
+            intent.x += 1.0;
+        }
+        // Normalize so that diagonal movement has the same speed as
+        // horizontal and vertical movement, but keep analog stick precision.
+        // Devices add up, so e.g. a stick can steer slightly while a key is held.
+        (intent.normalize_or_zero()
+            + left_stick(&self.gamepads, &self.gamepad_axes)
+            + self.touch.joystick)
+            .clamp_length_max(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_presets_parse() {
+        let presets = BindingPresets::load();
+        // The default preset is always there, so this checks that the file added the rest.
+        assert!(presets.0.len() > 1);
+    }
+}
@@ -0,0 +1,150 @@
+//! Checks that the assets the game loads are the ones it was built with, so a corrupted
+//! or partially uploaded build says so, instead of acting strangely later on.
+//!
+//! `build.rs` hashes every file in `assets` into an [`AssetManifest`], which is embedded
+//! in the binary. The loading screen starts an [`IntegrityCheck`] of the files its
+//! catalogs load, which reads them a second time as plain bytes to hash them.
+//! Files that fail to load aren't checked, since the loading screen reports those already.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState},
+    prelude::*,
+    utils::HashMap,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<AssetBytes>();
+    app.init_asset_loader::<AssetBytesLoader>();
+}
+
+/// The hash of every file in `assets` at build time, by path relative to `assets`.
+#[derive(Debug, Default)]
+pub struct AssetManifest(HashMap<String, u64>);
+
+impl AssetManifest {
+    const SOURCE: &'static str = include_str!(concat!(env!("OUT_DIR"), "/asset_manifest.txt"));
+
+    pub fn embedded() -> Self {
+        Self::parse(Self::SOURCE)
+    }
+
+    /// Parses `<hash> <path>` lines, skipping any that aren't.
+    pub fn parse(source: &str) -> Self {
+        Self(
+            source
+                .lines()
+                .filter_map(|line| {
+                    let (hash, path) = line.split_once(' ')?;
+                    Some((path.to_string(), u64::from_str_radix(hash, 16).ok()?))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, path: &str) -> Option<u64> {
+        self.0.get(path).copied()
+    }
+}
+
+/// 64-bit FNV-1a, the same as `build.rs` hashes the manifest with.
+pub fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A file's contents as they are, whatever kind of asset it holds.
+/// Only loaded by type, since its loader claims no extensions.
+#[derive(Asset, TypePath, Debug)]
+pub struct AssetBytes(pub Vec<u8>);
+
+#[derive(Default)]
+struct AssetBytesLoader;
+
+impl AssetLoader for AssetBytesLoader {
+    type Asset = AssetBytes;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<AssetBytes, std::io::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(AssetBytes(bytes))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+}
+
+/// Files being read to compare against the [`AssetManifest`].
+/// Remove it once it [`is_done`](Self::is_done), so the bytes aren't kept around.
+#[derive(Resource, Debug, Default)]
+pub struct IntegrityCheck {
+    files: Vec<(String, u64, Handle<AssetBytes>)>,
+}
+
+impl IntegrityCheck {
+    /// Start reading the files at these paths, skipping any the manifest doesn't know.
+    pub fn start(asset_server: &AssetServer, paths: impl IntoIterator<Item = String>) -> Self {
+        let manifest = AssetManifest::embedded();
+        let mut files = Vec::new();
+        for path in paths {
+            let Some(expected) = manifest.get(&path) else {
+                warn!("{path} isn't in the asset manifest, so it can't be checked.");
+                continue;
+            };
+            let handle = asset_server.load(path.clone());
+            files.push((path, expected, handle));
+        }
+        Self { files }
+    }
+
+    /// Whether every file was either read or failed to be.
+    pub fn is_done(&self, asset_server: &AssetServer) -> bool {
+        self.files.iter().all(|(_, _, handle)| {
+            matches!(
+                asset_server.get_load_state(handle.id()),
+                Some(LoadState::Loaded | LoadState::Failed(_))
+            )
+        })
+    }
+
+    /// Paths of the files that were read, but don't match the manifest.
+    pub fn mismatches(&self, bytes: &Assets<AssetBytes>) -> Vec<String> {
+        self.files
+            .iter()
+            .filter(|(_, expected, handle)| {
+                bytes
+                    .get(handle)
+                    .is_some_and(|file| hash(&file.0) != *expected)
+            })
+            .map(|(path, _, _)| path.clone())
+            .collect()
+    }
+}
+
+/// The files that didn't match the [`AssetManifest`], once the [`IntegrityCheck`] is done.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct AssetMismatches(pub Vec<String>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asset_manifest_hashes_files_like_the_game_does() {
+        let manifest = AssetManifest::embedded();
+        let level = include_bytes!("../../assets/levels/main.level.ron");
+        assert_eq!(manifest.get("levels/main.level.ron"), Some(hash(level)));
+        assert!(manifest.get("images/ducky.png").is_some());
+        assert_eq!(manifest.get("images/missing.png"), None);
+        // A corrupted file hashes differently.
+        assert_ne!(hash(&level[1..]), hash(level));
+    }
+}
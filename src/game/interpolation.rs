@@ -0,0 +1,113 @@
+//! Smooths the motion of entities that move in `FixedUpdate`. Their transforms only change
+//! on fixed ticks, which don't line up with frames, so their motion would look steppy,
+//! especially at high refresh rates. Instead, each frame draws them between their last
+//! two simulated positions, by how far time has advanced towards the next tick.
+//!
+//! Only the position in the plane is smoothed; z is left to [`OnLayer`](crate::layers::OnLayer).
+//! Simulation systems always see the simulated position, since it is restored before each
+//! tick. Disabled by the motion smoothing display setting, which draws the latest tick.
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::GameSettings;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<InterpolatedTransform>();
+    app.add_systems(FixedFirst, restore_simulated_transforms);
+    app.add_systems(FixedLast, record_simulated_transforms);
+    app.add_systems(
+        PostUpdate,
+        interpolate_transforms.before(TransformSystem::TransformPropagate),
+    );
+}
+
+/// Draws this entity between its last two positions from `FixedUpdate`.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct InterpolatedTransform {
+    previous: Vec2,
+    current: Vec2,
+    /// Whether a tick has been recorded yet. Until then, the transform is left alone.
+    ready: bool,
+}
+
+/// Moving farther than this in one tick counts as a teleport, like wrapping around the
+/// window, which is drawn as a jump instead of sweeping across the screen.
+const TELEPORT_DISTANCE: f32 = 256.0;
+
+impl InterpolatedTransform {
+    /// The position to draw, `fraction` of the way from the previous tick to the current one.
+    pub fn lerp(&self, fraction: f32) -> Vec2 {
+        self.previous.lerp(self.current, fraction)
+    }
+
+    /// Record the position at the end of a tick.
+    pub fn record(&mut self, position: Vec2) {
+        let teleported = position.distance(self.current) > TELEPORT_DISTANCE;
+        self.previous = if self.ready && !teleported {
+            self.current
+        } else {
+            position
+        };
+        self.current = position;
+        self.ready = true;
+    }
+}
+
+fn restore_simulated_transforms(
+    mut interpolated_query: Query<(&InterpolatedTransform, &mut Transform)>,
+) {
+    for (interpolated, mut transform) in &mut interpolated_query {
+        if interpolated.ready {
+            set_position(&mut transform, interpolated.current);
+        }
+    }
+}
+
+fn record_simulated_transforms(
+    mut interpolated_query: Query<(&mut InterpolatedTransform, &Transform)>,
+) {
+    for (mut interpolated, transform) in &mut interpolated_query {
+        interpolated.record(transform.translation.truncate());
+    }
+}
+
+fn interpolate_transforms(
+    settings: Res<GameSettings>,
+    time: Res<Time<Fixed>>,
+    mut interpolated_query: Query<(&InterpolatedTransform, &mut Transform)>,
+) {
+    let fraction = if settings.display.motion_smoothing.is_on() {
+        time.overstep_fraction()
+    } else {
+        1.0
+    };
+    for (interpolated, mut transform) in &mut interpolated_query {
+        if interpolated.ready {
+            set_position(&mut transform, interpolated.lerp(fraction));
+        }
+    }
+}
+
+/// Only touches the transform if the position changes, to keep change detection useful.
+fn set_position(transform: &mut Mut<Transform>, position: Vec2) {
+    if transform.translation.truncate() != position {
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolation_blends_ticks_but_not_teleports() {
+        let mut interpolated = InterpolatedTransform::default();
+        interpolated.record(Vec2::new(10.0, 0.0));
+        assert_eq!(interpolated.lerp(0.5), Vec2::new(10.0, 0.0));
+        interpolated.record(Vec2::new(20.0, 0.0));
+        assert_eq!(interpolated.lerp(0.5), Vec2::new(15.0, 0.0));
+        interpolated.record(Vec2::new(-1000.0, 0.0));
+        assert_eq!(interpolated.lerp(0.5), Vec2::new(-1000.0, 0.0));
+    }
+}
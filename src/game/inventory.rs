@@ -0,0 +1,360 @@
+//! Items the player collects, defined in `assets/items/items.ron`.
+//!
+//! Enemies drop [`Pickup`]s when they die, rolled from [`GameRng::loot`]. Pickups within
+//! reach of a [`Magnet`] are drawn toward it, and collected into the [`Inventory`] when
+//! they touch the player, see `collision`. Items of a kind stack up in one slot.
+//! Pickups that don't fit stay where they are, and aren't drawn in.
+//!
+//! The run's [`SaveGame`] keeps the inventory. Pickups on the ground aren't saved,
+//! like enemies.
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    assets::{parse_embedded, SfxKey},
+    audio::sfx::PlaySfx,
+    collision::{Collider, CollisionLayer, CollisionReactions},
+    gamepad::Rumble,
+    health::DeathReactions,
+    interpolation::InterpolatedTransform,
+    rng::GameRng,
+    save::SaveGame,
+    spawn::{player::Player, wave::Enemy},
+};
+use crate::{
+    events::{DeathEvent, PickupEvent},
+    layers::{Layer, OnLayer},
+    screen::{PlayingState, Screen},
+    ui::{
+        announcer::{Announce, Importance},
+        palette::GameplayTint,
+    },
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Pickup, Magnet)>();
+    app.insert_resource(ItemCatalog::load());
+    app.init_resource::<Inventory>();
+    app.add_systems(OnEnter(Screen::Playing), resume_inventory);
+    app.add_systems(
+        FixedUpdate,
+        (
+            attract_pickups.in_set(AppSet::Update),
+            collect_pickups
+                .in_set(AppSet::HandleEvents)
+                .after(CollisionReactions),
+            drop_loot.in_set(DeathReactions),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+    app.add_systems(
+        Update,
+        track_inventory.run_if(resource_changed::<Inventory>),
+    );
+}
+
+/// How many stacks of items the player can carry.
+pub const INVENTORY_SLOTS: usize = 4;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ItemDefinition {
+    /// Stays the same when the name changes, since it is saved.
+    pub id: String,
+    pub name: String,
+    pub color: (f32, f32, f32),
+    /// Width and height of the pickup, in pixels.
+    pub size: f32,
+    /// How many fit in one slot.
+    pub max_stack: u32,
+    /// Chance from 0 to 1 that a dying enemy drops one.
+    pub drop_chance: f64,
+}
+
+impl ItemDefinition {
+    pub fn color(&self) -> Color {
+        let (red, green, blue) = self.color;
+        Color::srgb(red, green, blue)
+    }
+}
+
+/// All items from `assets/items/items.ron`.
+#[derive(Resource, Debug, Default)]
+pub struct ItemCatalog(pub Vec<ItemDefinition>);
+
+impl ItemCatalog {
+    const SOURCE: &'static str = include_str!("../../assets/items/items.ron");
+
+    pub fn load() -> Self {
+        Self(parse_embedded(Self::SOURCE, "items"))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ItemDefinition> {
+        self.0.iter().find(|item| item.id == id)
+    }
+}
+
+/// A number of one item, taking up a slot.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: String,
+    pub count: u32,
+}
+
+/// The items the player carries, in up to [`INVENTORY_SLOTS`] stacks.
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    pub stacks: Vec<ItemStack>,
+}
+
+impl Inventory {
+    /// How many of `item` fit, in stacks of it and empty slots.
+    pub fn room_for(&self, item: &ItemDefinition) -> u32 {
+        let in_stacks: u32 = self
+            .stacks
+            .iter()
+            .filter(|stack| stack.item == item.id)
+            .map(|stack| item.max_stack.saturating_sub(stack.count))
+            .sum();
+        let free_slots = INVENTORY_SLOTS.saturating_sub(self.stacks.len()) as u32;
+        in_stacks.saturating_add(free_slots.saturating_mul(item.max_stack))
+    }
+
+    /// Add up to `count` of `item`, topping up its stacks before starting new ones.
+    /// Returns how many were added.
+    pub fn add(&mut self, item: &ItemDefinition, count: u32) -> u32 {
+        let mut left = count;
+        for stack in self.stacks.iter_mut().filter(|stack| stack.item == item.id) {
+            let added = left.min(item.max_stack.saturating_sub(stack.count));
+            stack.count += added;
+            left -= added;
+        }
+        while left > 0 && self.stacks.len() < INVENTORY_SLOTS && item.max_stack > 0 {
+            let added = left.min(item.max_stack);
+            self.stacks.push(ItemStack {
+                item: item.id.clone(),
+                count: added,
+            });
+            left -= added;
+        }
+        count - left
+    }
+
+    /// How many of the item with id `item` there are, in all its stacks.
+    pub fn count(&self, item: &str) -> u32 {
+        self.stacks
+            .iter()
+            .filter(|stack| stack.item == item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    /// Take `count` of the item with id `item`, from its last stacks first,
+    /// if there are that many. Returns whether they were taken.
+    pub fn spend(&mut self, item: &str, count: u32) -> bool {
+        if self.count(item) < count {
+            return false;
+        }
+        let mut left = count;
+        for stack in self
+            .stacks
+            .iter_mut()
+            .rev()
+            .filter(|stack| stack.item == item)
+        {
+            let taken = left.min(stack.count);
+            stack.count -= taken;
+            left -= taken;
+        }
+        self.stacks.retain(|stack| stack.count > 0);
+        true
+    }
+}
+
+/// A number of an item lying around, collected by touching it.
+#[derive(Component, Reflect, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct Pickup {
+    /// Id of the item, see [`ItemCatalog`].
+    pub item: String,
+    pub count: u32,
+}
+
+/// Draws in pickups within `radius` pixels that fit the [`Inventory`].
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+pub struct Magnet {
+    pub radius: f32,
+}
+
+impl Default for Magnet {
+    fn default() -> Self {
+        Self { radius: 240.0 }
+    }
+}
+
+/// How fast pickups are drawn in at the edge of a magnet's reach, and right next to it,
+/// in pixels per second.
+const MAGNET_SPEED: (f32, f32) = (150.0, 900.0);
+/// How far from where an enemy died its drops land, at most, in pixels.
+const DROP_SCATTER: f32 = 40.0;
+
+/// Spawn `count` of `item` at `position`.
+pub fn spawn_pickup(commands: &mut Commands, item: &ItemDefinition, count: u32, position: Vec2) {
+    commands.spawn((
+        Name::new(format!("Pickup {}", item.name)),
+        Pickup {
+            item: item.id.clone(),
+            count,
+        },
+        SpriteBundle {
+            sprite: Sprite {
+                color: item.color(),
+                custom_size: Some(Vec2::splat(item.size)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(-1.0)),
+            ..default()
+        },
+        GameplayTint(item.color()),
+        OnLayer::new(Layer::World),
+        InterpolatedTransform::default(),
+        Collider::Circle {
+            radius: item.size / 2.0,
+        },
+        CollisionLayer::new(CollisionLayer::PICKUP, CollisionLayer::PLAYER),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+/// Continues with the items of the run being played, or none for a new one.
+fn resume_inventory(mut inventory: ResMut<Inventory>, save: Res<SaveGame>) {
+    inventory.clone_from(&save.inventory);
+}
+
+fn track_inventory(inventory: Res<Inventory>, mut save: ResMut<SaveGame>) {
+    save.inventory.clone_from(&inventory);
+}
+
+fn attract_pickups(
+    time: Res<Time>,
+    catalog: Res<ItemCatalog>,
+    inventory: Res<Inventory>,
+    magnet_query: Query<(&Transform, &Magnet), Without<Pickup>>,
+    mut pickup_query: Query<(&mut Transform, &Pickup)>,
+) {
+    let dt = time.delta_seconds();
+    for (mut transform, pickup) in &mut pickup_query {
+        let fits = catalog
+            .get(&pickup.item)
+            .is_some_and(|item| inventory.room_for(item) > 0);
+        if !fits {
+            continue;
+        }
+        let position = transform.translation.xy();
+        // The closest magnet in reach.
+        let closest = magnet_query
+            .iter()
+            .map(|(magnet_transform, magnet)| {
+                let offset = magnet_transform.translation.xy() - position;
+                (offset, offset.length() / magnet.radius)
+            })
+            .filter(|&(_, reach)| reach < 1.0)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((offset, reach)) = closest else {
+            continue;
+        };
+        // Faster the closer it gets, without overshooting.
+        let speed = MAGNET_SPEED.0 + (MAGNET_SPEED.1 - MAGNET_SPEED.0) * (1.0 - reach);
+        let step = offset.clamp_length_max(speed * dt);
+        transform.translation += step.extend(0.0);
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    mut pickup_events: EventReader<PickupEvent>,
+    catalog: Res<ItemCatalog>,
+    mut inventory: ResMut<Inventory>,
+    player_query: Query<(), With<Player>>,
+    mut pickup_query: Query<&mut Pickup>,
+) {
+    for event in pickup_events.read() {
+        if !player_query.contains(event.collector) {
+            continue;
+        }
+        let Ok(mut pickup) = pickup_query.get_mut(event.pickup) else {
+            continue;
+        };
+        let Some(item) = catalog.get(&pickup.item) else {
+            continue;
+        };
+        let added = inventory.add(item, pickup.count);
+        if added == 0 {
+            continue;
+        }
+        pickup.count -= added;
+        if pickup.count == 0 {
+            commands.entity(event.pickup).despawn_recursive();
+        }
+        commands.trigger(PlaySfx::Key(SfxKey::Pickup));
+        commands.trigger(Rumble::PICKUP);
+        commands.trigger(Announce::new(
+            Importance::Detail,
+            format!("Picked up {} {}", added, item.name),
+        ));
+    }
+}
+
+fn drop_loot(
+    mut commands: Commands,
+    mut death_events: EventReader<DeathEvent>,
+    catalog: Res<ItemCatalog>,
+    mut rng: ResMut<GameRng>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+) {
+    for event in death_events.read() {
+        let Ok(transform) = enemy_query.get(event.entity) else {
+            continue;
+        };
+        let position = transform.translation.xy();
+        for item in &catalog.0 {
+            let rng = rng.loot();
+            if !rng.gen_bool(item.drop_chance.clamp(0.0, 1.0)) {
+                continue;
+            }
+            let scatter = Vec2::new(
+                rng.gen_range(-DROP_SCATTER..=DROP_SCATTER),
+                rng.gen_range(-DROP_SCATTER..=DROP_SCATTER),
+            );
+            spawn_pickup(&mut commands, item, 1, position + scatter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_stack_until_the_inventory_is_full() {
+        let catalog = ItemCatalog::load();
+        let gem = catalog.get("gem").unwrap();
+        let coin = catalog.get("coin").unwrap();
+        let mut inventory = Inventory::default();
+        assert_eq!(inventory.add(gem, 1), 1);
+        assert_eq!(inventory.add(gem, 1), 1);
+        assert_eq!(inventory.stacks.len(), 1);
+
+        // Full stacks spill into new slots, until there are none left.
+        let room = inventory.room_for(gem);
+        assert_eq!(room, gem.max_stack * INVENTORY_SLOTS as u32 - 2);
+        assert_eq!(inventory.add(gem, room + 5), room);
+        assert_eq!(inventory.stacks.len(), INVENTORY_SLOTS);
+        assert_eq!(inventory.room_for(gem), 0);
+        assert_eq!(inventory.room_for(coin), 0);
+        assert_eq!(inventory.add(coin, 1), 0);
+    }
+}
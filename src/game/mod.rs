@@ -2,18 +2,113 @@
 
 use bevy::prelude::*;
 
-mod animation;
+pub mod ai;
+pub mod animation;
 pub mod assets;
 pub mod audio;
+pub mod camera;
+pub mod challenge;
+pub mod checksum;
+pub mod collision;
+pub mod cosmetics;
+pub mod cutscene;
+pub mod cycle;
+pub mod damage_numbers;
+pub mod dialogue;
+pub mod fallback;
+pub mod gamepad;
+pub mod ghost;
+pub mod health;
+pub mod high_scores;
+pub mod input;
+pub mod integrity;
+pub mod interpolation;
+pub mod inventory;
+pub mod mode;
 mod movement;
+pub mod mutators;
+pub mod navigation;
+pub mod objectives;
+pub mod palette;
+pub mod particles;
+#[cfg(feature = "physics")]
+mod physics;
+pub mod pixel_canvas;
+pub mod post_processing;
+pub mod profile;
+pub mod quick_actions;
+pub mod rewind;
+pub mod rng;
+pub mod save;
+pub mod score;
+pub mod snapshot_codec;
 pub mod spawn;
+pub mod sprite_effects;
+pub mod stable_id;
+pub mod stagger;
+pub mod stats;
+pub mod status;
+pub mod touch;
+pub mod trail;
+pub mod tutorial;
+pub mod upgrades;
+pub mod weekly;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         animation::plugin,
-        audio::plugin,
         assets::plugin,
+        audio::plugin,
+        camera::plugin,
+        checksum::plugin,
+        collision::plugin,
+        cycle::plugin,
+        gamepad::plugin,
+        input::plugin,
+        interpolation::plugin,
         movement::plugin,
+        rewind::plugin,
         spawn::plugin,
+        stable_id::plugin,
+        touch::plugin,
+    ));
+    app.add_plugins((
+        ai::plugin,
+        challenge::plugin,
+        cutscene::plugin,
+        dialogue::plugin,
+        fallback::plugin,
+        integrity::plugin,
+        inventory::plugin,
+        mode::plugin,
+        mutators::plugin,
+        navigation::plugin,
+        objectives::plugin,
+        quick_actions::plugin,
+        rng::plugin,
+        upgrades::plugin,
+        weekly::plugin,
+    ));
+    // Combat.
+    app.add_plugins((health::plugin, stagger::plugin, status::plugin));
+    // Presentation and progress.
+    app.add_plugins((
+        cosmetics::plugin,
+        damage_numbers::plugin,
+        ghost::plugin,
+        high_scores::plugin,
+        palette::plugin,
+        particles::plugin,
+        pixel_canvas::plugin,
+        post_processing::plugin,
+        profile::plugin,
+        save::plugin,
+        score::plugin,
+        sprite_effects::plugin,
+        stats::plugin,
+        trail::plugin,
+        tutorial::plugin,
     ));
+    #[cfg(feature = "physics")]
+    app.add_plugins(physics::plugin);
 }
@@ -0,0 +1,93 @@
+//! Ways to play, picked on the title screen. A normal run is saved in a slot and
+//! ends on the game over screen. An endless run isn't saved: its waves loop with
+//! rising difficulty, its soundtrack rotates every few waves, and it ends on a results
+//! screen that records the score with how long the player survived.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{assets::SoundtrackKey, audio::soundtrack::SoundtrackCommand};
+use crate::{events::WaveStarted, screen::Screen, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GameMode>();
+    app.init_resource::<GameMode>();
+    app.add_systems(
+        Update,
+        rotate_endless_soundtrack
+            .in_set(AppSet::HandleEvents)
+            .run_if(in_state(Screen::Playing).and_then(resource_equals(GameMode::Endless))),
+    );
+}
+
+/// The mode of the current or last run.
+#[derive(
+    Resource,
+    Serialize,
+    Deserialize,
+    Reflect,
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+#[reflect(Resource)]
+pub enum GameMode {
+    #[default]
+    Normal,
+    Endless,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 2] = [GameMode::Normal, GameMode::Endless];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Normal => "Normal",
+            GameMode::Endless => "Endless",
+        }
+    }
+
+    /// Where the run goes when the player dies.
+    pub fn results_screen(self) -> Screen {
+        match self {
+            GameMode::Normal => Screen::GameOver,
+            GameMode::Endless => Screen::EndlessResults,
+        }
+    }
+
+    /// The soundtrack to start a run with.
+    pub fn soundtrack(self) -> SoundtrackKey {
+        match self {
+            GameMode::Normal => SoundtrackKey::Gameplay,
+            GameMode::Endless => endless_soundtrack(1),
+        }
+    }
+}
+
+/// The tracks endless mode rotates through.
+const ENDLESS_SOUNDTRACKS: [SoundtrackKey; 3] = [
+    SoundtrackKey::Endless,
+    SoundtrackKey::Gameplay,
+    SoundtrackKey::Credits,
+];
+/// How many waves each track of [`ENDLESS_SOUNDTRACKS`] plays for.
+const WAVES_PER_TRACK: u32 = 3;
+
+/// The track playing during endless wave `wave`, counting from 1.
+pub fn endless_soundtrack(wave: u32) -> SoundtrackKey {
+    let track = (wave.max(1) - 1) / WAVES_PER_TRACK;
+    ENDLESS_SOUNDTRACKS[track as usize % ENDLESS_SOUNDTRACKS.len()]
+}
+
+fn rotate_endless_soundtrack(mut commands: Commands, mut started_events: EventReader<WaveStarted>) {
+    // Playing the track that is already on does nothing, so this only changes every few waves.
+    if let Some(event) = started_events.read().last() {
+        commands.trigger(SoundtrackCommand::Play(endless_soundtrack(event.wave)));
+    }
+}
@@ -1,62 +1,122 @@
 //! Handle player input and translate it into movement.
-//! Note that the approach used here is simple for demonstration purposes.
-//! If you want to move the player in a smoother way,
-//! consider using a [fixed timestep](https://github.com/bevyengine/bevy/blob/main/examples/movement/physics_in_fixed_timestep.rs).
+//! Movement is applied in `FixedUpdate`, so it doesn't depend on the frame rate.
+//! Moving entities should have an [`InterpolatedTransform`](super::interpolation::InterpolatedTransform)
+//! so they don't look steppy between ticks. Neither input nor movement apply while
+//! [rewinding](super::rewind), which moves entities itself, and movement input isn't
+//! recorded while it aims the [quick-action wheel](super::quick_actions).
+//! Entities with an [`AiBrain`] are steered by it instead of by input, along their
+//! [`PathFollow`] when they have one. Nothing with a [`Collider`] moves into a [`Wall`].
+//! The player leaves a [`Trail`] while sprinting.
 
 use bevy::{prelude::*, window::PrimaryWindow};
 
-use crate::AppSet;
+use super::{
+    ai::AiBrain,
+    animation::{AnimationController, AnimationState},
+    collision::Collider,
+    input::{Action, ActionInput},
+    navigation::PathFollow,
+    quick_actions::is_quick_wheel_open,
+    rewind::is_rewinding,
+    spawn::{level::Wall, player::Player},
+    stagger::Staggered,
+    trail::Trail,
+    upgrades::Upgrades,
+};
+use crate::{screen::PlayingState, AppSet};
+
 pub(super) fn plugin(app: &mut App) {
     // Record directional input as movement controls.
     app.register_type::<MovementController>();
     app.add_systems(
         Update,
-        record_movement_controller.in_set(AppSet::RecordInput),
+        (
+            record_movement_controller
+                .in_set(AppSet::RecordInput)
+                .run_if(not(is_rewinding).and_then(not(is_quick_wheel_open))),
+            (animate_movement, trail_sprint).in_set(AppSet::Update),
+        )
+            .run_if(in_state(PlayingState::Running)),
     );
 
-    // Apply movement based on controls.
+    // Apply movement based on controls, on fixed ticks.
     app.register_type::<(Movement, WrapWithinWindow)>();
     app.add_systems(
-        Update,
-        (apply_movement, wrap_within_window)
+        FixedUpdate,
+        (
+            (follow_paths, apply_movement, stay_out_of_walls)
+                .chain()
+                .run_if(not(is_rewinding)),
+            wrap_within_window,
+        )
             .chain()
-            .in_set(AppSet::Update),
+            .in_set(AppSet::Update)
+            .run_if(in_state(PlayingState::Running)),
     );
 }
 
+/// Movement intent, with a length of at most 1, or [`SPRINT_MULTIPLIER`] while sprinting.
 #[derive(Component, Reflect, Default)]
 #[reflect(Component)]
 pub struct MovementController(pub Vec2);
 
+const SPRINT_MULTIPLIER: f32 = 1.6;
+
 fn record_movement_controller(
-    input: Res<ButtonInput<KeyCode>>,
-    mut controller_query: Query<&mut MovementController>,
+    actions: ActionInput,
+    mut controller_query: Query<&mut MovementController, Without<AiBrain>>,
 ) {
-    // Collect directional input.
-    let mut intent = Vec2::ZERO;
-    if input.pressed(KeyCode::KeyW) || input.pressed(KeyCode::ArrowUp) {
-        intent.y += 1.0;
-    }
-    if input.pressed(KeyCode::KeyS) || input.pressed(KeyCode::ArrowDown) {
-        intent.y -= 1.0;
-    }
-    if input.pressed(KeyCode::KeyA) || input.pressed(KeyCode::ArrowLeft) {
-        intent.x -= 1.0;
-    }
-    if input.pressed(KeyCode::KeyD) || input.pressed(KeyCode::ArrowRight) {
-        intent.x += 1.0;
+    let mut intent = actions.movement();
+    if actions.pressed(Action::Sprint) {
+        intent *= SPRINT_MULTIPLIER;
     }
 
-    // Normalize so that diagonal movement has the same speed as
-    // horizontal and vertical movement.
-    let intent = intent.normalize_or_zero();
-
     // Apply movement intent to controllers.
     for mut controller in &mut controller_query {
         controller.0 = intent;
     }
 }
 
+/// Face the direction of movement, and run or idle depending on whether there is any.
+fn animate_movement(
+    mut movement_query: Query<
+        (&MovementController, &mut Sprite, &mut AnimationController),
+        Without<Staggered>,
+    >,
+) {
+    for (controller, mut sprite, mut animation) in &mut movement_query {
+        let dx = controller.0.x;
+        if dx != 0.0 {
+            sprite.flip_x = dx < 0.0;
+        }
+        animation.set_state(if controller.0 == Vec2::ZERO {
+            AnimationState::Idle
+        } else {
+            AnimationState::Run
+        });
+    }
+}
+
+/// Give the player a trail while sprinting, which fades out once they stop.
+fn trail_sprint(
+    mut commands: Commands,
+    player_query: Query<(Entity, &MovementController, Has<Trail>), With<Player>>,
+) {
+    for (entity, controller, has_trail) in &player_query {
+        // Only sprinting makes the intent longer than 1.
+        let sprinting = controller.0.length() > 1.0;
+        if sprinting && !has_trail {
+            commands.entity(entity).insert(Trail::new(
+                24.0,
+                0.25,
+                Color::srgba(0.8, 0.9, 1.0, 0.6),
+            ));
+        } else if !sprinting && has_trail {
+            commands.entity(entity).remove::<Trail>();
+        }
+    }
+}
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct Movement {
@@ -69,14 +129,63 @@ pub struct Movement {
 
 fn apply_movement(
     time: Res<Time>,
-    mut movement_query: Query<(&MovementController, &Movement, &mut Transform)>,
+    upgrades: Res<Upgrades>,
+    mut movement_query: Query<
+        (&MovementController, &Movement, &mut Transform, Has<Player>),
+        Without<Staggered>,
+    >,
 ) {
-    for (controller, movement, mut transform) in &mut movement_query {
-        let velocity = movement.speed * controller.0;
+    for (controller, movement, mut transform, is_player) in &mut movement_query {
+        let speed = if is_player {
+            movement.speed * upgrades.player_speed
+        } else {
+            movement.speed
+        };
+        let velocity = speed * controller.0;
         transform.translation += velocity.extend(0.0) * time.delta_seconds();
     }
 }
 
+/// How close a waypoint has to be to count as reached, in pixels.
+const WAYPOINT_REACHED: f32 = 8.0;
+
+/// Point controllers at the next waypoint of their path, keeping their speed.
+fn follow_paths(mut path_query: Query<(&mut PathFollow, &mut MovementController, &Transform)>) {
+    for (mut path, mut controller, transform) in &mut path_query {
+        let position = transform.translation.xy();
+        while let Some(waypoint) = path.next_waypoint() {
+            // The last waypoint is the target, which is approached like without a path.
+            if path.waypoints.len() > 1 && waypoint.distance(position) <= WAYPOINT_REACHED {
+                path.waypoints.pop_front();
+                continue;
+            }
+            let direction = (waypoint - position).normalize_or_zero();
+            if direction != Vec2::ZERO {
+                controller.0 = direction * controller.0.length();
+            }
+            break;
+        }
+    }
+}
+
+fn stay_out_of_walls(
+    wall_query: Query<(&Transform, &Collider), With<Wall>>,
+    mut mover_query: Query<(&mut Transform, &Collider), (With<MovementController>, Without<Wall>)>,
+) {
+    for (mut transform, collider) in &mut mover_query {
+        for (wall_transform, wall_collider) in &wall_query {
+            let push = collider.push_out_of_box(
+                transform.translation.xy(),
+                wall_transform.translation.xy(),
+                wall_collider.half_extents(),
+            );
+            if let Some(push) = push {
+                transform.translation += push.extend(0.0);
+            }
+        }
+    }
+}
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct WrapWithinWindow;
@@ -0,0 +1,203 @@
+//! Run mutators, defined in `assets/mutators/mutators.ron` and picked on the mutators
+//! screen before starting a run. A new run takes the [`MutatorSelection`] into its
+//! [`SaveGame`], so continuing keeps the mutators it started with, and the combined
+//! [`Modifiers`] of the run's [`RunMutators`] are applied where the stats they change
+//! come from, like spawning the player and enemies.
+//!
+//! High scores are kept per set of mutators, see `high_scores`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{assets::parse_embedded, save::SaveGame};
+use crate::{screen::Screen, storage};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(MutatorCatalog::load());
+    app.insert_resource(storage::load::<MutatorSelection>(MUTATORS_KEY).unwrap_or_default());
+    app.init_resource::<RunMutators>();
+    app.init_resource::<Modifiers>();
+    app.add_systems(OnEnter(Screen::Playing), start_run_mutators);
+    app.add_systems(
+        Update,
+        save_selection.run_if(resource_changed::<MutatorSelection>),
+    );
+}
+
+/// Key under which the [`MutatorSelection`] is persisted.
+const MUTATORS_KEY: &str = "mutators";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Mutator {
+    /// Stays the same when the name changes, since it is persisted.
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub modifiers: Vec<Modifier>,
+}
+
+/// A change to the rules of a run.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Modifier {
+    /// Multiplies how fast enemies move.
+    EnemySpeed(f32),
+    /// Multiplies enemies' health.
+    EnemyHealth(f32),
+    /// Multiplies the damage enemies deal.
+    EnemyDamage(f32),
+    /// Multiplies how fast the player moves.
+    PlayerSpeed(f32),
+    /// The player dies to any damage.
+    OneHitDeath,
+    /// Multiplies gravity. The game is seen from above, so only particles fall.
+    Gravity(f32),
+}
+
+/// All mutators from `assets/mutators/mutators.ron`.
+#[derive(Resource, Debug, Default)]
+pub struct MutatorCatalog(pub Vec<Mutator>);
+
+impl MutatorCatalog {
+    const SOURCE: &'static str = include_str!("../../assets/mutators/mutators.ron");
+
+    pub fn load() -> Self {
+        Self(parse_embedded(Self::SOURCE, "mutators"))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Mutator> {
+        self.0.iter().find(|mutator| mutator.id == id)
+    }
+
+    /// The names of the mutators with `ids`, like "Double Speed, One-Hit Death",
+    /// or "None". Unknown ids, like ones removed since, are shown as they are.
+    pub fn names(&self, ids: &[String]) -> String {
+        if ids.is_empty() {
+            return "None".to_string();
+        }
+        ids.iter()
+            .map(|id| self.get(id).map_or(id.as_str(), |mutator| &mutator.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Ids of the mutators picked for new runs, sorted. Persisted between sessions.
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct MutatorSelection(pub Vec<String>);
+
+impl MutatorSelection {
+    pub fn contains(&self, id: &str) -> bool {
+        self.0.iter().any(|selected| selected == id)
+    }
+
+    /// Pick the mutator if it wasn't, or unpick it if it was.
+    pub fn toggle(&mut self, id: &str) {
+        if let Some(index) = self.0.iter().position(|selected| selected == id) {
+            self.0.remove(index);
+        } else {
+            self.0.push(id.to_string());
+            self.0.sort();
+        }
+    }
+}
+
+/// Ids of the mutators of the current or last run, sorted.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct RunMutators(pub Vec<String>);
+
+/// The combined effect of the current run's mutators.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Modifiers {
+    pub enemy_speed: f32,
+    pub enemy_health: f32,
+    pub enemy_damage: f32,
+    pub player_speed: f32,
+    pub one_hit_death: bool,
+    pub gravity: f32,
+}
+
+impl Default for Modifiers {
+    fn default() -> Self {
+        Self {
+            enemy_speed: 1.0,
+            enemy_health: 1.0,
+            enemy_damage: 1.0,
+            player_speed: 1.0,
+            one_hit_death: false,
+            gravity: 1.0,
+        }
+    }
+}
+
+impl Modifiers {
+    /// The modifiers of the mutators with `ids`. Unknown ids are skipped.
+    pub fn from_mutators(catalog: &MutatorCatalog, ids: &[String]) -> Self {
+        let mut modifiers = Self::default();
+        let all = ids.iter().filter_map(|id| catalog.get(id));
+        for &modifier in all.flat_map(|mutator| &mutator.modifiers) {
+            match modifier {
+                Modifier::EnemySpeed(factor) => modifiers.enemy_speed *= factor,
+                Modifier::EnemyHealth(factor) => modifiers.enemy_health *= factor,
+                Modifier::EnemyDamage(factor) => modifiers.enemy_damage *= factor,
+                Modifier::PlayerSpeed(factor) => modifiers.player_speed *= factor,
+                Modifier::OneHitDeath => modifiers.one_hit_death = true,
+                Modifier::Gravity(factor) => modifiers.gravity *= factor,
+            }
+        }
+        modifiers
+    }
+}
+
+/// The level is spawned by a trigger on entering as well, which only runs once commands
+/// are applied, so the player and the level see these modifiers.
+fn start_run_mutators(
+    catalog: Res<MutatorCatalog>,
+    selection: Res<MutatorSelection>,
+    mut save: ResMut<SaveGame>,
+    mut run_mutators: ResMut<RunMutators>,
+    mut modifiers: ResMut<Modifiers>,
+) {
+    // A run without randomness saved is a new one, like when seeding it.
+    // Weekly challenges come with mutators of their own.
+    if save.rng.is_none() && save.weekly.is_none() {
+        save.mutators.clone_from(&selection.0);
+    }
+    run_mutators.0.clone_from(&save.mutators);
+    *modifiers = Modifiers::from_mutators(&catalog, &run_mutators.0);
+    if !run_mutators.0.is_empty() {
+        info!("Playing with mutators: {}.", catalog.names(&run_mutators.0));
+    }
+}
+
+fn save_selection(selection: Res<MutatorSelection>) {
+    storage::save(MUTATORS_KEY, &*selection);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutators_combine_into_modifiers() {
+        let catalog = MutatorCatalog::load();
+        assert!(catalog.get("double_enemy_speed").is_some());
+        let mut selection = MutatorSelection::default();
+        selection.toggle("one_hit_death");
+        selection.toggle("double_enemy_speed");
+        assert_eq!(selection.0, ["double_enemy_speed", "one_hit_death"]);
+        let modifiers = Modifiers::from_mutators(&catalog, &selection.0);
+        assert_eq!(modifiers.enemy_speed, 2.0);
+        assert!(modifiers.one_hit_death);
+        assert_eq!(modifiers.player_speed, 1.0);
+        assert_eq!(catalog.names(&selection.0), "Double Speed, One-Hit Death");
+        selection.toggle("one_hit_death");
+        assert!(!selection.contains("one_hit_death"));
+
+        // Mutators that are gone are skipped.
+        let gone = ["removed".to_string()];
+        assert_eq!(
+            Modifiers::from_mutators(&catalog, &gone),
+            Modifiers::default()
+        );
+    }
+}
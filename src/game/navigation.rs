@@ -0,0 +1,382 @@
+//! Pathfinding for enemies, so they go around walls instead of into them.
+//!
+//! When the level spawns, its walls are rasterized into a [`NavGrid`] of walkable cells,
+//! grown by [`NAV_CLEARANCE`] so paths keep enemies' bodies off the walls. Entities with
+//! a [`PathFollow`] and a target are queued for an A* search whenever their target moves
+//! to another cell. Searches run a few per tick, see [`PATHS_PER_TICK`], so a big wave
+//! asking at once spreads out over several ticks. They run on the fixed tick instead of
+//! on a task pool, so a path lands on the same tick every time a run is replayed.
+//!
+//! Movement consumes the path, see `game::movement`. Until one is found, entities head
+//! straight for their target.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{
+    assets::LevelAssets,
+    save::SaveGame,
+    spawn::level::{LevelData, PlacementKind, SpawnLevel},
+};
+use crate::{screen::PlayingState, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PathFollow>();
+    app.init_resource::<NavGrid>();
+    app.init_resource::<PathQueue>();
+    app.observe(build_nav_grid);
+    app.configure_sets(FixedUpdate, Pathfinding.in_set(AppSet::RecordInput));
+    app.add_systems(
+        FixedUpdate,
+        (request_paths, find_paths)
+            .chain()
+            .in_set(Pathfinding)
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// Queues and runs path searches. Set a [`PathFollow`]'s target before this.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pathfinding;
+
+/// Width and height of a grid cell, in pixels.
+pub const NAV_CELL_SIZE: f32 = 32.0;
+/// How far paths keep from walls, in pixels.
+pub const NAV_CLEARANCE: f32 = 40.0;
+/// How far the grid reaches past the level's walls and spawn, in pixels.
+/// Outside of it there are no walls, so paths there go straight.
+const NAV_MARGIN: f32 = 1024.0;
+/// How many searches run per fixed tick.
+pub const PATHS_PER_TICK: usize = 4;
+/// How many cells a search may expand before giving up, as on huge grids.
+const MAX_EXPANDED: usize = 16_384;
+
+/// Which cells of the level can be walked through.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct NavGrid {
+    /// The corner of the first cell, with the lowest x and y.
+    origin: Vec2,
+    width: usize,
+    height: usize,
+    blocked: Vec<bool>,
+}
+
+impl Default for NavGrid {
+    /// A grid without walls, until a level is spawned.
+    fn default() -> Self {
+        Self::new(Vec2::ZERO, 0, 0)
+    }
+}
+
+impl NavGrid {
+    /// A grid of `width` by `height` walkable cells, starting at `origin`.
+    pub fn new(origin: Vec2, width: usize, height: usize) -> Self {
+        Self {
+            origin,
+            width,
+            height,
+            blocked: vec![false; width * height],
+        }
+    }
+
+    /// The grid around `level`'s walls and player spawn.
+    pub fn from_level(level: &LevelData) -> Self {
+        let walls: Vec<_> = level
+            .placements
+            .iter()
+            .filter_map(|placement| match placement.kind {
+                PlacementKind::Wall { size, .. } => Some((placement.position, size / 2.0)),
+                PlacementKind::Decoration { .. } | PlacementKind::Trigger { .. } => None,
+            })
+            .collect();
+        let (min, max) = walls.iter().fold(
+            (level.player_spawn, level.player_spawn),
+            |(min, max), &(center, half_size)| {
+                (min.min(center - half_size), max.max(center + half_size))
+            },
+        );
+        let (min, max) = (min - NAV_MARGIN, max + NAV_MARGIN);
+        let cells = ((max - min) / NAV_CELL_SIZE).ceil();
+        let mut grid = Self::new(min, cells.x as usize, cells.y as usize);
+        for (center, half_size) in walls {
+            grid.block(center, half_size + NAV_CLEARANCE);
+        }
+        grid
+    }
+
+    /// Block every cell whose center is within the box around `center`.
+    pub fn block(&mut self, center: Vec2, half_size: Vec2) {
+        let min = self.cell_clamped(center - half_size);
+        let max = self.cell_clamped(center + half_size);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let cell = UVec2::new(x, y);
+                let offset = (self.center(cell) - center).abs();
+                if offset.x <= half_size.x && offset.y <= half_size.y {
+                    let index = self.index(cell);
+                    self.blocked[index] = true;
+                }
+            }
+        }
+    }
+
+    /// The cell containing `position`, if it is on the grid.
+    pub fn cell(&self, position: Vec2) -> Option<UVec2> {
+        let cell = ((position - self.origin) / NAV_CELL_SIZE).floor();
+        let inside = cell.x >= 0.0
+            && cell.y >= 0.0
+            && (cell.x as usize) < self.width
+            && (cell.y as usize) < self.height;
+        inside.then(|| cell.as_uvec2())
+    }
+
+    /// The cell of the grid closest to `position`.
+    fn cell_clamped(&self, position: Vec2) -> UVec2 {
+        let max = UVec2::new(self.width as u32, self.height as u32).saturating_sub(UVec2::ONE);
+        ((position - self.origin) / NAV_CELL_SIZE)
+            .floor()
+            .max(Vec2::ZERO)
+            .as_uvec2()
+            .min(max)
+    }
+
+    /// The center of `cell`, in world space.
+    pub fn center(&self, cell: UVec2) -> Vec2 {
+        self.origin + (cell.as_vec2() + 0.5) * NAV_CELL_SIZE
+    }
+
+    fn index(&self, cell: UVec2) -> usize {
+        cell.y as usize * self.width + cell.x as usize
+    }
+
+    /// Whether `cell` is on the grid and not blocked.
+    pub fn is_walkable(&self, cell: UVec2) -> bool {
+        (cell.x as usize) < self.width
+            && (cell.y as usize) < self.height
+            && !self.blocked[self.index(cell)]
+    }
+
+    /// The walkable neighbors of `cell` with the cost to step to them: 10 straight,
+    /// 14 diagonally. Diagonal steps can't cut the corners of blocked cells.
+    fn neighbors(&self, cell: UVec2) -> impl Iterator<Item = (UVec2, u32)> + '_ {
+        let step = move |dx: i32, dy: i32| {
+            let x = cell.x.checked_add_signed(dx)?;
+            let y = cell.y.checked_add_signed(dy)?;
+            let next = UVec2::new(x, y);
+            self.is_walkable(next).then_some(next)
+        };
+        [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (-1, 1),
+            (1, -1),
+            (1, 1),
+        ]
+        .into_iter()
+        .filter_map(move |(dx, dy)| {
+            let next = step(dx, dy)?;
+            if dx != 0 && dy != 0 {
+                step(dx, 0)?;
+                step(0, dy)?;
+                Some((next, 14))
+            } else {
+                Some((next, 10))
+            }
+        })
+    }
+
+    /// Waypoints from `from` to `to` around blocked cells, ending at `to`, or `None` if
+    /// there is no way. Positions off the grid are searched from the closest cell.
+    /// Waypoints are only kept where the path turns.
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        if self.width == 0 || self.height == 0 {
+            return Some(vec![to]);
+        }
+        let start = self.cell_clamped(from);
+        let goal = self.cell_clamped(to);
+        if !self.is_walkable(goal) {
+            return None;
+        }
+        // Octile distance, in the same units as the step costs.
+        let heuristic = |cell: UVec2| {
+            let delta = (cell.as_ivec2() - goal.as_ivec2()).abs();
+            let (long, short) = (delta.max_element() as u32, delta.min_element() as u32);
+            10 * long + 4 * short
+        };
+        let mut open = BinaryHeap::from([(Reverse(heuristic(start)), start.to_array())]);
+        let mut costs = HashMap::<UVec2, u32>::default();
+        costs.insert(start, 0);
+        let mut came_from = HashMap::<UVec2, UVec2>::default();
+        let mut expanded = 0;
+        while let Some((_, cell)) = open.pop() {
+            let cell = UVec2::from_array(cell);
+            if cell == goal {
+                return Some(self.waypoints(&came_from, goal, to));
+            }
+            expanded += 1;
+            if expanded > MAX_EXPANDED {
+                break;
+            }
+            let cost = costs[&cell];
+            for (next, step) in self.neighbors(cell) {
+                let next_cost = cost + step;
+                if costs.get(&next).is_some_and(|&known| known <= next_cost) {
+                    continue;
+                }
+                costs.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push((Reverse(next_cost + heuristic(next)), next.to_array()));
+            }
+        }
+        None
+    }
+
+    /// Walk back from `goal`, keeping the cells where the direction changes.
+    fn waypoints(&self, came_from: &HashMap<UVec2, UVec2>, goal: UVec2, to: Vec2) -> Vec<Vec2> {
+        let mut waypoints = vec![to];
+        let mut cell = goal;
+        let mut direction = IVec2::ZERO;
+        while let Some(&previous) = came_from.get(&cell) {
+            let step = cell.as_ivec2() - previous.as_ivec2();
+            if step != direction && cell != goal {
+                waypoints.push(self.center(cell));
+            }
+            direction = step;
+            cell = previous;
+        }
+        waypoints.reverse();
+        waypoints
+    }
+}
+
+/// Follow a path to [`PathFollow::target`], found on the [`NavGrid`].
+/// Movement steers along it, keeping the speed of the [`MovementController`](super::movement::MovementController).
+#[derive(Component, Reflect, Debug, Default, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct PathFollow {
+    /// Where to go, or `None` to not follow a path.
+    pub target: Option<Vec2>,
+    /// The rest of the path, next waypoint first. Movement removes waypoints as they
+    /// are reached.
+    pub waypoints: VecDeque<Vec2>,
+    /// The cell of the target the path was found for, or was queued for.
+    searched: Option<UVec2>,
+}
+
+impl PathFollow {
+    /// The next waypoint, once a path was found.
+    pub fn next_waypoint(&self) -> Option<Vec2> {
+        self.target.and(self.waypoints.front().copied())
+    }
+}
+
+/// Entities waiting for a search, in the order they asked.
+#[derive(Resource, Debug, Default)]
+struct PathQueue(VecDeque<Entity>);
+
+fn build_nav_grid(
+    _trigger: Trigger<SpawnLevel>,
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<LevelData>>,
+    save: Res<SaveGame>,
+) {
+    let grid = levels
+        .get(level_assets.level(&save.level))
+        .map(NavGrid::from_level)
+        .unwrap_or_default();
+    commands.insert_resource(grid);
+}
+
+fn request_paths(
+    grid: Res<NavGrid>,
+    mut queue: ResMut<PathQueue>,
+    mut path_query: Query<(Entity, &mut PathFollow)>,
+) {
+    for (entity, mut path) in &mut path_query {
+        let Some(target) = path.target else {
+            if path.searched.is_some() {
+                path.searched = None;
+                path.waypoints.clear();
+            }
+            continue;
+        };
+        let cell = grid.cell(target);
+        // Off the grid, there is nothing to go around.
+        if cell.is_none() {
+            path.searched = None;
+            path.waypoints = VecDeque::from([target]);
+            continue;
+        }
+        if path.searched != cell {
+            path.searched = cell;
+            if !queue.0.contains(&entity) {
+                queue.0.push_back(entity);
+            }
+        }
+    }
+}
+
+fn find_paths(
+    grid: Res<NavGrid>,
+    mut queue: ResMut<PathQueue>,
+    mut path_query: Query<(&mut PathFollow, &Transform)>,
+) {
+    let mut searched = 0;
+    while searched < PATHS_PER_TICK {
+        let Some(entity) = queue.0.pop_front() else {
+            break;
+        };
+        // Gone, or not going anywhere anymore.
+        let Ok((mut path, transform)) = path_query.get_mut(entity) else {
+            continue;
+        };
+        let Some(target) = path.target else {
+            continue;
+        };
+        searched += 1;
+        let from = transform.translation.xy();
+        path.waypoints = grid
+            .find_path(from, target)
+            .map(VecDeque::from)
+            .unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_go_around_walls() {
+        let mut grid = NavGrid::new(Vec2::ZERO, 10, 10);
+        // A wall down the middle, open at the top.
+        let cell = |x: f32, y: f32| Vec2::new(x + 0.5, y + 0.5) * NAV_CELL_SIZE;
+        grid.block(cell(5.0, 3.5), Vec2::new(0.5, 4.0) * NAV_CELL_SIZE);
+        let (from, to) = (cell(1.0, 1.0), cell(8.0, 1.0));
+        let path = grid.find_path(from, to).unwrap();
+        assert_eq!(path.last(), Some(&to));
+        assert!(path.len() > 1);
+        let mut previous = from;
+        for &waypoint in &path {
+            // Every straight line between waypoints stays on walkable cells.
+            for step in 0..=32 {
+                let point = previous.lerp(waypoint, step as f32 / 32.0);
+                assert!(grid.is_walkable(grid.cell(point).unwrap()), "{point}");
+            }
+            previous = waypoint;
+        }
+
+        // Enclosed or blocked goals can't be reached.
+        assert_eq!(grid.find_path(from, cell(5.0, 3.0)), None);
+        grid.block(cell(5.0, 8.5), Vec2::new(0.5, 1.0) * NAV_CELL_SIZE);
+        assert_eq!(grid.find_path(from, to), None);
+    }
+}
@@ -0,0 +1,378 @@
+//! Goals for each level, declared in its file as [`ObjectiveData`]: defeating enemies,
+//! surviving cycles or reaching a place.
+//!
+//! Gameplay counts towards them with [`ObjectiveProgress`] events. The [`Objectives`] of the
+//! level being played track how far along each one is, which the run's [`SaveGame`] keeps.
+//! Completing one grants its rewards, and completing all of them triggers [`LevelCompleted`]
+//! and enters the level's [`next_level`](LevelData::next_level). The last level plays the
+//! ending cutscene instead, which ends the run.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    assets::LevelAssets,
+    cutscene::{PlayCutscene, ENDING},
+    health::DeathReactions,
+    inventory::{Inventory, ItemCatalog},
+    save::SaveGame,
+    spawn::{
+        level::{EnterLevel, LevelData, SpawnLevel},
+        player::Player,
+        wave::Enemy,
+    },
+};
+use crate::{
+    events::{DeathEvent, ObjectiveProgress, OnCycleAdvance, ScoreEvent},
+    screen::PlayingState,
+    ui::announcer::{Announce, Importance},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Objectives>();
+    app.observe(set_objectives);
+    app.add_systems(
+        FixedUpdate,
+        (
+            track_position
+                .in_set(AppSet::Update)
+                .run_if(|objectives: Res<Objectives>| objectives.wants_position()),
+            (
+                count_kills.in_set(DeathReactions),
+                count_cycles.in_set(AppSet::HandleEvents),
+                apply_progress.in_set(AppSet::HandleEvents),
+            )
+                .chain(),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// A goal as written in a level file.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ObjectiveData {
+    pub goal: Goal,
+    /// Granted when the goal is reached.
+    #[serde(default)]
+    pub rewards: Vec<Reward>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum Goal {
+    /// Defeat this many enemies.
+    Kill(u32),
+    /// Complete this many cycles.
+    SurviveCycles(u32),
+    /// Bring the player within `radius` pixels of `position`, a place called `name`.
+    Reach {
+        name: String,
+        position: Vec2,
+        radius: f32,
+    },
+}
+
+impl Goal {
+    /// How much progress completes it.
+    pub fn target(&self) -> u32 {
+        match self {
+            Goal::Kill(count) | Goal::SurviveCycles(count) => *count,
+            Goal::Reach { .. } => 1,
+        }
+    }
+
+    /// How much `event` counts towards it.
+    pub fn progress(&self, event: &ObjectiveProgress) -> u32 {
+        match (self, event) {
+            (Goal::Kill(_), ObjectiveProgress::EnemyKilled)
+            | (Goal::SurviveCycles(_), ObjectiveProgress::CycleSurvived) => 1,
+            (
+                Goal::Reach {
+                    position, radius, ..
+                },
+                ObjectiveProgress::PlayerAt(at),
+            ) => u32::from(at.distance(*position) <= *radius),
+            _ => 0,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Goal::Kill(1) => "Defeat an enemy".to_string(),
+            Goal::Kill(count) => format!("Defeat {count} enemies"),
+            Goal::SurviveCycles(1) => "Survive a cycle".to_string(),
+            Goal::SurviveCycles(count) => format!("Survive {count} cycles"),
+            Goal::Reach { name, .. } => format!("Reach {name}"),
+        }
+    }
+}
+
+/// What completing an objective grants.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum Reward {
+    /// Items for the inventory, as many as fit, see `inventory`.
+    Item { item: String, count: u32 },
+    /// Points for the score, multiplied by the combo like any other.
+    Score(u64),
+}
+
+impl Reward {
+    pub fn grant(
+        &self,
+        catalog: &ItemCatalog,
+        inventory: &mut Inventory,
+        score_events: &mut EventWriter<ScoreEvent>,
+    ) {
+        match self {
+            Reward::Item { item, count } => match catalog.get(item) {
+                Some(item) => {
+                    inventory.add(item, *count);
+                }
+                None => warn!("Reward {item} isn't in the item catalog."),
+            },
+            Reward::Score(amount) => {
+                score_events.send(ScoreEvent {
+                    amount: *amount,
+                    source: None,
+                });
+            }
+        }
+    }
+}
+
+/// Triggered once every objective of the level being played is done,
+/// before moving on to the next level or the ending.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct LevelCompleted;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Objective {
+    pub data: ObjectiveData,
+    pub progress: u32,
+}
+
+impl Objective {
+    pub fn is_complete(&self) -> bool {
+        self.progress >= self.data.goal.target()
+    }
+
+    /// A line of the HUD's checklist, like "[ ] Defeat 10 enemies (3/10)".
+    pub fn checklist_line(&self) -> String {
+        let mark = if self.is_complete() { "[x]" } else { "[ ]" };
+        let description = self.data.goal.description();
+        let target = self.data.goal.target();
+        if target > 1 {
+            format!(
+                "{mark} {description} ({}/{target})",
+                self.progress.min(target)
+            )
+        } else {
+            format!("{mark} {description}")
+        }
+    }
+}
+
+/// The objectives of the level being played, set when it spawns.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct Objectives {
+    pub list: Vec<Objective>,
+    pub next_level: Option<String>,
+}
+
+impl Objectives {
+    /// The objectives of `level`, continuing from `progress`, like that of a [`SaveGame`].
+    pub fn new(level: &LevelData, progress: &[u32]) -> Self {
+        let list = level
+            .objectives
+            .iter()
+            .enumerate()
+            .map(|(index, data)| Objective {
+                data: data.clone(),
+                progress: progress.get(index).copied().unwrap_or_default(),
+            })
+            .collect();
+        Self {
+            list,
+            next_level: level.next_level.clone(),
+        }
+    }
+
+    /// Count `event` towards every objective, returning the indices of those it completed.
+    pub fn record(&mut self, event: &ObjectiveProgress) -> Vec<usize> {
+        let mut completed = Vec::new();
+        for (index, objective) in self.list.iter_mut().enumerate() {
+            let amount = objective.data.goal.progress(event);
+            if amount == 0 || objective.is_complete() {
+                continue;
+            }
+            objective.progress += amount;
+            if objective.is_complete() {
+                completed.push(index);
+            }
+        }
+        completed
+    }
+
+    pub fn progress(&self) -> Vec<u32> {
+        self.list
+            .iter()
+            .map(|objective| objective.progress)
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.list.iter().all(Objective::is_complete)
+    }
+
+    /// Whether a place is left to reach, so the player's position is needed.
+    fn wants_position(&self) -> bool {
+        self.list.iter().any(|objective| {
+            matches!(objective.data.goal, Goal::Reach { .. }) && !objective.is_complete()
+        })
+    }
+}
+
+fn set_objectives(
+    _trigger: Trigger<SpawnLevel>,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<LevelData>>,
+    save: Res<SaveGame>,
+    mut objectives: ResMut<Objectives>,
+) {
+    *objectives = levels
+        .get(level_assets.level(&save.level))
+        .map(|level| Objectives::new(level, &save.objectives))
+        .unwrap_or_default();
+}
+
+fn track_position(
+    mut progress_events: EventWriter<ObjectiveProgress>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    for transform in &player_query {
+        progress_events.send(ObjectiveProgress::PlayerAt(transform.translation.xy()));
+    }
+}
+
+fn count_kills(
+    mut death_events: EventReader<DeathEvent>,
+    mut progress_events: EventWriter<ObjectiveProgress>,
+    enemy_query: Query<(), With<Enemy>>,
+) {
+    for event in death_events.read() {
+        if enemy_query.contains(event.entity) {
+            progress_events.send(ObjectiveProgress::EnemyKilled);
+        }
+    }
+}
+
+fn count_cycles(
+    mut cycle_events: EventReader<OnCycleAdvance>,
+    mut progress_events: EventWriter<ObjectiveProgress>,
+) {
+    for _ in cycle_events.read() {
+        progress_events.send(ObjectiveProgress::CycleSurvived);
+    }
+}
+
+fn apply_progress(
+    mut commands: Commands,
+    mut progress_events: EventReader<ObjectiveProgress>,
+    mut objectives: ResMut<Objectives>,
+    mut save: ResMut<SaveGame>,
+    catalog: Res<ItemCatalog>,
+    mut inventory: ResMut<Inventory>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    // Only marked as changed when progress was made, since positions are sent every tick.
+    let before = objectives.progress();
+    let mut completed = Vec::new();
+    for event in progress_events.read() {
+        completed.extend(objectives.bypass_change_detection().record(event));
+    }
+    let progress = objectives.progress();
+    if progress == before {
+        return;
+    }
+    objectives.set_changed();
+    save.objectives = progress;
+
+    for &index in &completed {
+        let objective = &objectives.list[index];
+        for reward in &objective.data.rewards {
+            reward.grant(&catalog, &mut inventory, &mut score_events);
+        }
+        commands.trigger(Announce::new(
+            Importance::Normal,
+            format!("Objective complete: {}", objective.data.goal.description()),
+        ));
+    }
+    if completed.is_empty() || !objectives.is_complete() {
+        return;
+    }
+    commands.trigger(Announce::new(Importance::Essential, "Level complete"));
+    commands.trigger(LevelCompleted);
+    match &objectives.next_level {
+        Some(next_level) => commands.trigger(EnterLevel(next_level.clone())),
+        None => commands.trigger(PlayCutscene(ENDING.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::assets::LEVELS;
+
+    #[test]
+    fn objectives_count_progress_and_lead_to_the_next_level() {
+        let items = ItemCatalog::load();
+        let levels = [
+            include_str!("../../assets/levels/main.level.ron"),
+            include_str!("../../assets/levels/grove.level.ron"),
+        ]
+        .map(|source| ron::from_str::<LevelData>(source).unwrap());
+        for level in &levels {
+            if let Some(next_level) = &level.next_level {
+                assert!(LEVELS.contains(&next_level.as_str()), "{next_level}");
+            }
+            for objective in &level.objectives {
+                assert!(objective.goal.target() > 0);
+                for reward in &objective.rewards {
+                    if let Reward::Item { item, .. } = reward {
+                        assert!(items.get(item).is_some(), "{item}");
+                    }
+                }
+            }
+        }
+
+        // Continues from saved progress, and only completes each objective once.
+        let mut objectives = Objectives::new(&levels[0], &[9]);
+        assert_eq!(objectives.next_level.as_deref(), Some("grove"));
+        assert_eq!(
+            objectives.list[0].checklist_line(),
+            "[ ] Defeat 10 enemies (9/10)"
+        );
+        assert_eq!(objectives.record(&ObjectiveProgress::EnemyKilled), vec![0]);
+        assert!(objectives
+            .record(&ObjectiveProgress::EnemyKilled)
+            .is_empty());
+        assert_eq!(
+            objectives.list[0].checklist_line(),
+            "[x] Defeat 10 enemies (10/10)"
+        );
+        assert_eq!(
+            objectives.record(&ObjectiveProgress::CycleSurvived),
+            vec![1]
+        );
+        assert!(!objectives.is_complete());
+        assert!(objectives
+            .record(&ObjectiveProgress::PlayerAt(Vec2::ZERO))
+            .is_empty());
+        assert_eq!(
+            objectives.record(&ObjectiveProgress::PlayerAt(Vec2::new(450.0, 200.0))),
+            vec![2]
+        );
+        assert!(objectives.is_complete());
+        assert_eq!(objectives.progress(), vec![10, 1, 1]);
+    }
+}
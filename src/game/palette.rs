@@ -0,0 +1,485 @@
+//! Palette swaps for sprites. A swapped sheet is drawn through an index texture, holding
+//! a color index per pixel, and a lookup table with one row of colors per palette,
+//! so the same sheet can be drawn in any number of palettes without copies of it.
+//! Both are built from the loaded sheet, whose own colors become the original palette,
+//! so no separate index sheet has to be painted.
+//!
+//! Register palettes in the [`PaletteLibrary`], then add a [`PaletteSwap`] to a sprite
+//! or UI image to draw it in one. Skins use this for the player, and enemy tiers or
+//! co-op team colors can register their own palettes the same way.
+//!
+//! Sprites drawn this way also show [`sprite_effects`](super::sprite_effects),
+//! which is why sprites with one of those are drawn this way too.
+
+use bevy::{
+    color::ColorToPacked,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            AsBindGroup, Extent3d, ShaderRef, ShaderType, TextureDimension, TextureFormat,
+        },
+    },
+    sprite::{Material2d, Material2dPlugin, Mesh2dHandle},
+    utils::HashMap,
+};
+use serde::Deserialize;
+
+use super::sprite_effects::{HitFlash, SpriteOutline};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((
+        Material2dPlugin::<PaletteMaterial>::default(),
+        UiMaterialPlugin::<PaletteUiMaterial>::default(),
+    ));
+    app.register_type::<PaletteSwap>();
+    app.init_resource::<PaletteLibrary>();
+    app.init_resource::<PaletteSheets>();
+    // After gameplay and animations have picked this frame's sprites.
+    app.add_systems(
+        PostUpdate,
+        (
+            prepare_palette_sheets,
+            swap_in_palette_materials,
+            update_palette_materials,
+        )
+            .chain(),
+    );
+}
+
+/// Draws a sprite or UI image in the palette with this id from the [`PaletteLibrary`],
+/// instead of in its own colors. Unknown ids draw the original colors.
+///
+/// The sheet has to be RGBA8 with at most 255 colors, or it is drawn as usual.
+/// Sprites keep their [`Sprite`], whose color still tints them and whose flips still apply.
+/// Sprites with only a sprite effect are drawn in their original colors.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct PaletteSwap(pub String);
+
+/// Color replacements, as pairs of hex colors from a sheet's original palette to new ones.
+/// Colors that aren't listed are kept.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct Palette(pub Vec<(String, String)>);
+
+impl Palette {
+    /// The colors of `original` with the replacements applied, keeping their alpha.
+    pub fn recolor(&self, original: &[[u8; 4]]) -> Vec<[u8; 4]> {
+        let swaps = self
+            .0
+            .iter()
+            .filter_map(|(from, to)| match (Srgba::hex(from), Srgba::hex(to)) {
+                (Ok(from), Ok(to)) => Some((from.to_u8_array(), to.to_u8_array())),
+                _ => {
+                    warn!("Invalid palette swap from {from} to {to}.");
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        original
+            .iter()
+            .map(|&color| {
+                swaps
+                    .iter()
+                    .find(|(from, _)| from[..3] == color[..3])
+                    .map_or(color, |(_, to)| [to[0], to[1], to[2], color[3]])
+            })
+            .collect()
+    }
+}
+
+/// Every palette a [`PaletteSwap`] can pick, by id.
+#[derive(Resource, Debug, Default)]
+pub struct PaletteLibrary(Vec<(String, Palette)>);
+
+impl PaletteLibrary {
+    /// Add a palette, or replace the one with the same id.
+    pub fn insert(&mut self, id: impl Into<String>, palette: Palette) {
+        let id = id.into();
+        match self.0.iter_mut().find(|(other, _)| *other == id) {
+            Some((_, existing)) => *existing = palette,
+            None => self.0.push((id, palette)),
+        }
+    }
+
+    /// Row of a palette in the lookup tables, where row 0 holds the original colors.
+    fn row(&self, id: &str) -> u32 {
+        self.0
+            .iter()
+            .position(|(other, _)| other == id)
+            .map_or(0, |i| i as u32 + 1)
+    }
+
+    /// The lookup table for a sheet with these original colors.
+    fn lookup_table(&self, colors: &[[u8; 4]]) -> Image {
+        let rows = std::iter::once(colors.to_vec())
+            .chain(self.0.iter().map(|(_, palette)| palette.recolor(colors)));
+        Image::new(
+            Extent3d {
+                width: colors.len() as u32,
+                height: self.0.len() as u32 + 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            rows.flatten().flatten().collect(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+    }
+}
+
+/// Split RGBA8 pixels into a color index per pixel, and the colors that were indexed.
+/// Index 0 is kept for fully transparent pixels. `None` if there are more than 255 colors.
+pub fn index_pixels(rgba: &[u8]) -> Option<(Vec<u8>, Vec<[u8; 4]>)> {
+    let mut colors = vec![[0; 4]];
+    let mut indices = Vec::with_capacity(rgba.len() / 4);
+    for pixel in rgba.chunks_exact(4) {
+        let color = [pixel[0], pixel[1], pixel[2], pixel[3]];
+        if color[3] == 0 {
+            indices.push(0);
+            continue;
+        }
+        let index = match colors.iter().position(|&other| other == color) {
+            Some(index) => index,
+            None => {
+                colors.push(color);
+                colors.len() - 1
+            }
+        };
+        indices.push(u8::try_from(index).ok()?);
+    }
+    Some((indices, colors))
+}
+
+/// Draws a sprite in a palette, as a mesh in place of the sprite.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct PaletteMaterial {
+    #[uniform(0)]
+    params: PaletteParams,
+    #[texture(1)]
+    indices: Handle<Image>,
+    #[texture(2)]
+    palettes: Handle<Image>,
+}
+
+impl Material2d for PaletteMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/palette_swap.wgsl".into()
+    }
+}
+
+/// Draws a UI image in a palette. The same as [`PaletteMaterial`], for UI nodes.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct PaletteUiMaterial {
+    #[uniform(0)]
+    params: PaletteParams,
+    #[texture(1)]
+    indices: Handle<Image>,
+    #[texture(2)]
+    palettes: Handle<Image>,
+}
+
+impl UiMaterial for PaletteUiMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/palette_swap_ui.wgsl".into()
+    }
+}
+
+/// Colors are in linear space, and positions in pixels of the index texture.
+#[derive(ShaderType, Debug, Clone, Copy, Default, PartialEq)]
+struct PaletteParams {
+    /// Corner and size of the drawn area, which is the frame and the margin around it.
+    /// A negative size flips it.
+    rect: Vec4,
+    /// Corners of the frame. Pixels outside of it are transparent.
+    frame: Vec4,
+    /// Multiplies the palette's colors.
+    tint: Vec4,
+    /// Mixed over the palette's colors by its alpha.
+    flash: Vec4,
+    /// Drawn around the silhouette, unless its alpha is 0.
+    outline: Vec4,
+    /// Row of the palette in the lookup table.
+    row: u32,
+}
+
+impl PaletteParams {
+    /// `margin` pixels are drawn around the frame on every side, to make room for outlines.
+    fn new(frame: Rect, margin: f32, flip: BVec2, tint: Color, row: u32) -> Self {
+        let area = frame.inflate(margin);
+        let mut corner = area.min;
+        let mut size = area.size();
+        if flip.x {
+            corner.x = area.max.x;
+            size.x = -size.x;
+        }
+        if flip.y {
+            corner.y = area.max.y;
+            size.y = -size.y;
+        }
+        Self {
+            rect: Vec4::new(corner.x, corner.y, size.x, size.y),
+            frame: Vec4::new(frame.min.x, frame.min.y, frame.max.x, frame.max.y),
+            tint: tint.to_linear().to_vec4(),
+            flash: Vec4::ZERO,
+            outline: Vec4::ZERO,
+            row,
+        }
+    }
+
+    fn with_effects(mut self, flash: Option<&HitFlash>, outline: Option<&SpriteOutline>) -> Self {
+        if let Some(flash) = flash {
+            self.flash = flash
+                .color
+                .to_linear()
+                .with_alpha(flash.strength())
+                .to_vec4();
+        }
+        if let Some(outline) = outline {
+            self.outline = outline.0.to_linear().to_vec4();
+        }
+        self
+    }
+}
+
+/// Pixels drawn around each sprite frame, so there is room for a [`SpriteOutline`].
+const SPRITE_MARGIN: f32 = 1.0;
+
+/// Sprites that are drawn with a [`PaletteMaterial`].
+type PaletteSprite = Or<(With<PaletteSwap>, With<HitFlash>, With<SpriteOutline>)>;
+
+/// A sprite sheet prepared for palette swaps.
+struct PaletteSheet {
+    indices: Handle<Image>,
+    /// The sheet's own colors, as in row 0 of the lookup table.
+    colors: Vec<[u8; 4]>,
+    lookup_table: Handle<Image>,
+    size: Vec2,
+}
+
+/// Prepared sheets by their source image, or `None` for sheets that can't be indexed.
+#[derive(Resource, Default)]
+struct PaletteSheets(HashMap<AssetId<Image>, Option<PaletteSheet>>);
+
+/// The sheet that a palette-swapped entity is drawn from, in place of its image.
+#[derive(Component)]
+struct PaletteSource(Handle<Image>);
+
+fn prepare_palette_sheets(
+    library: Res<PaletteLibrary>,
+    mut sheets: ResMut<PaletteSheets>,
+    mut images: ResMut<Assets<Image>>,
+    sprite_query: Query<&Handle<Image>, PaletteSprite>,
+    ui_query: Query<&UiImage, With<PaletteSwap>>,
+) {
+    let sources = sprite_query
+        .iter()
+        .chain(ui_query.iter().map(|image| &image.texture));
+    for source in sources {
+        if sheets.0.contains_key(&source.id()) {
+            continue;
+        }
+        let Some(image) = images.get(source) else {
+            continue;
+        };
+        let size = image.size();
+        let indexed = (image.texture_descriptor.format == TextureFormat::Rgba8UnormSrgb)
+            .then(|| index_pixels(&image.data))
+            .flatten();
+        let Some((indices, colors)) = indexed else {
+            warn!(
+                "Can't swap the palette of {:?}, which needs to be RGBA8 with at most 255 colors.",
+                source.path()
+            );
+            sheets.0.insert(source.id(), None);
+            continue;
+        };
+        let index_image = Image::new(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            indices,
+            TextureFormat::R8Unorm,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        let sheet = PaletteSheet {
+            indices: images.add(index_image),
+            lookup_table: images.add(library.lookup_table(&colors)),
+            colors,
+            size: size.as_vec2(),
+        };
+        sheets.0.insert(source.id(), Some(sheet));
+    }
+
+    if library.is_changed() {
+        // New tables rather than edited ones, so materials rebind to them.
+        for sheet in sheets.0.values_mut().flatten() {
+            sheet.lookup_table = images.add(library.lookup_table(&sheet.colors));
+        }
+    }
+}
+
+/// The part of a sheet that an entity shows, in pixels.
+fn frame_rect(
+    atlas: Option<&TextureAtlas>,
+    layouts: &Assets<TextureAtlasLayout>,
+    sheet_size: Vec2,
+) -> Rect {
+    atlas
+        .and_then(|atlas| atlas.texture_rect(layouts))
+        .map_or(Rect::from_corners(Vec2::ZERO, sheet_size), |rect| {
+            rect.as_rect()
+        })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn swap_in_palette_materials(
+    mut commands: Commands,
+    sheets: Res<PaletteSheets>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PaletteMaterial>>,
+    mut ui_materials: ResMut<Assets<PaletteUiMaterial>>,
+    sprite_query: Query<(Entity, &Handle<Image>, &Sprite, Option<&TextureAtlas>), PaletteSprite>,
+    ui_query: Query<(Entity, &UiImage), With<PaletteSwap>>,
+) {
+    // Removing the image stops the sprite or UI image from being drawn,
+    // while everything else about the entity stays as it was.
+    for (entity, source, sprite, atlas) in &sprite_query {
+        let Some(Some(sheet)) = sheets.0.get(&source.id()) else {
+            continue;
+        };
+        let frame_size = frame_rect(atlas, &layouts, sheet.size).size();
+        let size = sprite.custom_size.unwrap_or(frame_size);
+        // Grown by the margin, in the sprite's own units.
+        let size = size + 2.0 * SPRITE_MARGIN * size / frame_size;
+        commands.entity(entity).remove::<Handle<Image>>().insert((
+            PaletteSource(source.clone()),
+            Mesh2dHandle(meshes.add(Rectangle::from_size(size))),
+            materials.add(PaletteMaterial {
+                params: default(),
+                indices: sheet.indices.clone(),
+                palettes: sheet.lookup_table.clone(),
+            }),
+        ));
+    }
+    for (entity, image) in &ui_query {
+        let Some(Some(sheet)) = sheets.0.get(&image.texture.id()) else {
+            continue;
+        };
+        commands.entity(entity).remove::<UiImage>().insert((
+            PaletteSource(image.texture.clone()),
+            ui_materials.add(PaletteUiMaterial {
+                params: default(),
+                indices: sheet.indices.clone(),
+                palettes: sheet.lookup_table.clone(),
+            }),
+        ));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_palette_materials(
+    library: Res<PaletteLibrary>,
+    sheets: Res<PaletteSheets>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    mut materials: ResMut<Assets<PaletteMaterial>>,
+    mut ui_materials: ResMut<Assets<PaletteUiMaterial>>,
+    sprite_query: Query<(
+        &Handle<PaletteMaterial>,
+        &PaletteSource,
+        Option<&PaletteSwap>,
+        &Sprite,
+        Option<&TextureAtlas>,
+        Option<&HitFlash>,
+        Option<&SpriteOutline>,
+    )>,
+    ui_query: Query<(
+        &Handle<PaletteUiMaterial>,
+        &PaletteSource,
+        &PaletteSwap,
+        Option<&TextureAtlas>,
+    )>,
+) {
+    // Only stale materials are touched, since getting them mutably uploads them again.
+    for (handle, source, swap, sprite, atlas, flash, outline) in &sprite_query {
+        let Some(Some(sheet)) = sheets.0.get(&source.0.id()) else {
+            continue;
+        };
+        let params = PaletteParams::new(
+            frame_rect(atlas, &layouts, sheet.size),
+            SPRITE_MARGIN,
+            BVec2::new(sprite.flip_x, sprite.flip_y),
+            sprite.color,
+            swap.map_or(0, |swap| library.row(&swap.0)),
+        )
+        .with_effects(flash, outline);
+        let stale = materials.get(handle).is_some_and(|material| {
+            material.params != params || material.palettes != sheet.lookup_table
+        });
+        if !stale {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            material.params = params;
+            material.palettes = sheet.lookup_table.clone();
+        }
+    }
+    for (handle, source, swap, atlas) in &ui_query {
+        let Some(Some(sheet)) = sheets.0.get(&source.0.id()) else {
+            continue;
+        };
+        let params = PaletteParams::new(
+            frame_rect(atlas, &layouts, sheet.size),
+            0.0,
+            BVec2::FALSE,
+            Color::WHITE,
+            library.row(&swap.0),
+        );
+        let stale = ui_materials.get(handle).is_some_and(|material| {
+            material.params != params || material.palettes != sheet.lookup_table
+        });
+        if !stale {
+            continue;
+        }
+        if let Some(material) = ui_materials.get_mut(handle) {
+            material.params = params;
+            material.palettes = sheet.lookup_table.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_swaps_keep_indices_and_alpha() {
+        let pixels = [
+            [0, 0, 0, 0],
+            [0xfc, 0xc5, 0x52, 0xff],
+            [0xff, 0xff, 0xff, 0xff],
+            [0xfc, 0xc5, 0x52, 0xff],
+        ];
+        let (indices, colors) = index_pixels(&pixels.concat()).unwrap();
+        assert_eq!(indices, [0, 1, 2, 1]);
+        let palette = Palette(vec![("fcc552".to_string(), "#6dab5a".to_string())]);
+        assert_eq!(
+            palette.recolor(&colors),
+            [
+                [0, 0, 0, 0],
+                [0x6d, 0xab, 0x5a, 0xff],
+                [0xff, 0xff, 0xff, 0xff]
+            ]
+        );
+        // Index 0 is transparent, so only 255 colors fit.
+        let too_many = (0..=255u8)
+            .flat_map(|i| [i, 0, 0, 0xff])
+            .collect::<Vec<_>>();
+        assert!(index_pixels(&too_many).is_none());
+    }
+}
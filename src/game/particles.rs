@@ -0,0 +1,412 @@
+//! Particle effects, spawned as a [`ParticleEmitter`] with a [`ParticlePreset`].
+//!
+//! There are two backends. The CPU backend spawns a sprite per particle, which is simple
+//! and works everywhere but gets slow for dense effects. The GPU backend draws all of an
+//! emitter's particles as one mesh, moved by `shaders/particles.wgsl`, so dense effects
+//! like rain and embers cost a single draw call. Presets pick the backend they suit,
+//! and the particles display setting can force the CPU backend with fewer particles,
+//! which is the default on the web.
+//!
+//! Either way, particles are drawn on [`Layer::Fx`], freeze while the game is paused,
+//! and are despawned with their emitter.
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        render_resource::{AsBindGroup, ShaderRef, ShaderType},
+        view::NoFrustumCulling,
+    },
+    sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle},
+};
+use rand::Rng;
+
+use super::{mutators::Modifiers, rng::GameRng};
+use crate::{
+    display::ParticleSetting,
+    layers::{Layer, OnLayer},
+    screen::{PlayingState, Screen},
+    AppSet, GameSettings,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(Material2dPlugin::<GpuParticleMaterial>::default());
+    app.register_type::<(ParticleEmitter, Particle)>();
+    app.add_systems(
+        Update,
+        (
+            choose_particle_backends,
+            follow_emitters,
+            (emit_particles, simulate_particles, advance_gpu_particles)
+                .chain()
+                // Menus can't be paused, so their particles always move.
+                .run_if(in_state(PlayingState::Running).or_else(not(in_state(Screen::Playing)))),
+        )
+            .chain()
+            .in_set(AppSet::Update),
+    );
+}
+
+/// Which backend simulates and draws an emitter's particles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum ParticleBackend {
+    /// One sprite entity per particle.
+    Cpu,
+    /// One mesh for all particles, moved by a shader.
+    Gpu,
+}
+
+/// What an emitter's particles look like and how they move.
+/// Distances are in world units and times in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct ParticlePreset {
+    /// The backend to use, unless the particles setting asks for the CPU one.
+    pub backend: ParticleBackend,
+    /// How many particles are alive at once, when the emitter runs steadily.
+    pub count: u32,
+    pub lifetime: f32,
+    /// Size of the box around the emitter that particles start in.
+    pub area: Vec2,
+    pub velocity: Vec2,
+    /// How far each particle's starting velocity may be off, in each direction.
+    pub velocity_spread: Vec2,
+    pub gravity: Vec2,
+    pub size_start: Vec2,
+    pub size_end: Vec2,
+    pub color_start: Color,
+    pub color_end: Color,
+}
+
+impl ParticlePreset {
+    /// Thin streaks falling across an area wider than the screen.
+    pub const RAIN: Self = Self {
+        backend: ParticleBackend::Gpu,
+        count: 900,
+        lifetime: 0.8,
+        area: Vec2::new(2400.0, 1000.0),
+        velocity: Vec2::new(-120.0, -1100.0),
+        velocity_spread: Vec2::new(20.0, 150.0),
+        gravity: Vec2::ZERO,
+        size_start: Vec2::new(3.0, 28.0),
+        size_end: Vec2::new(3.0, 28.0),
+        color_start: Color::srgba(0.7, 0.8, 1.0, 0.5),
+        color_end: Color::srgba(0.7, 0.8, 1.0, 0.3),
+    };
+    /// Glowing sparks drifting up from a fire.
+    pub const EMBERS: Self = Self {
+        backend: ParticleBackend::Gpu,
+        count: 300,
+        lifetime: 2.5,
+        area: Vec2::new(200.0, 40.0),
+        velocity: Vec2::new(0.0, 120.0),
+        velocity_spread: Vec2::new(40.0, 40.0),
+        gravity: Vec2::new(10.0, 20.0),
+        size_start: Vec2::splat(10.0),
+        size_end: Vec2::splat(2.0),
+        color_start: Color::srgba(1.0, 0.7, 0.2, 1.0),
+        color_end: Color::srgba(1.0, 0.2, 0.05, 0.0),
+    };
+    /// The backend and particle count to use with the particles setting.
+    pub fn resolve(&self, setting: &ParticleSetting) -> (ParticleBackend, u32) {
+        if setting.allows_gpu() {
+            (self.backend, self.count)
+        } else {
+            let count = (self.count as f32 * setting.count_factor()).ceil() as u32;
+            (ParticleBackend::Cpu, count)
+        }
+    }
+}
+
+/// Emits particles around its entity for as long as it exists.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct ParticleEmitter {
+    pub preset: ParticlePreset,
+    /// The backend and count in use, once chosen.
+    resolved: Option<(ParticleBackend, u32)>,
+    /// Particles owed to the CPU backend, which only spawns whole ones.
+    pending: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(preset: ParticlePreset) -> Self {
+        Self {
+            preset,
+            resolved: None,
+            pending: 0.0,
+        }
+    }
+}
+
+/// A particle of the CPU backend.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Particle {
+    emitter: Entity,
+    preset: ParticlePreset,
+    velocity: Vec2,
+    age: f32,
+}
+
+/// The mesh drawing an emitter's particles with the GPU backend, kept on the emitter.
+#[derive(Component)]
+struct GpuParticles {
+    emitter: Entity,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct GpuParticleMaterial {
+    #[uniform(0)]
+    params: GpuParticleParams,
+}
+
+impl Material2d for GpuParticleMaterial {
+    fn vertex_shader() -> ShaderRef {
+        "shaders/particles.wgsl".into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        "shaders/particles.wgsl".into()
+    }
+}
+
+#[derive(ShaderType, Debug, Clone, Copy)]
+struct GpuParticleParams {
+    area: Vec2,
+    velocity: Vec2,
+    velocity_spread: Vec2,
+    gravity: Vec2,
+    size_start: Vec2,
+    size_end: Vec2,
+    color_start: Vec4,
+    color_end: Vec4,
+    lifetime: f32,
+    /// Seconds the particles have moved for, which stops while the game is paused.
+    time: f32,
+}
+
+impl From<&ParticlePreset> for GpuParticleParams {
+    fn from(preset: &ParticlePreset) -> Self {
+        Self {
+            area: preset.area,
+            velocity: preset.velocity,
+            velocity_spread: preset.velocity_spread,
+            gravity: preset.gravity,
+            size_start: preset.size_start,
+            size_end: preset.size_end,
+            color_start: preset.color_start.to_linear().to_vec4(),
+            color_end: preset.color_end.to_linear().to_vec4(),
+            lifetime: preset.lifetime.max(f32::EPSILON),
+            time: 0.0,
+        }
+    }
+}
+
+/// A mesh with a unit quad per particle, with the particle's seed from 0 to 1 in z.
+pub fn particle_mesh(count: u32) -> Mesh {
+    let corners = [[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]];
+    let uvs = [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+    let mut positions = Vec::with_capacity(count as usize * 4);
+    let mut mesh_uvs = Vec::with_capacity(count as usize * 4);
+    let mut indices = Vec::with_capacity(count as usize * 6);
+    for i in 0..count {
+        let seed = i as f32 / count as f32;
+        for ([x, y], uv) in corners.into_iter().zip(uvs) {
+            positions.push([x, y, seed]);
+            mesh_uvs.push(uv);
+        }
+        let first = i * 4;
+        indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, mesh_uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Sets up new emitters, and switches existing ones over when the particles setting changes.
+fn choose_particle_backends(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<GpuParticleMaterial>>,
+    mut emitter_query: Query<(Entity, &mut ParticleEmitter, &GlobalTransform)>,
+    gpu_query: Query<(Entity, &GpuParticles)>,
+) {
+    for (entity, mut emitter, transform) in &mut emitter_query {
+        let resolved = emitter.preset.resolve(&settings.display.particles);
+        if emitter.resolved == Some(resolved) {
+            continue;
+        }
+        emitter.resolved = Some(resolved);
+        for (gpu_entity, gpu_particles) in &gpu_query {
+            if gpu_particles.emitter == entity {
+                commands.entity(gpu_entity).despawn_recursive();
+            }
+        }
+        let (ParticleBackend::Gpu, count) = resolved else {
+            continue;
+        };
+        // Not a child of the emitter, so it can be on its own layer.
+        commands.spawn((
+            Name::new("GPU Particles"),
+            GpuParticles { emitter: entity },
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(particle_mesh(count))),
+                material: materials.add(GpuParticleMaterial {
+                    params: (&emitter.preset).into(),
+                }),
+                transform: Transform::from_translation(transform.translation()),
+                ..default()
+            },
+            // Particles move far outside the mesh's own bounds.
+            NoFrustumCulling,
+            OnLayer::new(Layer::Fx),
+        ));
+    }
+}
+
+/// Keep GPU particles on their emitter, and despawn the particles of emitters that are gone.
+fn follow_emitters(
+    mut commands: Commands,
+    emitter_query: Query<&GlobalTransform, With<ParticleEmitter>>,
+    mut gpu_query: Query<(Entity, &GpuParticles, &mut Transform)>,
+    particle_query: Query<(Entity, &Particle)>,
+) {
+    for (entity, gpu_particles, mut transform) in &mut gpu_query {
+        match emitter_query.get(gpu_particles.emitter) {
+            Ok(emitter_transform) => {
+                let position = emitter_transform.translation().truncate();
+                transform.translation = position.extend(transform.translation.z);
+            }
+            Err(_) => commands.entity(entity).despawn_recursive(),
+        }
+    }
+    for (entity, particle) in &particle_query {
+        if !emitter_query.contains(particle.emitter) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn emit_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut game_rng: ResMut<GameRng>,
+    mut emitter_query: Query<(Entity, &mut ParticleEmitter, &GlobalTransform)>,
+) {
+    let rng = game_rng.vfx();
+    for (entity, mut emitter, transform) in &mut emitter_query {
+        let Some((ParticleBackend::Cpu, count)) = emitter.resolved else {
+            continue;
+        };
+        let preset = emitter.preset;
+        // Spawning `count` per lifetime keeps about `count` alive.
+        emitter.pending += count as f32 / preset.lifetime.max(f32::EPSILON) * time.delta_seconds();
+        let center = transform.translation().truncate();
+        while emitter.pending >= 1.0 {
+            emitter.pending -= 1.0;
+            let offset = Vec2::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5));
+            let spread = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+            commands.spawn((
+                Name::new("Particle"),
+                Particle {
+                    emitter: entity,
+                    preset,
+                    velocity: preset.velocity + spread * preset.velocity_spread,
+                    age: 0.0,
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: preset.color_start,
+                        custom_size: Some(preset.size_start),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(
+                        (center + offset * preset.area).extend(0.0),
+                    ),
+                    ..default()
+                },
+                OnLayer::new(Layer::Fx),
+            ));
+        }
+    }
+}
+
+fn simulate_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    modifiers: Res<Modifiers>,
+    mut particle_query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in &mut particle_query {
+        particle.age += dt;
+        let progress = particle.age / particle.preset.lifetime;
+        if progress >= 1.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let gravity = particle.preset.gravity * modifiers.gravity;
+        particle.velocity += gravity * dt;
+        transform.translation += (particle.velocity * dt).extend(0.0);
+        sprite.color = particle
+            .preset
+            .color_start
+            .mix(&particle.preset.color_end, progress);
+        sprite.custom_size = Some(
+            particle
+                .preset
+                .size_start
+                .lerp(particle.preset.size_end, progress),
+        );
+    }
+}
+
+/// Move GPU particles along, with the same scaled gravity as the CPU ones.
+fn advance_gpu_particles(
+    time: Res<Time>,
+    modifiers: Res<Modifiers>,
+    mut materials: ResMut<Assets<GpuParticleMaterial>>,
+    emitter_query: Query<&ParticleEmitter>,
+    gpu_query: Query<(&GpuParticles, &Handle<GpuParticleMaterial>)>,
+) {
+    for (gpu_particles, material) in &gpu_query {
+        let (Ok(emitter), Some(material)) = (
+            emitter_query.get(gpu_particles.emitter),
+            materials.get_mut(material),
+        ) else {
+            continue;
+        };
+        material.params.time += time.delta_seconds();
+        material.params.gravity = emitter.preset.gravity * modifiers.gravity;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LevelSetting;
+
+    #[test]
+    fn particle_presets_fall_back_to_fewer_cpu_particles() {
+        let rain = ParticlePreset::RAIN;
+        let setting = ParticleSetting::from_max();
+        assert_eq!(rain.resolve(&setting), (ParticleBackend::Gpu, rain.count));
+        let setting = ParticleSetting::from_raw(1);
+        assert_eq!(rain.resolve(&setting), (ParticleBackend::Cpu, rain.count));
+        let setting = ParticleSetting::from_raw(0);
+        assert_eq!(
+            rain.resolve(&setting),
+            (ParticleBackend::Cpu, rain.count / 4)
+        );
+
+        let mesh = particle_mesh(3);
+        assert_eq!(mesh.count_vertices(), 12);
+        assert_eq!(mesh.indices().map(|indices| indices.len()), Some(18));
+    }
+}
@@ -0,0 +1,134 @@
+//! Full physics with avian, behind the `physics` cargo feature, for prototypes that need
+//! forces, joints or bodies pushing each other around.
+//!
+//! Each [`Collider`] from `game::collision` is mirrored into a physics collider, so entities
+//! are set up the same way either way. Entities without a [`RigidBody`] get one: kinematic if
+//! they have [`Movement`], static otherwise. Insert `RigidBody::Dynamic` yourself to let
+//! physics move an entity. Pickups and trigger zones become sensors, so nothing bumps into them.
+//!
+//! With this feature, contacts from the physics engine replace the simple overlap tests and
+//! are sent as the same [`CollisionEvent`]s, so the reactions in [`CollisionReactions`]
+//! (damage, pickups, trigger zones) work unchanged.
+//!
+//! Physics steps at the end of each fixed tick, after the gameplay in `FixedUpdate`, and its
+//! contacts are sent on the next tick. Avian keeps bodies and their `Transform`s in sync,
+//! so [`InterpolatedTransform`](super::interpolation::InterpolatedTransform) still smooths them.
+
+use avian2d::prelude::{
+    Collider as PhysicsCollider, CollisionLayers, Collisions, Gravity, LayerMask, PhysicsPlugins,
+    RigidBody, Sensor,
+};
+use bevy::{prelude::*, utils::HashSet};
+
+use super::{
+    collision::{Collider, CollisionLayer, CollisionReactions},
+    movement::Movement,
+};
+use crate::{events::CollisionEvent, screen::PlayingState, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(PhysicsPlugins::new(FixedPostUpdate));
+    // The game is top-down, so nothing falls.
+    app.insert_resource(Gravity(Vec2::ZERO));
+    app.add_systems(
+        Update,
+        (remove_physics_colliders, mirror_colliders)
+            .chain()
+            .in_set(AppSet::Update),
+    );
+    app.add_systems(
+        FixedUpdate,
+        send_collision_events
+            .in_set(AppSet::HandleEvents)
+            .before(CollisionReactions)
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+fn physics_collider(collider: &Collider) -> PhysicsCollider {
+    match *collider {
+        Collider::Aabb { half_size } => {
+            PhysicsCollider::rectangle(half_size.x * 2.0, half_size.y * 2.0)
+        }
+        Collider::Circle { radius } => PhysicsCollider::circle(radius),
+    }
+}
+
+fn mirror_colliders(
+    mut commands: Commands,
+    collider_query: Query<
+        (
+            Entity,
+            &Collider,
+            Option<&CollisionLayer>,
+            Has<RigidBody>,
+            Has<Movement>,
+        ),
+        Or<(Changed<Collider>, Changed<CollisionLayer>)>,
+    >,
+) {
+    for (entity, collider, layer, has_body, moves) in &collider_query {
+        let mut entity = commands.entity(entity);
+        entity.insert(physics_collider(collider));
+        if !has_body {
+            entity.insert(if moves {
+                RigidBody::Kinematic
+            } else {
+                RigidBody::Static
+            });
+        }
+        if let Some(layer) = layer {
+            // Avian only reports a contact if both sides' filters match the other, while
+            // `CollisionLayer` needs just one, so filtering is left to `send_collision_events`.
+            entity.insert(CollisionLayers::new(layer.member, LayerMask::ALL));
+            if layer.member & (CollisionLayer::PICKUP | CollisionLayer::TRIGGER) != 0 {
+                entity.insert(Sensor);
+            }
+        }
+    }
+}
+
+fn remove_physics_colliders(
+    mut commands: Commands,
+    mut removed_colliders: RemovedComponents<Collider>,
+) {
+    for entity in removed_colliders.read() {
+        if let Some(mut entity) = commands.get_entity(entity) {
+            entity.remove::<(PhysicsCollider, CollisionLayers, Sensor)>();
+        }
+    }
+}
+
+/// Whether a collision just started is tracked per tick here, like the simple collision
+/// detection does.
+fn send_collision_events(
+    collisions: Res<Collisions>,
+    mut touching: Local<HashSet<(Entity, Entity)>>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    layer_query: Query<&CollisionLayer>,
+) {
+    let mut overlapping = HashSet::new();
+    for contacts in collisions.iter() {
+        if !contacts.during_current_frame {
+            continue;
+        }
+        let (a, b) = if contacts.entity1 < contacts.entity2 {
+            (contacts.entity1, contacts.entity2)
+        } else {
+            (contacts.entity2, contacts.entity1)
+        };
+        let Ok([layer_a, layer_b]) = layer_query.get_many([a, b]) else {
+            continue;
+        };
+        if !layer_a.interacts_with(layer_b) {
+            continue;
+        }
+        overlapping.insert((a, b));
+        collision_events.send(CollisionEvent {
+            a,
+            b,
+            started: !touching.contains(&(a, b)),
+        });
+    }
+    *touching = overlapping;
+}
@@ -0,0 +1,212 @@
+//! The pixel perfect display mode. The world camera renders to a small canvas with one
+//! canvas pixel per art pixel, which the UI camera scales up to fill the window.
+//! With crisp upscaling, the canvas is scaled by a whole number and sampled sharply,
+//! leaving black bars around it; with smooth upscaling it fills as much of the window as
+//! it can. Sprites snap to whole canvas pixels, so they never sit between two.
+//!
+//! The canvas is shown on [`Layer::ScreenUi`], behind the UI, which is still drawn at
+//! full resolution.
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        texture::ImageSampler,
+    },
+    sprite::Mesh2dHandle,
+    transform::TransformSystem,
+    window::PrimaryWindow,
+};
+
+use super::camera::{UiCamera, WorldCamera};
+use crate::{layers::Layer, GameSettings};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            toggle_pixel_canvas.run_if(resource_changed::<GameSettings>),
+            fit_canvas.run_if(resource_exists::<PixelCanvas>),
+        )
+            .chain(),
+    );
+    app.add_systems(
+        PostUpdate,
+        snap_to_canvas_pixels
+            .after(TransformSystem::TransformPropagate)
+            .run_if(resource_exists::<PixelCanvas>),
+    );
+}
+
+/// World units per art pixel, since sprites are drawn 8 times their size.
+pub const WORLD_PIXEL: f32 = 8.0;
+
+/// Size of the canvas in art pixels, which shows 1280x720 world units at the default zoom.
+const CANVAS_SIZE: UVec2 = UVec2::new(160, 90);
+
+/// The canvas the world is rendered to, while the pixel perfect mode is on.
+#[derive(Resource, Debug)]
+pub struct PixelCanvas {
+    pub image: Handle<Image>,
+    /// Whether the canvas is sampled sharply, for crisp upscaling.
+    crisp: bool,
+}
+
+/// The sprite showing the canvas.
+#[derive(Component)]
+struct CanvasSprite;
+
+fn toggle_pixel_canvas(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    canvas: Option<Res<PixelCanvas>>,
+    mut images: ResMut<Assets<Image>>,
+    mut world_camera_query: Query<
+        (&mut Camera, &mut OrthographicProjection),
+        (With<WorldCamera>, Without<UiCamera>),
+    >,
+    mut ui_camera_query: Query<&mut Camera, (With<UiCamera>, Without<WorldCamera>)>,
+    sprite_query: Query<Entity, With<CanvasSprite>>,
+) {
+    let display = &settings.display;
+    let crisp = display.upscaling.is_crisp();
+    let sampler = if crisp {
+        ImageSampler::nearest()
+    } else {
+        ImageSampler::linear()
+    };
+    let (Ok((mut camera, mut projection)), Ok(mut ui_camera)) = (
+        world_camera_query.get_single_mut(),
+        ui_camera_query.get_single_mut(),
+    ) else {
+        return;
+    };
+
+    match (display.pixel_perfect.is_on(), canvas) {
+        (true, None) => {
+            let image = images.add(canvas_image(sampler));
+            camera.target = RenderTarget::Image(image.clone());
+            projection.scaling_mode = ScalingMode::WindowSize(1.0 / WORLD_PIXEL);
+            // The world camera no longer draws to the window, so this clears it instead,
+            // leaving the bars around the canvas black.
+            ui_camera.clear_color = ClearColorConfig::Custom(Color::BLACK);
+            commands.spawn((
+                Name::new("Pixel Canvas"),
+                CanvasSprite,
+                SpriteBundle {
+                    texture: image.clone(),
+                    ..default()
+                },
+                Layer::ScreenUi.render_layers(),
+            ));
+            commands.insert_resource(PixelCanvas { image, crisp });
+        }
+        // Only touch the image when the upscaling changed, since that re-uploads it.
+        (true, Some(canvas)) if canvas.crisp != crisp => {
+            if let Some(image) = images.get_mut(&canvas.image) {
+                image.sampler = sampler;
+            }
+            commands.insert_resource(PixelCanvas {
+                image: canvas.image.clone(),
+                crisp,
+            });
+        }
+        (false, Some(canvas)) => {
+            camera.target = RenderTarget::Window(default());
+            projection.scaling_mode = ScalingMode::WindowSize(1.0);
+            ui_camera.clear_color = ClearColorConfig::None;
+            for entity in &sprite_query {
+                commands.entity(entity).despawn_recursive();
+            }
+            images.remove(&canvas.image);
+            commands.remove_resource::<PixelCanvas>();
+        }
+        _ => (),
+    }
+}
+
+fn canvas_image(sampler: ImageSampler) -> Image {
+    let size = Extent3d {
+        width: CANVAS_SIZE.x,
+        height: CANVAS_SIZE.y,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Bgra8UnormSrgb,
+        default(),
+    );
+    image.texture_descriptor.label = Some("pixel canvas");
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image.sampler = sampler;
+    image
+}
+
+/// How many window pixels each canvas pixel covers.
+pub fn canvas_scale(window_size: Vec2, crisp: bool) -> f32 {
+    let fit = (window_size / CANVAS_SIZE.as_vec2()).min_element();
+    if crisp {
+        fit.floor().max(1.0)
+    } else {
+        fit
+    }
+}
+
+fn fit_canvas(
+    settings: Res<GameSettings>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut canvas_query: Query<&mut Transform, With<CanvasSprite>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    // Whole physical pixels, since those are what have to line up.
+    let scale = canvas_scale(
+        window.physical_size().as_vec2(),
+        settings.display.upscaling.is_crisp(),
+    ) / window.scale_factor();
+    for mut transform in &mut canvas_query {
+        if transform.scale.x != scale {
+            transform.scale = Vec3::new(scale, scale, 1.0);
+        }
+    }
+}
+
+fn snap_to_canvas_pixels(
+    mut sprite_query: Query<
+        &mut GlobalTransform,
+        (
+            Or<(With<Sprite>, With<Mesh2dHandle>)>,
+            Without<CanvasSprite>,
+        ),
+    >,
+) {
+    for mut global_transform in &mut sprite_query {
+        let mut affine = global_transform.affine();
+        let position = affine.translation.truncate();
+        let snapped = (position / WORLD_PIXEL).round() * WORLD_PIXEL;
+        if snapped != position {
+            affine.translation = snapped.extend(affine.translation.z).into();
+            *global_transform = affine.into();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crisp_canvas_scales_by_whole_numbers() {
+        let window = Vec2::new(1366.0, 768.0);
+        assert_eq!(canvas_scale(window, true), 8.0);
+        assert!(canvas_scale(window, false) > 8.5);
+        assert_eq!(canvas_scale(Vec2::new(1920.0, 1080.0), true), 12.0);
+        // Tiny windows still show the whole canvas, cropped.
+        assert_eq!(canvas_scale(Vec2::new(100.0, 50.0), true), 1.0);
+    }
+}
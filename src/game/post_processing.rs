@@ -0,0 +1,362 @@
+//! Optional effects over the world, each behind its own setting in
+//! [`EffectSettings`](crate::display::EffectSettings):
+//!
+//! - Bloom, so bright colors glow.
+//! - A red vignette that closes in as the player's health runs low, over the world like
+//!   the rewind vignette, reusing its [`VignetteMaterial`].
+//! - [`ChromaticAberration`] for a moment when the player is hit, as a render graph node
+//!   that runs after tonemapping on the [`WorldCamera`], leaving the UI camera alone.
+
+use bevy::{
+    core_pipeline::{
+        bloom::{BloomPrefilterSettings, BloomSettings},
+        core_2d::graph::{Core2d, Node2d},
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    },
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+            UniformComponentPlugin,
+        },
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::BevyDefault,
+        view::ViewTarget,
+        RenderApp,
+    },
+    ui::Val::*,
+};
+
+use super::{camera::WorldCamera, health::Health, rewind::VignetteMaterial, spawn::player::Player};
+use crate::{events::DamageTaken, layers::Layer, screen::Screen, AppSet, GameSettings};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(ChromaticAberrationPlugin);
+    app.register_type::<LowHealthVignette>();
+    app.add_systems(
+        Update,
+        apply_effect_settings.run_if(resource_changed::<GameSettings>),
+    );
+    app.add_systems(OnEnter(Screen::Playing), spawn_low_health_vignette);
+    app.add_systems(
+        Update,
+        (
+            (aberrate_on_hits, fade_aberration).chain(),
+            fade_low_health_vignette.run_if(in_state(Screen::Playing)),
+        )
+            .in_set(AppSet::HandleEvents),
+    );
+}
+
+/// Bloom for a world without HDR, so only colors close to white glow.
+const BLOOM: BloomSettings = BloomSettings {
+    prefilter_settings: BloomPrefilterSettings {
+        threshold: 0.6,
+        threshold_softness: 0.2,
+    },
+    ..BloomSettings::OLD_SCHOOL
+};
+
+fn apply_effect_settings(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    camera_query: Query<(Entity, Has<BloomSettings>, Has<ChromaticAberration>), With<WorldCamera>>,
+) {
+    let effects = &settings.display.effects;
+    for (camera, has_bloom, has_aberration) in &camera_query {
+        let mut camera = commands.entity(camera);
+        match (effects.bloom.is_on(), has_bloom) {
+            (true, false) => {
+                camera.insert(BLOOM);
+            }
+            (false, true) => {
+                camera.remove::<BloomSettings>();
+            }
+            _ => (),
+        }
+        match (effects.hit_aberration.is_on(), has_aberration) {
+            (true, false) => {
+                camera.insert(ChromaticAberration::default());
+            }
+            (false, true) => {
+                camera.remove::<ChromaticAberration>();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// How far the colors split on a hit, as a fraction of the screen's size.
+const HIT_ABERRATION: f32 = 0.012;
+/// How fast the split closes again, in intensity per second.
+const ABERRATION_FADE_SPEED: f32 = 0.05;
+
+fn aberrate_on_hits(
+    mut taken_events: EventReader<DamageTaken>,
+    player_query: Query<(), With<Player>>,
+    mut camera_query: Query<&mut ChromaticAberration>,
+) {
+    // Damage over time would keep the screen split for as long as it lasts.
+    let hit = taken_events
+        .read()
+        .any(|event| !event.from_status && player_query.contains(event.target));
+    if !hit {
+        return;
+    }
+    for mut aberration in &mut camera_query {
+        aberration.intensity = HIT_ABERRATION;
+    }
+}
+
+fn fade_aberration(time: Res<Time>, mut camera_query: Query<&mut ChromaticAberration>) {
+    for mut aberration in &mut camera_query {
+        if aberration.intensity > 0.0 {
+            aberration.intensity =
+                (aberration.intensity - ABERRATION_FADE_SPEED * time.delta_seconds()).max(0.0);
+        }
+    }
+}
+
+/// The vignette shown while the player's health is low.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+pub struct LowHealthVignette {
+    /// How strong the vignette is, from 0 to 1.
+    pub strength: f32,
+}
+
+/// Fraction of the player's health below which the vignette shows, getting stronger
+/// the closer the player gets to dying.
+const LOW_HEALTH: f32 = 0.35;
+/// How fast the vignette follows the player's health, in strength per second.
+const LOW_HEALTH_FADE_SPEED: f32 = 2.0;
+const LOW_HEALTH_COLOR: Color = Color::srgba(0.5, 0.0, 0.0, 0.8);
+
+fn spawn_low_health_vignette(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<VignetteMaterial>>,
+) {
+    commands.spawn((
+        Name::new("Low Health Vignette"),
+        LowHealthVignette::default(),
+        MaterialNodeBundle {
+            style: Style {
+                width: Percent(100.0),
+                height: Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            material: materials.add(VignetteMaterial::new(LOW_HEALTH_COLOR)),
+            // Under the HUD, which stays readable.
+            z_index: Layer::WorldUi.z_index(0),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn fade_low_health_vignette(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    mut materials: ResMut<Assets<VignetteMaterial>>,
+    player_query: Query<&Health, With<Player>>,
+    mut vignette_query: Query<(&mut LowHealthVignette, &Handle<VignetteMaterial>)>,
+) {
+    let target = match player_query.get_single() {
+        Ok(health) if settings.display.effects.low_health_vignette.is_on() => {
+            ((LOW_HEALTH - health.fraction()) / LOW_HEALTH).clamp(0.0, 1.0)
+        }
+        _ => 0.0,
+    };
+    let step = LOW_HEALTH_FADE_SPEED * time.delta_seconds();
+    for (mut vignette, handle) in &mut vignette_query {
+        if vignette.strength == target {
+            continue;
+        }
+        vignette.strength = if vignette.strength < target {
+            (vignette.strength + step).min(target)
+        } else {
+            (vignette.strength - step).max(target)
+        };
+        if let Some(material) = materials.get_mut(handle) {
+            material.set_strength(vignette.strength);
+        }
+    }
+}
+
+/// Splits the red and blue channels of the picture of the camera it is on apart, by
+/// `intensity` of the screen's size at its edges. Nothing is drawn while it is 0.
+#[derive(Component, Debug, Clone, Copy, Default, ExtractComponent, ShaderType)]
+pub struct ChromaticAberration {
+    pub intensity: f32,
+    /// Uniforms have to be 16 byte aligned on WebGL2.
+    _padding: Vec3,
+}
+
+/// A plugin instead of a function, since the pipeline can only be made once the render
+/// device exists, in [`Plugin::finish`].
+struct ChromaticAberrationPlugin;
+
+impl Plugin for ChromaticAberrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ExtractComponentPlugin::<ChromaticAberration>::default(),
+            UniformComponentPlugin::<ChromaticAberration>::default(),
+        ));
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<ChromaticAberrationNode>>(
+                Core2d,
+                ChromaticAberrationLabel,
+            )
+            .add_render_graph_edges(
+                Core2d,
+                (
+                    Node2d::Tonemapping,
+                    ChromaticAberrationLabel,
+                    Node2d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<ChromaticAberrationPipeline>();
+    }
+}
+
+#[derive(RenderLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct ChromaticAberrationLabel;
+
+#[derive(Default)]
+struct ChromaticAberrationNode;
+
+impl ViewNode for ChromaticAberrationNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ChromaticAberration,
+        &'static DynamicUniformIndex<ChromaticAberration>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, aberration, uniform_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        if aberration.intensity <= 0.0 {
+            return Ok(());
+        }
+        let aberration_pipeline = world.resource::<ChromaticAberrationPipeline>();
+        let Some(pipeline) = world
+            .resource::<PipelineCache>()
+            .get_render_pipeline(aberration_pipeline.pipeline_id)
+        else {
+            return Ok(());
+        };
+        let Some(uniforms) = world
+            .resource::<ComponentUniforms<ChromaticAberration>>()
+            .uniforms()
+            .binding()
+        else {
+            return Ok(());
+        };
+
+        // Reads the picture so far, and writes to the other main texture, which then
+        // becomes the picture. The bind group has to be made here, since which of the two
+        // textures is which changes with every write.
+        let post_process = view_target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "chromatic_aberration_bind_group",
+            &aberration_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &aberration_pipeline.sampler,
+                uniforms,
+            )),
+        );
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("chromatic_aberration_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[uniform_index.index()]);
+        // One triangle covering the screen.
+        render_pass.draw(0..3, 0..1);
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct ChromaticAberrationPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for ChromaticAberrationPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(
+            "chromatic_aberration_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<ChromaticAberration>(true),
+                ),
+            ),
+        );
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+        let shader = world.load_asset("shaders/chromatic_aberration.wgsl");
+        let pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("chromatic_aberration_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader,
+                        shader_defs: vec![],
+                        entry_point: "fragment".into(),
+                        // The world camera isn't HDR, so its main textures have the default format.
+                        targets: vec![Some(ColorTargetState {
+                            format: TextureFormat::bevy_default(),
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                });
+        Self {
+            layout,
+            sampler,
+            pipeline_id,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! The player's profile, set on the profile screen and persisted between sessions.
+//! Its name is shown while playing and written next to high scores,
+//! and it holds what the quick-action wheel does, see [`QuickActions`],
+//! which tutorial [`Lesson`]s were learned, and the player's skins, see [`Cosmetics`].
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use super::{cosmetics::Cosmetics, quick_actions::QuickActions, tutorial::Lesson};
+use crate::storage;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(storage::load::<Profile>(PROFILE_KEY).unwrap_or_default());
+    app.add_systems(Update, save_profile.run_if(resource_changed::<Profile>));
+}
+
+/// Key under which the [`Profile`] is persisted.
+const PROFILE_KEY: &str = "profile";
+
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub quick_actions: QuickActions,
+    /// Lessons the player doesn't need to be prompted for again.
+    pub lessons: HashSet<Lesson>,
+    pub cosmetics: Cosmetics,
+}
+
+impl Profile {
+    /// The longest name that can be entered.
+    pub const MAX_NAME_CHARS: usize = 16;
+    const DEFAULT_NAME: &'static str = "Ducky";
+
+    /// Set the name, falling back to the default one for blank names.
+    pub fn set_name(&mut self, name: &str) {
+        let name = name.trim();
+        self.name = if name.is_empty() {
+            Self::DEFAULT_NAME.to_string()
+        } else {
+            name.chars().take(Self::MAX_NAME_CHARS).collect()
+        };
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: Self::DEFAULT_NAME.to_string(),
+            quick_actions: default(),
+            lessons: default(),
+            cosmetics: default(),
+        }
+    }
+}
+
+fn save_profile(profile: Res<Profile>) {
+    storage::save(PROFILE_KEY, &*profile);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_profile_names_fall_back_to_the_default() {
+        let mut profile = Profile::default();
+        profile.set_name("  Quackers  ");
+        assert_eq!(profile.name, "Quackers");
+        profile.set_name("   ");
+        assert_eq!(profile.name, Profile::default().name);
+    }
+
+    #[test]
+    fn learned_lessons_persist_in_the_profile() {
+        for lesson in Lesson::ALL {
+            assert!(!lesson.actions().is_empty());
+        }
+        let profile = Profile {
+            lessons: [Lesson::Move, Lesson::Rewind].into_iter().collect(),
+            ..default()
+        };
+        let ron = ron::to_string(&profile).unwrap();
+        assert_eq!(ron::from_str::<Profile>(&ron).unwrap(), profile);
+        // Profiles saved before the tutorial existed haven't learned anything.
+        let old: Profile = ron::from_str(r#"(name: "Quackers")"#).unwrap();
+        assert!(old.lessons.is_empty());
+        assert_eq!(old.cosmetics, Cosmetics::default());
+    }
+}
@@ -0,0 +1,336 @@
+//! The quick-action wheel. Holding [`Action::QuickWheel`] opens a [`RadialMenu`] of the
+//! [`QuickActions`] in the player's [`Profile`], and releasing it uses the slot that
+//! the movement input or the mouse points at. The player doesn't move while it is open.
+//!
+//! Slots hold abilities, which press their [`Action`] as if its key was pressed, or
+//! emotes, which float up from the player. Players assign them on the profile screen.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    input::{Action, ActionInput, QueuedActions},
+    movement::MovementController,
+    profile::Profile,
+    spawn::player::Player,
+};
+use crate::{
+    layers::{Layer, OnLayer},
+    screen::{PlayingState, Screen},
+    ui::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(QuickWheel, QuickWheelMenu, EmoteBubble)>();
+    app.init_resource::<QuickWheel>();
+    app.add_systems(OnExit(PlayingState::Running), close_quick_wheel);
+    app.add_systems(
+        Update,
+        (
+            (open_quick_wheel, aim_quick_wheel, use_quick_wheel)
+                .chain()
+                .in_set(AppSet::RecordInput),
+            float_emote_bubbles.in_set(AppSet::Update),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// How many slots the wheel has, one per direction of a keyboard or D-pad.
+pub const QUICK_SLOTS: usize = 8;
+
+/// Something a slot of the wheel does.
+#[derive(Serialize, Deserialize, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum QuickAction {
+    #[default]
+    Empty,
+    /// Press an action, like its key would.
+    Ability(Action),
+    Emote(Emote),
+}
+
+impl QuickAction {
+    /// Actions that make sense to use from the wheel: ones that happen on a press,
+    /// instead of lasting while held.
+    const ABILITIES: [Action; 1] = [Action::AdvanceCycle];
+
+    /// Everything that can be put in a slot, in the order the profile screen cycles through.
+    pub fn choices() -> Vec<QuickAction> {
+        std::iter::once(QuickAction::Empty)
+            .chain(QuickAction::ABILITIES.map(QuickAction::Ability))
+            .chain(Emote::ALL.map(QuickAction::Emote))
+            .collect()
+    }
+
+    /// The choice after this one, wrapping around.
+    pub fn next(self) -> Self {
+        let choices = Self::choices();
+        let index = choices.iter().position(|&choice| choice == self);
+        choices[index.map_or(0, |index| (index + 1) % choices.len())]
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            QuickAction::Empty => "Empty".to_string(),
+            QuickAction::Ability(action) => action.name().to_string(),
+            QuickAction::Emote(emote) => emote.name().to_string(),
+        }
+    }
+}
+
+/// Expressions for the player to show.
+#[derive(Serialize, Deserialize, Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Emote {
+    Wave,
+    Cheer,
+    Laugh,
+    Shrug,
+    Love,
+}
+
+impl Emote {
+    pub const ALL: [Emote; 5] = [
+        Emote::Wave,
+        Emote::Cheer,
+        Emote::Laugh,
+        Emote::Shrug,
+        Emote::Love,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Emote::Wave => "Wave",
+            Emote::Cheer => "Cheer",
+            Emote::Laugh => "Laugh",
+            Emote::Shrug => "Shrug",
+            Emote::Love => "Love",
+        }
+    }
+
+    /// What floats up from the player, in characters the default font has.
+    fn bubble_text(self) -> &'static str {
+        match self {
+            Emote::Wave => "o/",
+            Emote::Cheer => "\\o/",
+            Emote::Laugh => "haha!",
+            Emote::Shrug => "?_?",
+            Emote::Love => "<3",
+        }
+    }
+}
+
+/// The [`QuickAction`] in each slot of the wheel, clockwise from the top.
+/// Part of the [`Profile`].
+#[derive(Serialize, Deserialize, Reflect, Debug, Clone, PartialEq, Eq)]
+pub struct QuickActions(pub [QuickAction; QUICK_SLOTS]);
+
+impl Default for QuickActions {
+    fn default() -> Self {
+        Self([
+            QuickAction::Emote(Emote::Wave),
+            QuickAction::Emote(Emote::Cheer),
+            QuickAction::Ability(Action::AdvanceCycle),
+            QuickAction::Emote(Emote::Laugh),
+            QuickAction::Emote(Emote::Shrug),
+            QuickAction::Emote(Emote::Love),
+            QuickAction::Empty,
+            QuickAction::Empty,
+        ])
+    }
+}
+
+/// Whether the wheel is open.
+#[derive(Resource, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[reflect(Resource)]
+pub struct QuickWheel {
+    pub open: bool,
+}
+
+/// A run condition for systems that shouldn't run while the wheel is open.
+pub fn is_quick_wheel_open(wheel: Res<QuickWheel>) -> bool {
+    wheel.open
+}
+
+/// The wheel's [`RadialMenu`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+struct QuickWheelMenu;
+
+const WHEEL_RADIUS: f32 = 200.0;
+
+fn open_quick_wheel(
+    mut commands: Commands,
+    actions: ActionInput,
+    profile: Res<Profile>,
+    mut wheel: ResMut<QuickWheel>,
+    mut controller_query: Query<&mut MovementController, With<Player>>,
+) {
+    if wheel.open || !actions.pressed(Action::QuickWheel) {
+        return;
+    }
+    wheel.open = true;
+    // Movement input now aims the wheel, so the player shouldn't keep going.
+    for mut controller in &mut controller_query {
+        controller.0 = Vec2::ZERO;
+    }
+    let labels: Vec<_> = profile
+        .quick_actions
+        .0
+        .iter()
+        .map(|action| action.name())
+        .collect();
+    commands
+        .ui_root()
+        .insert((
+            Name::new("Quick Wheel"),
+            StateScoped(PlayingState::Running),
+            Layer::ScreenUi.z_index(0),
+        ))
+        .with_children(|children| {
+            children
+                .radial_menu(&labels, WHEEL_RADIUS)
+                .insert(QuickWheelMenu);
+        });
+}
+
+fn aim_quick_wheel(
+    actions: ActionInput,
+    wheel: Res<QuickWheel>,
+    mut menu_query: Query<&mut RadialMenu, With<QuickWheelMenu>>,
+) {
+    if !wheel.open {
+        return;
+    }
+    let Some(slot) = radial_slot(actions.movement(), QUICK_SLOTS) else {
+        return;
+    };
+    for mut menu in &mut menu_query {
+        if menu.selected != Some(slot) {
+            menu.selected = Some(slot);
+        }
+    }
+}
+
+fn use_quick_wheel(
+    mut commands: Commands,
+    actions: ActionInput,
+    profile: Res<Profile>,
+    mut wheel: ResMut<QuickWheel>,
+    mut queued: ResMut<QueuedActions>,
+    menu_query: Query<(&RadialMenu, &Parent), With<QuickWheelMenu>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+) {
+    if !wheel.open || actions.pressed(Action::QuickWheel) {
+        return;
+    }
+    wheel.open = false;
+    for (menu, parent) in &menu_query {
+        commands.entity(parent.get()).despawn_recursive();
+        let Some(slot) = menu.selected else {
+            continue;
+        };
+        match profile.quick_actions.0[slot] {
+            QuickAction::Empty => (),
+            QuickAction::Ability(action) => queued.press(action),
+            QuickAction::Emote(emote) => {
+                for transform in &player_query {
+                    spawn_emote_bubble(&mut commands, emote, transform.translation().xy());
+                }
+            }
+        }
+    }
+}
+
+fn close_quick_wheel(mut wheel: ResMut<QuickWheel>) {
+    // The wheel itself is despawned with the state.
+    wheel.open = false;
+}
+
+/// An emote floating up from where it was used, fading out.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+pub struct EmoteBubble {
+    age: f32,
+}
+
+/// Seconds an emote stays up.
+const EMOTE_LIFETIME: f32 = 2.0;
+/// How fast emotes float up, in world units per second.
+const EMOTE_RISE_SPEED: f32 = 40.0;
+/// How far above the player's center emotes start.
+const EMOTE_OFFSET: f32 = 112.0;
+const EMOTE_FONT_SIZE: f32 = 40.0;
+
+fn spawn_emote_bubble(commands: &mut Commands, emote: Emote, position: Vec2) {
+    commands.spawn((
+        Name::new("Emote Bubble"),
+        EmoteBubble::default(),
+        Text2dBundle {
+            text: Text::from_section(
+                emote.bubble_text(),
+                TextStyle {
+                    font_size: EMOTE_FONT_SIZE,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation((position + Vec2::Y * EMOTE_OFFSET).extend(0.0)),
+            ..default()
+        },
+        OnLayer::new(Layer::WorldUi),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn float_emote_bubbles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bubble_query: Query<(Entity, &mut EmoteBubble, &mut Transform, &mut Text)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut bubble, mut transform, mut text) in &mut bubble_query {
+        bubble.age += dt;
+        if bubble.age >= EMOTE_LIFETIME {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.translation.y += EMOTE_RISE_SPEED * dt;
+        // Fade out over the second half.
+        let alpha = (2.0 * (1.0 - bubble.age / EMOTE_LIFETIME)).min(1.0);
+        for section in &mut text.sections {
+            section.style.color = Color::WHITE.with_alpha(alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::radial_menu::radial_slot;
+
+    #[test]
+    fn quick_wheel_slots_follow_directions() {
+        assert_eq!(radial_slot(Vec2::ZERO, 8), None);
+        assert_eq!(radial_slot(Vec2::Y, 8), Some(0));
+        assert_eq!(radial_slot(Vec2::new(1.0, 1.0), 8), Some(1));
+        assert_eq!(radial_slot(Vec2::X, 8), Some(2));
+        assert_eq!(radial_slot(-Vec2::Y, 8), Some(4));
+        assert_eq!(radial_slot(Vec2::new(-1.0, 0.9), 8), Some(7));
+
+        // Cycling through the choices comes back around.
+        let choices = QuickAction::choices();
+        let mut action = QuickAction::Empty;
+        for _ in 0..choices.len() {
+            action = action.next();
+        }
+        assert_eq!(action, QuickAction::Empty);
+
+        let profile = Profile {
+            quick_actions: QuickActions([QuickAction::Empty; 8]),
+            ..default()
+        };
+        let ron = ron::to_string(&profile).unwrap();
+        assert_eq!(ron::from_str::<Profile>(&ron).unwrap(), profile);
+    }
+}
@@ -0,0 +1,329 @@
+//! Rewinding time. Entities tagged [`Rewindable`] record a [`Snapshot`] of where they
+//! are, how fast they move and their health every fixed tick, keeping the last
+//! [`HISTORY_SECONDS`]. Holding [`Action::Rewind`] plays those back, newest first,
+//! one per tick, with a vignette over the screen.
+//!
+//! While rewinding, movement input isn't recorded and [`Movement`] isn't applied,
+//! so the snapshots are the only thing moving rewindable entities.
+//! Systems that should also pause can use [`is_rewinding`].
+
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef, ShaderType},
+    ui::Val::*,
+};
+
+use super::{
+    health::Health,
+    input::{Action, ActionInput},
+    movement::{Movement, MovementController},
+};
+use crate::{
+    layers::Layer,
+    screen::{PlayingState, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(UiMaterialPlugin::<VignetteMaterial>::default());
+    app.register_type::<(Rewindable, RewindHistory, RewindVignette, Rewind)>();
+    app.init_resource::<Rewind>();
+    app.observe(add_rewind_history);
+    app.add_systems(OnEnter(Screen::Playing), spawn_vignette);
+    app.add_systems(OnExit(Screen::Playing), stop_rewinding);
+    app.add_systems(
+        Update,
+        (
+            hold_to_rewind.in_set(AppSet::RecordInput),
+            fade_vignette.in_set(AppSet::Update),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+    app.add_systems(
+        FixedUpdate,
+        play_back_snapshots
+            .in_set(AppSet::Update)
+            .run_if(in_state(PlayingState::Running).and_then(is_rewinding)),
+    );
+    // After everything that moves entities during the tick.
+    app.add_systems(
+        FixedPostUpdate,
+        record_snapshots.run_if(in_state(PlayingState::Running).and_then(not(is_rewinding))),
+    );
+}
+
+/// Seconds of history that can be rewound.
+pub const HISTORY_SECONDS: f32 = 5.0;
+
+/// Records this entity's [`Snapshot`]s so it can be rewound.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct Rewindable;
+
+/// The state of a rewindable entity at the end of a fixed tick.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub struct Snapshot {
+    pub position: Vec2,
+    /// In pixels per second, which faces the entity the way it was moving.
+    pub velocity: Vec2,
+    pub health: Option<f32>,
+}
+
+/// The latest [`Snapshot`]s of a [`Rewindable`] entity, which is added with it.
+/// A ring buffer that drops the oldest snapshot once it is full.
+#[derive(Component, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct RewindHistory {
+    snapshots: VecDeque<Snapshot>,
+    capacity: usize,
+}
+
+impl RewindHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Record the latest snapshot, forgetting the oldest if there is no room.
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// Take back the latest snapshot.
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        self.snapshots.pop_back()
+    }
+
+    /// The snapshots from oldest to latest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Snapshot> {
+        self.snapshots.iter()
+    }
+
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.snapshots.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+/// Whether time is being rewound.
+#[derive(Resource, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[reflect(Resource)]
+pub struct Rewind {
+    pub active: bool,
+}
+
+/// A run condition for systems that only run while rewinding.
+pub fn is_rewinding(rewind: Res<Rewind>) -> bool {
+    rewind.active
+}
+
+fn add_rewind_history(
+    trigger: Trigger<OnAdd, Rewindable>,
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+) {
+    let ticks = (HISTORY_SECONDS / time.timestep().as_secs_f32()).ceil() as usize;
+    commands
+        .entity(trigger.entity())
+        .insert(RewindHistory::new(ticks));
+}
+
+fn hold_to_rewind(
+    actions: ActionInput,
+    mut rewind: ResMut<Rewind>,
+    mut controller_query: Query<&mut MovementController, With<Rewindable>>,
+) {
+    let held = actions.pressed(Action::Rewind);
+    if held == rewind.active {
+        return;
+    }
+    rewind.active = held;
+    if !held {
+        // Playback set these to face the way entities were going,
+        // which shouldn't carry over into the first tick after.
+        for mut controller in &mut controller_query {
+            controller.0 = Vec2::ZERO;
+        }
+    }
+}
+
+fn stop_rewinding(mut rewind: ResMut<Rewind>) {
+    rewind.active = false;
+}
+
+fn record_snapshots(
+    time: Res<Time>,
+    mut history_query: Query<(&mut RewindHistory, &Transform, Option<&Health>)>,
+) {
+    let dt = time.delta_seconds();
+    for (mut history, transform, health) in &mut history_query {
+        let position = transform.translation.xy();
+        let velocity = match history.latest() {
+            Some(previous) if dt > 0.0 => (position - previous.position) / dt,
+            _ => Vec2::ZERO,
+        };
+        history.push(Snapshot {
+            position,
+            velocity,
+            health: health.map(|health| health.current),
+        });
+    }
+}
+
+fn play_back_snapshots(
+    mut history_query: Query<(
+        &mut RewindHistory,
+        &mut Transform,
+        Option<&mut Health>,
+        Option<(&mut MovementController, &Movement)>,
+    )>,
+) {
+    for (mut history, mut transform, health, controller) in &mut history_query {
+        let Some(snapshot) = history.pop() else {
+            // Out of history, so the entity holds still until rewinding stops.
+            if let Some((mut controller, _)) = controller {
+                controller.0 = Vec2::ZERO;
+            }
+            continue;
+        };
+        transform.translation = snapshot.position.extend(transform.translation.z);
+        if let (Some(mut health), Some(current)) = (health, snapshot.health) {
+            health.current = current.min(health.max);
+        }
+        // Faces the entity the way it was going, so it runs backwards.
+        if let Some((mut controller, movement)) = controller {
+            controller.0 = if movement.speed > 0.0 {
+                snapshot.velocity / movement.speed
+            } else {
+                Vec2::ZERO
+            };
+        }
+    }
+}
+
+/// The vignette shown while rewinding, fading in and out with it.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+pub struct RewindVignette {
+    /// How strong the vignette is, from 0 to 1.
+    pub strength: f32,
+}
+
+/// How fast the vignette fades, in strength per second.
+const VIGNETTE_FADE_SPEED: f32 = 4.0;
+const VIGNETTE_COLOR: Color = Color::srgb(0.15, 0.1, 0.3);
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct VignetteMaterial {
+    #[uniform(0)]
+    params: VignetteParams,
+}
+
+impl VignetteMaterial {
+    /// A vignette of this color, starting out clear.
+    pub fn new(color: Color) -> Self {
+        Self {
+            params: VignetteParams {
+                color: color.to_linear().to_vec4(),
+                strength: 0.0,
+            },
+        }
+    }
+
+    /// From 0 to 1.
+    pub fn set_strength(&mut self, strength: f32) {
+        self.params.strength = strength;
+    }
+}
+
+impl UiMaterial for VignetteMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/vignette.wgsl".into()
+    }
+}
+
+/// The color is in linear space.
+#[derive(ShaderType, Debug, Clone, Copy, Default, PartialEq)]
+struct VignetteParams {
+    color: Vec4,
+    strength: f32,
+}
+
+fn spawn_vignette(mut commands: Commands, mut materials: ResMut<Assets<VignetteMaterial>>) {
+    commands.spawn((
+        Name::new("Rewind Vignette"),
+        RewindVignette::default(),
+        MaterialNodeBundle {
+            style: Style {
+                width: Percent(100.0),
+                height: Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            material: materials.add(VignetteMaterial::new(VIGNETTE_COLOR)),
+            // Under the HUD, which stays readable.
+            z_index: Layer::WorldUi.z_index(0),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn fade_vignette(
+    time: Res<Time>,
+    rewind: Res<Rewind>,
+    mut materials: ResMut<Assets<VignetteMaterial>>,
+    mut vignette_query: Query<(&mut RewindVignette, &Handle<VignetteMaterial>)>,
+) {
+    let target = if rewind.active { 1.0 } else { 0.0 };
+    let step = VIGNETTE_FADE_SPEED * time.delta_seconds();
+    for (mut vignette, handle) in &mut vignette_query {
+        if vignette.strength == target {
+            continue;
+        }
+        vignette.strength = if vignette.strength < target {
+            (vignette.strength + step).min(target)
+        } else {
+            (vignette.strength - step).max(target)
+        };
+        if let Some(material) = materials.get_mut(handle) {
+            material.set_strength(vignette.strength);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewind_history_plays_back_the_latest_snapshots() {
+        let mut history = RewindHistory::new(3);
+        for x in 0..5 {
+            history.push(Snapshot {
+                position: Vec2::new(x as f32, 0.0),
+                ..default()
+            });
+        }
+        assert_eq!(history.len(), 3);
+        let positions: Vec<f32> = std::iter::from_fn(|| history.pop())
+            .map(|snapshot| snapshot.position.x)
+            .collect();
+        assert_eq!(positions, [4.0, 3.0, 2.0]);
+        assert!(history.is_empty());
+    }
+}
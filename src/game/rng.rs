@@ -0,0 +1,111 @@
+//! The game's randomness, all drawn from [`GameRng`], so a run can be reproduced from
+//! its seed for replays, debugging, and players retrying a run they liked.
+//!
+//! Each kind of randomness gets its own stream from the one seed, so drawing more of
+//! one doesn't change the others. Visual effects draw per frame, and frame rates differ
+//! between machines, so sharing a stream with spawning would make runs diverge.
+//!
+//! The state is kept in the [`SaveGame`], so continuing a run continues its streams.
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use super::save::SaveGame;
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(GameRng::new(rand::random()));
+    app.init_resource::<NextRunSeed>();
+    app.add_systems(OnEnter(Screen::Playing), seed_run);
+}
+
+/// ChaCha keeps the same output across versions and platforms, unlike `StdRng`,
+/// and has independent streams for the same seed.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GameRng {
+    seed: u64,
+    spawning: ChaCha8Rng,
+    loot: ChaCha8Rng,
+    vfx: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let stream = |stream| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(stream);
+            rng
+        };
+        Self {
+            seed,
+            spawning: stream(0),
+            loot: stream(1),
+            vfx: stream(2),
+        }
+    }
+
+    /// The seed of the current run, which reproduces it.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Randomness for what spawns where and when, drawn from on fixed ticks only.
+    pub fn spawning(&mut self) -> &mut ChaCha8Rng {
+        &mut self.spawning
+    }
+
+    /// Randomness for drops and rewards, drawn from on fixed ticks only.
+    pub fn loot(&mut self) -> &mut ChaCha8Rng {
+        &mut self.loot
+    }
+
+    /// Randomness for presentation, like particles and sound variations,
+    /// which may be drawn from at any time.
+    pub fn vfx(&mut self) -> &mut ChaCha8Rng {
+        &mut self.vfx
+    }
+}
+
+/// The seed for the next run, like one retried from the game over screen.
+/// Without one, each run gets a random seed.
+#[derive(Resource, Debug, Default)]
+pub struct NextRunSeed(pub Option<u64>);
+
+fn seed_run(mut rng: ResMut<GameRng>, mut next_seed: ResMut<NextRunSeed>, save: Res<SaveGame>) {
+    if let Some(saved) = &save.rng {
+        *rng = saved.clone();
+        info!("Continuing a run with seed {:#018x}.", rng.seed());
+        return;
+    }
+    let seed = next_seed.0.take().unwrap_or_else(rand::random);
+    info!("Starting a run with seed {seed:#018x}.");
+    *rng = GameRng::new(seed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    const SEED: u64 = 0x5eed_b0a7_5e77_1265;
+
+    #[test]
+    fn game_rng_streams_are_reproducible_and_independent() {
+        let mut first = GameRng::new(SEED);
+        let mut second = GameRng::new(SEED);
+        // Drawing effects in between doesn't change what spawning gets.
+        let _: u64 = first.vfx().gen();
+        let spawns: [u32; 4] = first.spawning().gen();
+        assert_eq!(spawns, second.spawning().gen::<[u32; 4]>());
+        assert_ne!(
+            GameRng::new(SEED).loot().gen::<u64>(),
+            GameRng::new(SEED).spawning().gen::<u64>()
+        );
+        // Saved state picks up where it left off.
+        let mut saved: GameRng = ron::from_str(&ron::to_string(&first).unwrap()).unwrap();
+        assert_eq!(saved.seed(), SEED);
+        assert_eq!(saved.loot().gen::<u64>(), first.loot().gen::<u64>());
+    }
+}
@@ -0,0 +1,238 @@
+//! Saved games, in a few slots picked before playing.
+//! The picked slot's [`SaveGame`] is loaded into a resource that gameplay keeps up to date,
+//! and is written back to [`storage`] when leaving the game and every so often while playing.
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    challenge::{BonusObjective, ChallengeProgress},
+    cycle::CyclePhase,
+    health::DeathReactions,
+    inventory::Inventory,
+    rng::GameRng,
+    score::Score,
+    spawn::{level::LevelEntity, player::Player, wave::WaveDirector},
+    stable_id::StableId,
+    weekly::IsoWeek,
+};
+use crate::{
+    events::DeathEvent,
+    screen::{ExitingScreen, PlayingState, Screen},
+    storage, AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SaveGame>();
+    app.observe(save_on_exit);
+    app.add_systems(
+        Update,
+        (track_progress, autosave)
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(PlayingState::Running).and_then(resource_exists::<ActiveSlot>)),
+    );
+    app.add_systems(
+        FixedUpdate,
+        remember_dead_level_entities
+            .in_set(DeathReactions)
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// One of the places a game can be saved to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct SaveSlot(u8);
+
+impl SaveSlot {
+    pub const ALL: [Self; 3] = [Self(1), Self(2), Self(3)];
+
+    pub fn name(self) -> String {
+        format!("Slot {}", self.0)
+    }
+
+    fn key(self) -> String {
+        format!("save_{}", self.0)
+    }
+
+    /// The game saved in this slot, if any.
+    pub fn load(self) -> Option<SaveGame> {
+        storage::load(&self.key())
+    }
+
+    pub fn save(self, game: &SaveGame) {
+        storage::save(&self.key(), game);
+    }
+
+    pub fn delete(self) {
+        storage::remove(&self.key());
+    }
+}
+
+/// The slot being played, which [`SaveGame`] is written to.
+/// Missing when no slot was picked, in which case nothing is saved.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct ActiveSlot(pub SaveSlot);
+
+/// Progress of a run. Fields default when missing, so older saves still load.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SaveGame {
+    /// Name of the level being played.
+    pub level: String,
+    /// Where to put the player when continuing, instead of the level's spawn point.
+    pub player_position: Option<Vec2>,
+    pub score: u64,
+    /// Ids of whatever was unlocked during the run.
+    pub unlocks: HashSet<String>,
+    /// Seconds spent playing, not counting pauses.
+    pub play_time: f32,
+    /// How many cycles were completed, and the phase of the current one.
+    pub cycle: u32,
+    pub phase: u32,
+    /// How many waves of enemies were finished, see `spawn::wave`.
+    /// Continuing starts the next one, since enemies aren't saved.
+    pub wave: u32,
+    /// Level entities that died or were used up, which aren't spawned again when continuing.
+    pub removed: HashSet<StableId>,
+    /// Where the run's randomness is at, so continuing doesn't reroll it.
+    pub rng: Option<GameRng>,
+    /// Ids of the run's mutators, see `mutators`.
+    pub mutators: Vec<String>,
+    /// The items the player carries, see `inventory`.
+    pub inventory: Inventory,
+    /// Ids of the upgrade cards bought, in order, see `upgrades`.
+    pub upgrades: Vec<String>,
+    /// The week whose challenge is being played, if it is one, see `weekly`.
+    pub weekly: Option<IsoWeek>,
+    /// Progress towards each of the level's objectives, in order, see `objectives`.
+    pub objectives: Vec<u32>,
+    /// Which of the level's challenge rules were broken so far, see `challenge`.
+    pub challenge: ChallengeProgress,
+    /// The challenge rules of the levels completed so far, and whether they were kept.
+    pub bonus_objectives: Vec<BonusObjective>,
+}
+
+impl Default for SaveGame {
+    fn default() -> Self {
+        Self {
+            level: "main".to_string(),
+            player_position: None,
+            score: 0,
+            unlocks: default(),
+            play_time: 0.0,
+            cycle: 0,
+            phase: 0,
+            wave: 0,
+            removed: default(),
+            rng: None,
+            mutators: Vec::new(),
+            inventory: default(),
+            upgrades: Vec::new(),
+            weekly: None,
+            objectives: Vec::new(),
+            challenge: default(),
+            bonus_objectives: Vec::new(),
+        }
+    }
+}
+
+impl SaveGame {
+    /// A short description for the slot picker, like "main, 12:05".
+    pub fn summary(&self) -> String {
+        let seconds = self.play_time as u64;
+        format!("{}, {}:{:02}", self.level, seconds / 60, seconds % 60)
+    }
+}
+
+/// Seconds between saves while playing.
+const AUTOSAVE_INTERVAL: f32 = 30.0;
+
+fn track_progress(
+    time: Res<Time>,
+    score: Res<Score>,
+    cycle: Option<Res<CyclePhase>>,
+    director: Option<Res<WaveDirector>>,
+    rng: Res<GameRng>,
+    mut save: ResMut<SaveGame>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    save.play_time += time.delta_seconds();
+    save.score = score.points;
+    if rng.is_changed() {
+        save.rng = Some(rng.clone());
+    }
+    if let Some(cycle) = cycle {
+        save.cycle = cycle.cycle;
+        save.phase = cycle.phase;
+    }
+    if let Some(director) = director {
+        save.wave = director.finished();
+    }
+    if let Ok(transform) = player_query.get_single() {
+        save.player_position = Some(transform.translation.truncate());
+    }
+}
+
+fn remember_dead_level_entities(
+    mut death_events: EventReader<DeathEvent>,
+    mut save: ResMut<SaveGame>,
+    id_query: Query<&StableId, With<LevelEntity>>,
+) {
+    for event in death_events.read() {
+        if let Ok(&id) = id_query.get(event.entity) {
+            save.removed.insert(id);
+        }
+    }
+}
+
+fn autosave(
+    time: Res<Time>,
+    mut until_save: Local<f32>,
+    slot: Res<ActiveSlot>,
+    save: Res<SaveGame>,
+) {
+    *until_save += time.delta_seconds();
+    if *until_save >= AUTOSAVE_INTERVAL {
+        *until_save = 0.0;
+        slot.0.save(&save);
+    }
+}
+
+fn save_on_exit(
+    trigger: Trigger<ExitingScreen>,
+    slot: Option<Res<ActiveSlot>>,
+    save: Res<SaveGame>,
+) {
+    // A run that ended is deleted by the game over screen instead.
+    let event = trigger.event();
+    if event.from != Screen::Playing || event.to == Screen::GameOver {
+        return;
+    }
+    if let Some(slot) = slot {
+        slot.0.save(&save);
+        info!("Saved the game to {}.", slot.0.name());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_games_round_trip_and_fill_in_missing_fields() {
+        let save = SaveGame {
+            player_position: Some(Vec2::new(12.0, -3.5)),
+            score: 420,
+            play_time: 65.0,
+            removed: [StableId::for_placement("main", 3)].into_iter().collect(),
+            ..default()
+        };
+        let serialized = ron::to_string(&save).unwrap();
+        assert_eq!(ron::from_str::<SaveGame>(&serialized).unwrap(), save);
+        assert_eq!(save.summary(), "main, 1:05");
+        let old: SaveGame = ron::from_str("(score: 7)").unwrap();
+        assert_eq!(old.score, 7);
+        assert_eq!(old.level, SaveGame::default().level);
+    }
+}
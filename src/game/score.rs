@@ -0,0 +1,118 @@
+//! Points scored during a run. Send a [`ScoreEvent`] to score: points are multiplied by
+//! the combo, which grows with every score in quick succession and is lost when
+//! [`COMBO_WINDOW`] passes without one. The run's [`SaveGame`] keeps the points.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::save::SaveGame;
+use crate::{events::ScoreEvent, screen::PlayingState, screen::Screen, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Score>();
+    app.init_resource::<Score>();
+    app.add_systems(OnEnter(Screen::Playing), resume_score);
+    app.add_systems(
+        FixedUpdate,
+        (
+            tick_combo.in_set(AppSet::TickTimers),
+            apply_score_events.in_set(AppSet::Update),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// Seconds after scoring in which the next score keeps the combo going.
+pub const COMBO_WINDOW: f32 = 2.0;
+/// Scores in a row it takes to raise the multiplier by one.
+const COMBO_STEP: u32 = 5;
+const MAX_MULTIPLIER: u64 = 8;
+
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct Score {
+    pub points: u64,
+    /// Scores in a row, each within [`COMBO_WINDOW`] of the last.
+    pub combo: u32,
+    combo_timer: Timer,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Score {
+    pub fn new(points: u64) -> Self {
+        Self {
+            points,
+            combo: 0,
+            combo_timer: Timer::from_seconds(COMBO_WINDOW, TimerMode::Once),
+        }
+    }
+
+    /// What the next score is multiplied by.
+    pub fn multiplier(&self) -> u64 {
+        (1 + (self.combo / COMBO_STEP) as u64).min(MAX_MULTIPLIER)
+    }
+
+    /// Score `amount` times the multiplier, continuing the combo.
+    /// Returns the points that were added.
+    pub fn add(&mut self, amount: u64) -> u64 {
+        let points = amount.saturating_mul(self.multiplier());
+        self.points = self.points.saturating_add(points);
+        self.combo += 1;
+        self.combo_timer.reset();
+        points
+    }
+
+    /// Run down the combo timer, ending the combo once it runs out.
+    pub fn tick(&mut self, delta: Duration) {
+        if self.combo > 0 && self.combo_timer.tick(delta).finished() {
+            self.combo = 0;
+        }
+    }
+}
+
+/// Continues with the points of the run being played, or starts from 0 for a new one.
+fn resume_score(mut score: ResMut<Score>, save: Res<SaveGame>) {
+    *score = Score::new(save.score);
+}
+
+fn tick_combo(time: Res<Time>, mut score: ResMut<Score>) {
+    // Only while there is a combo, so the score isn't marked as changed every tick.
+    if score.combo > 0 {
+        score.tick(time.delta());
+    }
+}
+
+fn apply_score_events(mut score_events: EventReader<ScoreEvent>, mut score: ResMut<Score>) {
+    for event in score_events.read() {
+        let points = score.add(event.amount);
+        debug!("Scored {points} points from {:?}.", event.source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combos_multiply_scores_until_they_run_out() {
+        let mut score = Score::new(100);
+        for _ in 0..5 {
+            assert_eq!(score.add(10), 10);
+        }
+        assert_eq!(score.multiplier(), 2);
+        assert_eq!(score.add(10), 20);
+        assert_eq!(score.points, 170);
+
+        score.tick(std::time::Duration::from_secs_f32(1.0));
+        assert_eq!(score.multiplier(), 2);
+        score.tick(std::time::Duration::from_secs_f32(1.5));
+        assert_eq!((score.combo, score.multiplier()), (0, 1));
+        assert_eq!(score.points, 170);
+    }
+}
@@ -0,0 +1,253 @@
+//! A compact binary encoding for streams of [`Snapshot`]s, small enough that a whole run
+//! fits in browser local storage or a share code. Used for the [ghosts](super::ghost)
+//! of the fastest runs, and the replays attached to bug reports.
+//!
+//! Values are quantized to fixed steps, which are finer than anything that shows on screen.
+//! Each snapshot starts with a mask of which fields changed from what the previous one
+//! predicts, followed by just those changes. Positions are predicted to keep moving like
+//! they did the tick before, so steady motion costs a few bits per tick. Changes are
+//! bit-packed: a 6-bit length, then that many bits of the zigzag-encoded value.
+
+use bevy::prelude::*;
+
+use super::rewind::Snapshot;
+
+/// Bumped when the encoding changes, so old data is rejected instead of misread.
+const FORMAT_VERSION: u8 = 1;
+
+/// Positions are kept to an eighth of a pixel.
+const POSITION_STEP: f32 = 0.125;
+/// Velocities are kept to a pixel per second.
+const VELOCITY_STEP: f32 = 1.0;
+/// Health is kept to a quarter point.
+const HEALTH_STEP: f32 = 0.25;
+
+const POSITION_CHANGED: u64 = 1 << 0;
+const VELOCITY_CHANGED: u64 = 1 << 1;
+const HEALTH_CHANGED: u64 = 1 << 2;
+const MASK_BITS: u32 = 3;
+
+/// Bits of the length that precedes each packed value.
+const LENGTH_BITS: u32 = 6;
+
+/// Encode `snapshots`, which [`decode_snapshots`] turns back into them, quantized.
+pub fn encode_snapshots(snapshots: &[Snapshot]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    writer.write(FORMAT_VERSION.into(), 8);
+    writer.write(snapshots.len() as u64, 32);
+    let mut previous = Quantized::default();
+    let mut step = [0; 2];
+    for snapshot in snapshots {
+        let current = Quantized::from(snapshot);
+        let moved = [
+            current.position[0] - previous.position[0],
+            current.position[1] - previous.position[1],
+        ];
+        // How far the motion strayed from repeating the previous step.
+        let surprise = [moved[0] - step[0], moved[1] - step[1]];
+        let mut mask = 0;
+        if surprise != [0, 0] {
+            mask |= POSITION_CHANGED;
+        }
+        if current.velocity != previous.velocity {
+            mask |= VELOCITY_CHANGED;
+        }
+        if current.health != previous.health {
+            mask |= HEALTH_CHANGED;
+        }
+        writer.write(mask, MASK_BITS);
+        if mask & POSITION_CHANGED != 0 {
+            writer.write_signed(surprise[0]);
+            writer.write_signed(surprise[1]);
+        }
+        if mask & VELOCITY_CHANGED != 0 {
+            writer.write_signed(current.velocity[0] - previous.velocity[0]);
+            writer.write_signed(current.velocity[1] - previous.velocity[1]);
+        }
+        if mask & HEALTH_CHANGED != 0 {
+            writer.write_signed(current.health - previous.health);
+        }
+        step = moved;
+        previous = current;
+    }
+    writer.finish()
+}
+
+/// Decode snapshots from [`encode_snapshots`], or `None` if `bytes` are cut short
+/// or come from another version of the encoding.
+pub fn decode_snapshots(bytes: &[u8]) -> Option<Vec<Snapshot>> {
+    let mut reader = BitReader::new(bytes);
+    if reader.read(8)? != u64::from(FORMAT_VERSION) {
+        return None;
+    }
+    let count = reader.read(32)? as usize;
+    // Every snapshot takes at least its mask, so a larger count can't be right.
+    if count > bytes.len() * 8 / MASK_BITS as usize {
+        return None;
+    }
+    let mut snapshots = Vec::with_capacity(count);
+    let mut current = Quantized::default();
+    let mut step = [0; 2];
+    for _ in 0..count {
+        let mask = reader.read(MASK_BITS)?;
+        let mut moved = step;
+        if mask & POSITION_CHANGED != 0 {
+            moved[0] += reader.read_signed()?;
+            moved[1] += reader.read_signed()?;
+        }
+        current.position[0] += moved[0];
+        current.position[1] += moved[1];
+        if mask & VELOCITY_CHANGED != 0 {
+            current.velocity[0] += reader.read_signed()?;
+            current.velocity[1] += reader.read_signed()?;
+        }
+        if mask & HEALTH_CHANGED != 0 {
+            current.health += reader.read_signed()?;
+        }
+        step = moved;
+        snapshots.push(current.into());
+    }
+    Some(snapshots)
+}
+
+/// A [`Snapshot`] in whole steps, which deltas are taken between so they don't drift.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Quantized {
+    position: [i64; 2],
+    velocity: [i64; 2],
+    /// -1 for no health, which real health never quantizes to.
+    health: i64,
+}
+
+impl From<&Snapshot> for Quantized {
+    fn from(snapshot: &Snapshot) -> Self {
+        let quantize = |value: f32, step: f32| (value / step).round() as i64;
+        Self {
+            position: [
+                quantize(snapshot.position.x, POSITION_STEP),
+                quantize(snapshot.position.y, POSITION_STEP),
+            ],
+            velocity: [
+                quantize(snapshot.velocity.x, VELOCITY_STEP),
+                quantize(snapshot.velocity.y, VELOCITY_STEP),
+            ],
+            health: snapshot
+                .health
+                .map_or(-1, |health| quantize(health.max(0.0), HEALTH_STEP)),
+        }
+    }
+}
+
+impl From<Quantized> for Snapshot {
+    fn from(quantized: Quantized) -> Self {
+        let restore = |value: i64, step: f32| value as f32 * step;
+        Self {
+            position: Vec2::new(
+                restore(quantized.position[0], POSITION_STEP),
+                restore(quantized.position[1], POSITION_STEP),
+            ),
+            velocity: Vec2::new(
+                restore(quantized.velocity[0], VELOCITY_STEP),
+                restore(quantized.velocity[1], VELOCITY_STEP),
+            ),
+            health: (quantized.health >= 0).then(|| restore(quantized.health, HEALTH_STEP)),
+        }
+    }
+}
+
+/// Packs values of any number of bits, most significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// Bits used in the last byte, from 0 to 7.
+    used: u32,
+}
+
+impl BitWriter {
+    /// Write the lowest `bits` bits of `value`.
+    fn write(&mut self, value: u64, bits: u32) {
+        for bit in (0..bits).rev() {
+            if self.used == 0 {
+                self.bytes.push(0);
+            }
+            let set = (value >> bit) & 1;
+            *self.bytes.last_mut().unwrap() |= (set as u8) << (7 - self.used);
+            self.used = (self.used + 1) % 8;
+        }
+    }
+
+    /// Write a signed value in as few bits as its size needs, after its length.
+    fn write_signed(&mut self, value: i64) {
+        // Zigzag, so small negative values are small too.
+        let zigzag = ((value << 1) ^ (value >> 63)) as u64;
+        let bits = u64::BITS - zigzag.leading_zeros();
+        // 64 doesn't fit in the length, but only `i64::MIN` needs it.
+        let bits = bits.min((1 << LENGTH_BITS) - 1);
+        self.write(bits.into(), LENGTH_BITS);
+        self.write(zigzag, bits);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// Bits read so far.
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn read(&mut self, bits: u32) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = self.bytes.get(self.position / 8)?;
+            let set = (byte >> (7 - self.position % 8)) & 1;
+            value = (value << 1) | u64::from(set);
+            self.position += 1;
+        }
+        Some(value)
+    }
+
+    fn read_signed(&mut self) -> Option<i64> {
+        let bits = self.read(LENGTH_BITS)? as u32;
+        let zigzag = self.read(bits)?;
+        Some((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_survive_compression_to_a_few_bits_per_tick() {
+        // A minute at 64 ticks per second, running in circles and taking hits.
+        let snapshots: Vec<Snapshot> = (0..64 * 60)
+            .map(|tick| {
+                let angle = tick as f32 / 200.0;
+                Snapshot {
+                    position: Vec2::from_angle(angle) * 300.0,
+                    velocity: Vec2::from_angle(angle).perp() * 96.0,
+                    health: Some(100.0 - (tick / 500) as f32 * 7.5),
+                }
+            })
+            .collect();
+        let bytes = encode_snapshots(&snapshots);
+        assert!(bytes.len() < 20 * 1024, "{} bytes", bytes.len());
+        let decoded = decode_snapshots(&bytes).unwrap();
+        assert_eq!(decoded.len(), snapshots.len());
+        for (decoded, original) in decoded.iter().zip(&snapshots) {
+            assert!(decoded.position.distance(original.position) <= 0.1);
+            assert!(decoded.velocity.distance(original.velocity) <= 0.71);
+            assert_eq!(decoded.health, original.health);
+        }
+        assert_eq!(decode_snapshots(&bytes[..bytes.len() / 2]), None);
+        assert_eq!(decode_snapshots(&[]), None);
+    }
+}
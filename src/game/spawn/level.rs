@@ -1,18 +1,391 @@
 //! Spawn the main level by triggering other observers.
+//!
+//! Levels are [`LevelData`] assets, written in RON as `*.level.ron` files under
+//! `assets/levels`, so they can be edited without recompiling.
+//! In dev builds, saving a level file while playing it respawns the level.
+//!
+//! Runs start in the first of [`LEVELS`], and trigger [`EnterLevel`] to move on to another,
+//! like when a level's objectives are done, see `game::objectives`.
 
-use bevy::prelude::*;
+use std::fmt;
 
-use super::player::SpawnPlayer;
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use super::{
+    player::{Player, SpawnPlayer},
+    wave::Enemy,
+};
+use crate::{
+    game::{
+        assets::{LevelAssets, LEVELS},
+        camera::ViewLimits,
+        challenge::ChallengeData,
+        collision::{Collider, CollisionLayer, TriggerEntered, TriggerZone},
+        cycle::{CycleParameters, CyclePhase},
+        dialogue::StartDialogue,
+        health::Resistances,
+        objectives::ObjectiveData,
+        save::SaveGame,
+        stable_id::StableId,
+        stagger::Poise,
+    },
+    layers::{Layer, OnLayer},
+    screen::Screen,
+};
 
 pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<LevelData>();
+    app.init_asset_loader::<LevelDataLoader>();
+    app.register_type::<(LevelEntity, Wall)>();
     app.observe(spawn_level);
+    app.observe(enter_level);
+    app.add_systems(OnExit(Screen::Playing), reset_view_limits);
+
+    #[cfg(feature = "dev")]
+    app.add_systems(Update, reload_level.run_if(in_state(Screen::Playing)));
 }
 
 #[derive(Event, Debug)]
 pub struct SpawnLevel;
 
-fn spawn_level(_trigger: Trigger<SpawnLevel>, mut commands: Commands) {
-    // The only thing we have in our level is a player,
-    // but add things like walls etc. here.
-    commands.trigger(SpawnPlayer);
+/// Leave the level being played for the one with this name, one of [`LEVELS`].
+/// The rest of the run, like the score, cycle and inventory, carries over.
+#[derive(Event, Debug)]
+pub struct EnterLevel(pub String);
+
+/// A level, as described by its RON file.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct LevelData {
+    /// Where the player starts.
+    pub player_spawn: Vec2,
+    #[serde(default)]
+    pub parameters: LevelParameters,
+    /// Everything else placed in the level.
+    #[serde(default)]
+    pub placements: Vec<Placement>,
+    /// Goals to complete, in any order.
+    #[serde(default)]
+    pub objectives: Vec<ObjectiveData>,
+    /// Rules to complete the objectives under, for bonus objectives.
+    #[serde(default)]
+    pub challenge: ChallengeData,
+    /// The level to enter once every objective is done, if any.
+    #[serde(default)]
+    pub next_level: Option<String>,
+}
+
+/// Values that can be tuned per level.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LevelParameters {
+    /// How fast the player moves, in pixels per second.
+    pub player_speed: f32,
+    /// How far the camera can zoom in, as its smallest scale.
+    pub min_view_scale: f32,
+    /// How far the camera can zoom out, as its largest scale.
+    pub max_view_scale: f32,
+    /// How much of each type of damage the player takes.
+    pub player_resistances: Resistances,
+    /// How much poise damage staggers the player.
+    pub player_poise: Poise,
+    pub cycle: CycleParameters,
+}
+
+impl Default for LevelParameters {
+    fn default() -> Self {
+        let limits = ViewLimits::default();
+        Self {
+            player_speed: 420.0,
+            min_view_scale: limits.min_scale,
+            max_view_scale: limits.max_scale,
+            player_resistances: Resistances::default(),
+            player_poise: Poise::default(),
+            cycle: CycleParameters::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Placement {
+    /// A name that stays the same when placements are added or removed before this one,
+    /// for a [`StableId`] that saves can refer to. Defaults to the placement's position
+    /// in the list.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub position: Vec2,
+    pub kind: PlacementKind,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub enum PlacementKind {
+    /// A plain colored rectangle with no gameplay effect.
+    Decoration { size: Vec2, color: (f32, f32, f32) },
+    /// A solid rectangle that nothing moves through, and enemies find their way around.
+    Wall { size: Vec2, color: (f32, f32, f32) },
+    /// An invisible [`TriggerZone`] that starts a dialogue the first time the player
+    /// walks into it.
+    Trigger { size: Vec2, dialogue: String },
+}
+
+/// A solid [`PlacementKind::Wall`], with an axis-aligned box [`Collider`].
+/// Movement keeps entities with colliders out of it.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Wall;
+
+/// Marks entities spawned from [`LevelData`] placements, so the level can be respawned.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct LevelEntity;
+
+#[derive(Default)]
+struct LevelDataLoader;
+
+/// Why a level file couldn't be loaded.
+#[derive(Debug)]
+enum LevelDataLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for LevelDataLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read level: {error}"),
+            Self::Ron(error) => write!(f, "could not parse level: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for LevelDataLoaderError {}
+
+impl From<std::io::Error> for LevelDataLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for LevelDataLoaderError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        Self::Ron(error)
+    }
+}
+
+impl AssetLoader for LevelDataLoader {
+    type Asset = LevelData;
+    type Settings = ();
+    type Error = LevelDataLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<LevelData, LevelDataLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+fn spawn_level(
+    _trigger: Trigger<SpawnLevel>,
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<LevelData>>,
+    save: Res<SaveGame>,
+) {
+    // The loading screen waits for the levels, so this is only missing if a hot reload failed.
+    let Some(level) = levels.get(level_assets.level(&save.level)) else {
+        error!("The level isn't loaded, so there is nothing to spawn.");
+        return;
+    };
+    for (index, placement) in level.placements.iter().enumerate() {
+        let id = placement.id.as_deref().map_or_else(
+            || StableId::for_placement(&save.level, index),
+            StableId::from_name,
+        );
+        if save.removed.contains(&id) {
+            continue;
+        }
+        match &placement.kind {
+            PlacementKind::Decoration {
+                size,
+                color: (red, green, blue),
+            } => {
+                commands.spawn((
+                    Name::new("Decoration"),
+                    LevelEntity,
+                    id,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::srgb(*red, *green, *blue),
+                            custom_size: Some(*size),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(placement.position.extend(0.0)),
+                        ..default()
+                    },
+                    OnLayer::new(Layer::Background),
+                    StateScoped(Screen::Playing),
+                ));
+            }
+            PlacementKind::Wall {
+                size,
+                color: (red, green, blue),
+            } => {
+                commands.spawn((
+                    Name::new("Wall"),
+                    LevelEntity,
+                    Wall,
+                    id,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::srgb(*red, *green, *blue),
+                            custom_size: Some(*size),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(placement.position.extend(0.0)),
+                        ..default()
+                    },
+                    Collider::Aabb {
+                        half_size: *size / 2.0,
+                    },
+                    OnLayer::new(Layer::World),
+                    StateScoped(Screen::Playing),
+                ));
+            }
+            PlacementKind::Trigger { size, dialogue } => {
+                let dialogue = dialogue.clone();
+                commands
+                    .spawn((
+                        Name::new("Trigger Zone"),
+                        LevelEntity,
+                        TriggerZone,
+                        id,
+                        SpatialBundle::from_transform(Transform::from_translation(
+                            placement.position.extend(0.0),
+                        )),
+                        Collider::Aabb {
+                            half_size: *size / 2.0,
+                        },
+                        CollisionLayer::new(CollisionLayer::TRIGGER, CollisionLayer::PLAYER),
+                        StateScoped(Screen::Playing),
+                    ))
+                    .observe(
+                        move |trigger: Trigger<TriggerEntered>,
+                              mut commands: Commands,
+                              mut save: ResMut<SaveGame>| {
+                            commands.trigger(StartDialogue(dialogue.clone()));
+                            // Used up, so continuing the run doesn't start it again.
+                            save.removed.insert(id);
+                            commands.entity(trigger.entity()).despawn_recursive();
+                        },
+                    );
+            }
+        }
+    }
+    commands.insert_resource(ViewLimits {
+        min_scale: level.parameters.min_view_scale,
+        max_scale: level.parameters.max_view_scale,
+    });
+    commands.insert_resource(
+        CyclePhase::new(level.parameters.cycle.clone()).resumed(save.cycle, save.phase),
+    );
+    commands.trigger(SpawnPlayer {
+        // Continue where a saved game left off.
+        position: save.player_position.unwrap_or(level.player_spawn),
+        speed: level.parameters.player_speed,
+        resistances: level.parameters.player_resistances,
+        poise: level.parameters.player_poise,
+    });
+}
+
+fn enter_level(
+    trigger: Trigger<EnterLevel>,
+    mut commands: Commands,
+    mut save: ResMut<SaveGame>,
+    level_query: Query<Entity, Or<(With<LevelEntity>, With<Player>, With<Enemy>)>>,
+) {
+    let name = &trigger.event().0;
+    if !LEVELS.contains(&name.as_str()) {
+        error!("There is no level named {name} to enter.");
+        return;
+    }
+    info!("Entering level {name}.");
+    save.level.clone_from(name);
+    save.player_position = None;
+    save.objectives.clear();
+    save.challenge = default();
+    for entity in &level_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.trigger(SpawnLevel);
+}
+
+/// Menus aren't bound by the last level's limits.
+fn reset_view_limits(mut commands: Commands) {
+    commands.insert_resource(ViewLimits::default());
+}
+
+/// Respawn the level when its file changes.
+#[cfg(feature = "dev")]
+fn reload_level(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<LevelData>>,
+    level_assets: Res<LevelAssets>,
+    save: Res<SaveGame>,
+    level_query: Query<Entity, Or<(With<LevelEntity>, With<Player>)>>,
+) {
+    let level = level_assets.level(&save.level);
+    let modified = asset_events.read().any(|event| event.is_modified(level));
+    if !modified {
+        return;
+    }
+    info!("Level file changed, respawning the level.");
+    for entity in &level_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.trigger(SpawnLevel);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::dialogue::DialogueCatalog;
+
+    #[test]
+    fn main_level_parses() {
+        let level: LevelData =
+            ron::from_str(include_str!("../../../assets/levels/main.level.ron")).unwrap();
+        assert!(level.parameters.player_speed > 0.0);
+        assert!(level.parameters.min_view_scale <= level.parameters.max_view_scale);
+    }
+
+    #[test]
+    fn grove_triggers_existing_dialogue() {
+        let level: LevelData =
+            ron::from_str(include_str!("../../../assets/levels/grove.level.ron")).unwrap();
+        let catalog = DialogueCatalog::load();
+        let dialogues = level
+            .placements
+            .iter()
+            .filter_map(|placement| match &placement.kind {
+                PlacementKind::Trigger { dialogue, .. } => Some(dialogue),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(!dialogues.is_empty());
+        for dialogue in dialogues {
+            assert!(catalog.get(dialogue).is_some(), "{dialogue}");
+        }
+    }
 }
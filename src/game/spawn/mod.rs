@@ -6,7 +6,8 @@ use bevy::prelude::*;
 
 pub mod level;
 pub mod player;
+pub mod wave;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((level::plugin, player::plugin));
+    app.add_plugins((level::plugin, player::plugin, wave::plugin));
 }
@@ -4,11 +4,26 @@ use bevy::prelude::*;
 
 use crate::{
     game::{
-        animation::PlayerAnimation,
-        assets::{HandleMap, ImageKey},
-        movement::{Movement, MovementController, WrapWithinWindow},
+        animation::{AnimationController, AnimationState, SpriteAnimation},
+        assets::ImageAssets,
+        camera::{CameraFollow, WorldCamera},
+        checksum::Checksummed,
+        collision::{Collider, CollisionLayer},
+        cosmetics::SkinCatalog,
+        health::{Health, Resistances},
+        interpolation::InterpolatedTransform,
+        inventory::Magnet,
+        movement::{Movement, MovementController},
+        mutators::Modifiers,
+        palette::PaletteSwap,
+        profile::Profile,
+        rewind::Rewindable,
+        stable_id::StableId,
+        stagger::Poise,
     },
+    layers::{Layer, OnLayer},
     screen::Screen,
+    ui::prelude::WorldOutline,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -17,42 +32,121 @@ pub(super) fn plugin(app: &mut App) {
 }
 
 #[derive(Event, Debug)]
-pub struct SpawnPlayer;
+pub struct SpawnPlayer {
+    pub position: Vec2,
+    /// Movement speed, in pixels per second.
+    pub speed: f32,
+    pub resistances: Resistances,
+    pub poise: Poise,
+}
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 #[reflect(Component)]
 pub struct Player;
 
+const PLAYER_HEALTH: f32 = 100.0;
+/// Seconds the player can't be hurt after a hit.
+const PLAYER_INVULNERABILITY: f32 = 1.0;
+
 fn spawn_player(
-    _trigger: Trigger<SpawnPlayer>,
+    trigger: Trigger<SpawnPlayer>,
     mut commands: Commands,
-    image_handles: Res<HandleMap<ImageKey>>,
+    images: Res<ImageAssets>,
+    skins: Res<SkinCatalog>,
+    profile: Res<Profile>,
+    modifiers: Res<Modifiers>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    camera_query: Query<Entity, With<WorldCamera>>,
 ) {
     // A texture atlas is a way to split one image with a grid into multiple sprites.
     // By attaching it to a [`SpriteBundle`] and providing an index, we can specify which section of the image we want to see.
     // We will use this to animate our player character. You can learn more about texture atlases in this example:
     // https://github.com/bevyengine/bevy/blob/latest/examples/2d/texture_atlas.rs
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 6, 2, Some(UVec2::splat(1)), None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    let player_animation = PlayerAnimation::new();
-
-    commands.spawn((
-        Name::new("Player"),
-        Player,
-        SpriteBundle {
-            texture: image_handles[&ImageKey::Ducky].clone_weak(),
-            transform: Transform::from_scale(Vec2::splat(8.0).extend(1.0)),
-            ..Default::default()
-        },
-        TextureAtlas {
-            layout: texture_atlas_layout.clone(),
-            index: player_animation.get_atlas_index(),
-        },
-        MovementController::default(),
-        Movement { speed: 420.0 },
-        WrapWithinWindow,
-        player_animation,
-        StateScoped(Screen::Playing),
-    ));
+    let texture_atlas_layout = texture_atlas_layouts.add(ducky_atlas_layout());
+    let &SpawnPlayer {
+        position,
+        speed,
+        resistances,
+        poise,
+    } = trigger.event();
+
+    let player = commands
+        .spawn((
+            Name::new("Player"),
+            Player,
+            StableId::from_name("player"),
+            SpriteBundle {
+                texture: images.ducky.clone_weak(),
+                transform: Transform::from_translation(position.extend(0.0))
+                    .with_scale(Vec2::splat(8.0).extend(1.0)),
+                ..Default::default()
+            },
+            TextureAtlas {
+                layout: texture_atlas_layout.clone(),
+                index: 0,
+            },
+            PaletteSwap(skins.selected(&profile.cosmetics).palette_id()),
+            OnLayer::new(Layer::World),
+            // Bundles only go up to 15 components, so related ones are grouped.
+            (
+                MovementController::default(),
+                Movement {
+                    speed: speed * modifiers.player_speed,
+                },
+                InterpolatedTransform::default(),
+                Rewindable,
+            ),
+            (
+                Health::new(if modifiers.one_hit_death {
+                    // Any hit is at least this much.
+                    f32::EPSILON
+                } else {
+                    PLAYER_HEALTH
+                })
+                .with_invulnerability(PLAYER_INVULNERABILITY),
+                resistances,
+                poise,
+            ),
+            (
+                // The ducky's body, which is smaller than its frame.
+                Collider::Circle { radius: 80.0 },
+                CollisionLayer::new(
+                    CollisionLayer::PLAYER,
+                    CollisionLayer::ENEMY | CollisionLayer::PICKUP | CollisionLayer::TRIGGER,
+                ),
+                Magnet::default(),
+            ),
+            Checksummed,
+            // Each frame is 32x32 pixels, scaled up 8 times.
+            WorldOutline(Vec2::splat(32.0 * 8.0)),
+            ducky_animations(),
+            StateScoped(Screen::Playing),
+        ))
+        .id();
+    for camera in &camera_query {
+        commands.entity(camera).insert(CameraFollow::new(player));
+    }
+}
+
+/// The grid of animation frames in [`ImageAssets::ducky`], as used by [`ducky_animations`].
+pub fn ducky_atlas_layout() -> TextureAtlasLayout {
+    TextureAtlasLayout::from_grid(UVec2::splat(32), 6, 2, Some(UVec2::splat(1)), None)
+}
+
+/// The ducky bobbing in place, from the first row of [`ducky_atlas_layout`].
+pub fn ducky_idle() -> SpriteAnimation {
+    SpriteAnimation::new(0, 2, 2.0)
+}
+
+/// The ducky's clips. The sheet has no frames for being hit, staggering or dying,
+/// so those hold the first idle frame, which the hit flash and game over make up for.
+/// Hits don't cut a stagger short.
+pub fn ducky_animations() -> AnimationController {
+    AnimationController::new(ducky_idle())
+        .with_clip(AnimationState::Run, SpriteAnimation::new(6, 6, 20.0))
+        .with_clip(AnimationState::Hit, SpriteAnimation::new(0, 1, 5.0))
+        .with_clip(AnimationState::Stagger, SpriteAnimation::new(0, 1, 2.0))
+        .with_clip(AnimationState::Death, SpriteAnimation::new(0, 1, 1.0))
+        .with_blocked(AnimationState::Stagger, AnimationState::Hit)
+        .with_final(AnimationState::Death)
 }
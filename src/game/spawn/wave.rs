@@ -0,0 +1,498 @@
+//! The spawning director, which sends enemies at the player in waves.
+//!
+//! Waves are [`WaveData`] assets, written in RON as `*.waves.ron` files under
+//! `assets/waves`, next to the enemies they are made of. Each wave spawns its enemies
+//! one at a time on a ring around the camera, far enough out to be off-screen, and ends
+//! once they are all gone, or when its duration runs out. After a rest, the next one
+//! starts. Waves past the last one repeat it, or in [`GameMode::Endless`] loop back to
+//! the first. Every completed cycle and every loop raises the [`Difficulty`], which makes
+//! waves bigger and their enemies tougher. Waves hold off while a cutscene plays.
+//!
+//! Where and what spawns is drawn from [`GameRng::spawning`], so a seed reproduces waves.
+//! A [`WaveStarted`] and [`WaveEnded`] event are sent for the HUD to announce.
+
+use std::{collections::BTreeMap, f32::consts::TAU, fmt};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::{
+    events::{WaveEnded, WaveStarted},
+    game::{
+        ai::{AiBrain, AiTuning},
+        assets::LevelAssets,
+        camera::WorldCamera,
+        checksum::Checksummed,
+        collision::{Collider, CollisionLayer},
+        cutscene::ActiveCutscene,
+        cycle::CyclePhase,
+        health::{Damage, DamageType, Health},
+        interpolation::InterpolatedTransform,
+        mode::GameMode,
+        movement::{Movement, MovementController},
+        mutators::Modifiers,
+        navigation::PathFollow,
+        rng::GameRng,
+        save::SaveGame,
+    },
+    layers::{Layer, OnLayer},
+    screen::{PlayingState, Screen},
+    ui::palette::GameplayTint,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<WaveData>();
+    app.init_asset_loader::<WaveDataLoader>();
+    app.register_type::<(Enemy, WaveDirector, Difficulty)>();
+    app.init_resource::<Difficulty>();
+    app.add_systems(OnEnter(Screen::Playing), start_director);
+    app.add_systems(
+        FixedUpdate,
+        (scale_difficulty, direct_waves)
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(
+                in_state(PlayingState::Running)
+                    .and_then(resource_exists::<WaveDirector>)
+                    .and_then(not(resource_exists::<ActiveCutscene>)),
+            ),
+    );
+}
+
+/// The waves of a level, as described by its RON file.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct WaveData {
+    /// The kinds of enemies, by the names waves refer to them with.
+    pub enemies: BTreeMap<String, EnemyDefinition>,
+    /// The waves in order. Waves past the last one repeat it.
+    pub waves: Vec<WaveDefinition>,
+    /// Seconds between one wave ending and the next starting.
+    #[serde(default = "default_rest")]
+    pub rest: f32,
+    /// How much harder each completed cycle makes waves, as a fraction added to
+    /// their enemy count and their enemies' health and damage.
+    #[serde(default)]
+    pub difficulty_per_cycle: f32,
+    /// Like `difficulty_per_cycle`, for each time endless mode loops through the waves.
+    #[serde(default = "default_difficulty_per_loop")]
+    pub difficulty_per_loop: f32,
+}
+
+fn default_rest() -> f32 {
+    5.0
+}
+
+fn default_difficulty_per_loop() -> f32 {
+    0.5
+}
+
+impl WaveData {
+    /// The definition of wave `number`, counting from 1.
+    pub fn wave(&self, number: u32, mode: GameMode) -> Option<&WaveDefinition> {
+        let last = self.waves.len().checked_sub(1)?;
+        let index = number.max(1) as usize - 1;
+        match mode {
+            GameMode::Normal => self.waves.get(index.min(last)),
+            GameMode::Endless => self.waves.get(index % self.waves.len()),
+        }
+    }
+
+    /// How many times the waves were looped through before wave `number`.
+    /// Only endless mode loops.
+    pub fn loops(&self, number: u32, mode: GameMode) -> u32 {
+        match mode {
+            GameMode::Normal => 0,
+            GameMode::Endless => (number.max(1) - 1) / self.waves.len().max(1) as u32,
+        }
+    }
+
+    /// What enemy counts, health and damage are multiplied by after `cycles` cycles
+    /// and `loops` loops.
+    pub fn difficulty(&self, cycles: u32, loops: u32) -> f32 {
+        1.0 + self.difficulty_per_cycle.max(0.0) * cycles as f32
+            + self.difficulty_per_loop.max(0.0) * loops as f32
+    }
+}
+
+/// A kind of enemy.
+#[derive(Deserialize, Debug, Clone)]
+pub struct EnemyDefinition {
+    pub health: f32,
+    /// Damage dealt on contact.
+    pub damage: f32,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    /// Poise damage dealt on contact, see `game::stagger`.
+    #[serde(default)]
+    pub poise: f32,
+    /// How fast it moves, in pixels per second.
+    pub speed: f32,
+    /// Its width and height, in pixels.
+    pub size: f32,
+    pub color: (f32, f32, f32),
+    /// How it behaves, see `game::ai`.
+    #[serde(default)]
+    pub ai: AiTuning,
+}
+
+/// One wave of enemies.
+#[derive(Deserialize, Debug, Clone)]
+pub struct WaveDefinition {
+    /// How many enemies spawn, before difficulty.
+    pub count: u32,
+    /// Names of the [`EnemyDefinition`]s to pick from, at random for each enemy.
+    pub enemies: Vec<String>,
+    /// Seconds between spawns.
+    pub interval: f32,
+    /// How far from the camera's center enemies spawn, in pixels.
+    /// Pushed further out if that is still on-screen.
+    pub ring_radius: f32,
+    /// Seconds until the wave ends even if enemies are left, or 0 to wait for all of them.
+    #[serde(default)]
+    pub duration: f32,
+}
+
+/// An enemy, spawned by the [`WaveDirector`] in the given wave,
+/// or in wave 0 when spawned by a cutscene.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct Enemy {
+    pub wave: u32,
+}
+
+/// What enemy counts, health and damage are multiplied by, see [`WaveData::difficulty`].
+/// Kept up to date for the current or next wave while playing.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Deref, Reflect)]
+#[reflect(Resource)]
+pub struct Difficulty(pub f32);
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Where the waves of the current run are at.
+/// Kept after the run ends, so results screens can show how far it got.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct WaveDirector {
+    /// The number of the current or last wave, counting from 1, or 0 before the first.
+    pub wave: u32,
+    /// Whether a wave is going on, instead of a rest between waves.
+    pub active: bool,
+    /// Enemies of the current wave that haven't spawned yet.
+    to_spawn: u32,
+    spawn_timer: Timer,
+    rest_timer: Timer,
+    /// Seconds since the current wave started.
+    elapsed: f32,
+}
+
+impl WaveDirector {
+    /// Start resting before the wave after the `finished` ones.
+    pub fn new(finished: u32, rest: f32) -> Self {
+        Self {
+            wave: finished,
+            active: false,
+            to_spawn: 0,
+            spawn_timer: Timer::from_seconds(0.0, TimerMode::Repeating),
+            rest_timer: Timer::from_seconds(rest, TimerMode::Once),
+            elapsed: 0.0,
+        }
+    }
+
+    /// How many waves have ended.
+    pub fn finished(&self) -> u32 {
+        if self.active {
+            self.wave - 1
+        } else {
+            self.wave
+        }
+    }
+}
+
+#[derive(Default)]
+struct WaveDataLoader;
+
+/// Why a waves file couldn't be loaded.
+#[derive(Debug)]
+enum WaveDataLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for WaveDataLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read waves: {error}"),
+            Self::Ron(error) => write!(f, "could not parse waves: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for WaveDataLoaderError {}
+
+impl From<std::io::Error> for WaveDataLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for WaveDataLoaderError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        Self::Ron(error)
+    }
+}
+
+impl AssetLoader for WaveDataLoader {
+    type Asset = WaveData;
+    type Settings = ();
+    type Error = WaveDataLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<WaveData, WaveDataLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["waves.ron"]
+    }
+}
+
+/// Continues after the last wave of the run being played, or before the first one.
+fn start_director(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    waves: Res<Assets<WaveData>>,
+    save: Res<SaveGame>,
+) {
+    // The loading screen waits for the waves, so this is only missing if a hot reload failed.
+    let Some(data) = waves.get(&level_assets.waves) else {
+        error!("The waves aren't loaded, so no enemies will spawn.");
+        // The last run's director would otherwise carry over.
+        commands.remove_resource::<WaveDirector>();
+        return;
+    };
+    commands.insert_resource(WaveDirector::new(save.wave, data.rest));
+}
+
+fn scale_difficulty(
+    level_assets: Res<LevelAssets>,
+    waves: Res<Assets<WaveData>>,
+    mode: Res<GameMode>,
+    cycle: Option<Res<CyclePhase>>,
+    director: Res<WaveDirector>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    let Some(data) = waves.get(&level_assets.waves) else {
+        return;
+    };
+    // While resting, the next wave is what's coming.
+    let wave = director.finished() + 1;
+    let cycles = cycle.map_or(0, |cycle| cycle.cycle);
+    difficulty.set_if_neq(Difficulty(data.difficulty(cycles, data.loops(wave, *mode))));
+}
+
+fn direct_waves(
+    mut commands: Commands,
+    time: Res<Time>,
+    level_assets: Res<LevelAssets>,
+    waves: Res<Assets<WaveData>>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    modifiers: Res<Modifiers>,
+    mut director: ResMut<WaveDirector>,
+    mut rng: ResMut<GameRng>,
+    mut started_events: EventWriter<WaveStarted>,
+    mut ended_events: EventWriter<WaveEnded>,
+    enemy_query: Query<&Enemy>,
+    camera_query: Query<(&GlobalTransform, &OrthographicProjection), With<WorldCamera>>,
+) {
+    let Some(data) = waves.get(&level_assets.waves) else {
+        return;
+    };
+    let difficulty = difficulty.0;
+
+    if !director.active {
+        if !director.rest_timer.tick(time.delta()).finished() {
+            return;
+        }
+        let wave = director.wave + 1;
+        let Some(definition) = data.wave(wave, *mode) else {
+            return;
+        };
+        let count = (definition.count as f32 * difficulty).round() as u32;
+        *director = WaveDirector {
+            wave,
+            active: true,
+            to_spawn: count,
+            spawn_timer: Timer::from_seconds(definition.interval, TimerMode::Repeating),
+            rest_timer: Timer::from_seconds(data.rest, TimerMode::Once),
+            elapsed: 0.0,
+        };
+        // The first enemy comes right away.
+        let duration = director.spawn_timer.duration();
+        director.spawn_timer.set_elapsed(duration);
+        info!("Wave {wave} started, with {count} enemies.");
+        started_events.send(WaveStarted {
+            wave,
+            enemies: count,
+        });
+        return;
+    }
+
+    let wave = director.wave;
+    let Some(definition) = data.wave(wave, *mode) else {
+        return;
+    };
+    director.elapsed += time.delta_seconds();
+    let spawns = if director.spawn_timer.duration().is_zero() {
+        director.to_spawn
+    } else {
+        director.spawn_timer.tick(time.delta());
+        director
+            .spawn_timer
+            .times_finished_this_tick()
+            .min(director.to_spawn)
+    };
+    let (center, view_radius) =
+        camera_query
+            .get_single()
+            .map_or((Vec2::ZERO, 0.0), |(transform, projection)| {
+                (
+                    transform.translation().xy(),
+                    projection.area.half_size().length(),
+                )
+            });
+    for _ in 0..spawns {
+        director.to_spawn -= 1;
+        let Some(name) = definition.enemies.choose(rng.spawning()) else {
+            continue;
+        };
+        let Some(enemy) = data.enemies.get(name) else {
+            warn!("Wave {wave} has an unknown enemy \"{name}\", skipping it.");
+            continue;
+        };
+        // Beyond the corners of the view, so the whole enemy is off-screen.
+        let radius = definition.ring_radius.max(view_radius + enemy.size);
+        let angle = rng.spawning().gen_range(0.0..TAU);
+        let position = center + Vec2::from_angle(angle) * radius;
+        spawn_enemy(&mut commands, enemy, wave, difficulty, &modifiers, position);
+    }
+
+    let remaining = enemy_query.iter().any(|enemy| enemy.wave == wave);
+    let timed_out = definition.duration > 0.0 && director.elapsed >= definition.duration;
+    if (director.to_spawn == 0 && !remaining) || timed_out {
+        director.active = false;
+        director.to_spawn = 0;
+        director.rest_timer.reset();
+        info!("Wave {wave} ended.");
+        ended_events.send(WaveEnded { wave });
+    }
+}
+
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    enemy: &EnemyDefinition,
+    wave: u32,
+    difficulty: f32,
+    modifiers: &Modifiers,
+    position: Vec2,
+) {
+    let (red, green, blue) = enemy.color;
+    commands.spawn((
+        Name::new("Enemy"),
+        Enemy { wave },
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(red, green, blue),
+                custom_size: Some(Vec2::splat(enemy.size)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(0.0)),
+            ..default()
+        },
+        GameplayTint(Color::srgb(red, green, blue)),
+        OnLayer::new(Layer::World),
+        MovementController::default(),
+        Movement {
+            speed: enemy.speed * modifiers.enemy_speed,
+        },
+        InterpolatedTransform::default(),
+        (AiBrain::new(enemy.ai), PathFollow::default()),
+        Health::new(enemy.health * difficulty * modifiers.enemy_health),
+        Damage {
+            amount: enemy.damage * difficulty * modifiers.enemy_damage,
+            kind: enemy.damage_type,
+            poise: enemy.poise,
+        },
+        Collider::Circle {
+            radius: enemy.size / 2.0,
+        },
+        CollisionLayer::new(CollisionLayer::ENEMY, CollisionLayer::PLAYER),
+        Checksummed,
+        StateScoped(Screen::Playing),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::mode::endless_soundtrack;
+
+    #[test]
+    fn main_waves_parse_and_repeat_the_last_wave() {
+        let data: WaveData =
+            ron::from_str(include_str!("../../../assets/waves/main.waves.ron")).unwrap();
+        for wave in &data.waves {
+            assert!(wave
+                .enemies
+                .iter()
+                .all(|name| data.enemies.contains_key(name)));
+        }
+        let last = data.waves.len() as u32;
+        let normal = GameMode::Normal;
+        assert_eq!(data.wave(0, normal).unwrap().count, data.waves[0].count);
+        assert_eq!(
+            data.wave(last + 100, normal).unwrap().count,
+            data.waves[last as usize - 1].count
+        );
+        assert_eq!(data.loops(last + 100, normal), 0);
+        assert_eq!(data.difficulty(0, 0), 1.0);
+        assert!(data.difficulty(2, 0) > data.difficulty(1, 0));
+        assert_eq!(WaveDirector::new(3, data.rest).finished(), 3);
+    }
+
+    #[test]
+    fn endless_mode_loops_waves_and_rotates_soundtracks() {
+        let data: WaveData =
+            ron::from_str(include_str!("../../../assets/waves/main.waves.ron")).unwrap();
+        let last = data.waves.len() as u32;
+        let endless = GameMode::Endless;
+        assert_eq!(
+            data.wave(last + 1, endless).unwrap().count,
+            data.waves[0].count
+        );
+        assert_eq!(data.loops(last, endless), 0);
+        assert_eq!(data.loops(last + 1, endless), 1);
+        assert_eq!(data.loops(3 * last + 1, endless), 3);
+        assert!(data.difficulty(0, 1) > data.difficulty(0, 0));
+
+        assert_eq!(endless_soundtrack(1), endless.soundtrack());
+        assert_eq!(endless_soundtrack(3), endless_soundtrack(1));
+        assert_ne!(endless_soundtrack(4), endless_soundtrack(1));
+        assert_eq!(endless_soundtrack(10), endless_soundtrack(1));
+    }
+}
@@ -0,0 +1,69 @@
+//! Feedback effects on sprites: a brief flash when something is hit, and outlines to
+//! pick out what is highlighted or selected. Both are plain components, drawn by the
+//! sprite's [`PaletteMaterial`](super::palette::PaletteMaterial), so gameplay never has
+//! to deal with materials.
+
+use bevy::prelude::*;
+
+use crate::AppSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(HitFlash, SpriteOutline)>();
+    app.add_systems(Update, fade_hit_flashes.in_set(AppSet::Update));
+}
+
+/// Covers a sprite in a color, fading out over its duration. Removed once it has faded.
+/// Added when something takes damage, see `game::health`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct HitFlash {
+    pub color: Color,
+    /// Seconds the flash takes to fade out.
+    pub duration: f32,
+    elapsed: f32,
+}
+
+impl HitFlash {
+    /// The flash when something takes damage.
+    pub const DAMAGE: Self = Self::new(Color::WHITE, 0.15);
+
+    pub const fn new(color: Color, duration: f32) -> Self {
+        Self {
+            color,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    /// How much of the flash color covers the sprite, from 1 down to 0.
+    pub fn strength(&self) -> f32 {
+        (1.0 - self.elapsed / self.duration.max(f32::EPSILON)).clamp(0.0, 1.0)
+    }
+}
+
+/// Draws a one-pixel outline of this color around the sprite's silhouette.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct SpriteOutline(pub Color);
+
+impl SpriteOutline {
+    /// Around the interactable that the player would interact with.
+    #[allow(unused)]
+    pub const HIGHLIGHTED: Self = Self(Color::srgb(1.0, 0.85, 0.2));
+    /// Around entities selected in an editor.
+    #[allow(unused)]
+    pub const SELECTED: Self = Self(Color::srgb(0.3, 0.8, 1.0));
+}
+
+fn fade_hit_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flash_query: Query<(Entity, &mut HitFlash)>,
+) {
+    for (entity, mut flash) in &mut flash_query {
+        flash.elapsed += time.delta_seconds();
+        if flash.strength() <= 0.0 {
+            commands.entity(entity).remove::<HitFlash>();
+        }
+    }
+}
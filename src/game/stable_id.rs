@@ -0,0 +1,95 @@
+//! Identifiers for entities that stay the same across runs, unlike [`Entity`],
+//! so saves can refer to entities of a level, like which ones are gone for good.
+//!
+//! A [`StableId`] is a hash of a name: level placements are named in their level file,
+//! or by their level and position in it, and the player is just "player".
+//! [`StableIds`] finds the entity that currently has an id.
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<StableId>();
+    app.init_resource::<StableIds>();
+    app.observe(index_stable_id);
+    app.observe(unindex_stable_id);
+}
+
+#[derive(Component, Reflect, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub struct StableId(pub u64);
+
+impl StableId {
+    /// The id for `name`, which is the same on every platform and in every run.
+    pub fn from_name(name: &str) -> Self {
+        // 64-bit FNV-1a, since the standard library's hashers may change between versions.
+        let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        Self(hash)
+    }
+
+    /// The id of the placement at `index` in `level`, for placements without a name.
+    pub fn for_placement(level: &str, index: usize) -> Self {
+        Self::from_name(&format!("{level}#{index}"))
+    }
+}
+
+/// The entity with each [`StableId`], kept up to date as they are spawned and despawned.
+#[derive(Resource, Debug, Default)]
+pub struct StableIds(HashMap<StableId, Entity>);
+
+impl StableIds {
+    pub fn get(&self, id: StableId) -> Option<Entity> {
+        self.0.get(&id).copied()
+    }
+}
+
+fn index_stable_id(
+    trigger: Trigger<OnAdd, StableId>,
+    mut ids: ResMut<StableIds>,
+    id_query: Query<&StableId>,
+) {
+    let entity = trigger.entity();
+    let Ok(&id) = id_query.get(entity) else {
+        return;
+    };
+    if let Some(other) = ids.0.insert(id, entity) {
+        if other != entity {
+            warn!("{entity} has the same stable id as {other}, which will no longer be found.");
+        }
+    }
+}
+
+fn unindex_stable_id(
+    trigger: Trigger<OnRemove, StableId>,
+    mut ids: ResMut<StableIds>,
+    id_query: Query<&StableId>,
+) {
+    let entity = trigger.entity();
+    if let Ok(id) = id_query.get(entity) {
+        // Only if it wasn't taken over by another entity since.
+        if ids.get(*id) == Some(entity) {
+            ids.0.remove(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_ids_only_depend_on_names() {
+        assert_eq!(StableId::from_name(""), StableId(0xcbf2_9ce4_8422_2325));
+        assert_eq!(StableId::from_name("player"), StableId::from_name("player"));
+        assert_eq!(
+            StableId::for_placement("main", 2),
+            StableId::from_name("main#2")
+        );
+        assert_ne!(
+            StableId::for_placement("main", 2),
+            StableId::for_placement("main", 3)
+        );
+    }
+}
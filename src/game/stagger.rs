@@ -0,0 +1,156 @@
+//! Stagger from heavy hits. Entities with [`Poise`] lose some with each hit, by the
+//! hit's poise damage, and get it back over time. Running out staggers them:
+//! they are [`Staggered`] for a while, can't move, and play their stagger animation.
+//! While staggered they take no poise damage, which gives them a window to recover
+//! before they can be staggered again.
+//!
+//! AI should check for [`Staggered`] and hold off on acting while it's there.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    animation::{AnimationController, AnimationState},
+    health::ApplyDamage,
+};
+use crate::{events::DamageTaken, screen::PlayingState, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Poise, Staggered)>();
+    app.add_systems(
+        FixedUpdate,
+        (
+            (recover_from_stagger, recover_poise).in_set(AppSet::TickTimers),
+            apply_poise_damage
+                .in_set(AppSet::HandleEvents)
+                .after(ApplyDamage),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// How much poise damage an entity can take before it is staggered.
+/// Usually comes from level data, like the player's; tougher enemies and bosses have more.
+#[derive(Component, Reflect, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+#[serde(default)]
+pub struct Poise {
+    pub max: f32,
+    #[serde(skip)]
+    current: Option<f32>,
+    /// Poise regained per second, while not staggered.
+    pub recovery: f32,
+    /// Seconds a stagger lasts.
+    pub stagger_duration: f32,
+}
+
+impl Default for Poise {
+    fn default() -> Self {
+        Self::new(30.0)
+    }
+}
+
+impl Poise {
+    pub fn new(max: f32) -> Self {
+        Self {
+            max,
+            current: None,
+            recovery: 10.0,
+            stagger_duration: 0.6,
+        }
+    }
+
+    /// Poise left, which starts out full.
+    pub fn current(&self) -> f32 {
+        self.current.unwrap_or(self.max)
+    }
+
+    /// Lose `amount` poise, returning whether that broke it. Broken poise is restored to full.
+    pub fn take(&mut self, amount: f32) -> bool {
+        let left = self.current() - amount.max(0.0);
+        if left > 0.0 {
+            self.current = Some(left);
+            false
+        } else {
+            self.current = None;
+            true
+        }
+    }
+
+    /// Regain poise over `dt` seconds.
+    pub fn recover(&mut self, dt: f32) {
+        if let Some(current) = self.current {
+            let recovered = current + self.recovery * dt;
+            self.current = (recovered < self.max).then_some(recovered);
+        }
+    }
+}
+
+/// The entity's poise broke, and it can't act until the timer runs out.
+#[derive(Component, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Staggered(pub Timer);
+
+fn apply_poise_damage(
+    mut commands: Commands,
+    mut taken_events: EventReader<DamageTaken>,
+    mut poise_query: Query<(&mut Poise, Option<&mut AnimationController>), Without<Staggered>>,
+) {
+    for event in taken_events.read().filter(|event| event.poise > 0.0) {
+        let Ok((mut poise, animation)) = poise_query.get_mut(event.target) else {
+            continue;
+        };
+        if !poise.take(event.poise) {
+            continue;
+        }
+        commands
+            .entity(event.target)
+            .insert(Staggered(Timer::from_seconds(
+                poise.stagger_duration,
+                TimerMode::Once,
+            )));
+        if let Some(mut animation) = animation {
+            animation.interrupt(AnimationState::Stagger);
+        }
+    }
+}
+
+fn recover_from_stagger(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut staggered_query: Query<(Entity, &mut Staggered)>,
+) {
+    for (entity, mut staggered) in &mut staggered_query {
+        if staggered.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Staggered>();
+        }
+    }
+}
+
+fn recover_poise(time: Res<Time>, mut poise_query: Query<&mut Poise, Without<Staggered>>) {
+    for mut poise in &mut poise_query {
+        // Only while recovering, so full poise isn't marked as changed every tick.
+        if poise.current() < poise.max {
+            poise.recover(time.delta_seconds());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poise_breaks_under_heavy_hits_and_recovers() {
+        let mut poise = Poise::new(30.0);
+        assert!(!poise.take(20.0));
+        assert_eq!(poise.current(), 10.0);
+        poise.recover(0.5);
+        assert_eq!(poise.current(), 15.0);
+        poise.recover(10.0);
+        assert_eq!(poise.current(), 30.0);
+        // A heavy hit breaks it in one go, after which it starts over.
+        assert!(poise.take(30.0));
+        assert_eq!(poise.current(), 30.0);
+    }
+}
@@ -0,0 +1,171 @@
+//! Statistics of the current play session, from entering the game until quitting to the
+//! title or the game over screen, which show them in a summary.
+//!
+//! Unlike the [`SaveGame`](super::save::SaveGame), these start over every session,
+//! even when continuing a run.
+
+use bevy::prelude::*;
+
+use super::{
+    collision::CollisionReactions, health::ApplyDamage, rewind::Rewind, spawn::player::Player,
+};
+use crate::{
+    events::{DamageTaken, PickupEvent},
+    screen::{PlayingState, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SessionStats>();
+    app.init_resource::<SessionStats>();
+    app.add_systems(OnEnter(Screen::Playing), reset_stats);
+    app.add_systems(
+        FixedUpdate,
+        (
+            count_time.in_set(AppSet::TickTimers),
+            count_distance.in_set(AppSet::HandleEvents),
+            count_damage.in_set(AppSet::HandleEvents).after(ApplyDamage),
+            count_pickups
+                .in_set(AppSet::HandleEvents)
+                .after(CollisionReactions),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// Moves longer than this in one tick are teleports, like respawning,
+/// which don't count as distance moved.
+const MAX_STEP: f32 = 64.0;
+
+#[derive(Resource, Reflect, Debug, Clone, PartialEq, Default)]
+#[reflect(Resource)]
+pub struct SessionStats {
+    /// Seconds spent playing, not counting time paused.
+    pub time_played: f32,
+    /// Pixels the player moved, not counting rewinds.
+    pub distance_moved: f32,
+    /// Damage the player dealt to others.
+    pub damage_dealt: f32,
+    /// Damage the player took.
+    pub damage_taken: f32,
+    pub items_collected: u32,
+    /// Where the player was last tick.
+    last_position: Option<Vec2>,
+}
+
+impl SessionStats {
+    /// One line per statistic, for the summary panel.
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("Time played: {}", format_time(self.time_played)),
+            format!("Distance moved: {:.0} px", self.distance_moved),
+            format!("Damage dealt: {:.0}", self.damage_dealt),
+            format!("Damage taken: {:.0}", self.damage_taken),
+            format!("Items collected: {}", self.items_collected),
+        ]
+    }
+
+    /// A short text to share the session on social media.
+    pub fn share_text(&self) -> String {
+        format!(
+            "I played Bevy Jam 5 for {}, moved {:.0} px, dealt {:.0} damage, took {:.0} \
+             and collected {} item{}! #bevyjam",
+            format_time(self.time_played),
+            self.distance_moved,
+            self.damage_dealt,
+            self.damage_taken,
+            self.items_collected,
+            if self.items_collected == 1 { "" } else { "s" },
+        )
+    }
+}
+
+/// Minutes and seconds, with hours once there are any.
+pub fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+fn reset_stats(mut stats: ResMut<SessionStats>) {
+    *stats = SessionStats::default();
+}
+
+fn count_time(time: Res<Time>, mut stats: ResMut<SessionStats>) {
+    stats.time_played += time.delta_seconds();
+}
+
+fn count_distance(
+    rewind: Res<Rewind>,
+    mut stats: ResMut<SessionStats>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(transform) = player_query.get_single() else {
+        return;
+    };
+    let position = transform.translation.xy();
+    if let Some(last_position) = stats.last_position.filter(|_| !rewind.active) {
+        let step = position.distance(last_position);
+        if step <= MAX_STEP {
+            stats.distance_moved += step;
+        }
+    }
+    stats.last_position = Some(position);
+}
+
+fn count_damage(
+    mut taken_events: EventReader<DamageTaken>,
+    mut stats: ResMut<SessionStats>,
+    player_query: Query<(), With<Player>>,
+) {
+    for event in taken_events.read() {
+        if player_query.contains(event.target) {
+            stats.damage_taken += event.amount;
+        } else if event
+            .source
+            .is_some_and(|source| player_query.contains(source))
+        {
+            stats.damage_dealt += event.amount;
+        }
+    }
+}
+
+fn count_pickups(
+    mut pickup_events: EventReader<PickupEvent>,
+    mut stats: ResMut<SessionStats>,
+    player_query: Query<(), With<Player>>,
+) {
+    for event in pickup_events.read() {
+        if player_query.contains(event.collector) {
+            stats.items_collected += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_stats_are_summarized_for_sharing() {
+        let stats = SessionStats {
+            time_played: 3725.5,
+            distance_moved: 1234.4,
+            damage_dealt: 56.0,
+            damage_taken: 7.8,
+            items_collected: 1,
+            ..default()
+        };
+        assert_eq!(stats.lines()[0], "Time played: 1:02:05");
+        assert_eq!(
+            stats.share_text(),
+            "I played Bevy Jam 5 for 1:02:05, moved 1234 px, dealt 56 damage, took 8 \
+             and collected 1 item! #bevyjam"
+        );
+        assert!(SessionStats::default().lines()[0].ends_with(" 0:00"));
+    }
+}
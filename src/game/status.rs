@@ -0,0 +1,216 @@
+//! Status effects that some types of damage cause, like burning from fire.
+//!
+//! The rules: a hit whose [`DamageType`] has a [`StatusEffect`] applies it, for the effect's
+//! duration scaled by the target's resistance to that type, so immune targets never get it.
+//! Another hit refreshes the duration. Damage from status effects doesn't apply any itself.
+
+use bevy::prelude::*;
+
+use super::health::{ApplyDamage, DamageType, Resistances};
+use crate::{
+    events::{DamageEvent, DamageTaken},
+    screen::PlayingState,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<StatusEffects>();
+    app.add_systems(
+        FixedUpdate,
+        (
+            tick_status_effects.in_set(AppSet::TickTimers),
+            apply_status_effects
+                .in_set(AppSet::HandleEvents)
+                .after(ApplyDamage),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusEffect {
+    Burning,
+    Poisoned,
+}
+
+impl StatusEffect {
+    /// The effect that a hit of this type applies, if any.
+    pub fn from_damage(kind: DamageType) -> Option<Self> {
+        match kind {
+            DamageType::Physical => None,
+            DamageType::Fire => Some(Self::Burning),
+            DamageType::Poison => Some(Self::Poisoned),
+        }
+    }
+
+    /// The type of damage the effect deals.
+    pub fn damage_type(self) -> DamageType {
+        match self {
+            Self::Burning => DamageType::Fire,
+            Self::Poisoned => DamageType::Poison,
+        }
+    }
+
+    /// Damage per second, before resistances.
+    pub fn damage_per_second(self) -> f32 {
+        match self {
+            Self::Burning => 6.0,
+            Self::Poisoned => 3.0,
+        }
+    }
+
+    /// Seconds the effect lasts on a target without resistances.
+    pub fn duration(self) -> f32 {
+        match self {
+            Self::Burning => 2.0,
+            Self::Poisoned => 5.0,
+        }
+    }
+}
+
+/// Seconds between ticks of damage.
+const TICK_INTERVAL: f32 = 0.5;
+
+/// The status effects on an entity, added when the first one is applied.
+#[derive(Component, Reflect, Debug, Clone, Default, PartialEq)]
+#[reflect(Component)]
+pub struct StatusEffects {
+    active: Vec<ActiveStatus>,
+}
+
+#[derive(Reflect, Debug, Clone, PartialEq)]
+struct ActiveStatus {
+    effect: StatusEffect,
+    /// Seconds left.
+    remaining: f32,
+    /// Seconds until the next tick of damage.
+    until_tick: f32,
+}
+
+impl StatusEffects {
+    /// Apply `effect` for `duration` seconds, or refresh it if that is longer than what's left.
+    pub fn apply(&mut self, effect: StatusEffect, duration: f32) {
+        if duration <= 0.0 {
+            return;
+        }
+        match self
+            .active
+            .iter_mut()
+            .find(|active| active.effect == effect)
+        {
+            Some(active) => active.remaining = active.remaining.max(duration),
+            None => self.active.push(ActiveStatus {
+                effect,
+                remaining: duration,
+                until_tick: TICK_INTERVAL,
+            }),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn has(&self, effect: StatusEffect) -> bool {
+        self.active.iter().any(|active| active.effect == effect)
+    }
+
+    /// Advance by `dt` seconds, calling `deal` with each tick of damage, and remove
+    /// effects that ran out.
+    pub fn tick(&mut self, dt: f32, mut deal: impl FnMut(StatusEffect, f32)) {
+        for active in &mut self.active {
+            let dt = dt.min(active.remaining);
+            active.remaining -= dt;
+            active.until_tick -= dt;
+            while active.until_tick <= 0.0 {
+                deal(
+                    active.effect,
+                    active.effect.damage_per_second() * TICK_INTERVAL,
+                );
+                active.until_tick += TICK_INTERVAL;
+            }
+        }
+        self.active.retain(|active| active.remaining > 0.0);
+    }
+}
+
+fn tick_status_effects(
+    time: Res<Time>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut status_query: Query<(Entity, &mut StatusEffects)>,
+) {
+    for (entity, mut status) in &mut status_query {
+        status.tick(time.delta_seconds(), |effect, amount| {
+            damage_events.send(DamageEvent {
+                target: entity,
+                amount,
+                kind: effect.damage_type(),
+                poise: 0.0,
+                source: None,
+                from_status: true,
+            });
+        });
+    }
+}
+
+fn apply_status_effects(
+    mut commands: Commands,
+    mut taken_events: EventReader<DamageTaken>,
+    mut status_query: Query<(Option<&mut StatusEffects>, Option<&Resistances>)>,
+) {
+    for event in taken_events.read().filter(|event| !event.from_status) {
+        let Some(effect) = StatusEffect::from_damage(event.kind) else {
+            continue;
+        };
+        let Ok((status, resistances)) = status_query.get_mut(event.target) else {
+            continue;
+        };
+        let multiplier = resistances.map_or(1.0, |resistances| resistances.multiplier(event.kind));
+        let duration = effect.duration() * multiplier;
+        match status {
+            Some(mut status) => status.apply(effect, duration),
+            None => {
+                let mut status = StatusEffects::default();
+                status.apply(effect, duration);
+                commands.entity(event.target).insert(status);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_effects_tick_for_their_resisted_duration() {
+        let resistances = Resistances {
+            fire: 0.5,
+            poison: 0.0,
+            ..default()
+        };
+        assert_eq!(resistances.multiplier(DamageType::Physical), 1.0);
+        assert_eq!(StatusEffect::from_damage(DamageType::Physical), None);
+
+        let mut status = StatusEffects::default();
+        let burning = StatusEffect::Burning;
+        status.apply(
+            burning,
+            burning.duration() * resistances.multiplier(DamageType::Fire),
+        );
+        // Immune to poison, so it doesn't stick.
+        let poisoned = StatusEffect::Poisoned;
+        status.apply(
+            poisoned,
+            poisoned.duration() * resistances.multiplier(DamageType::Poison),
+        );
+        assert!(!status.has(poisoned));
+
+        let mut dealt = 0.0;
+        for _ in 0..10 {
+            status.tick(0.25, |effect, amount| {
+                assert_eq!(effect.damage_type(), DamageType::Fire);
+                dealt += amount;
+            });
+        }
+        assert_eq!(dealt, burning.damage_per_second());
+        assert!(!status.has(burning));
+    }
+}
@@ -0,0 +1,253 @@
+//! On-screen controls for touch screens, mainly for the web build on phones.
+//! A virtual joystick sits in the bottom left and action buttons in the bottom right.
+//! They are only spawned once a touch has been detected, and feed [`ActionInput`]
+//! through [`TouchInput`].
+//!
+//! [`ActionInput`]: super::input::ActionInput
+
+use bevy::{prelude::*, ui::UiSystem, ui::Val::*, utils::HashSet};
+
+use super::input::Action;
+use crate::{screen::PlayingState, ui::palette::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(VirtualJoystick, JoystickKnob, TouchButton)>();
+    app.init_resource::<TouchInput>();
+    app.add_systems(OnEnter(PlayingState::Running), spawn_touch_controls);
+    app.add_systems(
+        PreUpdate,
+        (
+            detect_touch_screen,
+            // Buttons use the interaction computed from this frame's touches.
+            (update_joystick, update_touch_buttons).after(UiSystem::Focus),
+        ),
+    );
+    app.add_systems(
+        Update,
+        spawn_touch_controls
+            .run_if(in_state(PlayingState::Running).and_then(resource_added::<TouchScreen>)),
+    );
+}
+
+/// Present once the player has touched the screen.
+#[derive(Resource, Debug)]
+struct TouchScreen;
+
+/// The state of the on-screen controls, read through [`ActionInput`](super::input::ActionInput).
+#[derive(Resource, Debug, Default)]
+pub struct TouchInput {
+    /// Deflection of the virtual joystick, with a length of at most 1.
+    pub joystick: Vec2,
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl TouchInput {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+/// The base of the virtual joystick, following the touch that started on it.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+struct VirtualJoystick {
+    touch: Option<u64>,
+}
+
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct JoystickKnob;
+
+/// An on-screen button that presses an [`Action`] while held.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+struct TouchButton(Action);
+
+/// Actions that get a button, since they can't be reached with the joystick.
+const BUTTON_ACTIONS: [Action; 4] = [
+    Action::AdvanceCycle,
+    Action::Rewind,
+    Action::QuickWheel,
+    Action::Pause,
+];
+
+const JOYSTICK_SIZE: f32 = 160.0;
+const KNOB_SIZE: f32 = 64.0;
+const BUTTON_SIZE: f32 = 80.0;
+const MARGIN: f32 = 32.0;
+const TOUCH_CONTROL_ALPHA: f32 = 0.4;
+
+fn detect_touch_screen(
+    mut commands: Commands,
+    touches: Res<Touches>,
+    touch_screen: Option<Res<TouchScreen>>,
+) {
+    if touch_screen.is_none() && touches.any_just_pressed() {
+        commands.insert_resource(TouchScreen);
+    }
+}
+
+fn spawn_touch_controls(
+    mut commands: Commands,
+    touch_screen: Option<Res<TouchScreen>>,
+    mut touch_input: ResMut<TouchInput>,
+) {
+    // Reset, since the controls may have been despawned mid-press.
+    *touch_input = default();
+    if touch_screen.is_none() {
+        return;
+    }
+
+    commands
+        .spawn((
+            Name::new("Virtual Joystick"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Px(MARGIN),
+                    bottom: Px(MARGIN),
+                    width: Px(JOYSTICK_SIZE),
+                    height: Px(JOYSTICK_SIZE),
+                    ..default()
+                },
+                background_color: BackgroundColor(NODE_BACKGROUND.with_alpha(TOUCH_CONTROL_ALPHA)),
+                border_radius: BorderRadius::MAX,
+                ..default()
+            },
+            VirtualJoystick::default(),
+            StateScoped(PlayingState::Running),
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Joystick Knob"),
+                NodeBundle {
+                    style: knob_style(Vec2::ZERO),
+                    background_color: BackgroundColor(BUTTON_PRESSED_BACKGROUND),
+                    border_radius: BorderRadius::MAX,
+                    ..default()
+                },
+                JoystickKnob,
+            ));
+        });
+
+    commands
+        .spawn((
+            Name::new("Touch Buttons"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Px(MARGIN),
+                    bottom: Px(MARGIN),
+                    column_gap: Px(MARGIN / 2.0),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(PlayingState::Running),
+        ))
+        .with_children(|children| {
+            for action in BUTTON_ACTIONS {
+                children
+                    .spawn((
+                        Name::new("Touch Button"),
+                        ButtonBundle {
+                            style: Style {
+                                width: Px(BUTTON_SIZE),
+                                height: Px(BUTTON_SIZE),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: BackgroundColor(
+                                NODE_BACKGROUND.with_alpha(TOUCH_CONTROL_ALPHA),
+                            ),
+                            border_radius: BorderRadius::MAX,
+                            ..default()
+                        },
+                        TouchButton(action),
+                    ))
+                    .with_children(|children| {
+                        children.spawn((
+                            Name::new("Touch Button Text"),
+                            TextBundle::from_section(
+                                action.name(),
+                                TextStyle {
+                                    font_size: 20.0,
+                                    color: BUTTON_TEXT,
+                                    ..default()
+                                },
+                            ),
+                        ));
+                    });
+            }
+        });
+}
+
+/// Place the knob at `offset` from the joystick center, in UI coordinates (y down).
+fn knob_style(offset: Vec2) -> Style {
+    let position = Vec2::splat((JOYSTICK_SIZE - KNOB_SIZE) / 2.0) + offset;
+    Style {
+        position_type: PositionType::Absolute,
+        left: Px(position.x),
+        top: Px(position.y),
+        width: Px(KNOB_SIZE),
+        height: Px(KNOB_SIZE),
+        ..default()
+    }
+}
+
+fn update_joystick(
+    touches: Res<Touches>,
+    mut touch_input: ResMut<TouchInput>,
+    mut joystick_query: Query<(&mut VirtualJoystick, &Node, &GlobalTransform, &Children)>,
+    mut knob_query: Query<&mut Style, With<JoystickKnob>>,
+) {
+    for (mut joystick, node, transform, children) in &mut joystick_query {
+        let center = transform.translation().xy();
+        let radius = node.size().x / 2.0;
+
+        // Claim a new touch that starts on the joystick, and let go when it ends.
+        if joystick.touch.is_none() {
+            joystick.touch = touches
+                .iter_just_pressed()
+                .find(|touch| touch.position().distance(center) <= radius)
+                .map(|touch| touch.id());
+        }
+        let offset = match joystick.touch.and_then(|id| touches.get_pressed(id)) {
+            Some(touch) => (touch.position() - center).clamp_length_max(radius),
+            None => {
+                joystick.touch = None;
+                Vec2::ZERO
+            }
+        };
+
+        // UI coordinates point down, while movement points up.
+        touch_input.joystick = if radius > 0.0 {
+            Vec2::new(offset.x, -offset.y) / radius
+        } else {
+            Vec2::ZERO
+        };
+        let mut knob_iter = knob_query.iter_many_mut(children);
+        while let Some(mut style) = knob_iter.fetch_next() {
+            style.set_if_neq(knob_style(offset));
+        }
+    }
+}
+
+fn update_touch_buttons(
+    mut touch_input: ResMut<TouchInput>,
+    button_query: Query<(&Interaction, &TouchButton)>,
+) {
+    let pressed = button_query
+        .iter()
+        .filter(|(interaction, _)| matches!(interaction, Interaction::Pressed))
+        .map(|(_, button)| button.0)
+        .collect::<HashSet<_>>();
+    touch_input.just_pressed = pressed.difference(&touch_input.pressed).copied().collect();
+    touch_input.pressed = pressed;
+}
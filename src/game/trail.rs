@@ -0,0 +1,393 @@
+//! Ribbons that follow moving entities, like the player while sprinting.
+//! Give a [`Rewindable`] entity a [`Trail`], and a ribbon is drawn through the positions
+//! it has been at in the last [`Trail::lifetime`] seconds, tapering and fading towards its tail.
+//!
+//! The positions come from the entity's [`RewindHistory`], which already has one per tick,
+//! with the drawn position at the head. Each ribbon rewrites the vertices of its own mesh
+//! in place every frame, so no mesh is allocated per frame, and meshes of finished ribbons
+//! go back to a pool for the next trail. A ribbon outlives its trail until its tail
+//! catches up, so it fades out instead of vanishing when the trail is removed.
+
+use bevy::{
+    color::ColorToComponents,
+    prelude::*,
+    render::{
+        mesh::{Indices, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+        view::NoFrustumCulling,
+    },
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    transform::TransformSystem,
+};
+
+use super::rewind::{RewindHistory, Snapshot};
+use crate::{
+    layers::{Layer, OnLayer},
+    screen::{PlayingState, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Trail, TrailRibbon)>();
+    app.init_resource::<TrailMeshes>();
+    app.add_systems(
+        Update,
+        attach_ribbons
+            .in_set(AppSet::Update)
+            .run_if(in_state(PlayingState::Running)),
+    );
+    // Follows the drawn positions, after interpolation.
+    app.add_systems(
+        PostUpdate,
+        (follow_trails, build_ribbons)
+            .chain()
+            .after(TransformSystem::TransformPropagate)
+            .run_if(in_state(PlayingState::Running)),
+    );
+    app.add_systems(OnExit(Screen::Playing), release_ribbons);
+}
+
+/// Draws a ribbon behind this entity as it moves, if it has a [`RewindHistory`].
+/// Distances are in world units and times in seconds.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Trail {
+    /// The width at the head of the ribbon. It tapers to nothing at the tail.
+    pub width: f32,
+    /// How long each point of the ribbon lasts.
+    pub lifetime: f32,
+    /// The color at the head of the ribbon.
+    pub color_start: Color,
+    /// The color at the tail of the ribbon, usually transparent.
+    pub color_end: Color,
+}
+
+impl Trail {
+    pub fn new(width: f32, lifetime: f32, color: Color) -> Self {
+        Self {
+            width,
+            lifetime,
+            color_start: color,
+            color_end: color.with_alpha(0.0),
+        }
+    }
+}
+
+/// Points closer than this to the previous one are skipped, so that standing still
+/// doesn't put copies of the same point in the ribbon.
+const MIN_POINT_DISTANCE: f32 = 4.0;
+
+/// At most this many points are kept, however long the trail lasts.
+const MAX_POINTS: usize = 64;
+
+/// The points of a ribbon, newest first, with how long ago its entity was there.
+#[derive(Debug, Clone, Default, PartialEq, Reflect)]
+pub struct TrailPoints {
+    points: Vec<(Vec2, f32)>,
+}
+
+impl TrailPoints {
+    /// Replace the points with the entity's drawn `head`, followed by its `snapshots`
+    /// from latest to oldest. The latest was taken `overstep` seconds ago, and the others
+    /// a `tick` apart, and those older than `lifetime` are left out.
+    pub fn follow<'a>(
+        &mut self,
+        head: Vec2,
+        snapshots: impl Iterator<Item = &'a Snapshot>,
+        overstep: f32,
+        tick: f32,
+        lifetime: f32,
+    ) {
+        self.points.clear();
+        self.points.push((head, 0.0));
+        for (i, snapshot) in snapshots.enumerate() {
+            let age = overstep + i as f32 * tick;
+            if age >= lifetime || self.points.len() == MAX_POINTS {
+                break;
+            }
+            let newest = self.points[self.points.len() - 1].0;
+            if newest.distance(snapshot.position) >= MIN_POINT_DISTANCE {
+                self.points.push((snapshot.position, age));
+            }
+        }
+    }
+
+    /// Age every point by `dt`, and forget those older than `lifetime`.
+    /// For ribbons whose entity no longer has a trail.
+    pub fn age(&mut self, dt: f32, lifetime: f32) {
+        for (_, age) in &mut self.points {
+            *age += dt;
+        }
+        self.points.retain(|&(_, age)| age < lifetime);
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Write the ribbon's vertices and triangles into the given buffers, replacing
+    /// their contents. Two vertices per point, on either side of the path.
+    pub fn write_ribbon(
+        &self,
+        trail: &Trail,
+        positions: &mut Vec<[f32; 3]>,
+        colors: &mut Vec<[f32; 4]>,
+        indices: &mut Vec<u32>,
+    ) {
+        positions.clear();
+        colors.clear();
+        indices.clear();
+        if self.points.len() < 2 {
+            return;
+        }
+
+        let color_start = LinearRgba::from(trail.color_start);
+        let color_end = LinearRgba::from(trail.color_end);
+        let last = self.points.len() - 1;
+        for (i, &(point, age)) in self.points.iter().enumerate() {
+            // The direction of the path here, from the neighbouring points.
+            let ahead = self.points[i.saturating_sub(1)].0;
+            let behind = self.points[(i + 1).min(last)].0;
+            let normal = (ahead - behind).normalize_or_zero().perp();
+            let progress = (age / trail.lifetime.max(f32::EPSILON)).min(1.0);
+            let offset = normal * trail.width * 0.5 * (1.0 - progress);
+            let color = color_start.mix(&color_end, progress).to_f32_array();
+            positions.push((point + offset).extend(0.0).to_array());
+            positions.push((point - offset).extend(0.0).to_array());
+            colors.extend([color, color]);
+        }
+        for i in 0..last as u32 {
+            let v = i * 2;
+            indices.extend([v, v + 1, v + 2, v + 1, v + 3, v + 2]);
+        }
+    }
+}
+
+/// The entity that draws a [`Trail`], in world space.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct TrailRibbon {
+    /// The entity with the [`Trail`], until it is despawned or the trail is removed.
+    owner: Option<Entity>,
+    trail: Trail,
+    points: TrailPoints,
+}
+
+/// Ribbons on the entities they follow, so each trail has one.
+#[derive(Component, Debug, Clone, Copy)]
+struct HasRibbon;
+
+/// Shared by all ribbons, with meshes from finished ribbons ready to be reused.
+#[derive(Resource)]
+struct TrailMeshes {
+    material: Handle<ColorMaterial>,
+    free: Vec<Handle<Mesh>>,
+}
+
+impl FromWorld for TrailMeshes {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        Self {
+            // White, so the vertex colors show as they are.
+            material: materials.add(ColorMaterial::default()),
+            free: Vec::new(),
+        }
+    }
+}
+
+fn empty_ribbon_mesh() -> Mesh {
+    // Kept in the main world too, so it can be rewritten every frame.
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new())
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, Vec::<[f32; 4]>::new())
+    .with_inserted_indices(Indices::U32(Vec::new()))
+}
+
+fn attach_ribbons(
+    mut commands: Commands,
+    mut trail_meshes: ResMut<TrailMeshes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    trail_query: Query<(Entity, &Trail), (With<RewindHistory>, Without<HasRibbon>)>,
+) {
+    for (entity, trail) in &trail_query {
+        let mesh = trail_meshes
+            .free
+            .pop()
+            .unwrap_or_else(|| meshes.add(empty_ribbon_mesh()));
+        commands.entity(entity).insert(HasRibbon);
+        commands.spawn((
+            Name::new("Trail"),
+            TrailRibbon {
+                owner: Some(entity),
+                trail: *trail,
+                points: TrailPoints::default(),
+            },
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(mesh),
+                material: trail_meshes.material.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            // The mesh moves with its entity, far from where its bounds were computed.
+            NoFrustumCulling,
+            OnLayer::new(Layer::Fx),
+        ));
+    }
+}
+
+fn follow_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    fixed_time: Res<Time<Fixed>>,
+    mut trail_meshes: ResMut<TrailMeshes>,
+    mut ribbon_query: Query<(Entity, &mut TrailRibbon, &Mesh2dHandle)>,
+    owner_query: Query<(&Trail, &GlobalTransform, &RewindHistory)>,
+) {
+    let dt = time.delta_seconds();
+    let tick = fixed_time.timestep().as_secs_f32();
+    let overstep = fixed_time.overstep().as_secs_f32();
+    for (entity, mut ribbon, mesh) in &mut ribbon_query {
+        let owner = ribbon.owner.and_then(|owner| owner_query.get(owner).ok());
+        match owner {
+            Some((trail, transform, history)) => {
+                // Settings changed on the owner apply to the whole ribbon.
+                ribbon.trail = *trail;
+                ribbon.points.follow(
+                    transform.translation().truncate(),
+                    history.iter().rev(),
+                    overstep,
+                    tick,
+                    trail.lifetime,
+                );
+            }
+            None => {
+                // The trail was removed, so its entity may get a new one later.
+                if let Some(mut owner) = ribbon
+                    .owner
+                    .take()
+                    .and_then(|owner| commands.get_entity(owner))
+                {
+                    owner.remove::<HasRibbon>();
+                }
+            }
+        }
+        if ribbon.owner.is_some() {
+            continue;
+        }
+        let lifetime = ribbon.trail.lifetime;
+        ribbon.points.age(dt, lifetime);
+        if ribbon.points.is_empty() {
+            trail_meshes.free.push(mesh.0.clone());
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn build_ribbons(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut ribbon_query: Query<(&TrailRibbon, &Mesh2dHandle, &mut Visibility)>,
+) {
+    for (ribbon, mesh, mut visibility) in &mut ribbon_query {
+        let shown = ribbon.points.len() >= 2;
+        let target = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != target {
+            *visibility = target;
+        }
+        if !shown {
+            continue;
+        }
+        let Some(mesh) = meshes.get_mut(&mesh.0) else {
+            continue;
+        };
+        // Reuse the mesh's own buffers.
+        let mut positions = take_attribute(mesh, Mesh::ATTRIBUTE_POSITION, |values| match values {
+            VertexAttributeValues::Float32x3(values) => Some(values),
+            _ => None,
+        });
+        let mut colors = take_attribute(mesh, Mesh::ATTRIBUTE_COLOR, |values| match values {
+            VertexAttributeValues::Float32x4(values) => Some(values),
+            _ => None,
+        });
+        let mut indices = match mesh.remove_indices() {
+            Some(Indices::U32(indices)) => indices,
+            _ => Vec::new(),
+        };
+        ribbon
+            .points
+            .write_ribbon(&ribbon.trail, &mut positions, &mut colors, &mut indices);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh.insert_indices(Indices::U32(indices));
+    }
+}
+
+/// Remove an attribute's values from the mesh, to refill and insert them again.
+fn take_attribute<T>(
+    mesh: &mut Mesh,
+    attribute: MeshVertexAttribute,
+    values: impl FnOnce(VertexAttributeValues) -> Option<Vec<T>>,
+) -> Vec<T> {
+    mesh.remove_attribute(attribute)
+        .and_then(values)
+        .unwrap_or_default()
+}
+
+/// Despawn all ribbons when leaving the screen, and put their meshes back in the pool.
+fn release_ribbons(
+    mut commands: Commands,
+    mut trail_meshes: ResMut<TrailMeshes>,
+    ribbon_query: Query<(Entity, &Mesh2dHandle), With<TrailRibbon>>,
+) {
+    for (entity, mesh) in &ribbon_query {
+        trail_meshes.free.push(mesh.0.clone());
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trails_follow_the_rewind_history_and_forget_old_points() {
+        let trail = Trail::new(10.0, 1.0, Color::WHITE);
+        let snapshot = |x| Snapshot {
+            position: Vec2::new(x, 0.0),
+            ..default()
+        };
+        let mut history = RewindHistory::new(8);
+        for x in [-100.0, 1.0, 0.0, 100.0] {
+            history.push(snapshot(x));
+        }
+        let mut points = TrailPoints::default();
+        // The oldest snapshot is too old, and the one at 1 too close to the one at 0.
+        let head = Vec2::new(200.0, 0.0);
+        points.follow(head, history.iter().rev(), 0.25, 0.25, trail.lifetime);
+        assert_eq!(points.len(), 3);
+
+        let (mut positions, mut colors, mut indices) = (vec![], vec![], vec![]);
+        points.write_ribbon(&trail, &mut positions, &mut colors, &mut indices);
+        assert_eq!((positions.len(), colors.len(), indices.len()), (6, 6, 12));
+        // Full width at the head, half at the point that is halfway through its lifetime.
+        assert_eq!(positions[0], [200.0, 5.0, 0.0]);
+        assert_eq!(positions[4], [0.0, 2.5, 0.0]);
+
+        let capacity = positions.capacity();
+        points.age(0.6, trail.lifetime);
+        assert_eq!(points.len(), 2);
+        points.write_ribbon(&trail, &mut positions, &mut colors, &mut indices);
+        assert_eq!(positions.len(), 4);
+        assert_eq!(positions.capacity(), capacity);
+    }
+}
@@ -0,0 +1,186 @@
+//! Prompts that teach an action the first time the player needs it, like moving when a
+//! run starts or rewinding after getting hit. Each prompt names the key or button of the
+//! device the player is using, see [`ActionPrompts`], and goes away once the action is used.
+//!
+//! The [`Lesson`]s learned are kept in the [`Profile`], so returning players aren't shown
+//! them again. Using an action before being prompted for it also counts.
+//! The HUD shows the current prompt of the [`TutorialPrompts`], see `screen::playing::hud`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    cycle::CyclePhase,
+    input::{Action, ActionInput, ActionMode, ActionModes, ActionPrompts, InputSource},
+    profile::Profile,
+    spawn::player::Player,
+};
+use crate::{
+    events::{DamageTaken, PhaseChanged, WaveStarted},
+    screen::{PlayingState, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Lesson>();
+    app.init_resource::<TutorialPrompts>();
+    app.add_systems(OnExit(Screen::Playing), clear_prompts);
+    app.add_systems(
+        Update,
+        (
+            learn_lessons.in_set(AppSet::RecordInput),
+            need_lessons.in_set(AppSet::HandleEvents),
+        )
+            .run_if(in_state(PlayingState::Running)),
+    );
+}
+
+/// Something the tutorial teaches, by prompting for one or more actions.
+#[derive(Serialize, Deserialize, Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lesson {
+    Move,
+    Sprint,
+    AdvanceCycle,
+    Rewind,
+}
+
+impl Lesson {
+    pub const ALL: [Lesson; 4] = [
+        Lesson::Move,
+        Lesson::Sprint,
+        Lesson::AdvanceCycle,
+        Lesson::Rewind,
+    ];
+
+    /// The actions that count as having learned it, in the order they are prompted.
+    pub fn actions(self) -> &'static [Action] {
+        match self {
+            Lesson::Move => &[
+                Action::MoveUp,
+                Action::MoveLeft,
+                Action::MoveDown,
+                Action::MoveRight,
+            ],
+            Lesson::Sprint => &[Action::Sprint],
+            Lesson::AdvanceCycle => &[Action::AdvanceCycle],
+            Lesson::Rewind => &[Action::Rewind],
+        }
+    }
+
+    /// What the action does, to finish a prompt like "Press E to ...".
+    fn goal(self) -> &'static str {
+        match self {
+            Lesson::Move => "move",
+            Lesson::Sprint => "sprint",
+            Lesson::AdvanceCycle => "advance the cycle",
+            Lesson::Rewind => "rewind time",
+        }
+    }
+
+    /// The prompt for the device the player is using, like "Hold Shift to sprint".
+    pub fn prompt(self, prompts: &ActionPrompts, modes: &ActionModes) -> String {
+        let action = self.actions()[0];
+        if self == Lesson::Move {
+            return match prompts.source(action) {
+                InputSource::Keyboard => {
+                    let keys = self
+                        .actions()
+                        .iter()
+                        .map(|&action| prompts.prompt(action))
+                        .collect::<Vec<_>>();
+                    format!("Press {} to move", keys.join(" "))
+                }
+                InputSource::Gamepad => "Use the left stick to move".to_string(),
+                InputSource::Touch => "Drag the joystick to move".to_string(),
+            };
+        }
+        let verb = if Action::SUSTAINED.contains(&action) && modes.get(action) == ActionMode::Hold {
+            "Hold"
+        } else {
+            "Press"
+        };
+        format!("{verb} {} to {}", prompts.prompt(action), self.goal())
+    }
+}
+
+/// Lessons waiting to be prompted for, in order. The first one is shown.
+#[derive(Resource, Debug, Default)]
+pub struct TutorialPrompts(Vec<Lesson>);
+
+impl TutorialPrompts {
+    pub fn current(&self) -> Option<Lesson> {
+        self.0.first().copied()
+    }
+
+    /// Whether `lesson` should be added, as it is neither learned nor already waiting.
+    fn needs(&self, lesson: Lesson, profile: &Profile) -> bool {
+        !profile.lessons.contains(&lesson) && !self.0.contains(&lesson)
+    }
+}
+
+fn clear_prompts(mut prompts: ResMut<TutorialPrompts>) {
+    prompts.0.clear();
+}
+
+fn learn_lessons(
+    actions: ActionInput,
+    mut profile: ResMut<Profile>,
+    mut prompts: ResMut<TutorialPrompts>,
+) {
+    for lesson in Lesson::ALL {
+        let used = if lesson == Lesson::Move {
+            actions.movement() != Vec2::ZERO
+        } else {
+            lesson
+                .actions()
+                .iter()
+                .any(|&action| actions.just_pressed(action))
+        };
+        if !used {
+            continue;
+        }
+        // Checked first, so the profile is only saved when something was learned.
+        if prompts.0.contains(&lesson) {
+            prompts.0.retain(|&waiting| waiting != lesson);
+        }
+        if !profile.lessons.contains(&lesson) {
+            profile.lessons.insert(lesson);
+        }
+    }
+}
+
+/// Prompt for the lessons that the game just started to need.
+fn need_lessons(
+    mut wave_events: EventReader<WaveStarted>,
+    mut phase_events: EventReader<PhaseChanged>,
+    mut damage_events: EventReader<DamageTaken>,
+    cycle: Option<Res<CyclePhase>>,
+    profile: Res<Profile>,
+    mut prompts: ResMut<TutorialPrompts>,
+    player_query: Query<(), With<Player>>,
+) {
+    let phase_changed = phase_events.read().count() > 0;
+    let needed = [
+        // Right away.
+        (Lesson::Move, true),
+        // When enemies come after the player.
+        (Lesson::Sprint, wave_events.read().count() > 0),
+        // Once the cycle is seen moving on its own.
+        (
+            Lesson::AdvanceCycle,
+            phase_changed && cycle.is_some_and(|cycle| cycle.advances_on_action()),
+        ),
+        // After getting hit.
+        (
+            Lesson::Rewind,
+            damage_events
+                .read()
+                .any(|event| !event.from_status && player_query.contains(event.target)),
+        ),
+    ];
+    for (lesson, needed) in needed {
+        if needed && prompts.needs(lesson, &profile) {
+            prompts.0.push(lesson);
+        }
+    }
+}
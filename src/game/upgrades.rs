@@ -0,0 +1,148 @@
+//! Upgrade cards, defined in `assets/upgrades/upgrades.ron` and bought with coins in the
+//! shop at the end of each cycle, see `screen::upgrade`.
+//!
+//! The run's [`SaveGame`] keeps the ids of the cards bought so far, and their combined
+//! effects are the [`Upgrades`] resource. Unlike [`Modifiers`](super::mutators::Modifiers),
+//! which are fixed at spawn, upgrades change during a run, so the systems they affect
+//! read them as multipliers every tick.
+
+use bevy::prelude::*;
+use rand::{seq::index, Rng};
+use serde::Deserialize;
+
+use super::{assets::parse_embedded, save::SaveGame};
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(UpgradeCatalog::load());
+    app.init_resource::<Upgrades>();
+    app.add_systems(OnEnter(Screen::Playing), resume_upgrades);
+}
+
+/// Id of the item that upgrades are paid in, see `inventory`.
+pub const CURRENCY: &str = "coin";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct UpgradeCard {
+    /// Stays the same when the name changes, since it is saved.
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// How many of the [`CURRENCY`] it costs.
+    pub cost: u32,
+    pub effects: Vec<UpgradeEffect>,
+}
+
+/// What an upgrade changes, for the rest of the run.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum UpgradeEffect {
+    /// Multiplies how fast the player moves.
+    PlayerSpeed(f32),
+    /// Multiplies the damage the player takes.
+    DamageTaken(f32),
+    /// Multiplies how long enemies wait between attacks.
+    EnemyCooldown(f32),
+}
+
+/// All upgrade cards from `assets/upgrades/upgrades.ron`.
+#[derive(Resource, Debug, Default)]
+pub struct UpgradeCatalog(pub Vec<UpgradeCard>);
+
+impl UpgradeCatalog {
+    const SOURCE: &'static str = include_str!("../../assets/upgrades/upgrades.ron");
+
+    pub fn load() -> Self {
+        Self(parse_embedded(Self::SOURCE, "upgrades"))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&UpgradeCard> {
+        self.0.iter().find(|card| card.id == id)
+    }
+
+    /// Indices of up to `count` different cards, in random order.
+    pub fn offer(&self, rng: &mut impl Rng, count: usize) -> Vec<usize> {
+        index::sample(rng, self.0.len(), count.min(self.0.len())).into_vec()
+    }
+}
+
+/// The combined effect of the upgrades bought during the current run.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Upgrades {
+    pub player_speed: f32,
+    pub damage_taken: f32,
+    pub enemy_cooldown: f32,
+}
+
+impl Default for Upgrades {
+    fn default() -> Self {
+        Self {
+            player_speed: 1.0,
+            damage_taken: 1.0,
+            enemy_cooldown: 1.0,
+        }
+    }
+}
+
+impl Upgrades {
+    /// The effects of the cards with `ids`, which may repeat. Unknown ids are skipped.
+    pub fn from_cards(catalog: &UpgradeCatalog, ids: &[String]) -> Self {
+        let mut upgrades = Self::default();
+        let all = ids.iter().filter_map(|id| catalog.get(id));
+        for &effect in all.flat_map(|card| &card.effects) {
+            match effect {
+                UpgradeEffect::PlayerSpeed(factor) => upgrades.player_speed *= factor,
+                UpgradeEffect::DamageTaken(factor) => upgrades.damage_taken *= factor,
+                UpgradeEffect::EnemyCooldown(factor) => upgrades.enemy_cooldown *= factor,
+            }
+        }
+        upgrades
+    }
+}
+
+/// Continues with the upgrades of the run being played, or none for a new one.
+fn resume_upgrades(
+    catalog: Res<UpgradeCatalog>,
+    save: Res<SaveGame>,
+    mut upgrades: ResMut<Upgrades>,
+) {
+    *upgrades = Upgrades::from_cards(&catalog, &save.upgrades);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        inventory::{Inventory, ItemCatalog},
+        rng::GameRng,
+    };
+
+    #[test]
+    fn upgrades_are_paid_in_coins_and_multiply() {
+        let items = ItemCatalog::load();
+        let catalog = UpgradeCatalog::load();
+        assert!(items.get(CURRENCY).is_some());
+        assert!(catalog.0.iter().all(|card| !card.effects.is_empty()));
+
+        // Coins are taken from the last stacks first, and only if there are enough.
+        let coin = items.get(CURRENCY).unwrap();
+        let mut inventory = Inventory::default();
+        inventory.add(coin, coin.max_stack + 10);
+        assert!(!inventory.spend(CURRENCY, coin.max_stack + 11));
+        assert!(inventory.spend(CURRENCY, 15));
+        assert_eq!(inventory.count(CURRENCY), coin.max_stack - 5);
+        assert_eq!(inventory.stacks.len(), 1);
+
+        let ids = ["swift_feet", "swift_feet", "tailwind", "unknown"].map(String::from);
+        let upgrades = Upgrades::from_cards(&catalog, &ids);
+        assert!((upgrades.player_speed - 1.15 * 1.15 * 1.3).abs() < 1e-4);
+        assert!((upgrades.damage_taken - 1.1).abs() < 1e-4);
+        assert_eq!(upgrades.enemy_cooldown, 1.0);
+
+        let offer = catalog.offer(GameRng::new(7).loot(), 3);
+        assert_eq!(offer.len(), 3.min(catalog.0.len()));
+        assert!(offer
+            .iter()
+            .enumerate()
+            .all(|(i, index)| !offer[..i].contains(index)));
+    }
+}
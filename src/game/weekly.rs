@@ -0,0 +1,246 @@
+//! The weekly challenge: a run with a seed and mutators that everyone gets for the same
+//! ISO week, picked by [`WeeklyChallenge::for_week`] from the week alone, so it needs no
+//! server. Weeks start on Monday at midnight UTC.
+//!
+//! A weekly run is a normal run whose [`SaveGame`] remembers its week. Its scores go in
+//! the week's own high score tables, and the best score and attempts of each week are
+//! kept separately in [`WeeklyRecords`].
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use super::{
+    mode::GameMode,
+    mutators::MutatorCatalog,
+    rng::NextRunSeed,
+    save::{ActiveSlot, SaveGame},
+};
+use crate::storage;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(storage::load::<WeeklyRecords>(WEEKLY_KEY).unwrap_or_default());
+    app.add_systems(
+        Update,
+        save_records.run_if(resource_changed::<WeeklyRecords>),
+    );
+}
+
+/// Key under which [`WeeklyRecords`] are persisted.
+const WEEKLY_KEY: &str = "weekly";
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+/// How many mutators a challenge has, at most.
+const MAX_CHALLENGE_MUTATORS: usize = 2;
+
+/// A week of the ISO calendar, like 2026-W42.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IsoWeek {
+    /// The year the week's Thursday is in, which differs from the calendar year
+    /// for some days around new year.
+    pub year: i32,
+    /// From 1 to 53.
+    pub week: u32,
+}
+
+impl IsoWeek {
+    /// The week containing `seconds` since the Unix epoch, in UTC.
+    pub fn from_unix(seconds: i64) -> Self {
+        let days = seconds.div_euclid(SECONDS_PER_DAY);
+        // 1970-01-01 was a Thursday, so weeks start 3 days before multiples of 7.
+        let monday = (days + 3).div_euclid(7) * 7 - 3;
+        let thursday = monday + 3;
+        let year = year_of_days(thursday);
+        let week = (thursday - days_of_new_year(year)) / 7 + 1;
+        Self {
+            year,
+            week: week as u32,
+        }
+    }
+
+    /// The current week, by the system clock.
+    pub fn now() -> Self {
+        Self::from_unix(unix_now())
+    }
+
+    /// Like "2026-W42".
+    pub fn name(self) -> String {
+        format!("{}-W{:02}", self.year, self.week)
+    }
+}
+
+/// Seconds since the Unix epoch, by the system clock.
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// Seconds from `seconds` since the Unix epoch until the next week starts.
+pub fn seconds_until_rotation(seconds: i64) -> i64 {
+    let days = seconds.div_euclid(SECONDS_PER_DAY);
+    let next_monday = (days + 3).div_euclid(7) * 7 - 3 + 7;
+    next_monday * SECONDS_PER_DAY - seconds
+}
+
+/// Like "3d 4h 12m", or "4h 12m 9s" in the last day.
+pub fn format_countdown(seconds: i64) -> String {
+    let (days, hours) = (seconds / SECONDS_PER_DAY, seconds / 3600 % 24);
+    let (minutes, seconds) = (seconds / 60 % 60, seconds % 60);
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else {
+        format!("{hours}h {minutes}m {seconds}s")
+    }
+}
+
+/// The proleptic Gregorian year of `days` since the Unix epoch.
+fn year_of_days(days: i64) -> i32 {
+    // Howard Hinnant's `civil_from_days`, counting years from March.
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let year = year_of_era + era * 400;
+    // January and February belong to the next year.
+    (year + i64::from(month_from_march >= 10)) as i32
+}
+
+/// Days since the Unix epoch of January 1st of `year`.
+fn days_of_new_year(year: i32) -> i64 {
+    // Howard Hinnant's `days_from_civil`, where January is in the year before.
+    let year = i64::from(year) - 1;
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = 306;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The run everyone gets in a week.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeeklyChallenge {
+    pub week: IsoWeek,
+    pub seed: u64,
+    /// Ids of the mutators, sorted.
+    pub mutators: Vec<String>,
+}
+
+impl WeeklyChallenge {
+    /// The challenge of `week`, the same on every device with the same mutators.
+    pub fn for_week(week: IsoWeek, catalog: &MutatorCatalog) -> Self {
+        let key = i64::from(week.year) * 100 + i64::from(week.week);
+        let mut rng = ChaCha8Rng::seed_from_u64(key as u64);
+        let seed = rng.gen();
+        let count = rng.gen_range(1..=MAX_CHALLENGE_MUTATORS.min(catalog.0.len()).max(1));
+        let mut mutators: Vec<_> = catalog
+            .0
+            .choose_multiple(&mut rng, count)
+            .map(|mutator| mutator.id.clone())
+            .collect();
+        mutators.sort();
+        Self {
+            week,
+            seed,
+            mutators,
+        }
+    }
+
+    /// Start a run of this challenge. Like endless runs, it isn't saved to a slot.
+    pub fn start(&self, commands: &mut Commands) {
+        commands.insert_resource(GameMode::Normal);
+        commands.insert_resource(SaveGame {
+            mutators: self.mutators.clone(),
+            weekly: Some(self.week),
+            ..default()
+        });
+        commands.insert_resource(NextRunSeed(Some(self.seed)));
+        commands.remove_resource::<ActiveSlot>();
+    }
+}
+
+/// How a week's challenge went.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WeeklyRecord {
+    pub week: IsoWeek,
+    pub best: u64,
+    pub attempts: u32,
+}
+
+/// The weekly challenges played on this device, persisted apart from the high scores.
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct WeeklyRecords(Vec<WeeklyRecord>);
+
+impl WeeklyRecords {
+    pub fn get(&self, week: IsoWeek) -> Option<&WeeklyRecord> {
+        self.0.iter().find(|record| record.week == week)
+    }
+
+    /// Count an attempt at `week`'s challenge that scored `score`.
+    /// Returns whether it beat the week's best.
+    pub fn record(&mut self, week: IsoWeek, score: u64) -> bool {
+        if let Some(record) = self.0.iter_mut().find(|record| record.week == week) {
+            record.attempts += 1;
+            let beaten = score > record.best;
+            record.best = record.best.max(score);
+            return beaten;
+        }
+        self.0.push(WeeklyRecord {
+            week,
+            best: score,
+            attempts: 1,
+        });
+        true
+    }
+}
+
+fn save_records(records: Res<WeeklyRecords>) {
+    storage::save(WEEKLY_KEY, &*records);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weekly_challenges_follow_the_iso_week() {
+        const DAY: i64 = 24 * 60 * 60;
+        let week = |year, week| IsoWeek { year, week };
+        // Friday 2026-10-16, and days around new year that belong to the week's Thursday.
+        assert_eq!(IsoWeek::from_unix(20742 * DAY), week(2026, 42));
+        assert_eq!(IsoWeek::from_unix(18630 * DAY), week(2020, 53));
+        assert_eq!(IsoWeek::from_unix(20087 * DAY), week(2025, 1));
+        assert_eq!(IsoWeek::from_unix(0), week(1970, 1));
+        assert_eq!(week(2026, 42).name(), "2026-W42");
+        // Rotates on Monday 2026-10-19 at midnight UTC.
+        assert_eq!(seconds_until_rotation(20742 * DAY + 60), 3 * DAY - 60);
+        assert_eq!(seconds_until_rotation(20745 * DAY), 7 * DAY);
+
+        let catalog = MutatorCatalog::load();
+        let challenge = WeeklyChallenge::for_week(week(2026, 42), &catalog);
+        assert_eq!(
+            challenge,
+            WeeklyChallenge::for_week(week(2026, 42), &catalog)
+        );
+        assert!(!challenge.mutators.is_empty());
+        assert!(challenge
+            .mutators
+            .iter()
+            .all(|id| catalog.get(id).is_some()));
+        assert_ne!(
+            challenge.seed,
+            WeeklyChallenge::for_week(week(2026, 43), &catalog).seed
+        );
+
+        let mut records = WeeklyRecords::default();
+        assert!(records.record(challenge.week, 100));
+        assert!(!records.record(challenge.week, 50));
+        let record = records.get(challenge.week).unwrap();
+        assert_eq!((record.best, record.attempts), (100, 2));
+    }
+}
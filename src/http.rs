@@ -0,0 +1,166 @@
+//! A minimal HTTP client for online features, with the same API on native and web.
+//! Native builds use `reqwest`, web builds use the browser's `fetch`.
+//!
+//! Requests are futures, so run them with [`SpawnTask`](crate::tasks::SpawnTask)
+//! instead of blocking a system.
+
+use std::{fmt, time::Duration};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// How long a request may take before it fails with [`HttpError::Network`].
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub enum HttpError {
+    /// The request couldn't be sent or the response couldn't be read, including timeouts.
+    Network(String),
+    /// The server responded with a non-success status code.
+    Status(u16),
+    /// The body couldn't be converted from or to JSON.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::Network(e) => write!(f, "network error: {e}"),
+            HttpError::Status(status) => write!(f, "server responded with status {status}"),
+            HttpError::Json(e) => write!(f, "invalid JSON: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+impl From<serde_json::Error> for HttpError {
+    fn from(e: serde_json::Error) -> Self {
+        HttpError::Json(e)
+    }
+}
+
+/// Fetch the body of `url` as text.
+#[allow(dead_code)]
+pub async fn get(url: &str) -> Result<String, HttpError> {
+    backend::send("GET", url, None).await
+}
+
+/// Send `body` with the given content type to `url`, returning the response body as text.
+pub async fn post(url: &str, content_type: &str, body: String) -> Result<String, HttpError> {
+    backend::send("POST", url, Some((content_type, body))).await
+}
+
+/// Fetch `url` and parse its body as JSON.
+#[allow(dead_code)]
+pub async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, HttpError> {
+    Ok(serde_json::from_str(&get(url).await?)?)
+}
+
+/// Send `body` as JSON to `url` and parse the response body as JSON.
+#[allow(dead_code)]
+pub async fn post_json<T: DeserializeOwned>(
+    url: &str,
+    body: &impl Serialize,
+) -> Result<T, HttpError> {
+    let body = serde_json::to_string(body)?;
+    Ok(serde_json::from_str(
+        &post(url, "application/json", body).await?,
+    )?)
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod backend {
+    use std::sync::OnceLock;
+
+    use reqwest::blocking::Client;
+
+    use super::*;
+
+    fn client() -> &'static Client {
+        static CLIENT: OnceLock<Client> = OnceLock::new();
+        CLIENT.get_or_init(|| {
+            Client::builder()
+                .timeout(TIMEOUT)
+                .build()
+                .expect("the HTTP client should build with a static configuration")
+        })
+    }
+
+    // Blocks the task pool thread it runs on, which is fine for the few requests we make.
+    pub(super) async fn send(
+        method: &str,
+        url: &str,
+        body: Option<(&str, String)>,
+    ) -> Result<String, HttpError> {
+        let mut request = match method {
+            "POST" => client().post(url),
+            _ => client().get(url),
+        };
+        if let Some((content_type, body)) = body {
+            request = request
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .body(body);
+        }
+        let response = request
+            .send()
+            .map_err(|e| HttpError::Network(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(HttpError::Status(status.as_u16()));
+        }
+        response
+            .text()
+            .map_err(|e| HttpError::Network(e.to_string()))
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod backend {
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{AbortSignal, Request, RequestInit, Response};
+
+    use super::*;
+
+    fn network_error(e: JsValue) -> HttpError {
+        HttpError::Network(format!("{e:?}"))
+    }
+
+    pub(super) async fn send(
+        method: &str,
+        url: &str,
+        body: Option<(&str, String)>,
+    ) -> Result<String, HttpError> {
+        let window =
+            web_sys::window().ok_or_else(|| HttpError::Network("no window".to_string()))?;
+        let init = RequestInit::new();
+        init.set_method(method);
+        init.set_signal(Some(&AbortSignal::timeout_with_u32(
+            TIMEOUT.as_millis() as u32
+        )));
+        if let Some((_, body)) = &body {
+            init.set_body(&JsValue::from_str(body));
+        }
+        let request = Request::new_with_str_and_init(url, &init).map_err(network_error)?;
+        if let Some((content_type, _)) = body {
+            request
+                .headers()
+                .set("Content-Type", content_type)
+                .map_err(network_error)?;
+        }
+
+        let response: Response = JsFuture::from(window.fetch_with_request(&request))
+            .await
+            .map_err(network_error)?
+            .dyn_into()
+            .map_err(network_error)?;
+        if !response.ok() {
+            return Err(HttpError::Status(response.status()));
+        }
+        JsFuture::from(response.text().map_err(network_error)?)
+            .await
+            .map_err(network_error)?
+            .as_string()
+            .ok_or_else(|| HttpError::Network("response body is not text".to_string()))
+    }
+}
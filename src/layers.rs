@@ -0,0 +1,112 @@
+//! Named layers for everything that is drawn, so features agree on what goes in front of
+//! what instead of picking z-coordinates, z-indices and render layers ad hoc.
+//!
+//! World entities get their z from an [`OnLayer`], within the range that the 2D camera
+//! sees. UI nodes take a global z-index from [`Layer::z_index`]. Cameras that only draw
+//! some layers, like a low-resolution world camera, pick them with [`Layer::render_layers`].
+
+use bevy::{prelude::*, render::view::RenderLayers, transform::TransformSystem};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Layer, OnLayer)>();
+    app.add_systems(
+        PostUpdate,
+        apply_layers.before(TransformSystem::TransformPropagate),
+    );
+}
+
+/// Layers from back to front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+pub enum Layer {
+    /// Scenery behind everything, like the menu backdrop and level decorations.
+    Background,
+    /// The player and everything they interact with.
+    World,
+    /// Particles and other effects, in front of what they come from.
+    Fx,
+    /// Labels, bars and outlines that belong to world entities.
+    WorldUi,
+    /// Menus and the HUD.
+    ScreenUi,
+    /// Dev tools, above everything else.
+    DevOverlay,
+}
+
+/// How much z each world layer spans. Orders within a layer go from 0 up to this.
+pub const LAYER_DEPTH: f32 = 100.0;
+
+/// How many z-indices each UI layer spans.
+const UI_LAYER_DEPTH: i32 = 1000;
+
+impl Layer {
+    /// The z of something on this layer, where `order` sorts it within the layer.
+    /// All layers are within the default 2D camera's view, which spans z from 0 to 999.9.
+    pub fn z(self, order: f32) -> f32 {
+        self as u8 as f32 * LAYER_DEPTH + order.clamp(0.0, LAYER_DEPTH - 1.0)
+    }
+
+    /// The global z-index of a UI node on this layer, where `order` sorts it within the layer.
+    pub fn z_index(self, order: i32) -> ZIndex {
+        ZIndex::Global(self as i32 * UI_LAYER_DEPTH + order.clamp(0, UI_LAYER_DEPTH - 1))
+    }
+
+    /// The render layers that cameras drawing this layer need.
+    /// World layers are all on the default layer, so the main camera draws them.
+    pub fn render_layers(self) -> RenderLayers {
+        RenderLayers::layer(match self {
+            Self::Background | Self::World | Self::Fx | Self::WorldUi => 0,
+            Self::ScreenUi => 1,
+            Self::DevOverlay => 2,
+        })
+    }
+}
+
+/// Keeps a world entity's z on a [`Layer`]. Only for entities without a parent,
+/// since children are placed relative to it and stay on its layer.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct OnLayer {
+    pub layer: Layer,
+    /// Sorts entities within the layer, from back to front.
+    pub order: f32,
+}
+
+impl OnLayer {
+    pub fn new(layer: Layer) -> Self {
+        Self { layer, order: 0.0 }
+    }
+
+    pub fn with_order(mut self, order: f32) -> Self {
+        self.order = order;
+        self
+    }
+}
+
+fn apply_layers(mut layer_query: Query<(&OnLayer, &mut Transform), Changed<OnLayer>>) {
+    for (on_layer, mut transform) in &mut layer_query {
+        transform.translation.z = on_layer.layer.z(on_layer.order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_stack_without_overlapping() {
+        let layers = [
+            Layer::Background,
+            Layer::World,
+            Layer::Fx,
+            Layer::WorldUi,
+            Layer::ScreenUi,
+            Layer::DevOverlay,
+        ];
+        for pair in layers.windows(2) {
+            assert!(pair[0].z(LAYER_DEPTH * 2.0) < pair[1].z(-1.0));
+        }
+        // The default 2D camera sees z from 0 to 999.9.
+        assert!(Layer::Background.z(0.0) >= 0.0);
+        assert!(Layer::DevOverlay.z(LAYER_DEPTH) < 999.9);
+    }
+}
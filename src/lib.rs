@@ -1,13 +1,26 @@
+mod background;
+mod clipboard;
 #[cfg(feature = "dev")]
 mod dev_tools;
+mod display;
+mod events;
 mod game;
+mod http;
+mod layers;
+mod logging;
 mod screen;
+mod storage;
+mod tasks;
+#[cfg(test)]
+mod tests;
 mod ui;
+mod window_placement;
 
-use bevy::{
-    asset::AssetMetaCheck,
-    audio::{AudioPlugin, Volume},
-    prelude::*,
+use bevy::{asset::AssetMetaCheck, audio::Volume, prelude::*};
+use game::{
+    audio::sfx::UiCue,
+    gamepad::RumbleSetting,
+    input::{ActionModes, KeyBindings},
 };
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
@@ -19,16 +32,25 @@ impl Plugin for AppPlugin {
         // Order new `AppStep` variants by adding them here:
         app.configure_sets(
             Update,
-            (AppSet::TickTimers, AppSet::RecordInput, AppSet::Update).chain(),
+            (
+                AppSet::TickTimers,
+                AppSet::RecordInput,
+                AppSet::Update,
+                AppSet::HandleEvents,
+            )
+                .chain(),
+        );
+        // Gameplay runs on fixed ticks, in the same order.
+        app.configure_sets(
+            FixedUpdate,
+            (
+                AppSet::TickTimers,
+                AppSet::RecordInput,
+                AppSet::Update,
+                AppSet::HandleEvents,
+            )
+                .chain(),
         );
-
-        let settings = GameSettings {
-            global_volume_level: VolumeSetting::from_divisor_added(2),
-            soundtrack_volume_level_relative: VolumeSetting::from_divisor_removed(
-                VolumeSetting::DIFF,
-            ),
-            sfx_volume_level_relative: VolumeSetting::from_divisor_removed(VolumeSetting::DIFF / 2),
-        };
 
         // Spawn the main camera.
         app.add_systems(Startup, spawn_camera);
@@ -55,18 +77,47 @@ impl Plugin for AppPlugin {
                     .into(),
                     ..default()
                 })
-                .set(AudioPlugin {
-                    global_volume: GlobalVolume {
-                        volume: (&settings.global_volume_level).into(),
-                    },
-                    ..default()
-                }),
+                .set(logging::log_plugin()),
         );
 
+        // Load stored settings, now that logging is set up to report problems.
+        let (settings, key_bindings, action_modes) =
+            match storage::load::<StoredSettings>(SETTINGS_KEY) {
+                Some(stored) => {
+                    let stored = stored.migrated();
+                    (stored.settings, stored.key_bindings, stored.action_modes)
+                }
+                None => default(),
+            };
+        app.insert_resource(GlobalVolume {
+            volume: (&settings.global_volume_level).into(),
+        });
         app.insert_resource(settings);
+        app.insert_resource(key_bindings);
+        app.insert_resource(action_modes);
+        app.add_systems(
+            Update,
+            save_settings.run_if(
+                resource_changed::<GameSettings>
+                    .or_else(resource_changed::<KeyBindings>)
+                    .or_else(resource_changed::<ActionModes>)
+                    // They were all just loaded, and count as changed on the first frame.
+                    .and_then(not(resource_added::<GameSettings>)),
+            ),
+        );
 
         // Add other plugins.
-        app.add_plugins((game::plugin, screen::plugin, ui::plugin));
+        app.add_plugins((
+            background::plugin,
+            display::plugin,
+            events::plugin,
+            game::plugin,
+            layers::plugin,
+            logging::plugin,
+            screen::plugin,
+            ui::plugin,
+            window_placement::plugin,
+        ));
 
         // Enable dev tools for dev builds.
         #[cfg(feature = "dev")]
@@ -74,9 +125,10 @@ impl Plugin for AppPlugin {
     }
 }
 
-/// High-level groupings of systems for the app in the `Update` schedule.
-/// When adding a new variant, make sure to order it in the `configure_sets`
-/// call above.
+/// High-level groupings of systems for the app in the `Update` schedule, and for
+/// gameplay in the `FixedUpdate` schedule, where they run in the same order.
+/// When adding a new variant, make sure to order it in both `configure_sets`
+/// calls above.
 #[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
 enum AppSet {
     /// Tick timers.
@@ -85,18 +137,35 @@ enum AppSet {
     RecordInput,
     /// Do everything else (consider splitting this into further variants).
     Update,
+    /// React to the gameplay events sent during [`AppSet::Update`].
+    /// See [`events`] for which events exist.
+    HandleEvents,
 }
 
 fn spawn_camera(mut commands: Commands) {
     commands.spawn((
         Name::new("Camera"),
         Camera2dBundle::default(),
+        game::camera::WorldCamera,
+        game::camera::CameraShake::default(),
+        game::camera::CameraZoom::default(),
+    ));
+    // A separate camera for UI, so shaking, zooming or panning the world camera
+    // leaves menus and the HUD alone. It draws after the world, on top of it.
+    commands.spawn((
+        Name::new("UI Camera"),
+        Camera2dBundle {
+            camera: Camera {
+                order: 1,
+                clear_color: ClearColorConfig::None,
+                ..default()
+            },
+            ..default()
+        },
+        game::camera::UiCamera,
+        layers::Layer::ScreenUi.render_layers(),
         // Render all UI to this camera.
-        // Not strictly necessary since we only use one camera,
-        // but if we don't use this component, our UI will disappear as soon
-        // as we add another camera. This includes indirect ways of adding cameras like using
-        // [ui node outlines](https://bevyengine.org/news/bevy-0-14/#ui-node-outline-gizmos)
-        // for debugging. So it's good to have this here for future-proofing.
+        // Without this component, UI would go to whichever camera happens to come first.
         IsDefaultUiCamera,
     ));
 }
@@ -105,9 +174,8 @@ trait Bounded<T>: Deref<Target = u8> {
     const MIN: T;
     const MAX: T;
 }
-#[derive(
-    Reflect, Serialize, Deserialize, Debug, Deref, Copy, Clone, Eq, PartialEq, Ord, PartialOrd,
-)]
+#[derive(Reflect, Serialize, Debug, Deref, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(transparent)]
 struct BoundedU8<const MIN: u8 = 0, const MAX: u8 = 255>(u8);
 impl<const MIN: u8, const MAX: u8> Bounded<u8> for BoundedU8<MIN, MAX> {
     const MIN: u8 = MIN;
@@ -132,6 +200,24 @@ impl<const A: u8, const B: u8> From<u8> for BoundedU8<A, B> {
         Self(value)
     }
 }
+impl<const A: u8, const B: u8> Default for BoundedU8<A, B> {
+    fn default() -> Self {
+        Self(A)
+    }
+}
+// Manual impl so out-of-range values (e.g. from an edited settings file) are rejected instead of
+// silently breaking the bound.
+impl<'de, const A: u8, const B: u8> Deserialize<'de> for BoundedU8<A, B> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        if !(A..=B).contains(&value) {
+            return Err(serde::de::Error::custom(format!(
+                "{value} is outside of the range {A}..={B}"
+            )));
+        }
+        Ok(Self(value))
+    }
+}
 
 trait LevelSetting: Deref<Target: Bounded<u8>> + Sized {
     const MIN: u8 = Self::Target::MIN;
@@ -144,7 +230,8 @@ trait LevelSetting: Deref<Target: Bounded<u8>> + Sized {
     #[allow(unused)]
     fn from_fraction(frac: f32) -> Self {
         assert!((0f32..=1f32).contains(&frac));
-        let diff_proportion = Self::DIFF as f32 * frac;
+        // Round instead of truncating, so `from_fraction(x.fraction())` returns `x`.
+        let diff_proportion = (Self::DIFF as f32 * frac).round();
         Self::from_raw(diff_proportion as u8 + Self::MIN)
     }
     /// Divisor, adding to min
@@ -161,7 +248,6 @@ trait LevelSetting: Deref<Target: Bounded<u8>> + Sized {
         let diff_proportion = Self::DIFF / divisor;
         Self::from_raw(Self::MAX - diff_proportion)
     }
-    #[allow(unused)]
     fn from_max() -> Self {
         Self::from_raw(Self::MAX)
     }
@@ -169,21 +255,104 @@ trait LevelSetting: Deref<Target: Bounded<u8>> + Sized {
     fn percent_display(&self) -> String {
         format!("{:.1}%", self.fraction() * 100f32)
     }
+    /// Levels moved by one press of an adjustment button: a tenth of the range,
+    /// or a tenth of that while fine adjustment is held.
+    fn step(fine: bool) -> u8 {
+        let coarse = (Self::DIFF / 10).max(1);
+        if fine {
+            (coarse / 10).max(1)
+        } else {
+            coarse
+        }
+    }
+    /// This level moved by one [`LevelSetting::step`], clamped to the range.
+    fn stepped(&self, adjustment: BinaryAdjustment, fine: bool) -> Self {
+        let level = *self.deref().deref();
+        let step = Self::step(fine);
+        Self::from_raw(match adjustment {
+            BinaryAdjustment::Up => level.saturating_add(step).min(Self::MAX),
+            BinaryAdjustment::Down => level.saturating_sub(step).max(Self::MIN),
+        })
+    }
     fn from_raw(value: u8) -> Self;
 }
 
 #[derive(Serialize, Deserialize, Deref, Clone, Debug, Eq, PartialEq, Reflect)]
-struct VolumeSetting(BoundedU8<0, 10>);
+struct VolumeSetting(BoundedU8<0, 100>);
 impl LevelSetting for VolumeSetting {
     fn from_raw(value: u8) -> Self {
         Self(value.into())
     }
+    fn percent_display(&self) -> String {
+        if self.is_muted() {
+            "Muted".to_string()
+        } else {
+            format!("{:.1}%", self.fraction() * 100f32)
+        }
+    }
+}
+impl VolumeSetting {
+    fn is_muted(&self) -> bool {
+        *self.0 == Self::MIN
+    }
+    /// Factor applied to amplitude, from 1 at the highest level to 0 when muted.
+    /// Used as is for volumes relative to another one, like sub-buses of the SFX bus.
+    fn gain(&self) -> f32 {
+        /// Attenuation at the lowest level above muted.
+        const MIN_DECIBELS: f32 = -40.0;
+        if self.is_muted() {
+            return 0.0;
+        }
+        // Loudness is perceived logarithmically, so space the levels evenly in decibels.
+        let decibels = MIN_DECIBELS * (1.0 - self.fraction());
+        10f32.powf(decibels / 20.0)
+    }
 }
 impl From<&VolumeSetting> for Volume {
     fn from(value: &VolumeSetting) -> Self {
         const MAX_VOLUME: f32 = 0.35;
         // note: not sure if this is "different" between browser and desktop build
-        Volume::new(value.fraction() * MAX_VOLUME)
+        Volume::new(value.gain() * MAX_VOLUME)
+    }
+}
+
+/// Volume of each UI sound cue, relative to the UI sounds volume.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Reflect)]
+#[serde(default)]
+struct UiCueVolumes {
+    focus_moved: VolumeSetting,
+    edge_reached: VolumeSetting,
+    invalid_action: VolumeSetting,
+    tab_switched: VolumeSetting,
+}
+
+impl Default for UiCueVolumes {
+    fn default() -> Self {
+        Self {
+            focus_moved: VolumeSetting::from_max(),
+            edge_reached: VolumeSetting::from_max(),
+            invalid_action: VolumeSetting::from_max(),
+            tab_switched: VolumeSetting::from_max(),
+        }
+    }
+}
+
+impl UiCueVolumes {
+    fn get(&self, cue: UiCue) -> &VolumeSetting {
+        match cue {
+            UiCue::FocusMoved => &self.focus_moved,
+            UiCue::EdgeReached => &self.edge_reached,
+            UiCue::InvalidAction => &self.invalid_action,
+            UiCue::TabSwitched => &self.tab_switched,
+        }
+    }
+    fn get_mut(&mut self, cue: UiCue) -> &mut VolumeSetting {
+        match cue {
+            UiCue::FocusMoved => &mut self.focus_moved,
+            UiCue::EdgeReached => &mut self.edge_reached,
+            UiCue::InvalidAction => &mut self.invalid_action,
+            UiCue::TabSwitched => &mut self.tab_switched,
+        }
     }
 }
 
@@ -205,5 +374,120 @@ struct GameSettings {
     global_volume_level: VolumeSetting,
     soundtrack_volume_level_relative: VolumeSetting,
     sfx_volume_level_relative: VolumeSetting,
+    /// Volume of UI sounds, relative to the SFX volume.
+    #[serde(default = "VolumeSetting::from_max")]
+    ui_volume_level_relative: VolumeSetting,
+    #[serde(default)]
+    ui_cue_volumes: UiCueVolumes,
+    log_level: logging::LogLevelSetting,
+    rumble_level: RumbleSetting,
+    #[serde(default)]
+    display: display::DisplaySettings,
+    /// Where the window was left, on native builds.
+    #[serde(default)]
+    window: window_placement::WindowPlacement,
+    #[serde(default)]
+    gamepad_layout: game::gamepad::GamepadLayoutSetting,
+    /// Confirm with the east button instead of the south button.
+    #[serde(default)]
+    swap_confirm: display::ToggleSetting,
+    #[serde(default)]
+    high_contrast: display::ToggleSetting,
+    #[serde(default)]
+    text_size: ui::text::TextSizeSetting,
+    #[serde(default)]
+    dyslexic_font: display::ToggleSetting,
+    /// Keep running while the window is unfocused. Ignored on the web.
+    #[serde(default)]
+    run_in_background: display::ToggleSetting,
+    /// What audio does while the window is unfocused. Ignored on the web.
+    #[serde(default)]
+    unfocused_audio: background::UnfocusedAudioSetting,
+    #[serde(default = "display::ToggleSetting::from_max")]
+    screen_shake_enabled: display::ToggleSetting,
+    /// How much gameplay text is announced to screen readers.
+    #[serde(default)]
+    announcement_verbosity: ui::announcer::AnnouncementVerbosity,
+    #[serde(default)]
+    colorblind: ui::palette::ColorblindSetting,
     // could add more settings, e.g. vfxs settings
 }
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            global_volume_level: VolumeSetting::from_divisor_added(2),
+            soundtrack_volume_level_relative: VolumeSetting::from_divisor_removed(10),
+            sfx_volume_level_relative: VolumeSetting::from_divisor_removed(5),
+            ui_volume_level_relative: VolumeSetting::from_max(),
+            ui_cue_volumes: default(),
+            log_level: default(),
+            rumble_level: RumbleSetting::from_max(),
+            display: default(),
+            window: default(),
+            gamepad_layout: default(),
+            swap_confirm: default(),
+            high_contrast: default(),
+            text_size: default(),
+            dyslexic_font: default(),
+            run_in_background: default(),
+            unfocused_audio: default(),
+            screen_shake_enabled: display::ToggleSetting::from_max(),
+            announcement_verbosity: default(),
+            colorblind: default(),
+        }
+    }
+}
+
+/// Key under which [`StoredSettings`] are persisted.
+const SETTINGS_KEY: &str = "settings";
+
+/// Bumped whenever a stored value changes meaning, see [`StoredSettings::migrated`].
+const SETTINGS_VERSION: u32 = 1;
+
+/// Everything that is persisted between sessions in the settings file.
+#[derive(Serialize, Deserialize)]
+struct StoredSettings {
+    /// Missing in files written before versioning was added.
+    #[serde(default)]
+    version: u32,
+    settings: GameSettings,
+    key_bindings: KeyBindings,
+    #[serde(default)]
+    action_modes: ActionModes,
+}
+
+impl StoredSettings {
+    /// Upgrade settings written by an older version of the game.
+    fn migrated(mut self) -> Self {
+        if self.version < 1 {
+            // Volumes used to have 10 levels instead of 100.
+            let settings = &mut self.settings;
+            for volume in [
+                &mut settings.global_volume_level,
+                &mut settings.soundtrack_volume_level_relative,
+                &mut settings.sfx_volume_level_relative,
+            ] {
+                *volume = VolumeSetting::from_raw((*volume.0).min(10) * 10);
+            }
+        }
+        self.version = SETTINGS_VERSION;
+        self
+    }
+}
+
+fn save_settings(
+    settings: Res<GameSettings>,
+    key_bindings: Res<KeyBindings>,
+    action_modes: Res<ActionModes>,
+) {
+    storage::save(
+        SETTINGS_KEY,
+        &StoredSettings {
+            version: SETTINGS_VERSION,
+            settings: settings.clone(),
+            key_bindings: key_bindings.clone(),
+            action_modes: action_modes.clone(),
+        },
+    );
+}
@@ -0,0 +1,245 @@
+//! Log output configuration.
+//! Release builds hide the console on Windows, so native builds additionally
+//! write rotating log files to the platform data directory.
+//!
+//! All output goes through a reloadable [`EnvFilter`], so verbosity can be
+//! changed at runtime through [`LogFilter`] without restarting the game.
+
+use bevy::{
+    log::{
+        tracing_subscriber::{
+            filter::{Directive, LevelFilter},
+            reload, EnvFilter, Layer, Registry,
+        },
+        BoxedLayer, Level, LogPlugin,
+    },
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{BoundedU8, GameSettings, LevelSetting};
+
+/// The [`LogPlugin`] used by the app, with our extra layers hooked in.
+pub(super) fn log_plugin() -> LogPlugin {
+    LogPlugin {
+        // Let everything through Bevy's own filter; ours decides what is shown.
+        level: Level::TRACE,
+        filter: String::new(),
+        custom_layer,
+    }
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<LogLevelSetting>();
+    app.add_systems(
+        Update,
+        apply_log_level_setting.run_if(resource_changed::<GameSettings>),
+    );
+}
+
+/// Directives always appended after the base level, matching Bevy's defaults.
+const DEFAULT_DIRECTIVES: &[&str] = &["wgpu=error", "naga=warn"];
+
+fn custom_layer(app: &mut App) -> Option<BoxedLayer> {
+    let log_filter = LogFilter::new(Level::INFO);
+    let (filter_layer, handle) = reload::Layer::new(
+        // `RUST_LOG` still wins at startup, like it would with Bevy's filter.
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| log_filter.build()),
+    );
+    app.insert_resource(LogFilter {
+        handle: Some(handle),
+        ..log_filter
+    });
+
+    #[cfg(not(target_family = "wasm"))]
+    let file_layer = file::layer(app);
+    #[cfg(target_family = "wasm")]
+    let file_layer: Option<BoxedLayer> = None;
+
+    Some(filter_layer.and_then(file_layer).boxed())
+}
+
+/// Runtime handle to the global log filter.
+/// The filter is made up of a base level plus per-target directives.
+#[derive(Resource)]
+pub struct LogFilter {
+    handle: Option<reload::Handle<EnvFilter, Registry>>,
+    level: Level,
+    directives: Vec<Directive>,
+}
+
+impl LogFilter {
+    fn new(level: Level) -> Self {
+        Self {
+            handle: None,
+            level,
+            directives: Vec::new(),
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    #[allow(unused)] // only used by dev tools
+    pub fn directives(&self) -> &[Directive] {
+        &self.directives
+    }
+
+    pub fn set_level(&mut self, level: Level) {
+        self.level = level;
+        self.apply();
+    }
+
+    /// Add a directive such as `bevy_jam_5::game=trace`, replacing any previous
+    /// directive for the same target. A leading `crate::` is expanded to our crate name.
+    #[allow(unused)] // only used by dev tools
+    pub fn set_directive(&mut self, directive: &str) -> Result<(), String> {
+        let directive = match directive.strip_prefix("crate::") {
+            Some(rest) => format!("{}::{rest}", env!("CARGO_CRATE_NAME")),
+            None => directive.to_string(),
+        };
+        let parsed = directive
+            .parse::<Directive>()
+            .map_err(|e| format!("Invalid directive '{directive}': {e}"))?;
+        let target = directive_target(&directive);
+        self.directives
+            .retain(|d| directive_target(&d.to_string()) != target);
+        self.directives.push(parsed);
+        self.apply();
+        Ok(())
+    }
+
+    /// Remove all directives, keeping the base level.
+    #[allow(unused)] // only used by dev tools
+    pub fn clear_directives(&mut self) {
+        self.directives.clear();
+        self.apply();
+    }
+
+    fn build(&self) -> EnvFilter {
+        let mut filter =
+            EnvFilter::default().add_directive(LevelFilter::from_level(self.level).into());
+        for directive in DEFAULT_DIRECTIVES {
+            filter = filter.add_directive(directive.parse().expect("default directives are valid"));
+        }
+        for directive in &self.directives {
+            filter = filter.add_directive(directive.clone());
+        }
+        filter
+    }
+
+    fn apply(&self) {
+        let Some(handle) = &self.handle else {
+            return;
+        };
+        if let Err(e) = handle.reload(self.build()) {
+            warn!("Could not update log filter: {e}");
+        }
+    }
+}
+
+/// The part of a directive before the level, e.g. `bevy_jam_5::game` in `bevy_jam_5::game=trace`.
+fn directive_target(directive: &str) -> &str {
+    directive.split('=').next().unwrap_or_default()
+}
+
+/// Base log verbosity, from errors only up to everything.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Eq, PartialEq, Reflect)]
+pub(crate) struct LogLevelSetting(pub(crate) BoundedU8<0, 4>);
+
+impl LevelSetting for LogLevelSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl LogLevelSetting {
+    pub(crate) fn level(&self) -> Level {
+        match self.0 .0 {
+            0 => Level::ERROR,
+            1 => Level::WARN,
+            2 => Level::INFO,
+            3 => Level::DEBUG,
+            _ => Level::TRACE,
+        }
+    }
+
+    /// Display the level by name instead of as a percentage.
+    pub(crate) fn name_display(&self) -> String {
+        self.level().to_string()
+    }
+}
+
+impl Default for LogLevelSetting {
+    fn default() -> Self {
+        Self::from_raw(2)
+    }
+}
+
+fn apply_log_level_setting(settings: Res<GameSettings>, log_filter: Option<ResMut<LogFilter>>) {
+    let Some(mut log_filter) = log_filter else {
+        return;
+    };
+    let level = settings.log_level.level();
+    if log_filter.level() != level {
+        log_filter.set_level(level);
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod file {
+    use std::path::PathBuf;
+
+    use bevy::{
+        log::{
+            tracing_subscriber::{fmt, EnvFilter, Layer},
+            BoxedLayer,
+        },
+        prelude::*,
+    };
+    use tracing_appender::{
+        non_blocking::WorkerGuard,
+        rolling::{RollingFileAppender, Rotation},
+    };
+
+    /// Environment variable holding extra filter directives for the log file only,
+    /// using the same syntax as `RUST_LOG` (e.g. `warn,bevy_jam_5::game=debug`).
+    /// Without it, the file receives everything the global [`super::LogFilter`] lets through.
+    const LOG_FILE_FILTER_ENV: &str = "BEVY_JAM_5_LOG";
+    const LOG_FILE_PREFIX: &str = "bevy-jam-5";
+    /// How many daily log files to keep around before the oldest is removed.
+    const MAX_LOG_FILES: usize = 5;
+
+    /// Keeps the background writer thread alive; dropping it flushes the log file.
+    #[derive(Resource)]
+    struct LogFileGuard(#[allow(dead_code)] WorkerGuard);
+
+    pub(super) fn layer(app: &mut App) -> Option<BoxedLayer> {
+        // Logging isn't set up yet, so failures can only go to stderr.
+        let Some(dir) = log_dir() else {
+            eprintln!("No data directory found, logs will not be written to disk.");
+            return None;
+        };
+        let appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix("log")
+            .max_log_files(MAX_LOG_FILES)
+            .build(&dir)
+            .inspect_err(|e| eprintln!("Could not create log file in {dir:?}: {e}"))
+            .ok()?;
+        let (writer, guard) = tracing_appender::non_blocking(appender);
+        app.insert_resource(LogFileGuard(guard));
+
+        let layer = fmt::layer().with_ansi(false).with_writer(writer);
+        Some(match EnvFilter::try_from_env(LOG_FILE_FILTER_ENV) {
+            Ok(filter) => layer.with_filter(filter).boxed(),
+            Err(_) => layer.boxed(),
+        })
+    }
+
+    fn log_dir() -> Option<PathBuf> {
+        Some(crate::storage::data_dir()?.join("logs"))
+    }
+}
@@ -0,0 +1,106 @@
+//! An about screen with build information, asset licenses and recent changes,
+//! accessed from the title screen. Useful when players report bugs.
+
+use bevy::prelude::*;
+
+use super::{credits::ASSET_LICENSES, Screen};
+use crate::{events::ScreenRequest, ui::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::About), enter_about);
+
+    app.register_type::<AboutAction>();
+    app.add_systems(Update, handle_about_action.run_if(in_state(Screen::About)));
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum AboutAction {
+    Back,
+}
+
+const CHANGELOG: &str = include_str!("../../CHANGELOG.md");
+
+/// How many of the newest changelog sections to show.
+const CHANGELOG_SECTIONS: usize = 2;
+
+/// Cargo features this build was compiled with.
+fn enabled_features() -> Vec<&'static str> {
+    [
+        ("dev", cfg!(feature = "dev")),
+        ("dev_native", cfg!(feature = "dev_native")),
+        ("physics", cfg!(feature = "physics")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+/// The newest sections of the changelog, as lines of text without markdown syntax.
+fn changelog_lines() -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut sections = 0;
+    for line in CHANGELOG.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            sections += 1;
+            if sections > CHANGELOG_SECTIONS {
+                break;
+            }
+            lines.push(heading.to_string());
+        } else if let Some(entry) = line.strip_prefix("- ") {
+            // Skip the intro text before the first section.
+            if sections > 0 {
+                lines.push(format!("  {entry}"));
+            }
+        }
+    }
+    lines
+}
+
+fn enter_about(mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::About))
+        .with_children(|children| {
+            children.header("Build");
+            children.label(format!("Version {}", env!("CARGO_PKG_VERSION")));
+            children.label(format!(
+                "Commit {} built on {}",
+                env!("BUILD_GIT_HASH"),
+                env!("BUILD_DATE"),
+            ));
+            let features = enabled_features();
+            children.label(if features.is_empty() {
+                "No features enabled".to_string()
+            } else {
+                format!("Features: {}", features.join(", "))
+            });
+
+            children.header("Asset licenses");
+            for (asset, license) in ASSET_LICENSES {
+                children.label(format!("{asset} - {license}"));
+            }
+
+            children.header("Changelog");
+            for line in changelog_lines() {
+                children.label(line);
+            }
+
+            children.button("Back").insert(AboutAction::Back);
+        });
+}
+
+fn handle_about_action(
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mut button_query: InteractionQuery<&AboutAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                AboutAction::Back => {
+                    screen_requests.send(ScreenRequest::Back);
+                }
+            }
+        }
+    }
+}
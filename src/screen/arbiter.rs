@@ -0,0 +1,94 @@
+//! The single place where [`Screen`] transitions are decided.
+//! Everything else sends a [`ScreenRequest`], which lets us keep a history for
+//! "back" buttons, resolve conflicting requests from the same frame deterministically,
+//! and hand the winner to [`ScreenTransition`] to be animated.
+
+use bevy::prelude::*;
+
+use super::{transition::ScreenTransition, Screen};
+use crate::events::ScreenRequest;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ScreenHistory>();
+    app.add_systems(PreUpdate, arbitrate_screen_requests);
+}
+
+/// Screens that [`ScreenRequest::Back`] returns to, most recent last.
+/// Cleared whenever the title screen is reached.
+#[derive(Resource, Debug, Default)]
+pub struct ScreenHistory(Vec<Screen>);
+
+/// Triggered right before leaving a screen, while its entities still exist,
+/// once the transition has covered it up.
+/// Observe this to save progress or other state that is about to be despawned.
+#[derive(Event, Debug)]
+pub struct ExitingScreen {
+    pub from: Screen,
+    pub to: Screen,
+}
+
+/// When multiple screens are requested in the same frame, the highest priority wins.
+/// Ties go to the first request.
+fn priority(screen: &Screen) -> u8 {
+    match screen {
+        // Quitting to the title must never be overridden.
+        Screen::Title => 2,
+        Screen::Playing => 0,
+        _ => 1,
+    }
+}
+
+fn arbitrate_screen_requests(
+    mut commands: Commands,
+    mut requests: EventReader<ScreenRequest>,
+    mut history: ResMut<ScreenHistory>,
+    screen: Res<State<Screen>>,
+    transition: Option<Res<ScreenTransition>>,
+) {
+    if transition.is_some() {
+        // Input is blocked during transitions, so these are rare, and going along with them
+        // could skip a screen before it was ever visible.
+        for request in requests.read() {
+            warn!("Ignoring {request:?} during a screen transition.");
+        }
+        return;
+    }
+    let mut winner: Option<(Screen, bool)> = None;
+    for request in requests.read() {
+        let (target, is_back) = match request {
+            ScreenRequest::To(target) => (target.clone(), false),
+            ScreenRequest::Back => match history.0.last() {
+                Some(previous) => (previous.clone(), true),
+                None => {
+                    warn!(
+                        "Requested to go back from {:?}, but there is no history.",
+                        screen.get()
+                    );
+                    continue;
+                }
+            },
+        };
+        match &winner {
+            Some((current, _)) if priority(current) >= priority(&target) => {
+                warn!("Ignoring request for {target:?}, {current:?} was requested first.");
+            }
+            _ => winner = Some((target, is_back)),
+        }
+    }
+
+    let Some((target, is_back)) = winner else {
+        return;
+    };
+    let from = screen.get().clone();
+    if target == from {
+        return;
+    }
+    if target == Screen::Title {
+        history.0.clear();
+    } else if is_back {
+        history.0.pop();
+    } else {
+        history.0.push(from.clone());
+    }
+    commands.insert_resource(ScreenTransition::new(from, target));
+}
@@ -0,0 +1,60 @@
+//! The screen shown when the loading screen finds files that differ from the ones the
+//! game was built with, see `game::integrity`. This usually means a download or upload
+//! was cut short, so it says which files and how to get a working copy.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{events::ScreenRequest, game::integrity::AssetMismatches, ui::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::AssetMismatch), enter_asset_mismatch);
+
+    app.register_type::<AssetMismatchAction>();
+    app.add_systems(
+        Update,
+        handle_asset_mismatch_action.run_if(in_state(Screen::AssetMismatch)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum AssetMismatchAction {
+    ContinueAnyway,
+}
+
+fn enter_asset_mismatch(mut commands: Commands, mismatches: Res<AssetMismatches>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::AssetMismatch))
+        .with_children(|children| {
+            children.header("Asset mismatch");
+            children.label("Some of the game's files aren't the ones it was built with.");
+            children.label(if cfg!(target_family = "wasm") {
+                "Try reloading the page, or clearing the browser's cache."
+            } else {
+                "Try downloading the game again."
+            });
+            for path in &mismatches.0 {
+                children.label(path.clone());
+            }
+            children
+                .button("Continue anyway")
+                .insert(AssetMismatchAction::ContinueAnyway);
+        });
+}
+
+fn handle_asset_mismatch_action(
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mut button_query: InteractionQuery<&AssetMismatchAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                AssetMismatchAction::ContinueAnyway => {
+                    screen_requests.send(ScreenRequest::To(Screen::Title));
+                }
+            }
+        }
+    }
+}
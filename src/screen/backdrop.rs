@@ -0,0 +1,283 @@
+//! An animated diorama behind the title screen and its menus:
+//! the camera drifts over a small scene while the ducky idles by a campfire in the rain,
+//! and the sky runs through a day cycle. Only spawned while a menu is shown and
+//! the graphics quality is high.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        animation::SpriteAnimation,
+        assets::ImageAssets,
+        camera::WorldCamera,
+        particles::{ParticleEmitter, ParticlePreset},
+        spawn::player::{ducky_atlas_layout, ducky_idle, Player},
+    },
+    layers::{Layer, OnLayer},
+    AppSet, GameSettings,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_computed_state::<MenuBackdrop>();
+    app.enable_state_scoped_entities::<MenuBackdrop>();
+    app.register_type::<Backdrop>();
+    app.add_systems(
+        Update,
+        (
+            sync_backdrop,
+            (animate_backdrop, pan_camera, run_day_cycle)
+                .chain()
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(MenuBackdrop)),
+    );
+    app.add_systems(OnExit(MenuBackdrop), reset_camera);
+}
+
+/// Active while one of the menus with the diorama behind it is shown.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+struct MenuBackdrop;
+
+impl ComputedStates for MenuBackdrop {
+    type SourceStates = Screen;
+
+    fn compute(screen: Screen) -> Option<Self> {
+        matches!(
+            screen,
+            Screen::Title
+                | Screen::SaveSlots
+                | Screen::Settings
+                | Screen::Controls
+                | Screen::Customize
+                | Screen::Profile
+                | Screen::HighScores
+                | Screen::Mutators
+                | Screen::Credits
+                | Screen::About
+        )
+        .then_some(Self)
+    }
+}
+
+/// The root of the diorama, which tracks the time of day.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+struct Backdrop {
+    /// Seconds since the diorama was spawned.
+    elapsed: f32,
+}
+
+/// How long a full day takes in the diorama, in seconds.
+const DAY_LENGTH: f32 = 60.0;
+/// How long the camera takes to drift to one side and back, in seconds.
+const PAN_PERIOD: f32 = 40.0;
+/// How far the camera drifts to either side, in pixels.
+const PAN_DISTANCE: f32 = 400.0;
+
+const DAY_SKY: Color = Color::srgb(0.45, 0.7, 0.95);
+const NIGHT_SKY: Color = Color::srgb(0.05, 0.06, 0.18);
+/// Tint of the scene at the darkest point of the night.
+const NIGHT_TINT: Color = Color::srgb(0.35, 0.38, 0.6);
+
+/// Parts of the scene that get darker at night.
+#[derive(Component)]
+struct DayTinted {
+    color: Color,
+}
+
+#[derive(Component)]
+struct Sky;
+
+/// Spawn or despawn the diorama to match the quality setting.
+/// This also spawns it when a menu is entered, so its assets are only set up when needed.
+fn sync_backdrop(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    images: Res<ImageAssets>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    backdrop_query: Query<Entity, With<Backdrop>>,
+    camera_query: Query<&mut Transform, With<WorldCamera>>,
+) {
+    let enabled = settings.display.quality.is_high();
+    match backdrop_query.get_single() {
+        Ok(backdrop) if !enabled => {
+            commands.entity(backdrop).despawn_recursive();
+            reset_camera(camera_query);
+        }
+        Err(_) if enabled => {
+            spawn_backdrop(&mut commands, &images, &mut texture_atlas_layouts);
+        }
+        _ => (),
+    }
+}
+
+fn spawn_backdrop(
+    commands: &mut Commands,
+    images: &ImageAssets,
+    texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
+) {
+    let player_animation = ducky_idle();
+    commands
+        .spawn((
+            Name::new("Menu Backdrop"),
+            Backdrop::default(),
+            SpatialBundle::default(),
+            OnLayer::new(Layer::Background),
+            StateScoped(MenuBackdrop),
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Sky"),
+                Sky,
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: DAY_SKY,
+                        custom_size: Some(Vec2::new(8000.0, 4000.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, 0.0),
+                    ..default()
+                },
+            ));
+            let ground = Color::srgb(0.3, 0.55, 0.25);
+            children.spawn((
+                Name::new("Ground"),
+                DayTinted { color: ground },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: ground,
+                        custom_size: Some(Vec2::new(8000.0, 2000.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, -1128.0, 1.0),
+                    ..default()
+                },
+            ));
+            // Reeds along the water's edge, spaced unevenly so the drift doesn't look tiled.
+            let reeds = Color::srgb(0.2, 0.4, 0.2);
+            for (i, x) in [-1100.0, -820.0, -610.0, -240.0, 260.0, 470.0, 790.0, 1150.0]
+                .into_iter()
+                .enumerate()
+            {
+                let height = 90.0 + 40.0 * (i % 3) as f32;
+                children.spawn((
+                    Name::new("Reed"),
+                    DayTinted { color: reeds },
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: reeds,
+                            custom_size: Some(Vec2::new(16.0, height)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(x, -128.0 + height / 2.0, 2.0),
+                        ..default()
+                    },
+                ));
+            }
+            // Not tinted, since the fire glows at night.
+            children.spawn((
+                Name::new("Campfire"),
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::srgb(0.9, 0.35, 0.1),
+                        custom_size: Some(Vec2::new(48.0, 16.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(180.0, -120.0, 2.0),
+                    ..default()
+                },
+                ParticleEmitter::new(ParticlePreset::EMBERS),
+            ));
+            children.spawn((
+                Name::new("Rain"),
+                SpatialBundle::from_transform(Transform::from_xyz(0.0, 400.0, 0.0)),
+                ParticleEmitter::new(ParticlePreset::RAIN),
+            ));
+            children.spawn((
+                Name::new("Ducky"),
+                DayTinted {
+                    color: Color::WHITE,
+                },
+                SpriteBundle {
+                    texture: images.ducky.clone_weak(),
+                    transform: Transform::from_xyz(0.0, 0.0, 3.0)
+                        .with_scale(Vec2::splat(8.0).extend(1.0)),
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: texture_atlas_layouts.add(ducky_atlas_layout()),
+                    index: player_animation.atlas_index(),
+                },
+                player_animation,
+            ));
+        });
+}
+
+/// The gameplay animation systems only run during gameplay, so the diorama animates itself.
+fn animate_backdrop(
+    time: Res<Time>,
+    mut backdrop_query: Query<&mut Backdrop>,
+    mut animation_query: Query<(&mut SpriteAnimation, &mut TextureAtlas), Without<Player>>,
+) {
+    for mut backdrop in &mut backdrop_query {
+        backdrop.elapsed += time.delta_seconds();
+    }
+    for (mut animation, mut atlas) in &mut animation_query {
+        animation.tick(time.delta());
+        if animation.changed() {
+            atlas.index = animation.atlas_index();
+        }
+    }
+}
+
+/// Drift the camera from side to side. This moves it by the change in drift,
+/// so the pixel perfect snapping in `game::camera` keeps working.
+fn pan_camera(
+    time: Res<Time>,
+    backdrop_query: Query<&Backdrop>,
+    mut camera_query: Query<&mut Transform, With<WorldCamera>>,
+) {
+    let Ok(backdrop) = backdrop_query.get_single() else {
+        return;
+    };
+    let pan = |elapsed: f32| (elapsed / PAN_PERIOD * TAU).sin() * PAN_DISTANCE;
+    let delta = pan(backdrop.elapsed) - pan(backdrop.elapsed - time.delta_seconds());
+    for mut transform in &mut camera_query {
+        transform.translation.x += delta;
+    }
+}
+
+fn run_day_cycle(
+    backdrop_query: Query<&Backdrop>,
+    mut sky_query: Query<&mut Sprite, (With<Sky>, Without<DayTinted>)>,
+    mut tinted_query: Query<(&mut Sprite, &DayTinted), Without<Sky>>,
+) {
+    let Ok(backdrop) = backdrop_query.get_single() else {
+        return;
+    };
+    // 0 at noon, 1 at midnight.
+    let night = (1.0 - (backdrop.elapsed / DAY_LENGTH * TAU).cos()) / 2.0;
+    for mut sprite in &mut sky_query {
+        sprite.color = DAY_SKY.mix(&NIGHT_SKY, night);
+    }
+    let tint = Color::WHITE.mix(&NIGHT_TINT, night).to_linear();
+    for (mut sprite, tinted) in &mut tinted_query {
+        let color = tinted.color.to_linear();
+        sprite.color = LinearRgba::rgb(
+            color.red * tint.red,
+            color.green * tint.green,
+            color.blue * tint.blue,
+        )
+        .into();
+    }
+}
+
+/// Put the camera back where gameplay expects it.
+fn reset_camera(mut camera_query: Query<&mut Transform, With<WorldCamera>>) {
+    for mut transform in &mut camera_query {
+        transform.translation = transform.translation.with_x(0.0).with_y(0.0);
+    }
+}
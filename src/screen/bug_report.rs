@@ -0,0 +1,331 @@
+//! The bug report form, opened from the pause menu.
+//!
+//! Reports bundle the player's description with a block of diagnostics, a screenshot
+//! taken as the game was paused, and optionally the last few seconds of the player's
+//! movement, with the simulation checksums of those ticks. They are sent as JSON to the
+//! endpoint set by the `BUG_REPORT_URL` environment variable at build time. Without one,
+//! or if sending fails, the report is saved locally.
+
+use std::{
+    io::Cursor,
+    sync::{Arc, Mutex},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{pause::pause_just_pressed, PlayingState};
+use crate::{
+    game::{
+        checksum::{ChecksumRange, SimulationChecksums},
+        rewind::RewindHistory,
+        rng::GameRng,
+        save::SaveGame,
+        snapshot_codec::encode_snapshots,
+        spawn::player::Player,
+    },
+    http::{self, HttpError},
+    storage,
+    tasks::{register_task, SpawnTask, TaskDone},
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PauseScreenshot>();
+    register_task::<SendResult>(app);
+    app.add_systems(
+        Update,
+        capture_screenshot.run_if(in_state(PlayingState::Running).and_then(pause_just_pressed)),
+    );
+    app.add_systems(OnEnter(PlayingState::BugReport), enter_bug_report);
+    app.observe(send_bug_report);
+
+    app.register_type::<BugReportAction>();
+    app.add_systems(
+        Update,
+        handle_bug_report_action.run_if(in_state(PlayingState::BugReport)),
+    );
+}
+
+type SendResult = Result<String, HttpError>;
+
+/// Where reports are sent, if anywhere.
+const ENDPOINT: Option<&str> = option_env!("BUG_REPORT_URL");
+const MAX_DESCRIPTION_CHARS: usize = 500;
+
+/// Everything a bug report sends.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BugReport {
+    pub description: String,
+    pub diagnostics: String,
+    /// A PNG, in base64.
+    pub screenshot: Option<String>,
+    /// Snapshots from `game::snapshot_codec`, in base64.
+    pub replay: Option<String>,
+    /// Simulation checksums of the ticks the replay covers, if they were recorded.
+    #[serde(default)]
+    pub checksums: Option<ChecksumRange>,
+}
+
+/// What the game was doing, for whoever reads the report.
+pub fn diagnostics(save: &SaveGame, seed: u64, checksums: &ChecksumRange) -> String {
+    let mut lines = vec![
+        format!(
+            "Version: {} ({}, built {})",
+            env!("CARGO_PKG_VERSION"),
+            env!("BUILD_GIT_HASH"),
+            env!("BUILD_DATE"),
+        ),
+        format!(
+            "Platform: {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ),
+        format!("Run: {}, seed {seed:#018x}", save.summary()),
+        format!("Ticks: {}", checksums.end()),
+    ];
+    if let Some(checksum) = checksums.checksums.last() {
+        lines.push(format!("Last checksum: {checksum:#018x}"));
+    }
+    lines.join("\n")
+}
+
+/// The screen as it was when the game was last paused, as a PNG.
+/// Taken then, so the pause menu isn't in it.
+#[derive(Resource, Default)]
+struct PauseScreenshot(Arc<Mutex<Option<Vec<u8>>>>);
+
+fn capture_screenshot(
+    mut screenshots: ResMut<ScreenshotManager>,
+    screenshot: Res<PauseScreenshot>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let slot = screenshot.0.clone();
+    *slot.lock().unwrap() = None;
+    let result = screenshots.take_screenshot(window, move |image| {
+        *slot.lock().unwrap() = encode_png(image);
+    });
+    if result.is_err() {
+        warn!("A screenshot is already being taken, so this pause has none.");
+    }
+}
+
+fn encode_png(image: Image) -> Option<Vec<u8>> {
+    let image = image
+        .try_into_dynamic()
+        .inspect_err(|e| warn!("Could not convert the screenshot: {e}"))
+        .ok()?;
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .inspect_err(|e| warn!("Could not encode the screenshot: {e}"))
+        .ok()?;
+    Some(png)
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum BugReportAction {
+    ToggleReplay,
+    Send,
+    Back,
+}
+
+/// The text field with the player's description.
+#[derive(Component)]
+struct DescriptionInput;
+
+/// Whether to attach the replay, which the button toggles.
+#[derive(Component)]
+struct AttachReplay(bool);
+
+/// The label that says how sending went.
+#[derive(Component)]
+struct ReportStatus;
+
+fn enter_bug_report(mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert(StateScoped(PlayingState::BugReport))
+        .with_children(|children| {
+            children.header("Report a bug");
+            children.label("What happened?");
+            children
+                .text_input("", MAX_DESCRIPTION_CHARS)
+                .insert(DescriptionInput);
+            children.label("Diagnostics and a screenshot are attached.");
+            children
+                .button(replay_button_text(true))
+                .insert((BugReportAction::ToggleReplay, AttachReplay(true)));
+            children.button("Send").insert(BugReportAction::Send);
+            children.button("Back").insert(BugReportAction::Back);
+            children.label("").insert(ReportStatus);
+        });
+}
+
+fn replay_button_text(attach: bool) -> String {
+    format!(
+        "Attach recent movement: {}",
+        if attach { "On" } else { "Off" }
+    )
+}
+
+fn handle_bug_report_action(
+    mut commands: Commands,
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+    mut button_query: InteractionQuery<(&BugReportAction, Option<&mut AttachReplay>, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, (action, attach_replay, children)) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        match action {
+            BugReportAction::ToggleReplay => {
+                if let Some(mut attach_replay) = attach_replay {
+                    attach_replay.0 = !attach_replay.0;
+                    set_child_text(
+                        children,
+                        &mut text_query,
+                        replay_button_text(attach_replay.0),
+                    );
+                }
+            }
+            BugReportAction::Send => commands.trigger(SendBugReport),
+            BugReportAction::Back => next_playing_state.set(PlayingState::Paused),
+        }
+    }
+}
+
+/// Replace the text of a widget, which is in its child.
+fn set_child_text(children: &Children, text_query: &mut Query<&mut Text>, value: String) {
+    for &child in children {
+        if let Ok(mut text) = text_query.get_mut(child) {
+            text.sections[0].value = value;
+            return;
+        }
+    }
+}
+
+#[derive(Event)]
+struct SendBugReport;
+
+fn send_bug_report(
+    _trigger: Trigger<SendBugReport>,
+    mut commands: Commands,
+    mut game_rng: ResMut<GameRng>,
+    mut text_query: Query<&mut Text>,
+    status_query: Query<&Children, With<ReportStatus>>,
+    description_query: Query<&TextInput, With<DescriptionInput>>,
+    attach_query: Query<&AttachReplay>,
+    history_query: Query<&RewindHistory, With<Player>>,
+    screenshot: Res<PauseScreenshot>,
+    save: Res<SaveGame>,
+    checksums: Res<SimulationChecksums>,
+) {
+    let attach_replay = attach_query.iter().any(|attach| attach.0);
+    let history = history_query
+        .get_single()
+        .ok()
+        .filter(|history| attach_replay && !history.is_empty());
+    let replay = history.map(|history| {
+        let snapshots: Vec<_> = history.iter().copied().collect();
+        BASE64.encode(encode_snapshots(&snapshots))
+    });
+    let checksums = checksums.ticks();
+    let replay_checksums = history
+        .map(|history| checksums.last(history.len()))
+        .filter(|range| !range.checksums.is_empty());
+    let report = BugReport {
+        description: description_query
+            .iter()
+            .next()
+            .map_or_else(String::new, |input| input.value.clone()),
+        diagnostics: diagnostics(&save, game_rng.seed(), checksums),
+        screenshot: screenshot
+            .0
+            .lock()
+            .unwrap()
+            .as_deref()
+            .map(|png| BASE64.encode(png)),
+        replay,
+        checksums: replay_checksums,
+    };
+    let id = format!("bug_report_{:08x}", game_rng.vfx().gen::<u32>());
+
+    let status = match (ENDPOINT, serde_json::to_string(&report)) {
+        (Some(endpoint), Ok(body)) => {
+            commands
+                .spawn_task(async move { http::post(endpoint, "application/json", body).await })
+                .observe(show_send_result(id, report));
+            "Sending...".to_string()
+        }
+        (_, result) => {
+            if let Err(e) = result {
+                error!("Could not serialize the bug report: {e}");
+            }
+            save_locally(&id, &report)
+        }
+    };
+    for children in &status_query {
+        set_child_text(children, &mut text_query, status.clone());
+    }
+}
+
+/// Say whether the report was sent, saving it locally if it wasn't.
+fn show_send_result(
+    id: String,
+    report: BugReport,
+) -> impl FnMut(Trigger<TaskDone<SendResult>>, Query<&mut Text>, Query<&Children, With<ReportStatus>>)
+{
+    move |trigger, mut text_query, status_query| {
+        let status = match &trigger.event().0 {
+            Ok(_) => "Sent, thank you!".to_string(),
+            Err(e) => {
+                warn!("Could not send the bug report: {e}");
+                save_locally(&id, &report)
+            }
+        };
+        for children in &status_query {
+            set_child_text(children, &mut text_query, status.clone());
+        }
+    }
+}
+
+/// Keep the report in storage, returning a status saying so.
+fn save_locally(id: &str, report: &BugReport) -> String {
+    storage::save(id, report);
+    format!("Saved the report as {id}, thank you!")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bug_reports_describe_the_run() {
+        let checksums = ChecksumRange {
+            first_tick: 5,
+            checksums: vec![1, 0x2a],
+        };
+        let block = diagnostics(&SaveGame::default(), 0xbeef, &checksums);
+        assert!(block.contains(env!("CARGO_PKG_VERSION")));
+        assert!(block.contains("main, 0:00, seed 0x000000000000beef"));
+        assert!(block.contains("Ticks: 7"));
+        assert!(block.contains("Last checksum: 0x000000000000002a"));
+        let report = BugReport {
+            description: "The ducky fell through the floor".to_string(),
+            diagnostics: block,
+            screenshot: None,
+            replay: Some("AQ==".to_string()),
+            checksums: Some(checksums),
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(serde_json::from_str::<BugReport>(&json).unwrap(), report);
+    }
+}
@@ -0,0 +1,426 @@
+//! A screen for rebinding the keys of each game action.
+//! Click a binding, then press the key that should replace it (Escape cancels).
+//! Keys that are already bound elsewhere ask whether to swap or overwrite,
+//! and the screen can't be left while an action has no key.
+//! Sustained actions can also be switched between hold and toggle here.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    events::ScreenRequest,
+    game::{
+        gamepad::GamepadBindings,
+        input::{Action, ActionMode, ActionModes, BindingPresets, KeyBindings, KeyboardLayout},
+    },
+    ui::prelude::*,
+    BinaryAdjustment, GameSettings, LevelSettingAction,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Controls), enter_controls);
+    app.add_systems(OnExit(Screen::Controls), exit_controls);
+
+    app.register_type::<(
+        ControlsAction,
+        ConflictAction,
+        BindingSlot,
+        ActionRow,
+        LevelSettingAction<PresetScope>,
+        LevelSettingAction<ActionModeScope>,
+    )>();
+    app.add_systems(
+        Update,
+        (
+            handle_controls_action,
+            handle_preset_action,
+            handle_action_mode_action,
+            handle_conflict_action,
+            capture_binding,
+            update_binding_labels,
+        )
+            .chain()
+            .run_if(in_state(Screen::Controls)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum ControlsAction {
+    Rebind(BindingSlot),
+    Reset,
+    Back,
+}
+
+/// One of the [`KeyBindings::SLOTS`] bindings of an action.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+struct BindingSlot {
+    action: Action,
+    slot: usize,
+}
+
+/// The row of bindings for an action, whose label is flagged while it has no key.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+struct ActionRow(Action);
+
+/// Marks the preset selector, which cycles through [`BindingPresets`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+struct PresetScope;
+
+/// Marks the hold/toggle selector of a sustained action.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+struct ActionModeScope(Action);
+
+/// Marks the root of the binding list, which is hidden while a conflict is resolved.
+#[derive(Component, Debug)]
+struct ControlsRoot;
+
+/// Marks the label that explains why the screen can't be left.
+#[derive(Component, Debug)]
+struct UnboundWarning;
+
+/// Present while waiting for the player to press the key for a slot.
+#[derive(Resource, Debug)]
+struct AwaitingBinding(BindingSlot);
+
+/// Present while asking what to do about a key that is already bound elsewhere.
+#[derive(Resource, Debug)]
+struct BindingConflict {
+    /// The slot that was being rebound.
+    slot: BindingSlot,
+    key: KeyCode,
+    /// Where `key` is currently bound.
+    existing: BindingSlot,
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum ConflictAction {
+    /// Move the key, and give the other slot the key it replaces.
+    Swap,
+    /// Move the key, leaving the other slot empty.
+    Overwrite,
+    Cancel,
+}
+
+fn enter_controls(
+    mut commands: Commands,
+    bindings: Res<KeyBindings>,
+    presets: Res<BindingPresets>,
+    modes: Res<ActionModes>,
+    layout: Res<KeyboardLayout>,
+    gamepad_bindings: Res<GamepadBindings>,
+    gamepads: Res<Gamepads>,
+    settings: Res<GameSettings>,
+) {
+    let gamepad_layout = settings.gamepad_layout.layout(&gamepads);
+    commands
+        .ui_root()
+        .insert((StateScoped(Screen::Controls), ControlsRoot))
+        .with_children(|children| {
+            children.header("Controls");
+
+            for action in Action::ALL {
+                children
+                    .label(action_text(&bindings, action))
+                    .insert(ActionRow(action))
+                    .with_children(|row| {
+                        for slot in 0..KeyBindings::SLOTS {
+                            let binding = BindingSlot { action, slot };
+                            row.button(binding_text(&layout, bindings.get(action, slot)))
+                                .insert((binding, ControlsAction::Rebind(binding)));
+                        }
+                        // Gamepad bindings can't be changed yet, but show them for reference.
+                        if let Some(button) = gamepad_bindings.get(action) {
+                            row.label(gamepad_layout.button_name(button));
+                        }
+                    });
+            }
+
+            for action in Action::SUSTAINED {
+                children.settings_field(
+                    format!("{} mode", action.name()),
+                    modes.get(action).name_display(),
+                    ActionModeScope(action),
+                );
+            }
+            children.settings_field("Preset", preset_text(&presets, &bindings), PresetScope);
+            children.button("Reset").insert(ControlsAction::Reset);
+            children.label("").insert(UnboundWarning);
+            children.button("Back").insert(ControlsAction::Back);
+        });
+}
+
+fn exit_controls(mut commands: Commands) {
+    commands.remove_resource::<AwaitingBinding>();
+    commands.remove_resource::<BindingConflict>();
+}
+
+fn binding_text(layout: &KeyboardLayout, key: Option<KeyCode>) -> String {
+    key.map_or("-".to_string(), |key| layout.key_name(key))
+}
+
+fn action_text(bindings: &KeyBindings, action: Action) -> String {
+    if bindings.keys(action).next().is_some() {
+        action.name().to_string()
+    } else {
+        format!("{} (unbound!)", action.name())
+    }
+}
+
+fn preset_text(presets: &BindingPresets, bindings: &KeyBindings) -> String {
+    presets
+        .position(bindings)
+        .map_or("Custom".to_string(), |index| presets.0[index].name.clone())
+}
+
+fn handle_preset_action(
+    presets: Res<BindingPresets>,
+    mut bindings: ResMut<KeyBindings>,
+    mut button_query: InteractionQuery<&LevelSettingAction<PresetScope>>,
+) {
+    let count = presets.0.len();
+    for &LevelSettingAction { adjustment, .. } in button_query
+        .iter_mut()
+        .filter_map(|(i, b)| matches!(i, Interaction::Pressed).then_some(b))
+    {
+        // Custom bindings continue from either end of the list.
+        let index = match (presets.position(&bindings), adjustment) {
+            (Some(index), BinaryAdjustment::Up) => (index + 1) % count,
+            (Some(index), BinaryAdjustment::Down) => (index + count - 1) % count,
+            (None, BinaryAdjustment::Up) => 0,
+            (None, BinaryAdjustment::Down) => count - 1,
+        };
+        let preset = &presets.0[index];
+        *bindings = preset.bindings.clone();
+        info!("Applied binding preset '{}'.", preset.name);
+    }
+}
+
+fn handle_action_mode_action(
+    mut modes: ResMut<ActionModes>,
+    mut text_query: Query<(&mut Text, &ActionModeScope)>,
+    mut button_query: InteractionQuery<&LevelSettingAction<ActionModeScope>>,
+) {
+    for &LevelSettingAction { scope, .. } in button_query
+        .iter_mut()
+        .filter_map(|(i, b)| matches!(i, Interaction::Pressed).then_some(b))
+    {
+        let ActionModeScope(action) = scope;
+        // With only two modes, both directions switch to the other one.
+        let mode = match modes.get(action) {
+            ActionMode::Hold => ActionMode::Toggle,
+            ActionMode::Toggle => ActionMode::Hold,
+        };
+        modes.set(action, mode);
+        if let Some((mut text, _)) = text_query.iter_mut().find(|(_, &test)| test == scope) {
+            text.sections[0].value = mode.name_display();
+        }
+        info!("Set {action:?} to {mode:?}.");
+    }
+}
+
+fn handle_controls_action(
+    mut commands: Commands,
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mut bindings: ResMut<KeyBindings>,
+    warning_query: Query<&Children, With<UnboundWarning>>,
+    mut text_query: Query<&mut Text>,
+    mut button_query: InteractionQuery<&ControlsAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                ControlsAction::Rebind(slot) => commands.insert_resource(AwaitingBinding(*slot)),
+                ControlsAction::Reset => *bindings = KeyBindings::default(),
+                ControlsAction::Back => {
+                    if bindings.unbound().next().is_some() {
+                        let mut text_iter =
+                            text_query.iter_many_mut(warning_query.iter().flatten());
+                        while let Some(mut text) = text_iter.fetch_next() {
+                            text.sections[0].value =
+                                "Bind a key to every action before leaving.".to_string();
+                        }
+                        continue;
+                    }
+                    screen_requests.send(ScreenRequest::Back);
+                }
+            }
+        }
+    }
+}
+
+fn capture_binding(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    awaiting: Option<Res<AwaitingBinding>>,
+    layout: Res<KeyboardLayout>,
+    mut bindings: ResMut<KeyBindings>,
+    mut root_query: Query<&mut Visibility, With<ControlsRoot>>,
+) {
+    // Skip the frame the capture started, so the key that pressed the button isn't bound.
+    let Some(awaiting) = awaiting.filter(|awaiting| !awaiting.is_added()) else {
+        return;
+    };
+    let Some(&key) = input.get_just_pressed().next() else {
+        return;
+    };
+    commands.remove_resource::<AwaitingBinding>();
+    let slot = awaiting.0;
+    if key == KeyCode::Escape {
+        return;
+    }
+    match bindings.find(key) {
+        Some((action, existing))
+            if (BindingSlot {
+                action,
+                slot: existing,
+            }) != slot =>
+        {
+            let existing = BindingSlot {
+                action,
+                slot: existing,
+            };
+            // Hide the bindings, so only the dialog can be focused and clicked.
+            for mut visibility in &mut root_query {
+                *visibility = Visibility::Hidden;
+            }
+            spawn_conflict_dialog(&mut commands, &layout, key, action);
+            commands.insert_resource(BindingConflict {
+                slot,
+                key,
+                existing,
+            });
+        }
+        _ => {
+            bindings.set(slot.action, slot.slot, Some(key));
+            info!("Bound {key:?} to {:?} (slot {}).", slot.action, slot.slot);
+        }
+    }
+}
+
+/// Marks the root of the conflict dialog.
+#[derive(Component, Debug)]
+struct ConflictDialog;
+
+fn spawn_conflict_dialog(
+    commands: &mut Commands,
+    layout: &KeyboardLayout,
+    key: KeyCode,
+    action: Action,
+) {
+    commands
+        .ui_root()
+        .insert((StateScoped(Screen::Controls), ConflictDialog))
+        .with_children(|children| {
+            children.header("Key already bound");
+            children.label(format!(
+                "{} is bound to {}.",
+                layout.key_name(key),
+                action.name()
+            ));
+            children.button("Swap").insert(ConflictAction::Swap);
+            children
+                .button("Overwrite")
+                .insert(ConflictAction::Overwrite);
+            children.button("Cancel").insert(ConflictAction::Cancel);
+        });
+}
+
+fn handle_conflict_action(
+    mut commands: Commands,
+    conflict: Option<Res<BindingConflict>>,
+    mut bindings: ResMut<KeyBindings>,
+    dialog_query: Query<Entity, With<ConflictDialog>>,
+    mut root_query: Query<&mut Visibility, With<ControlsRoot>>,
+    mut button_query: InteractionQuery<&ConflictAction>,
+) {
+    let Some(conflict) = conflict else {
+        return;
+    };
+    let Some(&action) = button_query
+        .iter_mut()
+        .find_map(|(i, b)| matches!(i, Interaction::Pressed).then_some(b))
+    else {
+        return;
+    };
+    let BindingConflict {
+        slot,
+        key,
+        existing,
+    } = *conflict;
+    match action {
+        ConflictAction::Swap => {
+            let replaced = bindings.get(slot.action, slot.slot);
+            bindings.set(existing.action, existing.slot, replaced);
+            bindings.set(slot.action, slot.slot, Some(key));
+        }
+        ConflictAction::Overwrite => {
+            bindings.set(existing.action, existing.slot, None);
+            bindings.set(slot.action, slot.slot, Some(key));
+        }
+        ConflictAction::Cancel => (),
+    }
+    info!("Resolved binding conflict for {key:?} with {action:?}.");
+
+    commands.remove_resource::<BindingConflict>();
+    for entity in &dialog_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    for mut visibility in &mut root_query {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+fn update_binding_labels(
+    bindings: Res<KeyBindings>,
+    presets: Res<BindingPresets>,
+    layout: Res<KeyboardLayout>,
+    awaiting: Option<Res<AwaitingBinding>>,
+    slot_query: Query<(&BindingSlot, &Children)>,
+    row_query: Query<(&ActionRow, &Children)>,
+    warning_query: Query<&Children, With<UnboundWarning>>,
+    mut text_query: Query<&mut Text, (Without<PresetScope>, Without<ActionModeScope>)>,
+    mut preset_text_query: Query<&mut Text, With<PresetScope>>,
+) {
+    if bindings.is_changed() {
+        for mut text in &mut preset_text_query {
+            text.sections[0].value = preset_text(&presets, &bindings);
+        }
+        for (&ActionRow(action), children) in &row_query {
+            // The first child is the label text, followed by the binding buttons.
+            if let Some(mut text) = children
+                .first()
+                .and_then(|&child| text_query.get_mut(child).ok())
+            {
+                text.sections[0].value = action_text(&bindings, action);
+            }
+        }
+        if bindings.unbound().next().is_none() {
+            let mut text_iter = text_query.iter_many_mut(warning_query.iter().flatten());
+            while let Some(mut text) = text_iter.fetch_next() {
+                text.sections[0].value.clear();
+            }
+        }
+    }
+
+    for (&binding, children) in &slot_query {
+        let value = if awaiting
+            .as_ref()
+            .is_some_and(|awaiting| awaiting.0 == binding)
+        {
+            "Press a key...".to_string()
+        } else {
+            binding_text(&layout, bindings.get(binding.action, binding.slot))
+        };
+        let mut text_iter = text_query.iter_many_mut(children);
+        while let Some(mut text) = text_iter.fetch_next() {
+            // Only write on change, to avoid relayouting the text every frame.
+            if text.sections[0].value != value {
+                text.sections[0].value.clone_from(&value);
+            }
+        }
+    }
+}
@@ -4,7 +4,8 @@ use bevy::prelude::*;
 
 use super::Screen;
 use crate::{
-    game::{assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack},
+    events::ScreenRequest,
+    game::{assets::SoundtrackKey, audio::soundtrack::SoundtrackCommand},
     ui::prelude::*,
 };
 
@@ -25,6 +26,14 @@ enum CreditsAction {
     Back,
 }
 
+/// Bundled assets and their licenses, also listed on the about screen.
+pub(super) const ASSET_LICENSES: &[(&str, &str)] = &[
+    ("Bevy logo", "All rights reserved by the Bevy Foundation."),
+    ("Ducky sprite", "CC0 by Caz Creates Games"),
+    ("Music", "CC 3.0/4.0 by Kevin MacLeod"),
+    ("OpenDyslexic font", "SIL OFL 1.1 by Abbie Gonzalez"),
+];
+
 fn enter_credits(mut commands: Commands) {
     commands
         .ui_root()
@@ -34,28 +43,30 @@ fn enter_credits(mut commands: Commands) {
             children.label("Mikkel (https://mikkelen.itch.io)");
 
             children.header("Assets");
-            children.label("Bevy logo - All rights reserved by the Bevy Foundation.");
-            children.label("Ducky sprite - CC0 by Caz Creates Games");
-            children.label("Music - CC 3.0/4.0 by Kevin MacLeod");
+            for (asset, license) in ASSET_LICENSES {
+                children.label(format!("{asset} - {license}"));
+            }
 
             children.button("Back").insert(CreditsAction::Back);
         });
 
-    commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Credits));
+    commands.trigger(SoundtrackCommand::Play(SoundtrackKey::Credits));
 }
 
 fn exit_credits(mut commands: Commands) {
-    commands.trigger(PlaySoundtrack::Disable);
+    commands.trigger(SoundtrackCommand::Stop);
 }
 
 fn handle_credits_action(
-    mut next_screen: ResMut<NextState<Screen>>,
+    mut screen_requests: EventWriter<ScreenRequest>,
     mut button_query: InteractionQuery<&CreditsAction>,
 ) {
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
             match action {
-                CreditsAction::Back => next_screen.set(Screen::Title),
+                CreditsAction::Back => {
+                    screen_requests.send(ScreenRequest::Back);
+                }
             }
         }
     }
@@ -0,0 +1,176 @@
+//! A customization screen, accessed from the title screen, to pick the player's skin.
+//! Locked skins are listed but can't be picked. Typing a skin's cheat code anywhere on
+//! this screen unlocks it.
+
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+    ui::Val::*,
+};
+
+use super::Screen;
+use crate::{
+    events::ScreenRequest,
+    game::{
+        assets::ImageAssets,
+        cosmetics::{Cosmetics, SkinCatalog},
+        palette::PaletteSwap,
+        profile::Profile,
+        spawn::player::ducky_atlas_layout,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Customize), enter_customize);
+
+    app.register_type::<CustomizeAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_customize_action,
+            enter_cheat_codes,
+            refresh_skin_labels.run_if(resource_changed::<Profile>),
+        )
+            .chain()
+            .run_if(in_state(Screen::Customize)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum CustomizeAction {
+    /// Select the skin at this index in the [`SkinCatalog`].
+    Skin(usize),
+    Back,
+}
+
+/// The ducky shown in the selected skin.
+#[derive(Component)]
+struct SkinPreview;
+
+/// The longest cheat code that can be recognized.
+const MAX_CODE_LENGTH: usize = 16;
+
+fn enter_customize(
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    skins: Res<SkinCatalog>,
+    profile: Res<Profile>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Customize))
+        .with_children(|children| {
+            children.header("Customize");
+            children.spawn((
+                Name::new("Skin Preview"),
+                SkinPreview,
+                ImageBundle {
+                    style: Style {
+                        width: Px(128.0),
+                        height: Px(128.0),
+                        ..default()
+                    },
+                    image: UiImage::new(images.ducky.clone_weak()),
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: texture_atlas_layouts.add(ducky_atlas_layout()),
+                    index: 0,
+                },
+                PaletteSwap(skins.selected(&profile.cosmetics).palette_id()),
+            ));
+            for i in 0..skins.0.len() {
+                children
+                    .button(skin_label(&skins, &profile.cosmetics, i))
+                    .insert(CustomizeAction::Skin(i));
+            }
+            children.button("Back").insert(CustomizeAction::Back);
+        });
+}
+
+/// The text of a skin's button, which marks the selected one and hides locked ones.
+fn skin_label(skins: &SkinCatalog, cosmetics: &Cosmetics, index: usize) -> String {
+    let skin = &skins.0[index];
+    if !cosmetics.is_unlocked(skin) {
+        "Locked".to_string()
+    } else if skins.selected(cosmetics).id == skin.id {
+        format!("> {} <", skin.name)
+    } else {
+        skin.name.clone()
+    }
+}
+
+fn handle_customize_action(
+    mut screen_requests: EventWriter<ScreenRequest>,
+    skins: Res<SkinCatalog>,
+    mut profile: ResMut<Profile>,
+    mut button_query: InteractionQuery<&CustomizeAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match *action {
+                CustomizeAction::Skin(i) => {
+                    let skin = &skins.0[i];
+                    if profile.cosmetics.is_unlocked(skin) {
+                        profile.cosmetics.selected = Some(skin.id.clone());
+                        info!("Selected skin {}.", skin.name);
+                    }
+                }
+                CustomizeAction::Back => {
+                    screen_requests.send(ScreenRequest::Back);
+                }
+            }
+        }
+    }
+}
+
+fn enter_cheat_codes(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut typed: Local<String>,
+    skins: Res<SkinCatalog>,
+    mut profile: ResMut<Profile>,
+) {
+    for event in keyboard_events.read() {
+        let Key::Character(c) = &event.logical_key else {
+            continue;
+        };
+        if !event.state.is_pressed() {
+            continue;
+        }
+        typed.push_str(c.as_str());
+        while typed.chars().count() > MAX_CODE_LENGTH {
+            typed.remove(0);
+        }
+        let unlocked = skins.matching_code(&typed).cloned().collect::<Vec<_>>();
+        for skin in unlocked {
+            if profile.cosmetics.unlock(&skin) {
+                info!("Unlocked skin {} with a code.", skin.name);
+                typed.clear();
+            }
+        }
+    }
+}
+
+fn refresh_skin_labels(
+    skins: Res<SkinCatalog>,
+    profile: Res<Profile>,
+    button_query: Query<(&CustomizeAction, &Children)>,
+    mut text_query: Query<&mut Text>,
+    mut preview_query: Query<&mut PaletteSwap, With<SkinPreview>>,
+) {
+    for (action, children) in &button_query {
+        let CustomizeAction::Skin(i) = *action else {
+            continue;
+        };
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = skin_label(&skins, &profile.cosmetics, i);
+        }
+    }
+    for mut palette in &mut preview_query {
+        palette.0 = skins.selected(&profile.cosmetics).palette_id();
+    }
+}
@@ -0,0 +1,118 @@
+//! Black bars over the top and bottom of the screen while a cutscene plays,
+//! see `game::cutscene`, with a button to skip it. Pause skips it too.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use super::{pause::pause_just_pressed, PlayingState, Screen};
+use crate::{
+    game::{
+        cutscene::{ActiveCutscene, SkipCutscene},
+        input::{Action, ActionPrompts},
+    },
+    layers::Layer,
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CutsceneAction>();
+    app.add_systems(
+        Update,
+        (
+            spawn_letterbox.run_if(resource_added::<ActiveCutscene>),
+            despawn_letterbox.run_if(not(resource_exists::<ActiveCutscene>)),
+            (
+                handle_cutscene_action,
+                skip_cutscene.run_if(pause_just_pressed),
+            )
+                .run_if(
+                    in_state(PlayingState::Running).and_then(resource_exists::<ActiveCutscene>),
+                ),
+        )
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// The root of the bars and the skip button.
+#[derive(Component)]
+struct Letterbox;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum CutsceneAction {
+    Skip,
+}
+
+/// Height of each bar, as a percentage of the screen.
+const BAR_HEIGHT: f32 = 12.0;
+
+fn spawn_letterbox(mut commands: Commands, prompts: ActionPrompts) {
+    let bar = |name| {
+        (
+            Name::new(name),
+            NodeBundle {
+                style: Style {
+                    width: Percent(100.0),
+                    height: Percent(BAR_HEIGHT),
+                    justify_content: JustifyContent::End,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::horizontal(Px(20.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::BLACK),
+                ..default()
+            },
+        )
+    };
+    commands
+        .spawn((
+            Name::new("Letterbox"),
+            Letterbox,
+            NodeBundle {
+                style: Style {
+                    width: Percent(100.0),
+                    height: Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                // Over the HUD, which has nothing to say during a cutscene.
+                z_index: Layer::ScreenUi.z_index(1),
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|children| {
+            children.spawn(bar("Letterbox Top"));
+            children
+                .spawn(bar("Letterbox Bottom"))
+                .with_children(|children| {
+                    children
+                        .button(format!("Skip ({})", prompts.prompt(Action::Pause)))
+                        .insert(CutsceneAction::Skip);
+                });
+        });
+}
+
+fn despawn_letterbox(mut commands: Commands, letterbox_query: Query<Entity, With<Letterbox>>) {
+    for entity in &letterbox_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn handle_cutscene_action(
+    mut commands: Commands,
+    mut button_query: InteractionQuery<&CutsceneAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                CutsceneAction::Skip => commands.trigger(SkipCutscene),
+            }
+        }
+    }
+}
+
+fn skip_cutscene(mut commands: Commands) {
+    commands.trigger(SkipCutscene);
+}
@@ -0,0 +1,168 @@
+//! The dialogue box, layered over the game while a dialogue is open, see `game::dialogue`.
+//! Interact or clicking the box reveals the rest of the line, and then goes on to the next.
+//! Lines with choices show them as buttons once revealed. Pause skips the whole dialogue.
+
+use bevy::prelude::*;
+
+use super::{pause::pause_just_pressed, PlayingState};
+use crate::{
+    game::{
+        dialogue::{ActiveDialogue, ContinueDialogue, DialogueCatalog},
+        input::{Action, ActionInput},
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(PlayingState::Dialogue), enter_dialogue);
+
+    app.register_type::<DialogueAction>();
+    app.add_systems(
+        Update,
+        (
+            show_line.run_if(resource_exists_and_changed::<ActiveDialogue>),
+            show_choices,
+            handle_dialogue_action,
+            skip_dialogue.run_if(pause_just_pressed),
+        )
+            .chain()
+            .run_if(in_state(PlayingState::Dialogue).and_then(resource_exists::<ActiveDialogue>)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum DialogueAction {
+    /// Reveal the rest of the line, or go on to the next one.
+    Advance,
+    /// Pick the choice at this index of the line.
+    Choose(usize),
+}
+
+fn enter_dialogue(mut commands: Commands) {
+    commands
+        .dialogue_box()
+        .insert((DialogueAction::Advance, StateScoped(PlayingState::Dialogue)));
+}
+
+fn show_line(
+    mut commands: Commands,
+    catalog: Res<DialogueCatalog>,
+    active: Res<ActiveDialogue>,
+    mut part_query: Query<(
+        Entity,
+        &DialoguePart,
+        Option<&mut Text>,
+        Option<&mut Typewriter>,
+    )>,
+) {
+    let Some(node) = active.current(&catalog) else {
+        return;
+    };
+    commands.trigger(Announce::new(
+        Importance::Normal,
+        format!("{}: {}", node.speaker, node.text),
+    ));
+    for (entity, part, text, typewriter) in &mut part_query {
+        match part {
+            DialoguePart::Speaker => {
+                if let Some(mut text) = text {
+                    text.sections[0].value.clone_from(&node.speaker);
+                }
+            }
+            DialoguePart::Text => {
+                if let Some(mut typewriter) = typewriter {
+                    typewriter.set(&node.text);
+                }
+            }
+            DialoguePart::Choices => {
+                commands.entity(entity).despawn_descendants();
+            }
+        }
+    }
+}
+
+/// Add the buttons for the line's choices, once the line is revealed.
+fn show_choices(
+    mut commands: Commands,
+    catalog: Res<DialogueCatalog>,
+    active: Res<ActiveDialogue>,
+    mut focus: ResMut<UiFocus>,
+    typewriter_query: Query<&Typewriter>,
+    choices_query: Query<(Entity, Option<&Children>, &DialoguePart)>,
+) {
+    if !typewriter_query.iter().all(Typewriter::is_finished) {
+        return;
+    }
+    let Some(node) = active
+        .current(&catalog)
+        .filter(|node| !node.choices.is_empty())
+    else {
+        return;
+    };
+    for (entity, children, part) in &choices_query {
+        if *part != DialoguePart::Choices || children.is_some_and(|c| !c.is_empty()) {
+            continue;
+        }
+        commands.entity(entity).with_children(|children| {
+            for (index, choice) in node.choices.iter().enumerate() {
+                let button = children
+                    .button(&choice.text)
+                    .insert(DialogueAction::Choose(index))
+                    .id();
+                // Ready to pick with the keyboard or a gamepad.
+                if index == 0 {
+                    focus.focused = Some(button);
+                }
+            }
+        });
+    }
+}
+
+fn handle_dialogue_action(
+    mut commands: Commands,
+    actions: ActionInput,
+    catalog: Res<DialogueCatalog>,
+    active: Res<ActiveDialogue>,
+    mut typewriter_query: Query<&mut Typewriter>,
+    mut button_query: InteractionQuery<&DialogueAction>,
+) {
+    let Some(node) = active.current(&catalog) else {
+        return;
+    };
+    let mut pressed = button_query
+        .iter_mut()
+        .filter_map(|(interaction, &action)| {
+            matches!(interaction, Interaction::Pressed).then_some(action)
+        })
+        .collect::<Vec<_>>();
+    if actions.just_pressed(Action::Interact) {
+        pressed.push(DialogueAction::Advance);
+    }
+    // One step per frame, so a key and a click together don't skip a line.
+    let Some(action) = pressed.first() else {
+        return;
+    };
+    match *action {
+        DialogueAction::Advance => {
+            let mut revealing = typewriter_query
+                .iter_mut()
+                .filter(|typewriter| !typewriter.is_finished())
+                .peekable();
+            if revealing.peek().is_some() {
+                revealing.for_each(|mut typewriter| typewriter.finish());
+            } else if node.choices.is_empty() {
+                commands.trigger(ContinueDialogue(node.next.clone()));
+            }
+        }
+        DialogueAction::Choose(index) => {
+            if let Some(choice) = node.choices.get(index) {
+                commands.trigger(ContinueDialogue(choice.next.clone()));
+            }
+        }
+    }
+}
+
+fn skip_dialogue(mut commands: Commands) {
+    commands.trigger(ContinueDialogue(None));
+}
@@ -0,0 +1,111 @@
+//! The screen shown when an endless run ends, with how long the player survived,
+//! how many waves they got through, the bonus objectives of the levels' challenges,
+//! and the endless high score table.
+//! The score is recorded on entering, together with the time survived.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    events::ScreenRequest,
+    game::{
+        challenge::Challenge,
+        high_scores::{HighScore, HighScores},
+        mode::GameMode,
+        mutators::{MutatorCatalog, RunMutators},
+        profile::Profile,
+        save::SaveGame,
+        score::Score,
+        spawn::wave::WaveDirector,
+        stats::{format_time, SessionStats},
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::EndlessResults), enter_endless_results);
+
+    app.register_type::<ResultsAction>();
+    app.add_systems(
+        Update,
+        handle_results_action.run_if(in_state(Screen::EndlessResults)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum ResultsAction {
+    Retry,
+    Title,
+}
+
+fn enter_endless_results(
+    mut commands: Commands,
+    score: Res<Score>,
+    profile: Res<Profile>,
+    stats: Res<SessionStats>,
+    run_mutators: Res<RunMutators>,
+    catalog: Res<MutatorCatalog>,
+    director: Option<Res<WaveDirector>>,
+    save: Res<SaveGame>,
+    challenge: Res<Challenge>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    let entry = HighScore {
+        name: profile.name.clone(),
+        score: score.points,
+        mode: GameMode::Endless,
+        mutators: run_mutators.0.clone(),
+        survived: stats.time_played as u32,
+        week: None,
+    };
+    let category = entry.category();
+    let rank = high_scores.insert(entry);
+    let waves = director.map_or(0, |director| director.finished());
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::EndlessResults))
+        .with_children(|children| {
+            children.header("Results");
+            children.label(format!("Score: {}", score.points));
+            if !run_mutators.0.is_empty() {
+                children.label(format!("Mutators: {}", catalog.names(&run_mutators.0)));
+            }
+            children.label(format!("Survived: {}", format_time(stats.time_played)));
+            children.label(format!("Waves cleared: {waves}"));
+            super::game_over::bonus_objectives(children, &save, &challenge);
+            match rank {
+                Some(0) => {
+                    children.label("New record!");
+                }
+                Some(rank) => {
+                    children.label(format!("Rank #{}", rank + 1));
+                }
+                None => (),
+            }
+            super::high_scores::high_score_table(children, &high_scores, &category, rank);
+            children.button("Retry").insert(ResultsAction::Retry);
+            children.button("Title").insert(ResultsAction::Title);
+        });
+}
+
+fn handle_results_action(
+    mut commands: Commands,
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mut button_query: InteractionQuery<&ResultsAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                ResultsAction::Retry => {
+                    // Another endless run, with a new seed.
+                    commands.insert_resource(SaveGame::default());
+                    screen_requests.send(ScreenRequest::To(Screen::Playing));
+                }
+                ResultsAction::Title => {
+                    screen_requests.send(ScreenRequest::To(Screen::Title));
+                }
+            }
+        }
+    }
+}
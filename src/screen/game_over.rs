@@ -0,0 +1,161 @@
+//! The screen shown when a run ends, with its final score, the bonus objectives of the
+//! levels' challenges, the high score table and a summary of the session.
+//! The score is recorded on entering, and the run's saved game is deleted, since a
+//! finished run can't be continued. The run's seed is shown, so it can be shared
+//! or retried to play the same run again. Weekly challenges are retried as they were,
+//! and count toward the week's record.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    events::ScreenRequest,
+    game::{
+        challenge::{bonus_objective_lines, Challenge},
+        high_scores::{HighScore, HighScores},
+        mode::GameMode,
+        mutators::{MutatorCatalog, RunMutators},
+        profile::Profile,
+        rng::{GameRng, NextRunSeed},
+        save::{ActiveSlot, SaveGame},
+        score::Score,
+        stats::SessionStats,
+        weekly::{WeeklyChallenge, WeeklyRecords},
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::GameOver), enter_game_over);
+
+    app.register_type::<GameOverAction>();
+    app.add_systems(
+        Update,
+        handle_game_over_action.run_if(in_state(Screen::GameOver)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum GameOverAction {
+    Retry,
+    RetrySeed,
+    Title,
+}
+
+fn enter_game_over(
+    mut commands: Commands,
+    score: Res<Score>,
+    slot: Option<Res<ActiveSlot>>,
+    profile: Res<Profile>,
+    rng: Res<GameRng>,
+    stats: Res<SessionStats>,
+    run_mutators: Res<RunMutators>,
+    catalog: Res<MutatorCatalog>,
+    save: Res<SaveGame>,
+    challenge: Res<Challenge>,
+    mut high_scores: ResMut<HighScores>,
+    mut weekly_records: ResMut<WeeklyRecords>,
+) {
+    if let Some(slot) = slot {
+        slot.0.delete();
+    }
+    let weekly_best = save
+        .weekly
+        .map(|week| weekly_records.record(week, score.points));
+    let entry = HighScore {
+        name: profile.name.clone(),
+        score: score.points,
+        mode: GameMode::Normal,
+        mutators: run_mutators.0.clone(),
+        survived: stats.time_played as u32,
+        week: save.weekly,
+    };
+    let category = entry.category();
+    let rank = high_scores.insert(entry);
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::GameOver))
+        .with_children(|children| {
+            children.header("Game Over");
+            children.label(format!("Score: {}", score.points));
+            if let (Some(week), Some(beaten)) = (save.weekly, weekly_best) {
+                let text = if beaten {
+                    format!("Best this week! ({})", week.name())
+                } else {
+                    format!("Weekly challenge {}", week.name())
+                };
+                children.label(text);
+            }
+            if !run_mutators.0.is_empty() {
+                children.label(format!("Mutators: {}", catalog.names(&run_mutators.0)));
+            }
+            bonus_objectives(children, &save, &challenge);
+            match rank {
+                Some(0) => {
+                    children.label("New record!");
+                }
+                Some(rank) => {
+                    children.label(format!("Rank #{}", rank + 1));
+                }
+                None => (),
+            }
+            super::high_scores::high_score_table(children, &high_scores, &category, rank);
+            super::session_summary::session_summary(children, &stats);
+            children.label(format!("Seed: {:016x}", rng.seed()));
+            children.button("Retry").insert(GameOverAction::Retry);
+            // Weekly challenges always have the same seed.
+            if save.weekly.is_none() {
+                children
+                    .button("Retry this seed")
+                    .insert(GameOverAction::RetrySeed);
+            }
+            children.button("Title").insert(GameOverAction::Title);
+        });
+}
+
+/// A label per bonus objective of the run, under a heading, if there are any.
+pub(super) fn bonus_objectives(
+    children: &mut ChildBuilder,
+    save: &SaveGame,
+    challenge: &Challenge,
+) {
+    let lines = bonus_objective_lines(save, challenge);
+    if lines.is_empty() {
+        return;
+    }
+    children.label("Bonus objectives:");
+    for line in lines {
+        children.label(line);
+    }
+}
+
+fn handle_game_over_action(
+    mut commands: Commands,
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mut next_seed: ResMut<NextRunSeed>,
+    rng: Res<GameRng>,
+    save: Res<SaveGame>,
+    catalog: Res<MutatorCatalog>,
+    mut button_query: InteractionQuery<&GameOverAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                GameOverAction::Retry | GameOverAction::RetrySeed => {
+                    if let Some(week) = save.weekly {
+                        WeeklyChallenge::for_week(week, &catalog).start(&mut commands);
+                    } else {
+                        next_seed.0 = (*action == GameOverAction::RetrySeed).then_some(rng.seed());
+                        // A new run, in the same slot if there was one.
+                        commands.insert_resource(SaveGame::default());
+                    }
+                    screen_requests.send(ScreenRequest::To(Screen::Playing));
+                }
+                GameOverAction::Title => {
+                    screen_requests.send(ScreenRequest::To(Screen::Title));
+                }
+            }
+        }
+    }
+}
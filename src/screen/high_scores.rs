@@ -0,0 +1,111 @@
+//! The high score tables of every mode, and of every set of mutators played with,
+//! accessed from the title screen.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    events::ScreenRequest,
+    game::{
+        high_scores::{HighScores, ScoreCategory},
+        mode::GameMode,
+        mutators::MutatorCatalog,
+        stats::format_time,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::HighScores), enter_high_scores);
+
+    app.register_type::<HighScoresAction>();
+    app.add_systems(
+        Update,
+        handle_high_scores_action.run_if(in_state(Screen::HighScores)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum HighScoresAction {
+    Back,
+}
+
+fn enter_high_scores(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    catalog: Res<MutatorCatalog>,
+) {
+    // Every mode without mutators, even without scores yet.
+    let mut categories = high_scores.categories();
+    categories.extend(GameMode::ALL.map(|mode| ScoreCategory {
+        mode,
+        mutators: Vec::new(),
+        week: None,
+    }));
+    categories.sort();
+    categories.dedup();
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::HighScores))
+        .with_children(|children| {
+            children.header("High Scores");
+            for category in &categories {
+                children.label(category_name(&catalog, category));
+                high_score_table(children, &high_scores, category, None);
+            }
+            children.button("Back").insert(HighScoresAction::Back);
+        });
+}
+
+/// Like "Endless" or "Endless: One-Hit Death".
+pub(super) fn category_name(catalog: &MutatorCatalog, category: &ScoreCategory) -> String {
+    let mode = match category.week {
+        Some(week) => format!("Weekly {}", week.name()),
+        None => category.mode.name().to_string(),
+    };
+    if category.mutators.is_empty() {
+        mode
+    } else {
+        format!("{mode}: {}", catalog.names(&category.mutators))
+    }
+}
+
+/// A label per entry of `category`, marking the one at `highlight` like a selected skin.
+/// Endless entries show how long the run lasted.
+pub(super) fn high_score_table(
+    children: &mut ChildBuilder,
+    high_scores: &HighScores,
+    category: &ScoreCategory,
+    highlight: Option<usize>,
+) {
+    if high_scores.entries(category).next().is_none() {
+        children.label("No scores yet");
+    }
+    for (rank, entry) in high_scores.entries(category).enumerate() {
+        let mut text = format!("{}. {} - {}", rank + 1, entry.name, entry.score);
+        if category.mode == GameMode::Endless {
+            text.push_str(&format!(" ({})", format_time(entry.survived as f32)));
+        }
+        if highlight == Some(rank) {
+            children.label(format!("> {text} <"));
+        } else {
+            children.label(text);
+        }
+    }
+}
+
+fn handle_high_scores_action(
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mut button_query: InteractionQuery<&HighScoresAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                HighScoresAction::Back => {
+                    screen_requests.send(ScreenRequest::Back);
+                }
+            }
+        }
+    }
+}
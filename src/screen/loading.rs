@@ -1,20 +1,102 @@
 //! A loading screen during which game assets are loaded.
 //! This reduces stuttering, especially for audio on WASM.
+//! A progress bar shows how many of the assets are ready, and assets that failed to load
+//! are listed instead of continuing without them. Images, sounds and fonts are replaced
+//! with fallbacks instead, see `game::fallback`, so only levels and waves can stop the game.
+//!
+//! On the web build, where assets are downloaded one by one and a single missing file would
+//! leave the bar stuck without saying why, every asset is listed with how far along it is.
+//! Failed ones show their error, and a button to try loading them again.
+//!
+//! The loaded files are also checked against the hashes they had at build time, see
+//! `game::integrity`, and any that differ lead to [`Screen::AssetMismatch`].
 
-use bevy::prelude::*;
+use bevy::{prelude::*, ui::Val::*};
 
 use super::Screen;
 use crate::{
-    game::assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+    events::ScreenRequest,
+    game::{
+        assets::{
+            AssetCatalog, AssetReport, AssetStatus, AudioAssets, FontAssets, ImageAssets,
+            LevelAssets, LoadProgress,
+        },
+        fallback::Fallbacks,
+        integrity::{AssetBytes, AssetMismatches, IntegrityCheck},
+    },
     ui::prelude::*,
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(Screen::Loading), enter_loading);
+    app.add_systems(
+        OnEnter(Screen::Loading),
+        (
+            (load_catalogs, start_integrity_check).chain(),
+            enter_loading,
+        ),
+    );
     app.add_systems(
         Update,
-        continue_to_title.run_if(in_state(Screen::Loading).and_then(all_assets_loaded)),
+        (
+            update_progress_bar,
+            check_integrity.run_if(resource_exists::<IntegrityCheck>),
+            continue_to_title.run_if(all_assets_loaded),
+        )
+            .run_if(in_state(Screen::Loading)),
     );
+    if cfg!(target_family = "wasm") {
+        app.register_type::<RetryAsset>();
+        app.add_systems(
+            Update,
+            (update_asset_list, retry_asset).run_if(in_state(Screen::Loading)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct ProgressText;
+
+/// The list of every asset, only on the web build.
+#[derive(Component)]
+struct AssetList;
+
+/// Loads the asset at this path again.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+struct RetryAsset(String);
+
+/// Start loading every asset at once.
+fn load_catalogs(mut commands: Commands) {
+    commands.init_resource::<ImageAssets>();
+    commands.init_resource::<AudioAssets>();
+    commands.init_resource::<FontAssets>();
+    commands.init_resource::<LevelAssets>();
+}
+
+/// Read every file the game waits for once more, to compare against the manifest.
+fn start_integrity_check(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    images: Res<ImageAssets>,
+    audio: Res<AudioAssets>,
+    fonts: Res<FontAssets>,
+    levels: Res<LevelAssets>,
+) {
+    let mut paths: Vec<_> = [
+        images.handles(),
+        audio.handles(),
+        fonts.handles(),
+        levels.handles(),
+    ]
+    .concat()
+    .iter()
+    .filter_map(|handle| handle.path())
+    .map(|path| path.path().to_string_lossy().into_owned())
+    .collect();
+    // Some sounds share a file.
+    paths.sort();
+    paths.dedup();
+    commands.insert_resource(IntegrityCheck::start(&asset_server, paths));
 }
 
 fn enter_loading(mut commands: Commands) {
@@ -22,21 +104,206 @@ fn enter_loading(mut commands: Commands) {
         .ui_root()
         .insert(StateScoped(Screen::Loading))
         .with_children(|children| {
-            children.label("Loading...");
+            children.label("Loading...").insert(ProgressText);
+            children.progress_bar();
+            if cfg!(target_family = "wasm") {
+                children.spawn((
+                    Name::new("Asset List"),
+                    AssetList,
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Start,
+                            row_gap: Px(2.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+/// Progress of every asset the game waits for.
+/// A font that fails to load gets a fallback like images and sounds, so it never stops the game.
+fn load_progress(
+    asset_server: &AssetServer,
+    images: &ImageAssets,
+    audio: &AudioAssets,
+    fonts: &FontAssets,
+    levels: &LevelAssets,
+    fallbacks: &Fallbacks,
+) -> LoadProgress {
+    images.load_progress(asset_server, fallbacks)
+        + audio.load_progress(asset_server, fallbacks)
+        + fonts.load_progress(asset_server, fallbacks)
+        + levels.load_progress(asset_server, fallbacks)
+}
+
+fn update_progress_bar(
+    asset_server: Res<AssetServer>,
+    images: Res<ImageAssets>,
+    audio: Res<AudioAssets>,
+    fonts: Res<FontAssets>,
+    levels: Res<LevelAssets>,
+    fallbacks: Res<Fallbacks>,
+    mut reported: Local<usize>,
+    label_query: Query<&Children, With<ProgressText>>,
+    mut text_query: Query<&mut Text>,
+    mut fill_query: Query<&mut Style, With<ProgressFill>>,
+) {
+    let progress = load_progress(&asset_server, &images, &audio, &fonts, &levels, &fallbacks);
+    for path in progress.failed.iter().skip(*reported) {
+        error!("Failed to load {path}, so the game can't start.");
+    }
+    *reported = progress.failed.len();
+
+    let message = if progress.failed.is_empty() {
+        format!("Loading... {}/{}", progress.done, progress.total)
+    } else if cfg!(target_family = "wasm") {
+        // The asset list says which ones and why.
+        format!(
+            "Failed to load {} of {}",
+            progress.failed.len(),
+            progress.total
+        )
+    } else {
+        format!("Failed to load:\n{}", progress.failed.join("\n"))
+    };
+    for children in &label_query {
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value.clone_from(&message);
+        }
+    }
+    for mut style in &mut fill_query {
+        set_progress(&mut style, progress.fraction());
+    }
+}
+
+/// Rebuild the asset list whenever an asset's status changes.
+fn update_asset_list(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    images: Res<ImageAssets>,
+    audio: Res<AudioAssets>,
+    fonts: Res<FontAssets>,
+    levels: Res<LevelAssets>,
+    fallbacks: Res<Fallbacks>,
+    mut shown: Local<Vec<AssetReport>>,
+    list_query: Query<Entity, With<AssetList>>,
+) {
+    let mut reports = [
+        images.reports(&asset_server, &fallbacks),
+        audio.reports(&asset_server, &fallbacks),
+        fonts.reports(&asset_server, &fallbacks),
+        levels.reports(&asset_server, &fallbacks),
+    ]
+    .concat();
+    // Some sounds share a file.
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+    reports.dedup_by(|a, b| a.path == b.path);
+    if *shown == reports {
+        return;
+    }
+    for list in &list_query {
+        commands
+            .entity(list)
+            .despawn_descendants()
+            .with_children(|children| {
+                for report in &reports {
+                    asset_row(children, report);
+                }
+            });
+    }
+    *shown = reports;
+}
+
+fn asset_row(children: &mut ChildBuilder, report: &AssetReport) {
+    let status = match &report.status {
+        AssetStatus::Loading => "loading".to_string(),
+        AssetStatus::Loaded => "done".to_string(),
+        AssetStatus::Failed(error) => format!("failed: {error}"),
+        AssetStatus::Fallback(error) => format!("using a fallback: {error}"),
+    };
+    let preset = TextPreset::Value;
+    children
+        .spawn((
+            Name::new("Asset Row"),
+            NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    column_gap: Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Asset Status"),
+                TextBundle::from_section(
+                    format!("{} - {status}", report.path),
+                    preset.style(ui_palette::LABEL_TEXT),
+                ),
+                preset,
+                Themed::LabelText,
+            ));
+            if matches!(
+                report.status,
+                AssetStatus::Failed(_) | AssetStatus::Fallback(_)
+            ) {
+                children
+                    .button("Retry")
+                    .insert(RetryAsset(report.path.clone()));
+            }
         });
 }
 
+fn retry_asset(asset_server: Res<AssetServer>, mut button_query: InteractionQuery<&RetryAsset>) {
+    for (interaction, retry) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            info!("Trying to load {} again.", retry.0);
+            asset_server.reload(retry.0.clone());
+        }
+    }
+}
+
+/// Once every file was read, keep the ones that don't match, and drop the rest.
+fn check_integrity(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    check: Res<IntegrityCheck>,
+    bytes: Res<Assets<AssetBytes>>,
+    mut screen_requests: EventWriter<ScreenRequest>,
+) {
+    if !check.is_done(&asset_server) {
+        return;
+    }
+    let mismatches = check.mismatches(&bytes);
+    for path in &mismatches {
+        error!("{path} doesn't match the file this build was made with.");
+    }
+    if !mismatches.is_empty() {
+        screen_requests.send(ScreenRequest::To(Screen::AssetMismatch));
+    }
+    commands.remove_resource::<IntegrityCheck>();
+    commands.insert_resource(AssetMismatches(mismatches));
+}
+
 fn all_assets_loaded(
     asset_server: Res<AssetServer>,
-    image_handles: Res<HandleMap<ImageKey>>,
-    sfx_handles: Res<HandleMap<SfxKey>>,
-    soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    images: Res<ImageAssets>,
+    audio: Res<AudioAssets>,
+    fonts: Res<FontAssets>,
+    levels: Res<LevelAssets>,
+    fallbacks: Res<Fallbacks>,
+    mismatches: Option<Res<AssetMismatches>>,
 ) -> bool {
-    image_handles.all_loaded(&asset_server)
-        && sfx_handles.all_loaded(&asset_server)
-        && soundtrack_handles.all_loaded(&asset_server)
+    mismatches.is_some_and(|mismatches| mismatches.0.is_empty())
+        && load_progress(&asset_server, &images, &audio, &fonts, &levels, &fallbacks).is_done()
 }
 
-fn continue_to_title(mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Title);
+fn continue_to_title(mut screen_requests: EventWriter<ScreenRequest>) {
+    screen_requests.send(ScreenRequest::To(Screen::Title));
 }
@@ -1,25 +1,75 @@
 //! The game's main screen states and transitions between them.
 
+mod about;
+mod arbiter;
+mod asset_mismatch;
+mod backdrop;
+pub(crate) mod bug_report;
+mod controls;
 mod credits;
+mod customize;
+mod cutscene;
+mod dialogue;
+mod endless_results;
+mod game_over;
+mod high_scores;
 mod loading;
+mod mutators;
+mod pause;
 mod playing;
+mod profile;
+mod save_slots;
+mod session_summary;
 pub(crate) mod settings;
 mod splash;
 mod title;
+mod transition;
+mod upgrade;
 
 use bevy::prelude::*;
 
+#[allow(unused_imports)]
+pub use arbiter::{ExitingScreen, ScreenHistory};
+#[allow(unused_imports)]
+pub use transition::{ScreenTransition, TransitionKind};
+
 pub(super) fn plugin(app: &mut App) {
     app.init_state::<Screen>();
     app.enable_state_scoped_entities::<Screen>();
+    app.add_sub_state::<PlayingState>();
+    app.enable_state_scoped_entities::<PlayingState>();
 
     app.add_plugins((
+        arbiter::plugin,
+        transition::plugin,
+        backdrop::plugin,
         splash::plugin,
         loading::plugin,
+        asset_mismatch::plugin,
+    ));
+    // Menus.
+    app.add_plugins((
         title::plugin,
+        save_slots::plugin,
         settings::plugin,
+        controls::plugin,
         credits::plugin,
+        customize::plugin,
+        profile::plugin,
+        high_scores::plugin,
+        mutators::plugin,
+        about::plugin,
+    ));
+    app.add_plugins((
         playing::plugin,
+        pause::plugin,
+        bug_report::plugin,
+        session_summary::plugin,
+        cutscene::plugin,
+        dialogue::plugin,
+        upgrade::plugin,
+        game_over::plugin,
+        endless_results::plugin,
     ));
 }
 
@@ -29,8 +79,40 @@ pub enum Screen {
     #[default]
     Splash,
     Loading,
+    /// Shown instead of [`Screen::Title`] when the assets don't match the build.
+    AssetMismatch,
     Title,
+    SaveSlots,
     Settings,
+    Controls,
+    Customize,
+    Profile,
+    HighScores,
+    Mutators,
     Credits,
+    About,
     Playing,
+    GameOver,
+    /// Where endless runs end, instead of [`Screen::GameOver`].
+    EndlessResults,
+}
+
+/// Sub-state of [`Screen::Playing`], so pausing keeps the level alive.
+/// Gameplay systems should only run in [`PlayingState::Running`].
+#[derive(SubStates, Debug, Hash, PartialEq, Eq, Clone, Default)]
+#[source(Screen = Screen::Playing)]
+pub enum PlayingState {
+    #[default]
+    Running,
+    Paused,
+    /// The settings menu, opened from the pause menu.
+    Settings,
+    /// The bug report form, opened from the pause menu.
+    BugReport,
+    /// The session summary, shown when quitting from the pause menu.
+    Summary,
+    /// The upgrade shop, shown at the end of each cycle.
+    Upgrade,
+    /// A dialogue is open, see `game::dialogue`.
+    Dialogue,
 }
@@ -0,0 +1,107 @@
+//! The mutator picker, accessed from the title screen.
+//! Picked mutators apply to the runs started afterwards, see `game::mutators`.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    events::ScreenRequest,
+    game::mutators::{Mutator, MutatorCatalog, MutatorSelection},
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Mutators), enter_mutators);
+
+    app.register_type::<MutatorAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_mutator_action,
+            refresh_mutator_labels.run_if(resource_changed::<MutatorSelection>),
+        )
+            .chain()
+            .run_if(in_state(Screen::Mutators)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum MutatorAction {
+    /// Toggle the mutator at this index of the catalog.
+    Toggle(usize),
+    Back,
+}
+
+fn enter_mutators(
+    mut commands: Commands,
+    catalog: Res<MutatorCatalog>,
+    selection: Res<MutatorSelection>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Mutators))
+        .with_children(|children| {
+            children.header("Mutators");
+            children.label("Picked mutators apply to new runs.");
+            for (index, mutator) in catalog.0.iter().enumerate() {
+                children
+                    .button(mutator_label(mutator, &selection))
+                    .insert(MutatorAction::Toggle(index));
+                children.label(&mutator.description);
+            }
+            children.button("Back").insert(MutatorAction::Back);
+        });
+}
+
+fn handle_mutator_action(
+    mut screen_requests: EventWriter<ScreenRequest>,
+    catalog: Res<MutatorCatalog>,
+    mut selection: ResMut<MutatorSelection>,
+    mut button_query: InteractionQuery<&MutatorAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match *action {
+                MutatorAction::Toggle(index) => {
+                    if let Some(mutator) = catalog.0.get(index) {
+                        selection.toggle(&mutator.id);
+                    }
+                }
+                MutatorAction::Back => {
+                    screen_requests.send(ScreenRequest::Back);
+                }
+            }
+        }
+    }
+}
+
+/// Whether the mutator is picked, like "[x] One-Hit Death".
+fn mutator_label(mutator: &Mutator, selection: &MutatorSelection) -> String {
+    let mark = if selection.contains(&mutator.id) {
+        "x"
+    } else {
+        " "
+    };
+    format!("[{mark}] {}", mutator.name)
+}
+
+fn refresh_mutator_labels(
+    catalog: Res<MutatorCatalog>,
+    selection: Res<MutatorSelection>,
+    button_query: Query<(&MutatorAction, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (action, children) in &button_query {
+        let MutatorAction::Toggle(index) = *action else {
+            continue;
+        };
+        let Some(mutator) = catalog.0.get(index) else {
+            continue;
+        };
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = mutator_label(mutator, &selection);
+        }
+    }
+}
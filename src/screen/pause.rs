@@ -0,0 +1,96 @@
+//! The pause menu, layered over the running game.
+
+use bevy::prelude::*;
+
+use super::{PlayingState, Screen};
+use crate::{
+    game::{
+        cutscene::ActiveCutscene,
+        input::{Action, ActionInput, ActionPrompts},
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(PlayingState::Paused), enter_pause);
+
+    app.register_type::<PauseAction>();
+    app.add_systems(
+        Update,
+        (
+            // Pausing skips cutscenes instead, see `screen::cutscene`.
+            toggle_pause.run_if(
+                in_state(Screen::Playing)
+                    .and_then(pause_just_pressed)
+                    .and_then(not(resource_exists::<ActiveCutscene>)),
+            ),
+            handle_pause_action.run_if(in_state(PlayingState::Paused)),
+        ),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum PauseAction {
+    Resume,
+    Settings,
+    ReportBug,
+    Quit,
+}
+
+const PAUSE_BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+fn enter_pause(mut commands: Commands, prompts: ActionPrompts) {
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(PlayingState::Paused),
+            BackgroundColor(PAUSE_BACKGROUND_COLOR),
+        ))
+        .with_children(|children| {
+            children.header("Paused");
+            children.label(format!("Press {} to resume", prompts.prompt(Action::Pause)));
+            children.button("Resume").insert(PauseAction::Resume);
+            children.button("Settings").insert(PauseAction::Settings);
+            children
+                .button("Report a bug")
+                .insert(PauseAction::ReportBug);
+            children.button("Quit to title").insert(PauseAction::Quit);
+        });
+}
+
+pub(super) fn pause_just_pressed(actions: ActionInput) -> bool {
+    actions.just_pressed(Action::Pause)
+}
+
+fn toggle_pause(
+    playing_state: Res<State<PlayingState>>,
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+) {
+    next_playing_state.set(match playing_state.get() {
+        PlayingState::Running => PlayingState::Paused,
+        PlayingState::Paused => PlayingState::Running,
+        // Back out of the settings menu and bug report form first.
+        PlayingState::Settings | PlayingState::BugReport => PlayingState::Paused,
+        // The session is over, there is nothing to resume.
+        PlayingState::Summary => return,
+        // The shop is left with its own button, and pausing skips dialogue instead.
+        PlayingState::Upgrade | PlayingState::Dialogue => return,
+    });
+}
+
+fn handle_pause_action(
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+    mut button_query: InteractionQuery<&PauseAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                PauseAction::Resume => next_playing_state.set(PlayingState::Running),
+                PauseAction::Settings => next_playing_state.set(PlayingState::Settings),
+                PauseAction::ReportBug => next_playing_state.set(PlayingState::BugReport),
+                PauseAction::Quit => next_playing_state.set(PlayingState::Summary),
+            }
+        }
+    }
+}
@@ -0,0 +1,464 @@
+//! The HUD shown over the level while playing: the player's name, health, inventory,
+//! the run's mutators and the level's objectives and challenge rules in one corner,
+//! the score and combo in the other, and the current cycle at the top, with a ring that
+//! fills up over the cycle. Waves of enemies are announced below it,
+//! and tutorial prompts are shown at the bottom.
+//!
+//! It is spawned once on entering [`Screen::Playing`]. Each part has a marker component,
+//! so systems update just its text or fill instead of rebuilding nodes.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{
+    events::{WaveEnded, WaveStarted},
+    game::{
+        challenge::Challenge,
+        cycle::CyclePhase,
+        health::Health,
+        input::{ActionModes, ActionPrompts},
+        inventory::{Inventory, ItemCatalog, INVENTORY_SLOTS},
+        mutators::{MutatorCatalog, RunMutators},
+        objectives::Objectives,
+        profile::Profile,
+        save::SaveGame,
+        score::Score,
+        spawn::player::Player,
+        tutorial::TutorialPrompts,
+    },
+    layers::Layer,
+    screen::Screen,
+    ui::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Playing), spawn_hud);
+    app.add_systems(
+        Update,
+        (
+            show_score.run_if(resource_changed::<Score>),
+            show_health,
+            show_inventory
+                .run_if(resource_changed::<Inventory>.or_else(resource_changed::<GameplayPalette>)),
+            show_mutators,
+            show_objectives.run_if(resource_changed::<Objectives>),
+            show_challenge.run_if(resource_changed::<Challenge>),
+            show_tutorial,
+            show_cycle
+                .in_set(AppSet::HandleEvents)
+                .run_if(resource_exists::<CyclePhase>),
+            (
+                announce_waves.in_set(AppSet::HandleEvents),
+                fade_announcements,
+            )
+                .chain(),
+        )
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// The root of the HUD.
+#[derive(Component)]
+pub struct Hud;
+
+/// The player's name.
+#[derive(Component)]
+pub struct HudName;
+
+/// The fill of the player's health bar. Set it with [`set_progress`].
+#[derive(Component)]
+pub struct HudHealth;
+
+/// The parts of an inventory slot, by index into [`Inventory::stacks`].
+#[derive(Component, Clone, Copy)]
+pub struct HudSlot(pub usize);
+
+/// Width and height of an inventory slot, in pixels.
+const SLOT_SIZE: f32 = 40.0;
+
+/// The names of the run's mutators, empty without any.
+#[derive(Component)]
+pub struct HudMutators;
+
+/// The checklist of the level's objectives, one per line.
+#[derive(Component)]
+pub struct HudObjectives;
+
+/// The rules of the level's challenge, one per line, empty without any.
+#[derive(Component)]
+pub struct HudChallenge;
+
+/// The score's [`Counter`].
+#[derive(Component)]
+pub struct HudScore;
+
+/// The combo multiplier, empty while there is none.
+#[derive(Component)]
+pub struct HudCombo;
+
+/// The cycle and phase the game is in.
+#[derive(Component)]
+pub struct HudCycle;
+
+/// The [`ProgressRing`] that fills up over the current cycle.
+#[derive(Component)]
+pub struct HudCycleRing;
+
+/// The current tutorial prompt, empty while there is none.
+#[derive(Component)]
+pub struct HudTutorial;
+
+/// Announcements like a wave starting, which fade out after a while.
+#[derive(Component, Debug, Default)]
+pub struct HudAnnouncement {
+    /// Seconds until the announcement is gone.
+    remaining: f32,
+}
+
+/// Seconds an announcement stays up, the last of which it fades out over.
+const ANNOUNCEMENT_SECONDS: f32 = 3.0;
+
+fn spawn_hud(mut commands: Commands, profile: Res<Profile>, save: Res<SaveGame>) {
+    commands
+        .spawn((
+            Name::new("HUD"),
+            Hud,
+            NodeBundle {
+                style: Style {
+                    width: Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::SpaceBetween,
+                    align_items: AlignItems::Start,
+                    padding: UiRect::axes(Px(20.0), Px(10.0)),
+                    ..default()
+                },
+                z_index: Layer::ScreenUi.z_index(0),
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|children| {
+            children
+                .spawn(hud_column(AlignItems::Start))
+                .with_children(|children| {
+                    children.spawn((hud_text(&profile.name, TextPreset::Label), HudName));
+                    children
+                        .spawn((
+                            Name::new("Health Bar"),
+                            NodeBundle {
+                                style: Style {
+                                    width: Px(200.0),
+                                    height: Px(12.0),
+                                    ..default()
+                                },
+                                background_color: BackgroundColor(ui_palette::NODE_BACKGROUND),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|children| {
+                            children.spawn((
+                                Name::new("Health Fill"),
+                                NodeBundle {
+                                    style: Style {
+                                        width: Percent(100.0),
+                                        height: Percent(100.0),
+                                        ..default()
+                                    },
+                                    background_color: BackgroundColor(ui_palette::LABEL_TEXT),
+                                    ..default()
+                                },
+                                ProgressFill,
+                                HudHealth,
+                            ));
+                        });
+                    children
+                        .spawn((
+                            Name::new("HUD Inventory"),
+                            NodeBundle {
+                                style: Style {
+                                    column_gap: Px(4.0),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                        ))
+                        .with_children(|children| {
+                            for index in 0..INVENTORY_SLOTS {
+                                children.item_slot(SLOT_SIZE, HudSlot(index));
+                            }
+                        });
+                    children.spawn((hud_text("", TextPreset::Label), HudMutators));
+                    children.spawn((hud_text("", TextPreset::Value), HudObjectives));
+                    children.spawn((hud_text("", TextPreset::Label), HudChallenge));
+                });
+            children
+                .spawn((
+                    Name::new("HUD Cycle"),
+                    NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Px(8.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children.ring(28.0).insert(HudCycleRing);
+                    children.spawn((hud_text("", TextPreset::Label), HudCycle));
+                });
+            children
+                .spawn(hud_column(AlignItems::End))
+                .with_children(|children| {
+                    // The score picks up where the run left off.
+                    children.counter(save.score).insert(HudScore);
+                    children.spawn((hud_text("", TextPreset::Value), HudCombo));
+                });
+        });
+    commands
+        .spawn((
+            Name::new("HUD Announcements"),
+            NodeBundle {
+                style: Style {
+                    width: Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    top: Px(80.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                z_index: Layer::ScreenUi.z_index(0),
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|children| {
+            children.spawn((hud_text("", TextPreset::Header), HudAnnouncement::default()));
+        });
+    commands
+        .spawn((
+            Name::new("HUD Tutorial"),
+            NodeBundle {
+                style: Style {
+                    width: Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    bottom: Px(40.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                z_index: Layer::ScreenUi.z_index(0),
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|children| {
+            children.spawn((hud_text("", TextPreset::Label), HudTutorial));
+        });
+}
+
+fn hud_column(align_items: AlignItems) -> impl Bundle {
+    (
+        Name::new("HUD Column"),
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items,
+                row_gap: Px(4.0),
+                ..default()
+            },
+            ..default()
+        },
+    )
+}
+
+fn hud_text(text: &str, preset: TextPreset) -> impl Bundle {
+    (
+        Name::new("HUD Text"),
+        TextBundle::from_section(text, preset.style(ui_palette::LABEL_TEXT)),
+        preset,
+        Themed::LabelText,
+    )
+}
+
+fn cycle_text(cycle: &CyclePhase) -> String {
+    format!("Cycle {} - {}", cycle.cycle + 1, cycle.phase_name())
+}
+
+fn show_score(
+    score: Res<Score>,
+    mut counter_query: Query<&mut Counter, With<HudScore>>,
+    mut combo_query: Query<&mut Text, With<HudCombo>>,
+) {
+    for mut counter in &mut counter_query {
+        if counter.target != score.points {
+            counter.target = score.points;
+        }
+    }
+    let combo = if score.multiplier() > 1 {
+        format!("x{}", score.multiplier())
+    } else {
+        String::new()
+    };
+    for mut text in &mut combo_query {
+        if text.sections[0].value != combo {
+            text.sections[0].value.clone_from(&combo);
+        }
+    }
+}
+
+fn show_health(
+    player_query: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut fill_query: Query<&mut Style, With<HudHealth>>,
+) {
+    let Ok(health) = player_query.get_single() else {
+        return;
+    };
+    for mut style in &mut fill_query {
+        set_progress(&mut style, health.fraction());
+    }
+}
+
+/// Once, since the run's mutators don't change.
+fn show_inventory(
+    inventory: Res<Inventory>,
+    catalog: Res<ItemCatalog>,
+    palette: Res<GameplayPalette>,
+    mut part_query: Query<(
+        &SlotPart,
+        &HudSlot,
+        &mut BackgroundColor,
+        &mut Visibility,
+        Option<&mut Text>,
+    )>,
+) {
+    for (&part, slot, mut background, mut visibility, text) in &mut part_query {
+        // Items missing from the catalog still show their count.
+        let stack = inventory.stacks.get(slot.0).map(|stack| {
+            let color = catalog
+                .get(&stack.item)
+                .map_or(ui_palette::LABEL_TEXT, |item| palette.color(item.color()));
+            (color, stack.count)
+        });
+        set_slot_part(
+            part,
+            stack,
+            &mut background,
+            &mut visibility,
+            text.map(Mut::into_inner),
+        );
+    }
+}
+
+fn show_mutators(
+    run_mutators: Res<RunMutators>,
+    catalog: Res<MutatorCatalog>,
+    mut text_query: Query<&mut Text, Added<HudMutators>>,
+) {
+    for mut text in &mut text_query {
+        if !run_mutators.0.is_empty() {
+            text.sections[0].value = catalog.names(&run_mutators.0);
+        }
+    }
+}
+
+fn show_objectives(
+    objectives: Res<Objectives>,
+    mut text_query: Query<&mut Text, With<HudObjectives>>,
+) {
+    let checklist = objectives
+        .list
+        .iter()
+        .map(|objective| objective.checklist_line())
+        .collect::<Vec<_>>()
+        .join("\n");
+    for mut text in &mut text_query {
+        text.sections[0].value.clone_from(&checklist);
+    }
+}
+
+fn show_challenge(challenge: Res<Challenge>, mut text_query: Query<&mut Text, With<HudChallenge>>) {
+    let rules = challenge.hud_lines().join("\n");
+    for mut text in &mut text_query {
+        text.sections[0].value.clone_from(&rules);
+    }
+}
+
+/// Every frame, since the prompt follows the device the player is using.
+fn show_tutorial(
+    tutorial: Res<TutorialPrompts>,
+    prompts: ActionPrompts,
+    modes: Res<ActionModes>,
+    mut text_query: Query<&mut Text, With<HudTutorial>>,
+) {
+    let value = tutorial
+        .current()
+        .map(|lesson| lesson.prompt(&prompts, &modes))
+        .unwrap_or_default();
+    for mut text in &mut text_query {
+        if text.sections[0].value != value {
+            text.sections[0].value.clone_from(&value);
+        }
+    }
+}
+
+fn show_cycle(
+    cycle: Res<CyclePhase>,
+    mut text_query: Query<&mut Text, With<HudCycle>>,
+    mut ring_query: Query<&mut ProgressRing, With<HudCycleRing>>,
+) {
+    let value = cycle_text(&cycle);
+    for mut text in &mut text_query {
+        if text.sections[0].value != value {
+            text.sections[0].value.clone_from(&value);
+        }
+    }
+    let fraction = cycle.cycle_progress();
+    for mut ring in &mut ring_query {
+        if ring.fraction != fraction {
+            ring.fraction = fraction;
+        }
+    }
+}
+
+fn announce_waves(
+    mut commands: Commands,
+    mut started_events: EventReader<WaveStarted>,
+    mut ended_events: EventReader<WaveEnded>,
+    mut announcement_query: Query<(&mut Text, &mut HudAnnouncement)>,
+) {
+    // A wave starting is more important than the last one ending.
+    let message = started_events
+        .read()
+        .last()
+        .map(|event| format!("Wave {}", event.wave))
+        .or_else(|| {
+            ended_events
+                .read()
+                .last()
+                .map(|event| format!("Wave {} cleared", event.wave))
+        });
+    let Some(message) = message else {
+        return;
+    };
+    ended_events.clear();
+    commands.trigger(Announce::new(Importance::Normal, message.clone()));
+    for (mut text, mut announcement) in &mut announcement_query {
+        text.sections[0].value.clone_from(&message);
+        announcement.remaining = ANNOUNCEMENT_SECONDS;
+    }
+}
+
+fn fade_announcements(
+    time: Res<Time>,
+    mut announcement_query: Query<(&mut Text, &mut HudAnnouncement)>,
+) {
+    for (mut text, mut announcement) in &mut announcement_query {
+        if announcement.remaining <= 0.0 {
+            continue;
+        }
+        announcement.remaining = (announcement.remaining - time.delta_seconds()).max(0.0);
+        let alpha = announcement.remaining.min(1.0);
+        for section in &mut text.sections {
+            section.style.color = section.style.color.with_alpha(alpha);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! The screen state for the main game loop.
+
+pub mod hud;
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::game::{audio::soundtrack::SoundtrackCommand, mode::GameMode, spawn::level::SpawnLevel};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Playing), enter_playing);
+    app.add_systems(OnExit(Screen::Playing), exit_playing);
+
+    app.add_plugins(hud::plugin);
+}
+
+fn enter_playing(mut commands: Commands, mode: Res<GameMode>) {
+    commands.trigger(SpawnLevel);
+    commands.trigger(SoundtrackCommand::Play(mode.soundtrack()));
+}
+
+fn exit_playing(mut commands: Commands) {
+    // We could use [`StateScoped`] on the sound playing entities instead.
+    commands.trigger(SoundtrackCommand::Stop);
+}
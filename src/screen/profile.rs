@@ -0,0 +1,155 @@
+//! The profile screen, accessed from the title screen, to enter the player's name
+//! and assign the slots of the quick-action wheel.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use super::{ExitingScreen, Screen};
+use crate::{
+    events::ScreenRequest,
+    game::{
+        profile::Profile,
+        quick_actions::{QuickActions, QUICK_SLOTS},
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Profile), enter_profile);
+    app.observe(keep_name_on_exit);
+
+    app.register_type::<ProfileAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_profile_action,
+            refresh_quick_slot_labels.run_if(resource_changed::<Profile>),
+        )
+            .chain()
+            .run_if(in_state(Screen::Profile)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum ProfileAction {
+    /// Cycle what this slot of the quick-action wheel does.
+    QuickSlot(usize),
+    Back,
+}
+
+/// The directions of the wheel's slots, clockwise from the top.
+const SLOT_DIRECTIONS: [&str; QUICK_SLOTS] = [
+    "Up",
+    "Up-right",
+    "Right",
+    "Down-right",
+    "Down",
+    "Down-left",
+    "Left",
+    "Up-left",
+];
+
+/// The text field with the player's name.
+#[derive(Component)]
+struct NameInput;
+
+fn enter_profile(mut commands: Commands, profile: Res<Profile>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Profile))
+        .with_children(|children| {
+            children.header("Profile");
+            children.label("Name");
+            children
+                .text_input(profile.name.clone(), Profile::MAX_NAME_CHARS)
+                .insert(NameInput)
+                .observe(submit_name);
+            children.label("Quick actions");
+            children
+                .spawn((
+                    Name::new("Quick Slots"),
+                    NodeBundle {
+                        style: Style {
+                            width: Px(860.0),
+                            flex_wrap: FlexWrap::Wrap,
+                            justify_content: JustifyContent::Center,
+                            row_gap: Px(10.0),
+                            column_gap: Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    for slot in 0..QUICK_SLOTS {
+                        children
+                            .button(quick_slot_label(&profile.quick_actions, slot))
+                            .insert(ProfileAction::QuickSlot(slot));
+                    }
+                });
+            children.button("Back").insert(ProfileAction::Back);
+        });
+}
+
+fn submit_name(trigger: Trigger<TextSubmitted>, mut profile: ResMut<Profile>) {
+    profile.set_name(&trigger.event().0);
+}
+
+/// Keep the name even if it wasn't submitted with Enter.
+fn keep_name_on_exit(
+    trigger: Trigger<ExitingScreen>,
+    mut profile: ResMut<Profile>,
+    input_query: Query<&TextInput, With<NameInput>>,
+) {
+    if trigger.event().from != Screen::Profile {
+        return;
+    }
+    for input in &input_query {
+        profile.set_name(&input.value);
+    }
+}
+
+/// The text of a slot's button, with its direction on the wheel.
+fn quick_slot_label(quick_actions: &QuickActions, slot: usize) -> String {
+    format!(
+        "{}: {}",
+        SLOT_DIRECTIONS[slot],
+        quick_actions.0[slot].name()
+    )
+}
+
+fn handle_profile_action(
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mut profile: ResMut<Profile>,
+    mut button_query: InteractionQuery<&ProfileAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match *action {
+                ProfileAction::QuickSlot(slot) => {
+                    let quick_action = &mut profile.quick_actions.0[slot];
+                    *quick_action = quick_action.next();
+                }
+                ProfileAction::Back => {
+                    screen_requests.send(ScreenRequest::Back);
+                }
+            }
+        }
+    }
+}
+
+fn refresh_quick_slot_labels(
+    profile: Res<Profile>,
+    button_query: Query<(&ProfileAction, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (action, children) in &button_query {
+        let ProfileAction::QuickSlot(slot) = *action else {
+            continue;
+        };
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = quick_slot_label(&profile.quick_actions, slot);
+        }
+    }
+}
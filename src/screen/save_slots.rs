@@ -0,0 +1,98 @@
+//! The slot picker, shown when starting to play from the title screen.
+//! Picking a slot continues the game saved in it, or starts a new one in an empty slot.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use super::Screen;
+use crate::{
+    events::ScreenRequest,
+    game::save::{ActiveSlot, SaveSlot},
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::SaveSlots), enter_save_slots);
+
+    app.register_type::<SlotAction>();
+    app.add_systems(
+        Update,
+        handle_slot_action.run_if(in_state(Screen::SaveSlots)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum SlotAction {
+    Play(SaveSlot),
+    Delete(SaveSlot),
+    Back,
+}
+
+/// The root of the menu, respawned when a slot is deleted.
+#[derive(Component)]
+struct SlotMenu;
+
+fn enter_save_slots(mut commands: Commands) {
+    spawn_slot_menu(&mut commands);
+}
+
+fn spawn_slot_menu(commands: &mut Commands) {
+    commands
+        .ui_root()
+        .insert((SlotMenu, StateScoped(Screen::SaveSlots)))
+        .with_children(|children| {
+            children.header("Choose a slot");
+            for slot in SaveSlot::ALL {
+                let save = slot.load();
+                children
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        let text = match &save {
+                            Some(save) => format!("{}: {}", slot.name(), save.summary()),
+                            None => format!("{}: New game", slot.name()),
+                        };
+                        row.button(text).insert(SlotAction::Play(slot));
+                        if save.is_some() {
+                            row.button("Delete").insert(SlotAction::Delete(slot));
+                        }
+                    });
+            }
+            children.button("Back").insert(SlotAction::Back);
+        });
+}
+
+fn handle_slot_action(
+    mut commands: Commands,
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mut button_query: InteractionQuery<&SlotAction>,
+    menu_query: Query<Entity, With<SlotMenu>>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match *action {
+                SlotAction::Play(slot) => {
+                    commands.insert_resource(slot.load().unwrap_or_default());
+                    commands.insert_resource(ActiveSlot(slot));
+                    screen_requests.send(ScreenRequest::To(Screen::Playing));
+                }
+                SlotAction::Delete(slot) => {
+                    slot.delete();
+                    info!("Deleted the game in {}.", slot.name());
+                    for menu in &menu_query {
+                        commands.entity(menu).despawn_recursive();
+                    }
+                    spawn_slot_menu(&mut commands);
+                }
+                SlotAction::Back => {
+                    screen_requests.send(ScreenRequest::Back);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! The summary of a play session, shown when quitting to the title from the pause menu,
+//! and on the game over screen. Its text for sharing can be copied to the clipboard.
+
+use bevy::prelude::*;
+
+use super::{PlayingState, Screen};
+use crate::{clipboard, events::ScreenRequest, game::stats::SessionStats, ui::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(PlayingState::Summary), enter_summary);
+
+    app.register_type::<SummaryAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_summary_action.run_if(in_state(PlayingState::Summary)),
+            copy_share_text,
+        ),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum SummaryAction {
+    Continue,
+}
+
+/// A button that copies its text for sharing to the clipboard.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+struct ShareText(String);
+
+fn enter_summary(mut commands: Commands, stats: Res<SessionStats>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(PlayingState::Summary))
+        .with_children(|children| {
+            children.header("Session Summary");
+            session_summary(children, &stats);
+            children.button("Continue").insert(SummaryAction::Continue);
+        });
+}
+
+/// A label per statistic, then the text for sharing and a button to copy it.
+pub(super) fn session_summary(children: &mut ChildBuilder, stats: &SessionStats) {
+    for line in stats.lines() {
+        children.label(line);
+    }
+    let share_text = stats.share_text();
+    children.label(share_text.clone());
+    children
+        .button("Copy to clipboard")
+        .insert(ShareText(share_text));
+}
+
+fn handle_summary_action(
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mut button_query: InteractionQuery<&SummaryAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                SummaryAction::Continue => {
+                    screen_requests.send(ScreenRequest::To(Screen::Title));
+                }
+            }
+        }
+    }
+}
+
+fn copy_share_text(mut button_query: InteractionQuery<&ShareText>) {
+    for (interaction, share_text) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            clipboard::copy(&share_text.0);
+        }
+    }
+}
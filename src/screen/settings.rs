@@ -1,61 +1,417 @@
-use crate::screen::Screen;
+use crate::display::MonitorChoice;
+use crate::events::ScreenRequest;
+use crate::game::audio::meter::AudioMeters;
+use crate::game::audio::sfx::{PlaySfx, UiCue};
+use crate::game::gamepad::{Rumble, RumbleSetting};
+use crate::screen::{PlayingState, Screen};
 use crate::ui::prelude::*;
-use crate::{BinaryAdjustment, GameSettings, LevelSetting, LevelSettingAction};
+use crate::window_placement::Monitors;
+use crate::{BinaryAdjustment, GameSettings, LevelSetting, LevelSettingAction, VolumeSetting};
 use bevy::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(Screen::Settings), enter_settings)
-        .add_systems(
-            Update,
-            (handle_volume_action, handle_settings_action).run_if(in_state(Screen::Settings)),
+    // The settings menu is reachable from the title screen and from the pause menu.
+    app.add_systems(
+        OnEnter(Screen::Settings),
+        enter_settings(Screen::Settings, true),
+    )
+    .add_systems(
+        OnEnter(PlayingState::Settings),
+        enter_settings(PlayingState::Settings, false),
+    )
+    .add_systems(
+        Update,
+        (
+            switch_settings_tab,
+            handle_volume_action,
+            update_volume_meters,
+            handle_log_level_action,
+            handle_rumble_action,
+            handle_display_action,
+            handle_effects_action,
+            handle_gamepad_action,
+            handle_accessibility_action,
+            handle_settings_action,
         )
-        .register_type::<LevelSettingAction<VolumeSettingScope>>()
-        .register_type::<ScreenAction>();
+            .run_if(in_state(Screen::Settings).or_else(in_state(PlayingState::Settings))),
+    )
+    .observe(apply_slider_entry)
+    .register_type::<LevelSettingAction<VolumeSettingScope>>()
+    .register_type::<LevelSettingAction<LogLevelScope>>()
+    .register_type::<LevelSettingAction<RumbleScope>>()
+    .register_type::<LevelSettingAction<DisplayScope>>()
+    .register_type::<LevelSettingAction<EffectsScope>>()
+    .register_type::<LevelSettingAction<GamepadScope>>()
+    .register_type::<LevelSettingAction<AccessibilityScope>>()
+    .register_type::<(ScreenAction, SettingsTab)>();
 }
 
 #[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
 #[reflect(Component)]
 enum ScreenAction {
+    Controls,
+    /// Restore the default settings.
+    Reset,
+    /// Undo all changes since the settings were opened, and go back.
+    Cancel,
     Back,
 }
 
+/// The settings as they were when the settings menu was opened, for [`ScreenAction::Cancel`].
+#[derive(Resource, Debug)]
+struct SettingsSnapshot(GameSettings);
+
 #[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
 enum VolumeSettingScope {
     Global,
     Soundtrack,
     Sfx,
+    Ui,
+    UiCue(UiCue),
+}
+
+fn volume_level(settings: &GameSettings, scope: VolumeSettingScope) -> &VolumeSetting {
+    match scope {
+        VolumeSettingScope::Global => &settings.global_volume_level,
+        VolumeSettingScope::Soundtrack => &settings.soundtrack_volume_level_relative,
+        VolumeSettingScope::Sfx => &settings.sfx_volume_level_relative,
+        VolumeSettingScope::Ui => &settings.ui_volume_level_relative,
+        VolumeSettingScope::UiCue(cue) => settings.ui_cue_volumes.get(cue),
+    }
+}
+
+fn volume_level_mut(settings: &mut GameSettings, scope: VolumeSettingScope) -> &mut VolumeSetting {
+    match scope {
+        VolumeSettingScope::Global => &mut settings.global_volume_level,
+        VolumeSettingScope::Soundtrack => &mut settings.soundtrack_volume_level_relative,
+        VolumeSettingScope::Sfx => &mut settings.sfx_volume_level_relative,
+        VolumeSettingScope::Ui => &mut settings.ui_volume_level_relative,
+        VolumeSettingScope::UiCue(cue) => settings.ui_cue_volumes.get_mut(cue),
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+struct LogLevelScope;
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+struct RumbleScope;
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+enum DisplayScope {
+    Fullscreen,
+    Vsync,
+    Resolution,
+    Monitor,
+    PixelPerfect,
+    Upscaling,
+    ViewSize,
+    MotionSmoothing,
+    Particles,
+    Quality,
+    RunInBackground,
+    UnfocusedAudio,
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+enum EffectsScope {
+    Bloom,
+    LowHealthVignette,
+    HitAberration,
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+enum AccessibilityScope {
+    HighContrast,
+    Colorblind,
+    TextSize,
+    DyslexicFont,
+    ScreenShake,
+    Announcements,
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+enum GamepadScope {
+    Layout,
+    SwapConfirm,
+}
+
+/// The categories of settings, each shown in its own tab.
+/// Marks both the tab button and the panel it shows.
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+#[reflect(Component)]
+enum SettingsTab {
+    Audio,
+    Display,
+    Effects,
+    Controls,
+    Accessibility,
+    Advanced,
+}
+
+/// `show_controls` links to the controls screen, which can't be opened during gameplay.
+fn enter_settings<S: States>(
+    scope: S,
+    show_controls: bool,
+) -> impl Fn(Commands, Res<GameSettings>, Monitors) {
+    move |mut commands, settings, monitors| {
+        let monitors = MonitorChoice::all(&monitors.all());
+        commands.insert_resource(SettingsSnapshot(settings.clone()));
+        commands
+            .ui_root()
+            .insert(StateScoped(scope.clone()))
+            .with_children(|children| {
+                children.header("Settings");
+                children.tab_bar(&[
+                    ("Audio", SettingsTab::Audio),
+                    ("Display", SettingsTab::Display),
+                    ("Effects", SettingsTab::Effects),
+                    ("Controls", SettingsTab::Controls),
+                    ("Accessibility", SettingsTab::Accessibility),
+                    ("Advanced", SettingsTab::Advanced),
+                ]);
+
+                children
+                    .tab_panel(SettingsTab::Audio, true)
+                    .with_children(|children| audio_settings(children, &settings));
+                children
+                    .tab_panel(SettingsTab::Display, false)
+                    .with_children(|children| display_settings(children, &settings, &monitors));
+                children
+                    .tab_panel(SettingsTab::Effects, false)
+                    .with_children(|children| effects_settings(children, &settings));
+                children
+                    .tab_panel(SettingsTab::Controls, false)
+                    .with_children(|children| {
+                        controls_settings(children, &settings, show_controls)
+                    });
+                children
+                    .tab_panel(SettingsTab::Accessibility, false)
+                    .with_children(|children| accessibility_settings(children, &settings));
+                children
+                    .tab_panel(SettingsTab::Advanced, false)
+                    .with_children(|children| advanced_settings(children, &settings));
+
+                children.button("Reset").insert(ScreenAction::Reset);
+                children.button("Cancel").insert(ScreenAction::Cancel);
+                children.button("Back").insert(ScreenAction::Back);
+            });
+    }
+}
+
+fn audio_settings(children: &mut ChildBuilder, settings: &GameSettings) {
+    children.slider(
+        "Global audio volume",
+        settings.global_volume_level.percent_display(),
+        VolumeSettingScope::Global,
+    );
+    children.meter(VolumeSettingScope::Global);
+
+    children.slider(
+        "Music volume (relative)",
+        settings.soundtrack_volume_level_relative.percent_display(),
+        VolumeSettingScope::Soundtrack,
+    );
+    children.meter(VolumeSettingScope::Soundtrack);
+
+    children.slider(
+        "SFX volume (relative)",
+        settings.sfx_volume_level_relative.percent_display(),
+        VolumeSettingScope::Sfx,
+    );
+    children.meter(VolumeSettingScope::Sfx);
+
+    children.slider(
+        "UI sounds volume (relative to SFX)",
+        settings.ui_volume_level_relative.percent_display(),
+        VolumeSettingScope::Ui,
+    );
+    children.meter(VolumeSettingScope::Ui);
+    for cue in UiCue::ALL {
+        children.slider(
+            format!("{} (relative to UI)", cue.name()),
+            settings.ui_cue_volumes.get(cue).percent_display(),
+            VolumeSettingScope::UiCue(cue),
+        );
+    }
+}
+
+fn display_settings(
+    children: &mut ChildBuilder,
+    settings: &GameSettings,
+    monitors: &[MonitorChoice],
+) {
+    children.settings_field(
+        "Fullscreen",
+        settings.display.fullscreen.name_display(),
+        DisplayScope::Fullscreen,
+    );
+
+    children.settings_field(
+        "VSync",
+        settings.display.vsync.name_display(),
+        DisplayScope::Vsync,
+    );
+
+    children.settings_field(
+        "Pixel perfect",
+        settings.display.pixel_perfect.name_display(),
+        DisplayScope::PixelPerfect,
+    );
+
+    children.settings_field(
+        "Upscaling",
+        settings.display.upscaling.name_display(),
+        DisplayScope::Upscaling,
+    );
+
+    children.settings_field(
+        "View size",
+        settings.display.view_size.name_display(),
+        DisplayScope::ViewSize,
+    );
+
+    children.settings_field(
+        "Motion smoothing",
+        settings.display.motion_smoothing.name_display(),
+        DisplayScope::MotionSmoothing,
+    );
+
+    children.settings_field(
+        "Graphics quality",
+        settings.display.quality.name_display(),
+        DisplayScope::Quality,
+    );
+
+    children.settings_field(
+        "Particles",
+        settings.display.particles.name_display(),
+        DisplayScope::Particles,
+    );
+
+    // The web build fits the canvas to the page instead.
+    if cfg!(not(target_family = "wasm")) {
+        children.settings_field(
+            "Window size",
+            settings.display.resolution.name_display(),
+            DisplayScope::Resolution,
+        );
+        children.settings_field(
+            "Fullscreen monitor",
+            settings.display.monitor.name_display(monitors),
+            DisplayScope::Monitor,
+        );
+        // Background tabs are always paused, since browsers throttle them.
+        children.settings_field(
+            "Run in background",
+            settings.run_in_background.name_display(),
+            DisplayScope::RunInBackground,
+        );
+        children.settings_field(
+            "Audio when unfocused",
+            settings.unfocused_audio.name_display(),
+            DisplayScope::UnfocusedAudio,
+        );
+    }
 }
 
-fn enter_settings(mut commands: Commands, settings: Res<GameSettings>) {
-    commands
-        .ui_root()
-        .insert(StateScoped(Screen::Settings))
-        .with_children(|children| {
-            children.header("Settings");
+fn effects_settings(children: &mut ChildBuilder, settings: &GameSettings) {
+    let effects = &settings.display.effects;
+    children.settings_field("Bloom", effects.bloom.name_display(), EffectsScope::Bloom);
+    children.settings_field(
+        "Low health vignette",
+        effects.low_health_vignette.name_display(),
+        EffectsScope::LowHealthVignette,
+    );
+    children.settings_field(
+        "Color split on hits",
+        effects.hit_aberration.name_display(),
+        EffectsScope::HitAberration,
+    );
+}
 
-            children.settings_field(
-                "Global audio volume",
-                settings.global_volume_level.percent_display(),
-                VolumeSettingScope::Global,
-            );
+fn controls_settings(children: &mut ChildBuilder, settings: &GameSettings, show_controls: bool) {
+    children.slider(
+        "Gamepad rumble",
+        settings.rumble_level.percent_display(),
+        RumbleScope,
+    );
 
-            children.settings_field(
-                "Music volume (relative)",
-                settings.soundtrack_volume_level_relative.percent_display(),
-                VolumeSettingScope::Soundtrack,
-            );
+    children.settings_field(
+        "Gamepad layout",
+        settings.gamepad_layout.name_display(),
+        GamepadScope::Layout,
+    );
 
-            children.settings_field(
-                "SFX volume (relative)",
-                settings.sfx_volume_level_relative.percent_display(),
-                VolumeSettingScope::Sfx,
-            );
+    children.settings_field(
+        "Swap confirm and cancel",
+        settings.swap_confirm.name_display(),
+        GamepadScope::SwapConfirm,
+    );
 
-            children.button("Back").insert(ScreenAction::Back);
-        });
+    if show_controls {
+        children
+            .button("Key bindings")
+            .insert(ScreenAction::Controls);
+    }
+}
+
+fn accessibility_settings(children: &mut ChildBuilder, settings: &GameSettings) {
+    children.settings_field(
+        "High contrast",
+        settings.high_contrast.name_display(),
+        AccessibilityScope::HighContrast,
+    );
+    children.settings_field(
+        "Colorblind mode",
+        settings.colorblind.name_display(),
+        AccessibilityScope::Colorblind,
+    );
+    children.settings_field(
+        "Text size",
+        settings.text_size.name_display(),
+        AccessibilityScope::TextSize,
+    );
+    children.settings_field(
+        "Dyslexia-friendly font",
+        settings.dyslexic_font.name_display(),
+        AccessibilityScope::DyslexicFont,
+    );
+    children.settings_field(
+        "Screen shake",
+        settings.screen_shake_enabled.name_display(),
+        AccessibilityScope::ScreenShake,
+    );
+    children.settings_field(
+        "Screen reader announcements",
+        settings.announcement_verbosity.name_display(),
+        AccessibilityScope::Announcements,
+    );
+}
+
+fn advanced_settings(children: &mut ChildBuilder, settings: &GameSettings) {
+    children.settings_field(
+        "Log level",
+        settings.log_level.name_display(),
+        LogLevelScope,
+    );
+}
+
+fn switch_settings_tab(
+    tab_query: InteractionQuery<&SettingsTab>,
+    mut panel_query: Query<(&SettingsTab, &mut Style, &mut Visibility), Without<Interaction>>,
+) {
+    for (interaction, &tab) in &tab_query {
+        if matches!(interaction, Interaction::Pressed) {
+            for (&panel_tab, mut style, mut visibility) in &mut panel_query {
+                set_tab_panel_visible(&mut style, &mut visibility, panel_tab == tab);
+            }
+        }
+    }
 }
 
 fn handle_volume_action(
+    mut commands: Commands,
+    fine_adjust: FineAdjust,
     mut global_volume: ResMut<GlobalVolume>,
     mut settings: ResMut<GameSettings>,
     mut text_query: Query<(&mut Text, &VolumeSettingScope)>,
@@ -66,18 +422,16 @@ fn handle_volume_action(
         .filter_map(|(i, b)| matches!(i, Interaction::Pressed).then_some(b))
     {
         // update record
-        let (setting_level, update_global) = match scope {
-            VolumeSettingScope::Global => (&mut settings.global_volume_level, true),
-            VolumeSettingScope::Soundtrack => {
-                (&mut settings.soundtrack_volume_level_relative, false)
-            }
-            VolumeSettingScope::Sfx => (&mut settings.sfx_volume_level_relative, false),
-        };
-        setting_level.0 = match adjustment {
-            // type ensures bound
-            BinaryAdjustment::Up => setting_level.0 + 1u8,
-            BinaryAdjustment::Down => setting_level.0 - 1u8,
-        };
+        let update_global = scope == VolumeSettingScope::Global;
+        let setting_level = volume_level_mut(&mut settings, scope);
+        let stepped = setting_level.stepped(adjustment, fine_adjust.held());
+        if stepped == *setting_level {
+            commands.trigger(PlaySfx::Key(UiCue::InvalidAction.key()));
+        } else if let VolumeSettingScope::UiCue(cue) = scope {
+            // Play the cue at its new volume, since it may be hard to tell apart otherwise.
+            commands.trigger(PlaySfx::Key(cue.key()));
+        }
+        *setting_level = stepped;
         // update ui
         text_query
             .iter_mut()
@@ -98,15 +452,405 @@ fn handle_volume_action(
     }
 }
 
+fn update_volume_meters(
+    meters: Res<AudioMeters>,
+    mut meter_query: Query<(&mut Style, &MeterPart, &VolumeSettingScope)>,
+) {
+    for (mut style, &part, scope) in &mut meter_query {
+        let level = match scope {
+            VolumeSettingScope::Global => meters.master,
+            VolumeSettingScope::Soundtrack => meters.soundtrack,
+            VolumeSettingScope::Sfx => meters.sfx,
+            VolumeSettingScope::Ui => meters.ui,
+            // Cues have no meters.
+            VolumeSettingScope::UiCue(_) => continue,
+        };
+        set_meter_level(&mut style, part, level.rms, level.peak);
+    }
+}
+
+fn handle_log_level_action(
+    mut settings: ResMut<GameSettings>,
+    mut text_query: Query<&mut Text, With<LogLevelScope>>,
+    mut button_query: InteractionQuery<&LevelSettingAction<LogLevelScope>>,
+) {
+    for &LevelSettingAction { adjustment, .. } in button_query
+        .iter_mut()
+        .filter_map(|(i, b)| matches!(i, Interaction::Pressed).then_some(b))
+    {
+        let log_level = &mut settings.log_level;
+        log_level.0 = match adjustment {
+            BinaryAdjustment::Up => log_level.0 + 1u8,
+            BinaryAdjustment::Down => log_level.0 - 1u8,
+        };
+        text_query.single_mut().sections[0].value = log_level.name_display();
+        info!("Updated log level to {}.", log_level.name_display());
+    }
+}
+
+fn handle_rumble_action(
+    mut commands: Commands,
+    fine_adjust: FineAdjust,
+    mut settings: ResMut<GameSettings>,
+    mut text_query: Query<&mut Text, With<RumbleScope>>,
+    mut button_query: InteractionQuery<&LevelSettingAction<RumbleScope>>,
+) {
+    for &LevelSettingAction { adjustment, .. } in button_query
+        .iter_mut()
+        .filter_map(|(i, b)| matches!(i, Interaction::Pressed).then_some(b))
+    {
+        let rumble_level = &mut settings.rumble_level;
+        let stepped = rumble_level.stepped(adjustment, fine_adjust.held());
+        if stepped == *rumble_level {
+            commands.trigger(PlaySfx::Key(UiCue::InvalidAction.key()));
+        }
+        *rumble_level = stepped;
+        text_query.single_mut().sections[0].value = rumble_level.percent_display();
+        // Preview the new strength.
+        commands.trigger(Rumble::HIT);
+    }
+}
+
+fn handle_display_action(
+    mut settings: ResMut<GameSettings>,
+    monitors: Monitors,
+    mut text_query: Query<(&mut Text, &DisplayScope)>,
+    mut button_query: InteractionQuery<&LevelSettingAction<DisplayScope>>,
+) {
+    for &LevelSettingAction { adjustment, scope } in button_query
+        .iter_mut()
+        .filter_map(|(i, b)| matches!(i, Interaction::Pressed).then_some(b))
+    {
+        let value = match scope {
+            DisplayScope::Fullscreen
+            | DisplayScope::Vsync
+            | DisplayScope::PixelPerfect
+            | DisplayScope::MotionSmoothing
+            | DisplayScope::RunInBackground => {
+                let toggle = match scope {
+                    DisplayScope::Fullscreen => &mut settings.display.fullscreen,
+                    DisplayScope::Vsync => &mut settings.display.vsync,
+                    DisplayScope::PixelPerfect => &mut settings.display.pixel_perfect,
+                    DisplayScope::MotionSmoothing => &mut settings.display.motion_smoothing,
+                    _ => &mut settings.run_in_background,
+                };
+                toggle.0 = match adjustment {
+                    BinaryAdjustment::Up => toggle.0 + 1u8,
+                    BinaryAdjustment::Down => toggle.0 - 1u8,
+                };
+                toggle.name_display()
+            }
+            DisplayScope::Resolution => {
+                let resolution = &mut settings.display.resolution;
+                resolution.0 = match adjustment {
+                    BinaryAdjustment::Up => resolution.0 + 1u8,
+                    BinaryAdjustment::Down => resolution.0 - 1u8,
+                };
+                resolution.name_display()
+            }
+            DisplayScope::Monitor => {
+                // Monitors can be plugged in or out while the settings are open.
+                let monitors = MonitorChoice::all(&monitors.all());
+                let monitor = &mut settings.display.monitor;
+                *monitor = monitor.stepped(adjustment, &monitors);
+                monitor.name_display(&monitors)
+            }
+            DisplayScope::Quality => {
+                let quality = &mut settings.display.quality;
+                quality.0 = match adjustment {
+                    BinaryAdjustment::Up => quality.0 + 1u8,
+                    BinaryAdjustment::Down => quality.0 - 1u8,
+                };
+                quality.name_display()
+            }
+            DisplayScope::Upscaling => {
+                let upscaling = &mut settings.display.upscaling;
+                upscaling.0 = match adjustment {
+                    BinaryAdjustment::Up => upscaling.0 + 1u8,
+                    BinaryAdjustment::Down => upscaling.0 - 1u8,
+                };
+                upscaling.name_display()
+            }
+            DisplayScope::ViewSize => {
+                let view_size = &mut settings.display.view_size;
+                view_size.0 = match adjustment {
+                    BinaryAdjustment::Up => view_size.0 + 1u8,
+                    BinaryAdjustment::Down => view_size.0 - 1u8,
+                };
+                view_size.name_display()
+            }
+            DisplayScope::Particles => {
+                let particles = &mut settings.display.particles;
+                particles.0 = match adjustment {
+                    BinaryAdjustment::Up => particles.0 + 1u8,
+                    BinaryAdjustment::Down => particles.0 - 1u8,
+                };
+                particles.name_display()
+            }
+            DisplayScope::UnfocusedAudio => {
+                let unfocused_audio = &mut settings.unfocused_audio;
+                unfocused_audio.0 = match adjustment {
+                    BinaryAdjustment::Up => unfocused_audio.0 + 1u8,
+                    BinaryAdjustment::Down => unfocused_audio.0 - 1u8,
+                };
+                unfocused_audio.name_display()
+            }
+        };
+        if let Some((mut text, _)) = text_query.iter_mut().find(|(_, &test)| test == scope) {
+            text.sections[0].value.clone_from(&value);
+        }
+        info!("Updated display setting {scope:?} to {value}.");
+    }
+}
+
+fn handle_effects_action(
+    mut settings: ResMut<GameSettings>,
+    mut text_query: Query<(&mut Text, &EffectsScope)>,
+    mut button_query: InteractionQuery<&LevelSettingAction<EffectsScope>>,
+) {
+    for &LevelSettingAction { adjustment, scope } in button_query
+        .iter_mut()
+        .filter_map(|(i, b)| matches!(i, Interaction::Pressed).then_some(b))
+    {
+        let effects = &mut settings.display.effects;
+        let toggle = match scope {
+            EffectsScope::Bloom => &mut effects.bloom,
+            EffectsScope::LowHealthVignette => &mut effects.low_health_vignette,
+            EffectsScope::HitAberration => &mut effects.hit_aberration,
+        };
+        toggle.0 = match adjustment {
+            BinaryAdjustment::Up => toggle.0 + 1u8,
+            BinaryAdjustment::Down => toggle.0 - 1u8,
+        };
+        let value = toggle.name_display();
+        if let Some((mut text, _)) = text_query.iter_mut().find(|(_, &test)| test == scope) {
+            text.sections[0].value.clone_from(&value);
+        }
+        info!("Updated effect setting {scope:?} to {value}.");
+    }
+}
+
+fn handle_gamepad_action(
+    mut settings: ResMut<GameSettings>,
+    mut text_query: Query<(&mut Text, &GamepadScope)>,
+    mut button_query: InteractionQuery<&LevelSettingAction<GamepadScope>>,
+) {
+    for &LevelSettingAction { adjustment, scope } in button_query
+        .iter_mut()
+        .filter_map(|(i, b)| matches!(i, Interaction::Pressed).then_some(b))
+    {
+        let value = match scope {
+            GamepadScope::Layout => {
+                let layout = &mut settings.gamepad_layout;
+                layout.0 = match adjustment {
+                    BinaryAdjustment::Up => layout.0 + 1u8,
+                    BinaryAdjustment::Down => layout.0 - 1u8,
+                };
+                layout.name_display()
+            }
+            GamepadScope::SwapConfirm => {
+                let swap_confirm = &mut settings.swap_confirm;
+                swap_confirm.0 = match adjustment {
+                    BinaryAdjustment::Up => swap_confirm.0 + 1u8,
+                    BinaryAdjustment::Down => swap_confirm.0 - 1u8,
+                };
+                swap_confirm.name_display()
+            }
+        };
+        if let Some((mut text, _)) = text_query.iter_mut().find(|(_, &test)| test == scope) {
+            text.sections[0].value.clone_from(&value);
+        }
+        info!("Updated gamepad setting {scope:?} to {value}.");
+    }
+}
+
+fn handle_accessibility_action(
+    mut settings: ResMut<GameSettings>,
+    mut text_query: Query<(&mut Text, &AccessibilityScope)>,
+    mut button_query: InteractionQuery<&LevelSettingAction<AccessibilityScope>>,
+) {
+    for &LevelSettingAction { adjustment, scope } in button_query
+        .iter_mut()
+        .filter_map(|(i, b)| matches!(i, Interaction::Pressed).then_some(b))
+    {
+        let value = match scope {
+            AccessibilityScope::HighContrast
+            | AccessibilityScope::DyslexicFont
+            | AccessibilityScope::ScreenShake => {
+                let setting = match scope {
+                    AccessibilityScope::HighContrast => &mut settings.high_contrast,
+                    AccessibilityScope::DyslexicFont => &mut settings.dyslexic_font,
+                    _ => &mut settings.screen_shake_enabled,
+                };
+                setting.0 = match adjustment {
+                    BinaryAdjustment::Up => setting.0 + 1u8,
+                    BinaryAdjustment::Down => setting.0 - 1u8,
+                };
+                setting.name_display()
+            }
+            AccessibilityScope::Colorblind => {
+                let setting = &mut settings.colorblind;
+                setting.0 = match adjustment {
+                    BinaryAdjustment::Up => setting.0 + 1u8,
+                    BinaryAdjustment::Down => setting.0 - 1u8,
+                };
+                setting.name_display()
+            }
+            AccessibilityScope::TextSize => {
+                let setting = &mut settings.text_size;
+                setting.0 = match adjustment {
+                    BinaryAdjustment::Up => setting.0 + 1u8,
+                    BinaryAdjustment::Down => setting.0 - 1u8,
+                };
+                setting.name_display()
+            }
+            AccessibilityScope::Announcements => {
+                let setting = &mut settings.announcement_verbosity;
+                setting.0 = match adjustment {
+                    BinaryAdjustment::Up => setting.0 + 1u8,
+                    BinaryAdjustment::Down => setting.0 - 1u8,
+                };
+                setting.name_display()
+            }
+        };
+        if let Some((mut text, _)) = text_query.iter_mut().find(|(_, &test)| test == scope) {
+            text.sections[0].value.clone_from(&value);
+        }
+        info!("Updated accessibility setting {scope:?} to {value}.");
+    }
+}
+
 fn handle_settings_action(
-    mut next_screen: ResMut<NextState<Screen>>,
+    mut screen_requests: EventWriter<ScreenRequest>,
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+    playing_state: Option<Res<State<PlayingState>>>,
+    mut settings: ResMut<GameSettings>,
+    snapshot: Res<SettingsSnapshot>,
+    mut global_volume: ResMut<GlobalVolume>,
+    monitors: Monitors,
+    mut label_query: Query<(&mut Text, SettingsLabel)>,
     mut button_query: InteractionQuery<&ScreenAction>,
 ) {
     for (interaction, action) in &mut button_query {
-        if matches!(interaction, Interaction::Pressed) {
-            match action {
-                ScreenAction::Back => next_screen.set(Screen::Title),
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        // Both of these change every setting at once.
+        if matches!(action, ScreenAction::Reset | ScreenAction::Cancel) {
+            *settings = match action {
+                ScreenAction::Reset => GameSettings::default(),
+                _ => snapshot.0.clone(),
+            };
+            global_volume.volume = (&settings.global_volume_level).into();
+            refresh_settings_labels(&settings, &monitors, &mut label_query);
+            info!("Settings were {action:?}.");
+        }
+        match action {
+            // Return to wherever the settings were opened from.
+            ScreenAction::Back | ScreenAction::Cancel if playing_state.is_some() => {
+                next_playing_state.set(PlayingState::Paused)
             }
+            ScreenAction::Back | ScreenAction::Cancel => {
+                screen_requests.send(ScreenRequest::Back);
+            }
+            ScreenAction::Controls => {
+                screen_requests.send(ScreenRequest::To(Screen::Controls));
+            }
+            ScreenAction::Reset => (),
         }
     }
 }
+
+/// Set a slider to the value typed into its numeric entry popup.
+fn apply_slider_entry(
+    trigger: Trigger<SliderEntered>,
+    mut commands: Commands,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut settings: ResMut<GameSettings>,
+    slider_query: Query<AnyOf<(&VolumeSettingScope, &RumbleScope)>>,
+    monitors: Monitors,
+    mut label_query: Query<(&mut Text, SettingsLabel)>,
+) {
+    let Ok((volume, rumble)) = slider_query.get(trigger.entity()) else {
+        return;
+    };
+    let fraction = trigger.event().percent as f32 / 100.0;
+    if let Some(&scope) = volume {
+        *volume_level_mut(&mut settings, scope) = VolumeSetting::from_fraction(fraction);
+        global_volume.volume = (&settings.global_volume_level).into();
+    } else if rumble.is_some() {
+        settings.rumble_level = RumbleSetting::from_fraction(fraction);
+        commands.trigger(Rumble::HIT);
+    }
+    refresh_settings_labels(&settings, &monitors, &mut label_query);
+}
+
+/// The scope of a settings field's text, whichever kind it is.
+type SettingsLabel = AnyOf<(
+    &'static VolumeSettingScope,
+    &'static LogLevelScope,
+    &'static RumbleScope,
+    &'static DisplayScope,
+    &'static EffectsScope,
+    &'static GamepadScope,
+    &'static AccessibilityScope,
+)>;
+
+/// Rewrite the text of every settings field from `settings`.
+fn refresh_settings_labels(
+    settings: &GameSettings,
+    monitors: &Monitors,
+    label_query: &mut Query<(&mut Text, SettingsLabel)>,
+) {
+    let monitors = MonitorChoice::all(&monitors.all());
+    for (mut text, (volume, log_level, rumble, display, effects, gamepad, accessibility)) in
+        label_query
+    {
+        let value = if let Some(&scope) = volume {
+            volume_level(settings, scope).percent_display()
+        } else if log_level.is_some() {
+            settings.log_level.name_display()
+        } else if rumble.is_some() {
+            settings.rumble_level.percent_display()
+        } else if let Some(scope) = display {
+            match scope {
+                DisplayScope::Fullscreen => settings.display.fullscreen.name_display(),
+                DisplayScope::Vsync => settings.display.vsync.name_display(),
+                DisplayScope::Resolution => settings.display.resolution.name_display(),
+                DisplayScope::Monitor => settings.display.monitor.name_display(&monitors),
+                DisplayScope::PixelPerfect => settings.display.pixel_perfect.name_display(),
+                DisplayScope::Upscaling => settings.display.upscaling.name_display(),
+                DisplayScope::ViewSize => settings.display.view_size.name_display(),
+                DisplayScope::MotionSmoothing => settings.display.motion_smoothing.name_display(),
+                DisplayScope::Particles => settings.display.particles.name_display(),
+                DisplayScope::Quality => settings.display.quality.name_display(),
+                DisplayScope::RunInBackground => settings.run_in_background.name_display(),
+                DisplayScope::UnfocusedAudio => settings.unfocused_audio.name_display(),
+            }
+        } else if let Some(scope) = effects {
+            let effects = &settings.display.effects;
+            match scope {
+                EffectsScope::Bloom => effects.bloom.name_display(),
+                EffectsScope::LowHealthVignette => effects.low_health_vignette.name_display(),
+                EffectsScope::HitAberration => effects.hit_aberration.name_display(),
+            }
+        } else if let Some(scope) = gamepad {
+            match scope {
+                GamepadScope::Layout => settings.gamepad_layout.name_display(),
+                GamepadScope::SwapConfirm => settings.swap_confirm.name_display(),
+            }
+        } else if let Some(scope) = accessibility {
+            match scope {
+                AccessibilityScope::HighContrast => settings.high_contrast.name_display(),
+                AccessibilityScope::Colorblind => settings.colorblind.name_display(),
+                AccessibilityScope::TextSize => settings.text_size.name_display(),
+                AccessibilityScope::DyslexicFont => settings.dyslexic_font.name_display(),
+                AccessibilityScope::ScreenShake => settings.screen_shake_enabled.name_display(),
+                AccessibilityScope::Announcements => settings.announcement_verbosity.name_display(),
+            }
+        } else {
+            continue;
+        };
+        text.sections[0].value = value;
+    }
+}
@@ -6,7 +6,7 @@ use bevy::{
 };
 
 use super::Screen;
-use crate::{ui::prelude::*, AppSet};
+use crate::{events::ScreenRequest, ui::prelude::*, AppSet};
 
 pub(super) fn plugin(app: &mut App) {
     // Spawn splash screen.
@@ -135,8 +135,8 @@ fn tick_splash_timer(time: Res<Time>, mut timer: ResMut<SplashTimer>) {
     timer.0.tick(time.delta());
 }
 
-fn check_splash_timer(timer: ResMut<SplashTimer>, mut next_screen: ResMut<NextState<Screen>>) {
+fn check_splash_timer(timer: ResMut<SplashTimer>, mut screen_requests: EventWriter<ScreenRequest>) {
     if timer.0.just_finished() {
-        next_screen.set(Screen::Loading);
+        screen_requests.send(ScreenRequest::To(Screen::Loading));
     }
 }
@@ -1,53 +1,323 @@
-//! The title screen that appears when the game starts.
+//! The title screen that appears when the game starts, where the [`GameMode`] is picked,
+//! or this week's challenge, with a countdown to the next one.
+//! The logo drops in and the buttons slide in one after another, while a few motes
+//! drift up the screen. Any input skips straight to the settled menu.
 
-use bevy::prelude::*;
+use bevy::{prelude::*, ui::Val::*};
+use rand::Rng;
 
 use super::Screen;
-use crate::ui::prelude::*;
+use crate::{
+    events::ScreenRequest,
+    game::{
+        mode::GameMode,
+        mutators::MutatorCatalog,
+        rng::GameRng,
+        save::{ActiveSlot, SaveGame},
+        weekly::{
+            format_countdown, seconds_until_rotation, unix_now, IsoWeek, WeeklyChallenge,
+            WeeklyRecords,
+        },
+    },
+    ui::prelude::*,
+    AppSet, GameSettings,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), enter_title);
 
-    app.register_type::<TitleAction>();
-    app.add_systems(Update, handle_title_action.run_if(in_state(Screen::Title)));
+    app.register_type::<(TitleAction, AmbientMote)>();
+    app.add_systems(
+        Update,
+        (
+            // Before skipping, so the press that skips the intro doesn't also press a button.
+            (handle_title_action, skip_intro).chain(),
+            (spawn_motes, drift_motes).chain().in_set(AppSet::Update),
+            show_weekly_challenge,
+        )
+            .run_if(in_state(Screen::Title)),
+    );
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 #[reflect(Component)]
 enum TitleAction {
     Play,
+    /// An endless run, which isn't saved, so it skips the slot picker.
+    Endless,
+    /// This week's challenge, which isn't saved either.
+    Weekly,
+    Mutators,
     Settings,
+    Customize,
+    Profile,
+    HighScores,
     Credits,
+    About,
     /// Exit doesn't work well with embedded applications.
     #[cfg(not(target_family = "wasm"))]
     Exit,
 }
 
+/// Seconds the logo takes to drop in, after which the buttons start sliding in.
+const LOGO_DROP: f32 = 0.9;
+/// Seconds between the buttons starting to slide in.
+const BUTTON_STAGGER: f32 = 0.08;
+const BUTTON_SLIDE: f32 = 0.4;
+
+/// A bit of dust drifting up behind the menu.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct AmbientMote {
+    velocity: Vec2,
+    /// Seconds left until it has faded out.
+    life: f32,
+}
+
+/// Full-screen layer behind the menu that the motes are spawned in.
+#[derive(Component)]
+struct MoteLayer;
+
+/// What this week's challenge is, when it rotates, and how it went so far.
+#[derive(Component)]
+struct WeeklyLabel;
+
+/// Seconds between spawning motes.
+const MOTE_INTERVAL: f32 = 0.25;
+const MOTE_LIFE: f32 = 6.0;
+
 fn enter_title(mut commands: Commands) {
+    commands.ui_root().insert((
+        Name::new("Mote Layer"),
+        MoteLayer,
+        StateScoped(Screen::Title),
+    ));
     commands
         .ui_root()
         .insert(StateScoped(Screen::Title))
         .with_children(|children| {
-            children.button("Play").insert(TitleAction::Play);
-            children.button("Settings").insert(TitleAction::Settings);
-            children.button("Credits").insert(TitleAction::Credits);
+            children.header("Bevy Jam 5").insert(UiTween::slide_in(
+                Vec2::new(0.0, -600.0),
+                LOGO_DROP,
+                Ease::OutBounce,
+            ));
+
+            // Each button starts sliding in a little after the one above it.
+            let slide_in = |i: usize| {
+                UiTween::slide_in(Vec2::new(-1200.0, 0.0), BUTTON_SLIDE, Ease::OutCubic)
+                    .with_delay(LOGO_DROP + BUTTON_STAGGER * i as f32)
+            };
+            children
+                .button("Play")
+                .insert((TitleAction::Play, slide_in(0)));
+            children
+                .button("Endless")
+                .insert((TitleAction::Endless, slide_in(1)));
+            children
+                .button("Weekly Challenge")
+                .insert((TitleAction::Weekly, slide_in(2)));
+            children.label("").insert((WeeklyLabel, slide_in(3)));
+            children
+                .button("Mutators")
+                .insert((TitleAction::Mutators, slide_in(4)));
+            children
+                .button("Settings")
+                .insert((TitleAction::Settings, slide_in(5)));
+            children
+                .button("Customize")
+                .insert((TitleAction::Customize, slide_in(6)));
+            children
+                .button("Profile")
+                .insert((TitleAction::Profile, slide_in(7)));
+            children
+                .button("High Scores")
+                .insert((TitleAction::HighScores, slide_in(8)));
+            children
+                .button("Credits")
+                .insert((TitleAction::Credits, slide_in(9)));
+            children
+                .button("About")
+                .insert((TitleAction::About, slide_in(10)));
 
             #[cfg(not(target_family = "wasm"))]
-            children.button("Exit").insert(TitleAction::Exit);
+            children
+                .button("Exit")
+                .insert((TitleAction::Exit, slide_in(11)));
+        });
+}
+
+/// Finish the intro on any key, mouse button or gamepad button.
+fn skip_intro(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mut tween_query: Query<&mut UiTween>,
+) {
+    if tween_query.is_empty() {
+        return;
+    }
+    if keyboard_input.get_just_pressed().next().is_some()
+        || mouse_input.get_just_pressed().next().is_some()
+        || gamepad_input.get_just_pressed().next().is_some()
+    {
+        for mut tween in &mut tween_query {
+            tween.finish();
+        }
+    }
+}
+
+fn spawn_motes(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    mut game_rng: ResMut<GameRng>,
+    mut until_next: Local<f32>,
+    layer_query: Query<Entity, With<MoteLayer>>,
+) {
+    // Decoration, like the menu backdrop.
+    if !settings.display.quality.is_high() {
+        return;
+    }
+    *until_next -= time.delta_seconds();
+    if *until_next > 0.0 {
+        return;
+    }
+    *until_next = MOTE_INTERVAL;
+    let rng = game_rng.vfx();
+    for layer in &layer_query {
+        let size = rng.gen_range(3.0..8.0);
+        commands.entity(layer).with_children(|children| {
+            children.spawn((
+                Name::new("Mote"),
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Percent(rng.gen_range(0.0..100.0)),
+                        top: Percent(100.0),
+                        width: Px(size),
+                        height: Px(size),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::WHITE.with_alpha(0.0)),
+                    ..default()
+                },
+                AmbientMote {
+                    velocity: Vec2::new(rng.gen_range(-10.0..10.0), rng.gen_range(-50.0..-25.0)),
+                    life: MOTE_LIFE,
+                },
+            ));
         });
+    }
+}
+
+fn drift_motes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut mote_query: Query<(Entity, &mut AmbientMote, &mut Style, &mut BackgroundColor)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut mote, mut style, mut color) in &mut mote_query {
+        mote.life -= delta;
+        if mote.life <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // Motes start out at a percentage of the screen and move in pixels from there.
+        let offset = mote.velocity * (MOTE_LIFE - mote.life);
+        style.margin = UiRect {
+            left: Px(offset.x),
+            top: Px(offset.y),
+            ..default()
+        };
+        // Fade in and out over the first and last second.
+        let alpha = mote.life.min(MOTE_LIFE - mote.life).min(1.0) * 0.5;
+        color.0 = Color::WHITE.with_alpha(alpha);
+    }
+}
+
+/// Kept up to date every frame, for the countdown and in case the week rotates.
+fn show_weekly_challenge(
+    catalog: Res<MutatorCatalog>,
+    records: Res<WeeklyRecords>,
+    label_query: Query<&Children, With<WeeklyLabel>>,
+    mut text_query: Query<&mut Text>,
+) {
+    let now = unix_now();
+    let week = IsoWeek::from_unix(now);
+    let challenge = WeeklyChallenge::for_week(week, &catalog);
+    let mut text = format!(
+        "{}: {}, new in {}",
+        week.name(),
+        catalog.names(&challenge.mutators),
+        format_countdown(seconds_until_rotation(now)),
+    );
+    if let Some(record) = records.get(week) {
+        let tries = if record.attempts == 1 { "try" } else { "tries" };
+        text.push_str(&format!(
+            "\nBest: {} in {} {tries}",
+            record.best, record.attempts
+        ));
+    }
+    for children in &label_query {
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut label) = texts.fetch_next() {
+            if label.sections[0].value != text {
+                label.sections[0].value.clone_from(&text);
+            }
+        }
+    }
 }
 
 fn handle_title_action(
-    mut next_screen: ResMut<NextState<Screen>>,
+    mut commands: Commands,
+    mut screen_requests: EventWriter<ScreenRequest>,
+    catalog: Res<MutatorCatalog>,
     mut button_query: InteractionQuery<&TitleAction>,
+    tween_query: Query<(), With<UiTween>>,
     #[cfg(not(target_family = "wasm"))] mut app_exit: EventWriter<AppExit>,
 ) {
+    // The menu isn't interactive until it has settled, or the intro was skipped.
+    if !tween_query.is_empty() {
+        return;
+    }
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
             match action {
-                TitleAction::Play => next_screen.set(Screen::Playing),
-                TitleAction::Settings => next_screen.set(Screen::Settings),
-                TitleAction::Credits => next_screen.set(Screen::Credits),
+                TitleAction::Play => {
+                    commands.insert_resource(GameMode::Normal);
+                    screen_requests.send(ScreenRequest::To(Screen::SaveSlots));
+                }
+                TitleAction::Endless => {
+                    commands.insert_resource(GameMode::Endless);
+                    commands.insert_resource(SaveGame::default());
+                    commands.remove_resource::<ActiveSlot>();
+                    screen_requests.send(ScreenRequest::To(Screen::Playing));
+                }
+                TitleAction::Weekly => {
+                    WeeklyChallenge::for_week(IsoWeek::now(), &catalog).start(&mut commands);
+                    screen_requests.send(ScreenRequest::To(Screen::Playing));
+                }
+                TitleAction::Mutators => {
+                    screen_requests.send(ScreenRequest::To(Screen::Mutators));
+                }
+                TitleAction::Settings => {
+                    screen_requests.send(ScreenRequest::To(Screen::Settings));
+                }
+                TitleAction::Customize => {
+                    screen_requests.send(ScreenRequest::To(Screen::Customize));
+                }
+                TitleAction::Profile => {
+                    screen_requests.send(ScreenRequest::To(Screen::Profile));
+                }
+                TitleAction::HighScores => {
+                    screen_requests.send(ScreenRequest::To(Screen::HighScores));
+                }
+                TitleAction::Credits => {
+                    screen_requests.send(ScreenRequest::To(Screen::Credits));
+                }
+                TitleAction::About => {
+                    screen_requests.send(ScreenRequest::To(Screen::About));
+                }
 
                 #[cfg(not(target_family = "wasm"))]
                 TitleAction::Exit => {
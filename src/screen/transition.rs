@@ -0,0 +1,175 @@
+//! Animated transitions between screens: cover the screen with an overlay,
+//! swap the [`Screen`] state while it is hidden, then reveal the new screen.
+//! Started by the arbiter, see [`ScreenTransition::new`].
+
+use std::time::Duration;
+
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use super::{ExitingScreen, Screen};
+use crate::layers::Layer;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TransitionOverlay>();
+    app.add_systems(Startup, spawn_overlay);
+    // Before `StateTransition`, so the state is swapped in the same frame the overlay covers it.
+    app.add_systems(
+        PreUpdate,
+        (
+            run_transition.run_if(resource_exists::<ScreenTransition>),
+            update_overlay,
+        )
+            .chain(),
+    );
+}
+
+/// How the overlay covers and reveals the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionKind {
+    /// Fade to black and back.
+    Fade,
+    /// Slide a black panel in from the left, then out to the right.
+    Wipe,
+}
+
+impl TransitionKind {
+    /// The transition to use between two screens.
+    pub fn between(from: &Screen, to: &Screen) -> Self {
+        match (from, to) {
+            // Starting a run should feel like moving somewhere new.
+            (Screen::Title, Screen::Playing) => TransitionKind::Wipe,
+            _ => TransitionKind::Fade,
+        }
+    }
+
+    /// Duration of each half: covering, and then revealing.
+    fn half_duration(self) -> Duration {
+        match self {
+            TransitionKind::Fade => Duration::from_millis(200),
+            TransitionKind::Wipe => Duration::from_millis(350),
+        }
+    }
+}
+
+/// A running transition. Present from when the target screen is decided
+/// until the new screen has been revealed.
+#[derive(Resource, Debug)]
+pub struct ScreenTransition {
+    from: Screen,
+    to: Screen,
+    kind: TransitionKind,
+    timer: Timer,
+    /// Whether the screen has been covered and swapped yet.
+    revealing: bool,
+}
+
+impl ScreenTransition {
+    pub fn new(from: Screen, to: Screen) -> Self {
+        let kind = TransitionKind::between(&from, &to);
+        Self {
+            from,
+            to,
+            kind,
+            timer: Timer::new(kind.half_duration(), TimerMode::Once),
+            revealing: false,
+        }
+    }
+
+    /// How much of the screen the overlay covers, from 0 to 1.
+    fn coverage(&self) -> f32 {
+        let progress = self.timer.fraction();
+        if self.revealing {
+            1.0 - progress
+        } else {
+            progress
+        }
+    }
+}
+
+/// The full-screen node that covers the screen during transitions.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct TransitionOverlay;
+
+fn spawn_overlay(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Transition Overlay"),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::NONE),
+            // Above all other UI.
+            z_index: Layer::ScreenUi.z_index(i32::MAX),
+            ..default()
+        },
+        TransitionOverlay,
+    ));
+}
+
+fn run_transition(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut transition: ResMut<ScreenTransition>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    transition.timer.tick(time.delta());
+    if !transition.timer.finished() {
+        return;
+    }
+    if transition.revealing {
+        commands.remove_resource::<ScreenTransition>();
+        return;
+    }
+    commands.trigger(ExitingScreen {
+        from: transition.from.clone(),
+        to: transition.to.clone(),
+    });
+    next_screen.set(transition.to.clone());
+    transition.revealing = true;
+    transition.timer.reset();
+}
+
+fn update_overlay(
+    transition: Option<Res<ScreenTransition>>,
+    mut overlay_query: Query<
+        (&mut Style, &mut BackgroundColor, &mut FocusPolicy),
+        With<TransitionOverlay>,
+    >,
+) {
+    let Ok((mut style, mut background, mut focus_policy)) = overlay_query.get_single_mut() else {
+        return;
+    };
+    let Some(transition) = transition else {
+        // Only write once after a transition, so the UI isn't relayouted every frame.
+        if *focus_policy == FocusPolicy::Block {
+            *focus_policy = FocusPolicy::Pass;
+            background.0 = Color::NONE;
+        }
+        return;
+    };
+    // Block clicks from reaching the screens underneath.
+    *focus_policy = FocusPolicy::Block;
+    let coverage = transition.coverage();
+    match transition.kind {
+        TransitionKind::Fade => {
+            style.left = Val::Percent(0.0);
+            style.width = Val::Percent(100.0);
+            background.0 = Color::BLACK.with_alpha(coverage);
+        }
+        TransitionKind::Wipe => {
+            // Enter from the left, and leave to the right.
+            let (left, width) = if transition.revealing {
+                (1.0 - coverage, coverage)
+            } else {
+                (0.0, coverage)
+            };
+            style.left = Val::Percent(left * 100.0);
+            style.width = Val::Percent(width * 100.0);
+            background.0 = Color::BLACK;
+        }
+    }
+}
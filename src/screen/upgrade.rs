@@ -0,0 +1,198 @@
+//! The upgrade shop, layered over the game at the end of each cycle.
+//! It offers a few random cards to buy with coins, see `game::upgrades`,
+//! and the run continues once the player leaves it.
+
+use bevy::prelude::*;
+
+use super::PlayingState;
+use crate::{
+    events::OnCycleAdvance,
+    game::{
+        audio::sfx::{PlaySfx, UiCue},
+        inventory::{Inventory, ItemCatalog},
+        rng::GameRng,
+        save::SaveGame,
+        upgrades::{UpgradeCard, UpgradeCatalog, Upgrades, CURRENCY},
+    },
+    ui::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ShopOffer>();
+    app.add_systems(OnEnter(PlayingState::Upgrade), enter_shop);
+
+    app.register_type::<ShopAction>();
+    app.add_systems(
+        Update,
+        (
+            open_shop
+                .in_set(AppSet::HandleEvents)
+                .run_if(in_state(PlayingState::Running)),
+            (
+                handle_shop_action,
+                refresh_shop_labels.run_if(resource_changed::<ShopOffer>),
+            )
+                .chain()
+                .run_if(in_state(PlayingState::Upgrade)),
+        ),
+    );
+}
+
+/// How many cards the shop offers at once.
+const OFFERED_CARDS: usize = 3;
+
+const SHOP_BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+/// The cards of the open shop, as indices into the [`UpgradeCatalog`],
+/// and whether each was bought.
+#[derive(Resource, Debug, Default)]
+struct ShopOffer(Vec<(usize, bool)>);
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum ShopAction {
+    /// Buy the card at this index of the [`ShopOffer`].
+    Buy(usize),
+    Continue,
+}
+
+/// How many coins the player has to spend.
+#[derive(Component)]
+struct ShopCoins;
+
+/// Open the shop with new cards each time a cycle is completed.
+fn open_shop(
+    mut cycle_events: EventReader<OnCycleAdvance>,
+    catalog: Res<UpgradeCatalog>,
+    mut rng: ResMut<GameRng>,
+    mut offer: ResMut<ShopOffer>,
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+) {
+    // Several cycles completed at once only open one shop.
+    if cycle_events.read().count() == 0 || catalog.0.is_empty() {
+        return;
+    }
+    offer.0 = catalog
+        .offer(rng.loot(), OFFERED_CARDS)
+        .into_iter()
+        .map(|index| (index, false))
+        .collect();
+    next_playing_state.set(PlayingState::Upgrade);
+}
+
+fn enter_shop(
+    mut commands: Commands,
+    catalog: Res<UpgradeCatalog>,
+    items: Res<ItemCatalog>,
+    offer: Res<ShopOffer>,
+    inventory: Res<Inventory>,
+) {
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(PlayingState::Upgrade),
+            BackgroundColor(SHOP_BACKGROUND_COLOR),
+        ))
+        .with_children(|children| {
+            children.header("Upgrades");
+            children
+                .label(coins_label(&items, &inventory))
+                .insert(ShopCoins);
+            for (slot, &(index, bought)) in offer.0.iter().enumerate() {
+                let Some(card) = catalog.0.get(index) else {
+                    continue;
+                };
+                children
+                    .button(card_label(card, bought))
+                    .insert(ShopAction::Buy(slot));
+                children.label(&card.description);
+            }
+            children.button("Continue").insert(ShopAction::Continue);
+        });
+}
+
+fn handle_shop_action(
+    mut commands: Commands,
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+    catalog: Res<UpgradeCatalog>,
+    mut offer: ResMut<ShopOffer>,
+    mut inventory: ResMut<Inventory>,
+    mut save: ResMut<SaveGame>,
+    mut upgrades: ResMut<Upgrades>,
+    mut button_query: InteractionQuery<&ShopAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        match *action {
+            ShopAction::Buy(slot) => {
+                let Some(&(index, bought)) = offer.0.get(slot) else {
+                    continue;
+                };
+                let Some(card) = catalog.0.get(index) else {
+                    continue;
+                };
+                if bought || !inventory.spend(CURRENCY, card.cost) {
+                    commands.trigger(PlaySfx::Key(UiCue::InvalidAction.key()));
+                    continue;
+                }
+                offer.0[slot].1 = true;
+                save.upgrades.push(card.id.clone());
+                *upgrades = Upgrades::from_cards(&catalog, &save.upgrades);
+                info!("Bought upgrade {}.", card.name);
+            }
+            ShopAction::Continue => next_playing_state.set(PlayingState::Running),
+        }
+    }
+}
+
+/// Like "Coins: 12".
+fn coins_label(items: &ItemCatalog, inventory: &Inventory) -> String {
+    let name = items
+        .get(CURRENCY)
+        .map_or(CURRENCY, |item| item.name.as_str());
+    format!("{name}s: {}", inventory.count(CURRENCY))
+}
+
+/// The card and its cost, like "Swift Feet (10)", or that it was bought.
+fn card_label(card: &UpgradeCard, bought: bool) -> String {
+    if bought {
+        format!("{} (bought)", card.name)
+    } else {
+        format!("{} ({})", card.name, card.cost)
+    }
+}
+
+fn refresh_shop_labels(
+    catalog: Res<UpgradeCatalog>,
+    items: Res<ItemCatalog>,
+    offer: Res<ShopOffer>,
+    inventory: Res<Inventory>,
+    button_query: Query<(&ShopAction, &Children)>,
+    coins_query: Query<&Children, With<ShopCoins>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (action, children) in &button_query {
+        let ShopAction::Buy(slot) = *action else {
+            continue;
+        };
+        let Some(&(index, bought)) = offer.0.get(slot) else {
+            continue;
+        };
+        let Some(card) = catalog.0.get(index) else {
+            continue;
+        };
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = card_label(card, bought);
+        }
+    }
+    for children in &coins_query {
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = coins_label(&items, &inventory);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Persistent key-value storage for small RON documents like the settings and saved games.
+//! Native builds write files to the platform config directory,
+//! web builds use the browser's `localStorage`.
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Load and deserialize the document stored under `key`.
+/// Returns `None` (and logs why) if it is missing or can't be parsed.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let contents = backend::read(key)?;
+    ron::from_str(&contents)
+        .inspect_err(|e| warn!("Could not parse stored '{key}', ignoring it: {e}"))
+        .ok()
+}
+
+/// Serialize and store `value` under `key`, overwriting any previous document.
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    match ron::ser::to_string_pretty(value, default()) {
+        Ok(contents) => backend::write(key, &contents),
+        Err(e) => error!("Could not serialize '{key}': {e}"),
+    }
+}
+
+/// Delete the document stored under `key`, if there is one.
+pub fn remove(key: &str) {
+    backend::remove(key);
+}
+
+/// Directory for files that aren't settings, like logs.
+#[cfg(not(target_family = "wasm"))]
+pub fn data_dir() -> Option<std::path::PathBuf> {
+    Some(dirs::data_dir()?.join(env!("CARGO_PKG_NAME")))
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod backend {
+    use std::{fs, path::PathBuf};
+
+    use bevy::prelude::*;
+
+    fn path(key: &str) -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join(env!("CARGO_PKG_NAME"))
+                .join(format!("{key}.ron")),
+        )
+    }
+
+    pub(super) fn read(key: &str) -> Option<String> {
+        fs::read_to_string(path(key)?).ok()
+    }
+
+    pub(super) fn write(key: &str, contents: &str) {
+        let Some(path) = path(key) else {
+            warn!("No config directory found, '{key}' will not be saved.");
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, contents));
+        if let Err(e) = result {
+            error!("Could not write '{key}' to {path:?}: {e}");
+        }
+    }
+
+    pub(super) fn remove(key: &str) {
+        let Some(path) = path(key) else {
+            return;
+        };
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                error!("Could not remove '{key}' from {path:?}: {e}");
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod backend {
+    use bevy::prelude::*;
+    use web_sys::Storage;
+
+    fn local_storage() -> Option<Storage> {
+        web_sys::window()?.local_storage().ok().flatten()
+    }
+
+    fn item_key(key: &str) -> String {
+        format!("{}/{key}", env!("CARGO_PKG_NAME"))
+    }
+
+    pub(super) fn read(key: &str) -> Option<String> {
+        local_storage()?.get_item(&item_key(key)).ok().flatten()
+    }
+
+    pub(super) fn write(key: &str, contents: &str) {
+        let Some(storage) = local_storage() else {
+            warn!("No local storage available, '{key}' will not be saved.");
+            return;
+        };
+        if let Err(e) = storage.set_item(&item_key(key), contents) {
+            error!("Could not write '{key}' to local storage: {e:?}");
+        }
+    }
+
+    pub(super) fn remove(key: &str) {
+        let Some(storage) = local_storage() else {
+            return;
+        };
+        if let Err(e) = storage.remove_item(&item_key(key)) {
+            error!("Could not remove '{key}' from local storage: {e:?}");
+        }
+    }
+}
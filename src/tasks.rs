@@ -0,0 +1,113 @@
+//! Background tasks with the same API on native and web.
+//!
+//! Spawn a future with [`SpawnTask::spawn_task`], which returns the entity that owns it.
+//! Once the future completes, [`TaskDone`] is triggered on that entity with its output,
+//! and the entity is despawned. Despawning it earlier cancels the task,
+//! so inserting a [`StateScoped`] cancels it when the state is exited.
+//!
+//! Each output type needs to be registered once with [`register_task`].
+//!
+//! Native builds run tasks on the [`AsyncComputeTaskPool`]. On the web there is only one thread,
+//! so tasks run as browser futures, and futures don't need to be `Send` there.
+//! Cancelling a web task only discards its output, since browser futures can't be aborted.
+
+use bevy::{
+    ecs::system::EntityCommands, prelude::*, tasks::AsyncComputeTaskPool,
+    utils::ConditionalSendFuture,
+};
+
+/// Poll tasks that output `T`, triggering [`TaskDone<T>`] when they complete.
+pub fn register_task<T: Send + Sync + 'static>(app: &mut App) {
+    app.add_systems(PreUpdate, poll_tasks::<T>);
+}
+
+/// Triggered on the entity of a task with its output.
+#[derive(Event, Debug)]
+pub struct TaskDone<T>(pub T);
+
+/// A running task, see the [module docs](self).
+#[derive(Component)]
+pub struct BackgroundTask<T: Send + Sync + 'static>(backend::Handle<T>);
+
+pub trait SpawnTask {
+    /// Run `future` in the background, see the [module docs](self).
+    fn spawn_task<T: Send + Sync + 'static>(
+        &mut self,
+        future: impl ConditionalSendFuture<Output = T> + 'static,
+    ) -> EntityCommands<'_>;
+}
+
+impl SpawnTask for Commands<'_, '_> {
+    fn spawn_task<T: Send + Sync + 'static>(
+        &mut self,
+        future: impl ConditionalSendFuture<Output = T> + 'static,
+    ) -> EntityCommands<'_> {
+        self.spawn((
+            Name::new("Background Task"),
+            BackgroundTask(backend::spawn(future)),
+        ))
+    }
+}
+
+fn poll_tasks<T: Send + Sync + 'static>(
+    mut commands: Commands,
+    mut task_query: Query<(Entity, &mut BackgroundTask<T>)>,
+) {
+    for (entity, mut task) in &mut task_query {
+        if let Some(output) = backend::poll(&mut task.0) {
+            commands.trigger_targets(TaskDone(output), entity);
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod backend {
+    use bevy::tasks::{block_on, poll_once, Task};
+
+    use super::*;
+
+    // Dropping a `Task` cancels it.
+    pub type Handle<T> = Task<T>;
+
+    pub fn spawn<T: Send + 'static>(
+        future: impl ConditionalSendFuture<Output = T> + 'static,
+    ) -> Handle<T> {
+        AsyncComputeTaskPool::get().spawn(future)
+    }
+
+    pub fn poll<T>(task: &mut Handle<T>) -> Option<T> {
+        task.is_finished()
+            .then(|| block_on(poll_once(task)))
+            .flatten()
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod backend {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// The single-threaded task pool doesn't return task outputs,
+    /// so the future writes its output here instead.
+    pub type Handle<T> = Arc<Mutex<Option<T>>>;
+
+    pub fn spawn<T: Send + 'static>(
+        future: impl ConditionalSendFuture<Output = T> + 'static,
+    ) -> Handle<T> {
+        let handle = Handle::default();
+        let output = handle.clone();
+        AsyncComputeTaskPool::get().spawn(async move {
+            let value = future.await;
+            if let Ok(mut output) = output.lock() {
+                *output = Some(value);
+            }
+        });
+        handle
+    }
+
+    pub fn poll<T>(handle: &mut Handle<T>) -> Option<T> {
+        handle.lock().ok()?.take()
+    }
+}
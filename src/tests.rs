@@ -0,0 +1,302 @@
+//! Tests for the bounded setting math in the crate root, and the settings built on it.
+//! The RNG seed is fixed so failures reproduce across machines and CI runs.
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use proptest::{prelude::*, test_runner::RngSeed};
+
+use crate::{
+    background::UnfocusedAudioSetting,
+    display::{
+        DisplaySettings, EffectSettings, MonitorSetting, ParticleSetting, QualitySetting,
+        ResolutionSetting, ToggleSetting, UpscalingSetting, ViewSizeSetting,
+    },
+    game::gamepad::{GamepadLayoutSetting, RumbleSetting},
+    logging::LogLevelSetting,
+    ui::{announcer::AnnouncementVerbosity, palette::ColorblindSetting, text::TextSizeSetting},
+    window_placement::WindowPlacement,
+    BinaryAdjustment, BoundedU8, GameSettings, LevelSetting, StoredSettings, UiCueVolumes,
+    VolumeSetting, SETTINGS_VERSION,
+};
+
+const SEED: u64 = 0x5eed_b0a7_5e77_1265;
+
+fn config() -> ProptestConfig {
+    ProptestConfig {
+        rng_seed: RngSeed::Fixed(SEED),
+        ..ProptestConfig::default()
+    }
+}
+
+/// A setting with a non-zero minimum, to catch math that assumes `MIN == 0`.
+#[derive(Deref, Debug, PartialEq)]
+struct OffsetSetting(BoundedU8<3, 200>);
+
+impl LevelSetting for OffsetSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+fn volume() -> impl Strategy<Value = VolumeSetting> {
+    (VolumeSetting::MIN..=VolumeSetting::MAX).prop_map(VolumeSetting::from_raw)
+}
+
+fn toggle() -> impl Strategy<Value = ToggleSetting> {
+    (ToggleSetting::MIN..=ToggleSetting::MAX).prop_map(ToggleSetting::from_raw)
+}
+
+fn display_settings() -> impl Strategy<Value = DisplaySettings> {
+    (
+        toggle(),
+        toggle(),
+        (ResolutionSetting::MIN..=ResolutionSetting::MAX).prop_map(ResolutionSetting::from_raw),
+        toggle(),
+        (QualitySetting::MIN..=QualitySetting::MAX).prop_map(QualitySetting::from_raw),
+        (UpscalingSetting::MIN..=UpscalingSetting::MAX).prop_map(UpscalingSetting::from_raw),
+        (ViewSizeSetting::MIN..=ViewSizeSetting::MAX).prop_map(ViewSizeSetting::from_raw),
+        (ParticleSetting::MIN..=ParticleSetting::MAX).prop_map(ParticleSetting::from_raw),
+        toggle(),
+        prop::option::of("[A-Za-z0-9 ]{0,16}").prop_map(MonitorSetting),
+        (toggle(), toggle(), toggle()).prop_map(|(bloom, low_health_vignette, hit_aberration)| {
+            EffectSettings {
+                bloom,
+                low_health_vignette,
+                hit_aberration,
+            }
+        }),
+    )
+        .prop_map(
+            |(
+                fullscreen,
+                vsync,
+                resolution,
+                pixel_perfect,
+                quality,
+                upscaling,
+                view_size,
+                particles,
+                motion_smoothing,
+                monitor,
+                effects,
+            )| {
+                DisplaySettings {
+                    fullscreen,
+                    vsync,
+                    resolution,
+                    pixel_perfect,
+                    quality,
+                    upscaling,
+                    view_size,
+                    particles,
+                    motion_smoothing,
+                    monitor,
+                    effects,
+                }
+            },
+        )
+}
+
+fn window_placement() -> impl Strategy<Value = WindowPlacement> {
+    (
+        prop::option::of((1u32..8000, 1u32..8000).prop_map(UVec2::from)),
+        prop::option::of((-8000i32..8000, -8000i32..8000).prop_map(IVec2::from)),
+        prop::option::of("[A-Za-z0-9 ]{0,16}"),
+    )
+        .prop_map(|(size, position, monitor)| WindowPlacement {
+            size,
+            position,
+            monitor,
+        })
+}
+
+fn game_settings() -> impl Strategy<Value = GameSettings> {
+    // Grouped, since strategies only exist for tuples of up to 12 elements.
+    let audio = (volume(), volume(), volume(), volume());
+    let cues = (volume(), volume(), volume(), volume()).prop_map(
+        |(focus_moved, edge_reached, invalid_action, tab_switched)| UiCueVolumes {
+            focus_moved,
+            edge_reached,
+            invalid_action,
+            tab_switched,
+        },
+    );
+    let controls = (
+        (RumbleSetting::MIN..=RumbleSetting::MAX).prop_map(RumbleSetting::from_raw),
+        (GamepadLayoutSetting::MIN..=GamepadLayoutSetting::MAX)
+            .prop_map(GamepadLayoutSetting::from_raw),
+        toggle(),
+    );
+    let accessibility = (
+        toggle(),
+        (TextSizeSetting::MIN..=TextSizeSetting::MAX).prop_map(TextSizeSetting::from_raw),
+        toggle(),
+        toggle(),
+        (AnnouncementVerbosity::MIN..=AnnouncementVerbosity::MAX)
+            .prop_map(AnnouncementVerbosity::from_raw),
+        (ColorblindSetting::MIN..=ColorblindSetting::MAX).prop_map(ColorblindSetting::from_raw),
+    );
+    (
+        (audio, cues),
+        controls,
+        accessibility,
+        display_settings(),
+        window_placement(),
+        (
+            toggle(),
+            (UnfocusedAudioSetting::MIN..=UnfocusedAudioSetting::MAX)
+                .prop_map(UnfocusedAudioSetting::from_raw),
+        ),
+        (LogLevelSetting::MIN..=LogLevelSetting::MAX).prop_map(LogLevelSetting::from_raw),
+    )
+        .prop_map(
+            |(
+                ((global, soundtrack, sfx, ui), ui_cue_volumes),
+                (rumble_level, gamepad_layout, swap_confirm),
+                (
+                    high_contrast,
+                    text_size,
+                    dyslexic_font,
+                    screen_shake_enabled,
+                    announcement_verbosity,
+                    colorblind,
+                ),
+                display,
+                window,
+                (run_in_background, unfocused_audio),
+                log_level,
+            )| GameSettings {
+                global_volume_level: global,
+                soundtrack_volume_level_relative: soundtrack,
+                sfx_volume_level_relative: sfx,
+                ui_volume_level_relative: ui,
+                ui_cue_volumes,
+                log_level,
+                rumble_level,
+                display,
+                window,
+                gamepad_layout,
+                swap_confirm,
+                high_contrast,
+                text_size,
+                dyslexic_font,
+                run_in_background,
+                unfocused_audio,
+                screen_shake_enabled,
+                announcement_verbosity,
+                colorblind,
+            },
+        )
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    #[test]
+    fn add_saturates_at_max(value in 3u8..=200, rhs: u8) {
+        let sum = BoundedU8::<3, 200>::from(value) + rhs;
+        prop_assert_eq!(*sum, value.saturating_add(rhs).min(200));
+    }
+
+    #[test]
+    fn sub_saturates_at_min(value in 3u8..=200, rhs: u8) {
+        let difference = BoundedU8::<3, 200>::from(value) - rhs;
+        prop_assert_eq!(*difference, value.saturating_sub(rhs).max(3));
+    }
+
+    #[test]
+    fn from_rejects_out_of_range(value in prop_oneof![0u8..3, 201u8..=255]) {
+        prop_assert!(std::panic::catch_unwind(|| BoundedU8::<3, 200>::from(value)).is_err());
+    }
+
+    #[test]
+    fn fraction_round_trips(value in 3u8..=200) {
+        let setting = OffsetSetting::from_raw(value);
+        let fraction = setting.fraction();
+        prop_assert!((0.0..=1.0).contains(&fraction));
+        prop_assert_eq!(OffsetSetting::from_fraction(fraction), setting);
+    }
+
+    #[test]
+    fn volume_fraction_round_trips(volume in volume()) {
+        prop_assert_eq!(VolumeSetting::from_fraction(volume.fraction()), volume);
+    }
+
+    #[test]
+    fn from_fraction_stays_in_bounds(fraction in 0.0f32..=1.0) {
+        let level = **OffsetSetting::from_fraction(fraction);
+        prop_assert!((3..=200).contains(&level));
+    }
+
+    #[test]
+    fn divisor_constructors_stay_in_bounds(divisor in 1u8..=255) {
+        let added = **OffsetSetting::from_divisor_added(divisor);
+        let removed = **OffsetSetting::from_divisor_removed(divisor);
+        prop_assert!((3..=200).contains(&added));
+        prop_assert!((3..=200).contains(&removed));
+        // Both are the same distance from their respective end of the range.
+        prop_assert_eq!(added - 3, 200 - removed);
+    }
+
+    #[test]
+    fn steps_stay_in_bounds(value in 3u8..=200, up: bool, fine: bool) {
+        let adjustment = if up { BinaryAdjustment::Up } else { BinaryAdjustment::Down };
+        let level = **OffsetSetting::from_raw(value).stepped(adjustment, fine);
+        prop_assert!((3..=200).contains(&level));
+        prop_assert!(OffsetSetting::step(true) <= OffsetSetting::step(false));
+    }
+
+    #[test]
+    fn settings_serialization_round_trips(settings in game_settings()) {
+        let serialized = ron::to_string(&settings).unwrap();
+        let deserialized: GameSettings = ron::from_str(&serialized).unwrap();
+        prop_assert_eq!(deserialized, settings);
+    }
+
+    #[test]
+    fn deserialization_rejects_out_of_range(value in 101u8..=255) {
+        let source = format!("({value})");
+        prop_assert!(ron::from_str::<VolumeSetting>(&source).is_err());
+    }
+}
+
+#[test]
+fn divisor_one_spans_whole_range() {
+    assert_eq!(**OffsetSetting::from_divisor_added(1), 200);
+    assert_eq!(**OffsetSetting::from_divisor_removed(1), 3);
+}
+
+#[test]
+fn volume_is_muted_at_zero_and_rises_with_level() {
+    let volumes = (VolumeSetting::MIN..=VolumeSetting::MAX)
+        .map(|level| Volume::from(&VolumeSetting::from_raw(level)).get())
+        .collect::<Vec<_>>();
+    assert_eq!(volumes[0], 0.0);
+    assert!(volumes.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(VolumeSetting::from_raw(0).percent_display(), "Muted");
+}
+
+#[test]
+fn old_volume_levels_are_migrated() {
+    let settings = GameSettings {
+        global_volume_level: VolumeSetting::from_raw(7),
+        ..default()
+    };
+    let stored = StoredSettings {
+        version: 0,
+        settings,
+        key_bindings: default(),
+        action_modes: default(),
+    }
+    .migrated();
+    assert_eq!(*stored.settings.global_volume_level.0, 70);
+    assert_eq!(stored.version, SETTINGS_VERSION);
+    // Current settings are left alone.
+    assert_eq!(*stored.migrated().settings.global_volume_level.0, 70);
+}
+
+#[test]
+#[should_panic]
+fn divisor_zero_panics() {
+    OffsetSetting::from_divisor_added(0);
+}
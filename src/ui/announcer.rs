@@ -0,0 +1,187 @@
+//! Gameplay text read out by screen readers, through a polite AccessKit live region.
+//!
+//! Trigger [`Announce`] with the text and its [`Importance`]. Announcements are only queued
+//! while a screen reader is connected, and only if the announcement verbosity setting allows
+//! them. They are spoken one at a time, a little apart, so none interrupt another.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    a11y::{
+        accesskit::{Live, NodeBuilder, Role},
+        AccessibilityNode, AccessibilityRequested,
+    },
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{BoundedU8, GameSettings, LevelSetting};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<LiveRegion>();
+    app.init_resource::<AnnouncementQueue>();
+    app.add_systems(Startup, spawn_live_region);
+    app.add_systems(Update, speak_announcements);
+    app.observe(queue_announcement);
+}
+
+/// How much gameplay text is announced: off, essential, normal or verbose.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Eq, PartialEq, Reflect)]
+pub(crate) struct AnnouncementVerbosity(pub(crate) BoundedU8<0, 3>);
+
+impl LevelSetting for AnnouncementVerbosity {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl Default for AnnouncementVerbosity {
+    fn default() -> Self {
+        Self::from_raw(Importance::Normal as u8)
+    }
+}
+
+impl AnnouncementVerbosity {
+    pub(crate) fn allows(&self, importance: Importance) -> bool {
+        importance as u8 <= self.0 .0
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        match self.0 .0 {
+            0 => "Off",
+            1 => "Essential",
+            2 => "Normal",
+            _ => "Verbose",
+        }
+        .to_string()
+    }
+}
+
+/// How much an announcement matters, compared to [`AnnouncementVerbosity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Reflect)]
+pub enum Importance {
+    /// Announced unless announcements are off, ahead of anything else queued.
+    Essential = 1,
+    Normal = 2,
+    /// Only announced on verbose.
+    Detail = 3,
+}
+
+/// Announce `text` to screen readers.
+#[derive(Event, Debug, Clone)]
+pub struct Announce {
+    pub text: String,
+    pub importance: Importance,
+}
+
+impl Announce {
+    pub fn new(importance: Importance, text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            importance,
+        }
+    }
+}
+
+/// Announcements waiting to be spoken.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct AnnouncementQueue {
+    pending: VecDeque<String>,
+    /// Seconds until the next announcement may replace the current one.
+    until_next: f32,
+}
+
+/// Announcements beyond this are dropped, as they would be stale by the time they are read.
+const MAX_PENDING: usize = 5;
+
+/// Time given to a screen reader to start reading an announcement before the next one.
+const ANNOUNCEMENT_GAP_SECONDS: f32 = 1.5;
+
+impl AnnouncementQueue {
+    /// Queue `text`, unless it is already waiting. Essential announcements skip the line.
+    pub(crate) fn push(&mut self, text: String, importance: Importance) {
+        if self.pending.contains(&text) {
+            return;
+        }
+        if importance == Importance::Essential {
+            self.pending.push_front(text);
+        } else if self.pending.len() < MAX_PENDING {
+            self.pending.push_back(text);
+        }
+        self.pending.truncate(MAX_PENDING);
+    }
+
+    /// The next announcement to speak, if it is time for one after `delta_seconds` passed.
+    pub(crate) fn next(&mut self, delta_seconds: f32) -> Option<String> {
+        self.until_next = (self.until_next - delta_seconds).max(0.0);
+        if self.until_next > 0.0 {
+            return None;
+        }
+        let text = self.pending.pop_front()?;
+        self.until_next = ANNOUNCEMENT_GAP_SECONDS;
+        Some(text)
+    }
+}
+
+/// The accessibility node whose name is read out whenever it changes.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+struct LiveRegion;
+
+fn spawn_live_region(mut commands: Commands) {
+    let mut node = NodeBuilder::new(Role::Status);
+    node.set_live(Live::Polite);
+    commands.spawn((Name::new("Announcer"), LiveRegion, AccessibilityNode(node)));
+}
+
+fn queue_announcement(
+    trigger: Trigger<Announce>,
+    requested: Res<AccessibilityRequested>,
+    settings: Res<GameSettings>,
+    mut queue: ResMut<AnnouncementQueue>,
+) {
+    let Announce { text, importance } = trigger.event();
+    if requested.get() && settings.announcement_verbosity.allows(*importance) {
+        queue.push(text.clone(), *importance);
+    }
+}
+
+// Real time, so announcements keep coming while the game is paused.
+fn speak_announcements(
+    time: Res<Time<Real>>,
+    mut queue: ResMut<AnnouncementQueue>,
+    mut region_query: Query<&mut AccessibilityNode, With<LiveRegion>>,
+) {
+    let Some(text) = queue.next(time.delta_seconds()) else {
+        return;
+    };
+    for mut node in &mut region_query {
+        node.set_name(text.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn announcements_follow_verbosity_and_importance() {
+        let normal = AnnouncementVerbosity::default();
+        assert!(normal.allows(Importance::Essential));
+        assert!(normal.allows(Importance::Normal));
+        assert!(!normal.allows(Importance::Detail));
+        let off = AnnouncementVerbosity::from_raw(AnnouncementVerbosity::MIN);
+        assert!(!off.allows(Importance::Essential));
+        assert!(AnnouncementVerbosity::from_max().allows(Importance::Detail));
+
+        let mut queue = AnnouncementQueue::default();
+        queue.push("Wave 2".to_string(), Importance::Normal);
+        queue.push("Wave 2".to_string(), Importance::Normal);
+        queue.push("Low health".to_string(), Importance::Essential);
+        assert_eq!(queue.next(0.0).as_deref(), Some("Low health"));
+        // The next one waits for the screen reader to start on the last.
+        assert_eq!(queue.next(0.5), None);
+        assert_eq!(queue.next(1.0).as_deref(), Some("Wave 2"));
+        assert_eq!(queue.next(2.0), None);
+    }
+}
@@ -0,0 +1,98 @@
+//! Numbers that count towards their value instead of jumping to it, spawned by
+//! [`Widgets::counter`](super::widgets::Widgets). Set `target` to change the value;
+//! the text rolls there over a short time and pops up in size as it does.
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Counter>();
+    app.add_systems(Update, animate_counters);
+}
+
+/// The value of a counter's text.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Counter {
+    pub target: u64,
+    /// What the text says, on its way to `target`.
+    shown: f64,
+    /// How much bigger the text is drawn, from 0 to 1.
+    pop: f32,
+}
+
+impl Counter {
+    pub fn new(value: u64) -> Self {
+        Self {
+            target: value,
+            shown: value as f64,
+            pop: 0.0,
+        }
+    }
+
+    /// Move the shown value towards the target, returning whether the text changed.
+    pub fn step(&mut self, delta_seconds: f32) -> bool {
+        let before = self.shown_value();
+        let distance = self.target as f64 - self.shown;
+        // Most of the way in a fraction of a second, however far it is.
+        let step = distance * (1.0 - (-COUNT_SPEED * delta_seconds as f64).exp());
+        self.shown = if distance.abs() < 1.0 {
+            self.target as f64
+        } else {
+            self.shown + step.abs().max(1.0).min(distance.abs()) * distance.signum()
+        };
+        let changed = self.shown_value() != before;
+        self.pop = if changed {
+            1.0
+        } else {
+            (self.pop - delta_seconds / POP_DURATION).max(0.0)
+        };
+        changed
+    }
+
+    pub fn shown_value(&self) -> u64 {
+        self.shown.round() as u64
+    }
+}
+
+/// How quickly counters catch up, as the fraction of the distance left after one second
+/// is `exp(-COUNT_SPEED)`.
+const COUNT_SPEED: f64 = 8.0;
+/// Seconds the text takes to shrink back after counting.
+const POP_DURATION: f32 = 0.2;
+/// How much bigger the text is at the height of a pop.
+const POP_SCALE: f32 = 0.25;
+
+fn animate_counters(
+    time: Res<Time>,
+    mut counter_query: Query<(&mut Counter, &mut Text, &mut Transform)>,
+) {
+    for (mut counter, mut text, mut transform) in &mut counter_query {
+        if counter.shown_value() == counter.target && counter.pop == 0.0 {
+            continue;
+        }
+        if counter.step(time.delta_seconds()) {
+            text.sections[0].value = counter.shown_value().to_string();
+        }
+        transform.scale = Vec3::splat(1.0 + POP_SCALE * counter.pop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_land_on_their_target() {
+        let mut counter = Counter::new(0);
+        counter.target = 1234;
+        for _ in 0..120 {
+            counter.step(1.0 / 60.0);
+        }
+        assert_eq!(counter.shown_value(), 1234);
+        counter.target = 1200;
+        for _ in 0..120 {
+            counter.step(1.0 / 60.0);
+        }
+        assert_eq!(counter.shown_value(), 1200);
+    }
+}
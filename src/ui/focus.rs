@@ -0,0 +1,400 @@
+//! Keyboard and gamepad navigation between [`Focusable`] widgets.
+//! Arrow keys / D-pad / left stick move focus spatially,
+//! Enter / Space / South (or East, with confirm swapped) press the focused widget
+//! as if it was clicked.
+//!
+//! Focus wraps around at the ends of lists, Page Up / Page Down jump
+//! several widgets at once, and Q / E (or the bumpers) switch between the tabs of a
+//! [`TabBar`].
+//!
+//! Moving focus, wrapping around or paging into the end of a list, and switching tabs
+//! each play their own [`UiCue`], so menus can be navigated by ear.
+
+use bevy::{prelude::*, ui::UiSystem};
+
+use super::{numeric_entry::NumericEntry, text_input::EditingText, theme::UiTheme};
+use crate::{
+    game::{
+        audio::sfx::{PlaySfx, UiCue},
+        gamepad::confirm_button,
+    },
+    GameSettings,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Focusable, UiFocus, TabBar, TabButton)>();
+    app.init_resource::<UiFocus>();
+    // Run right after Bevy updates `Interaction`, so our presses are seen
+    // by the regular `InteractionQuery` handlers in `Update`.
+    app.add_systems(
+        PreUpdate,
+        (
+            release_focus_press,
+            clear_lost_focus,
+            // The numeric entry popup and text fields take over input while they are open.
+            (navigate_focus, press_focused, cycle_tabs).run_if(
+                not(resource_exists::<NumericEntry>).and_then(not(resource_exists::<EditingText>)),
+            ),
+        )
+            .chain()
+            .in_set(NavigationSet)
+            .after(UiSystem::Focus),
+    );
+    app.add_systems(Update, (highlight_focus, track_selected_tab));
+}
+
+/// Systems that move and press the focus, in `PreUpdate`.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct NavigationSet;
+
+/// Marks a widget that can receive focus through keyboard or gamepad navigation.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Focusable;
+
+/// The currently focused widget, if any.
+/// Nothing is focused until navigation input is received,
+/// so mouse users never see the focus highlight.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct UiFocus {
+    pub focused: Option<Entity>,
+    /// Widget we pressed last frame, which needs to be released again.
+    pressed: Option<Entity>,
+}
+
+/// A row of [`TabButton`]s, of which one is selected.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct TabBar {
+    /// Index of the selected tab among the bar's children.
+    pub selected: usize,
+}
+
+/// A child of a [`TabBar`].
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct TabButton;
+
+/// How many widgets Page Up / Page Down move through.
+const PAGE_SIZE: usize = 5;
+
+/// Stick deflection needed to move focus, and the deflection it must return below
+/// before it can move focus again.
+const STICK_PRESS_THRESHOLD: f32 = 0.5;
+const STICK_RELEASE_THRESHOLD: f32 = 0.3;
+
+fn release_focus_press(
+    mut focus: ResMut<UiFocus>,
+    mut interaction_query: Query<&mut Interaction, With<Focusable>>,
+) {
+    // Check before taking, so `UiFocus` isn't marked as changed every frame.
+    if focus.pressed.is_none() {
+        return;
+    }
+    let Some(entity) = focus.pressed.take() else {
+        return;
+    };
+    if let Ok(mut interaction) = interaction_query.get_mut(entity) {
+        interaction.set_if_neq(Interaction::None);
+    }
+}
+
+fn clear_lost_focus(mut focus: ResMut<UiFocus>, focusable_query: Query<(), With<Focusable>>) {
+    if focus
+        .focused
+        .is_some_and(|entity| !focusable_query.contains(entity))
+    {
+        focus.focused = None;
+    }
+}
+
+/// Direction of navigation input this frame, in UI space (y pointing down).
+fn navigation_direction(
+    keyboard_input: &ButtonInput<KeyCode>,
+    gamepads: &Gamepads,
+    gamepad_input: &ButtonInput<GamepadButton>,
+    gamepad_axes: &Axis<GamepadAxis>,
+    stick_held: &mut bool,
+) -> Option<Vec2> {
+    let pressed = |key: KeyCode, button: GamepadButtonType| {
+        keyboard_input.just_pressed(key)
+            || gamepads
+                .iter()
+                .any(|gamepad| gamepad_input.just_pressed(GamepadButton::new(gamepad, button)))
+    };
+    if pressed(KeyCode::ArrowUp, GamepadButtonType::DPadUp) {
+        return Some(Vec2::NEG_Y);
+    }
+    if pressed(KeyCode::ArrowDown, GamepadButtonType::DPadDown) {
+        return Some(Vec2::Y);
+    }
+    if pressed(KeyCode::ArrowLeft, GamepadButtonType::DPadLeft) {
+        return Some(Vec2::NEG_X);
+    }
+    if pressed(KeyCode::ArrowRight, GamepadButtonType::DPadRight) {
+        return Some(Vec2::X);
+    }
+
+    let stick = gamepads
+        .iter()
+        .map(|gamepad| {
+            Vec2::new(
+                gamepad_axes
+                    .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                    .unwrap_or_default(),
+                // Stick y points up, UI y points down.
+                -gamepad_axes
+                    .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                    .unwrap_or_default(),
+            )
+        })
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .unwrap_or_default();
+    if *stick_held {
+        *stick_held = stick.length() > STICK_RELEASE_THRESHOLD;
+        return None;
+    }
+    if stick.length() < STICK_PRESS_THRESHOLD {
+        return None;
+    }
+    *stick_held = true;
+    Some(if stick.x.abs() > stick.y.abs() {
+        Vec2::new(stick.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, stick.y.signum())
+    })
+}
+
+/// Page Up / Page Down this frame, in UI space.
+/// The triggers aren't used for this, since they are held for fine adjustment of sliders.
+fn page_direction(keyboard_input: &ButtonInput<KeyCode>) -> Option<Vec2> {
+    if keyboard_input.just_pressed(KeyCode::PageUp) {
+        Some(Vec2::NEG_Y)
+    } else if keyboard_input.just_pressed(KeyCode::PageDown) {
+        Some(Vec2::Y)
+    } else {
+        None
+    }
+}
+
+fn navigate_focus(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut stick_held: Local<bool>,
+    mut focus: ResMut<UiFocus>,
+    focusable_query: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Focusable>>,
+) {
+    let (direction, steps) = match navigation_direction(
+        &keyboard_input,
+        &gamepads,
+        &gamepad_input,
+        &gamepad_axes,
+        &mut stick_held,
+    ) {
+        Some(direction) => (direction, 1),
+        None => match page_direction(&keyboard_input) {
+            Some(direction) => (direction, PAGE_SIZE),
+            None => return,
+        },
+    };
+
+    let candidates = focusable_query
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(entity, transform, _)| (entity, transform.translation().truncate()))
+        .collect::<Vec<_>>();
+    let origin = focus
+        .focused
+        .and_then(|entity| focusable_query.get(entity).ok())
+        .map(|(entity, transform, _)| (entity, transform.translation().truncate()));
+
+    // Whether we wrapped around or couldn't move, which sounds different.
+    let mut edge_reached = false;
+    let next = match origin {
+        // Start at the top-left widget.
+        None => candidates
+            .iter()
+            .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+            .copied(),
+        // Single steps wrap around, but pages stop at the end of the list.
+        Some((_, origin)) if steps == 1 => {
+            step_focus(origin, direction, &candidates).or_else(|| {
+                edge_reached = true;
+                wrap_focus(origin, direction, &candidates)
+            })
+        }
+        Some(mut current) => {
+            for _ in 0..steps {
+                match step_focus(current.1, direction, &candidates) {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+            edge_reached = origin.is_some_and(|(entity, _)| entity == current.0);
+            Some(current)
+        }
+    };
+    let Some((entity, _)) = next else {
+        return;
+    };
+    focus.focused = Some(entity);
+    let cue = if edge_reached {
+        UiCue::EdgeReached
+    } else {
+        UiCue::FocusMoved
+    };
+    commands.trigger(PlaySfx::Key(cue.key()));
+}
+
+/// The closest widget in `direction`, preferring widgets that are in line with `origin`.
+fn step_focus(
+    origin: Vec2,
+    direction: Vec2,
+    candidates: &[(Entity, Vec2)],
+) -> Option<(Entity, Vec2)> {
+    candidates
+        .iter()
+        .filter_map(|&(entity, position)| {
+            let delta = position - origin;
+            let along = delta.dot(direction);
+            if along <= 0.0 {
+                return None;
+            }
+            let across = (delta - direction * along).length();
+            Some(((entity, position), along + 2.0 * across))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _)| candidate)
+}
+
+/// The farthest widget in the opposite of `direction`, preferring widgets that are
+/// in line with `origin`, for wrapping around when there is nothing in `direction`.
+fn wrap_focus(
+    origin: Vec2,
+    direction: Vec2,
+    candidates: &[(Entity, Vec2)],
+) -> Option<(Entity, Vec2)> {
+    candidates
+        .iter()
+        .filter_map(|&(entity, position)| {
+            let delta = position - origin;
+            let along = delta.dot(direction);
+            if along >= 0.0 {
+                return None;
+            }
+            let across = (delta - direction * along).length();
+            Some(((entity, position), 2.0 * across + along))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _)| candidate)
+}
+
+fn press_focused(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    settings: Res<GameSettings>,
+    mut focus: ResMut<UiFocus>,
+    mut interaction_query: Query<&mut Interaction, With<Focusable>>,
+) {
+    let confirm = confirm_button(&settings.swap_confirm);
+    let pressed = keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::Space])
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad_input.just_pressed(GamepadButton::new(gamepad, confirm)));
+    if !pressed {
+        return;
+    }
+    let Some(entity) = focus.focused else {
+        return;
+    };
+    if let Ok(mut interaction) = interaction_query.get_mut(entity) {
+        *interaction = Interaction::Pressed;
+        focus.pressed = Some(entity);
+    }
+}
+
+/// Press the next or previous tab of each visible [`TabBar`], wrapping around.
+fn cycle_tabs(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mut focus: ResMut<UiFocus>,
+    bar_query: Query<(&TabBar, &Children, &InheritedVisibility)>,
+    mut interaction_query: Query<&mut Interaction, With<TabButton>>,
+) {
+    let pressed = |key: KeyCode, button: GamepadButtonType| {
+        keyboard_input.just_pressed(key)
+            || gamepads
+                .iter()
+                .any(|gamepad| gamepad_input.just_pressed(GamepadButton::new(gamepad, button)))
+    };
+    let offset = if pressed(KeyCode::KeyQ, GamepadButtonType::LeftTrigger) {
+        -1
+    } else if pressed(KeyCode::KeyE, GamepadButtonType::RightTrigger) {
+        1
+    } else {
+        return;
+    };
+    for (bar, children, visibility) in &bar_query {
+        if !visibility.get() || children.is_empty() {
+            continue;
+        }
+        let index = (bar.selected as isize + offset).rem_euclid(children.len() as isize) as usize;
+        // Pressed like a focused widget, and released the same way.
+        if let Ok(mut interaction) = interaction_query.get_mut(children[index]) {
+            *interaction = Interaction::Pressed;
+            focus.pressed = Some(children[index]);
+        }
+    }
+}
+
+fn track_selected_tab(
+    mut commands: Commands,
+    button_query: Query<(Entity, &Interaction, &Parent), (With<TabButton>, Changed<Interaction>)>,
+    mut bar_query: Query<(&mut TabBar, &Children)>,
+) {
+    for (entity, interaction, parent) in &button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        let Ok((mut bar, children)) = bar_query.get_mut(parent.get()) else {
+            continue;
+        };
+        let Some(index) = children.iter().position(|&child| child == entity) else {
+            continue;
+        };
+        if bar.selected != index {
+            bar.selected = index;
+            commands.trigger(PlaySfx::Key(UiCue::TabSwitched.key()));
+        }
+    }
+}
+
+fn highlight_focus(
+    mut commands: Commands,
+    focus: Res<UiFocus>,
+    theme: Res<UiTheme>,
+    outlined_query: Query<Entity, (With<Focusable>, With<Outline>)>,
+) {
+    if !focus.is_changed() && !theme.is_changed() {
+        return;
+    }
+    for entity in &outlined_query {
+        if Some(entity) != focus.focused {
+            commands.entity(entity).remove::<Outline>();
+        }
+    }
+    if let Some(entity) = focus.focused {
+        // The widget may have been despawned by a state transition since `PreUpdate`.
+        commands.entity(entity).try_insert(Outline::new(
+            Val::Px(theme.focus_outline_width),
+            Val::Px(2.0),
+            theme.focus_outline,
+        ));
+    }
+}
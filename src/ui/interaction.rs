@@ -1,15 +1,39 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*, ui::UiSystem};
 
+use super::focus::TabButton;
 use crate::game::{assets::SfxKey, audio::sfx::PlaySfx};
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<InteractionPalette>();
+    app.register_type::<(InteractionPalette, RepeatButton)>();
     app.add_systems(Update, (apply_interaction_palette, trigger_interaction_sfx));
+    app.add_systems(PreUpdate, repeat_held_buttons.after(UiSystem::Focus));
 }
 
 pub type InteractionQuery<'w, 's, T> =
     Query<'w, 's, (&'static Interaction, T), Changed<Interaction>>;
 
+/// Whether fine adjustment is held: either Shift key, or either trigger of a gamepad.
+/// Sliders move in smaller steps while it is.
+#[derive(SystemParam)]
+pub struct FineAdjust<'w> {
+    keyboard_input: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_input: Res<'w, ButtonInput<GamepadButton>>,
+}
+
+impl FineAdjust<'_> {
+    pub fn held(&self) -> bool {
+        self.keyboard_input
+            .any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+            || self.gamepads.iter().any(|gamepad| {
+                self.gamepad_input.any_pressed([
+                    GamepadButton::new(gamepad, GamepadButtonType::LeftTrigger2),
+                    GamepadButton::new(gamepad, GamepadButtonType::RightTrigger2),
+                ])
+            })
+    }
+}
+
 /// Palette for widget interactions.
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
@@ -19,6 +43,51 @@ pub struct InteractionPalette {
     pub pressed: Color,
 }
 
+/// Makes a button press again while it is held down, faster the longer it is held.
+/// Each repeat marks the [`Interaction`] as changed, so handlers using
+/// [`InteractionQuery`] see it as a new press without any extra code.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct RepeatButton {
+    /// Seconds until the next repeat, while held.
+    until_repeat: f32,
+    /// Seconds between repeats, shrinking with each repeat.
+    interval: f32,
+}
+
+/// Delay before the first repeat, so a normal click only presses once.
+const REPEAT_DELAY: f32 = 0.4;
+const REPEAT_START_INTERVAL: f32 = 0.15;
+const REPEAT_MIN_INTERVAL: f32 = 0.03;
+/// Factor applied to the interval after each repeat.
+const REPEAT_ACCELERATION: f32 = 0.85;
+
+fn repeat_held_buttons(
+    time: Res<Time>,
+    mut button_query: Query<(&mut Interaction, &mut RepeatButton)>,
+) {
+    for (mut interaction, mut repeat) in &mut button_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        // Our own repeats aren't seen as changes here, since they happened during our last run.
+        if interaction.is_changed() {
+            // Just pressed, which also resets the state from any earlier hold.
+            *repeat = RepeatButton {
+                until_repeat: REPEAT_DELAY,
+                interval: REPEAT_START_INTERVAL,
+            };
+            continue;
+        }
+        repeat.until_repeat -= time.delta_seconds();
+        if repeat.until_repeat <= 0.0 {
+            repeat.until_repeat += repeat.interval;
+            repeat.interval = (repeat.interval * REPEAT_ACCELERATION).max(REPEAT_MIN_INTERVAL);
+            interaction.set_changed();
+        }
+    }
+}
+
 fn apply_interaction_palette(
     mut palette_query: InteractionQuery<(&InteractionPalette, &mut BackgroundColor)>,
 ) {
@@ -32,15 +101,27 @@ fn apply_interaction_palette(
     }
 }
 
+/// Button sounds for every screen, played through [`PlaySfx`] so the SFX volume applies.
+/// Moving focus and switching tabs have their own cues, see `focus`.
 fn trigger_interaction_sfx(
-    mut interactions: Query<&Interaction, Changed<Interaction>>,
     mut commands: Commands,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    button_query: Query<&Interaction, (With<Button>, Without<TabButton>, Changed<Interaction>)>,
 ) {
-    for interaction in &mut interactions {
+    let mut hovered = false;
+    let mut pressed = false;
+    for interaction in &button_query {
         match interaction {
-            Interaction::Hovered => commands.trigger(PlaySfx::Key(SfxKey::ButtonHover)),
-            Interaction::Pressed => commands.trigger(PlaySfx::Key(SfxKey::ButtonPress)),
-            _ => (),
+            // Releasing a click also returns to hovered, which shouldn't sound like a new hover.
+            Interaction::Hovered => hovered |= !mouse_input.just_released(MouseButton::Left),
+            Interaction::Pressed => pressed = true,
+            Interaction::None => (),
         }
     }
+    // One sound per kind per frame, even if several buttons changed.
+    if pressed {
+        commands.trigger(PlaySfx::Key(SfxKey::ButtonPress));
+    } else if hovered {
+        commands.trigger(PlaySfx::Key(SfxKey::ButtonHover));
+    }
 }
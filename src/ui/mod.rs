@@ -3,20 +3,65 @@
 // Unused utilities and re-exports may trigger these lints undesirably.
 #![allow(dead_code, unused_imports)]
 
+pub mod announcer;
+pub mod counter;
+pub mod focus;
 pub mod interaction;
+pub mod numeric_entry;
 pub mod palette;
+pub mod radial_menu;
+pub mod ring;
+pub mod text;
+pub mod text_input;
+pub mod theme;
+pub mod tween;
+pub mod typewriter;
 mod widgets;
 
 pub mod prelude {
     pub use super::{
-        interaction::{InteractionPalette, InteractionQuery},
-        palette as ui_palette,
-        widgets::{Containers as _, Widgets as _},
+        announcer::{Announce, Importance},
+        counter::Counter,
+        focus::{Focusable, UiFocus},
+        interaction::{FineAdjust, InteractionPalette, InteractionQuery, RepeatButton},
+        numeric_entry::SliderEntered,
+        palette::{self as ui_palette, GameplayPalette, GameplayTint},
+        radial_menu::{radial_slot, RadialMenu, RadialSlot},
+        ring::ProgressRing,
+        text::TextPreset,
+        text_input::{TextInput, TextSubmitted},
+        theme::{Themed, UiTheme, WorldOutline},
+        tween::{Ease, UiTween},
+        typewriter::Typewriter,
+        widgets::{
+            set_meter_level, set_progress, set_slot_part, set_tab_panel_visible, Containers as _,
+            DialoguePart, MeterPart, ProgressFill, SlotPart, Widgets as _,
+        },
     };
 }
 
 use bevy::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(interaction::plugin);
+    app.register_type::<(
+        widgets::DialoguePart,
+        widgets::MeterPart,
+        widgets::ProgressFill,
+        widgets::SlotPart,
+    )>();
+    app.add_plugins((
+        announcer::plugin,
+        counter::plugin,
+        focus::plugin,
+        interaction::plugin,
+        numeric_entry::plugin,
+        palette::plugin,
+        radial_menu::plugin,
+        ring::plugin,
+        text::plugin,
+        text_input::plugin,
+        theme::plugin,
+        tween::plugin,
+        typewriter::plugin,
+    ));
 }
@@ -0,0 +1,222 @@
+//! A popup for typing in the exact value of a slider, opened by clicking its [`SliderValue`].
+//! Digits and Backspace edit the value, Enter confirms and Escape cancels.
+//! On a gamepad, the D-pad changes the value by one instead.
+
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+    ui::{FocusPolicy, UiSystem},
+};
+
+use super::prelude::*;
+use crate::{game::gamepad::confirm_button, layers::Layer, GameSettings};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(SliderValue, EntryAction)>();
+    // Before focus navigation, so the keys used here don't also press or move anything.
+    app.add_systems(
+        PreUpdate,
+        (open_numeric_entry, edit_numeric_entry)
+            .chain()
+            .after(UiSystem::Focus)
+            .before(super::focus::NavigationSet),
+    );
+}
+
+/// The clickable value of a slider, spawned by [`Widgets::slider`].
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct SliderValue;
+
+/// Triggered on a [`SliderValue`] when a value was entered for it.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SliderEntered {
+    /// From 0 to 100.
+    pub percent: u8,
+}
+
+/// The open popup, if any. Focus navigation is paused while it exists.
+#[derive(Resource, Debug)]
+pub struct NumericEntry {
+    slider: Entity,
+    text: String,
+}
+
+/// The highest value a slider goes up to.
+const MAX_PERCENT: u8 = 100;
+
+/// The longest value that can be typed, which is enough for [`MAX_PERCENT`].
+const MAX_DIGITS: usize = 3;
+
+impl NumericEntry {
+    /// The typed value, with anything above [`MAX_PERCENT`] lowered to it.
+    fn percent(&self) -> Option<u8> {
+        let value = self.text.parse::<u16>().ok()?;
+        Some(value.min(MAX_PERCENT as u16) as u8)
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+#[reflect(Component)]
+enum EntryAction {
+    Confirm,
+    Cancel,
+}
+
+#[derive(Component)]
+struct EntryPopup;
+
+#[derive(Component)]
+struct EntryText;
+
+fn open_numeric_entry(
+    mut commands: Commands,
+    entry: Option<Res<NumericEntry>>,
+    slider_query: Query<(Entity, &Interaction), (With<SliderValue>, Changed<Interaction>)>,
+) {
+    if entry.is_some() {
+        return;
+    }
+    let Some((slider, _)) = slider_query
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+    commands.insert_resource(NumericEntry {
+        slider,
+        text: String::new(),
+    });
+    commands
+        .ui_root()
+        .insert((
+            Name::new("Numeric Entry"),
+            EntryPopup,
+            // Above the menu, and catching clicks meant for it.
+            Layer::ScreenUi.z_index(10),
+            BackgroundColor(Color::BLACK.with_alpha(0.8)),
+            FocusPolicy::Block,
+        ))
+        .with_children(|children| {
+            children.header("Enter a value");
+            children.spawn((
+                Name::new("Entry Text"),
+                TextBundle::from_section("_%", TextPreset::Value.style(Color::WHITE)),
+                TextPreset::Value,
+                Themed::ValueText,
+                EntryText,
+            ));
+            children.button("OK").insert(EntryAction::Confirm);
+            children.button("Cancel").insert(EntryAction::Cancel);
+        });
+}
+
+fn edit_numeric_entry(
+    mut commands: Commands,
+    entry: Option<ResMut<NumericEntry>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    mut gamepad_input: ResMut<ButtonInput<GamepadButton>>,
+    settings: Res<GameSettings>,
+    action_query: InteractionQuery<&EntryAction>,
+    popup_query: Query<Entity, With<EntryPopup>>,
+    mut text_query: Query<&mut Text, With<EntryText>>,
+    slider_query: Query<(), With<SliderValue>>,
+) {
+    let Some(mut entry) = entry else {
+        keyboard_events.clear();
+        return;
+    };
+    let mut action = action_query.iter().find_map(|(interaction, &action)| {
+        (*interaction == Interaction::Pressed).then_some(action)
+    });
+
+    for event in keyboard_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(c)
+                if c.chars().all(|c| c.is_ascii_digit())
+                    && entry.text.len() + c.len() <= MAX_DIGITS =>
+            {
+                entry.text.push_str(c.as_str());
+            }
+            Key::Backspace => {
+                entry.text.pop();
+            }
+            Key::Enter => action = Some(EntryAction::Confirm),
+            Key::Escape => action = Some(EntryAction::Cancel),
+            _ => (),
+        }
+    }
+    // Keep these from pausing, going back or pressing the widget behind the popup.
+    for key in [KeyCode::Enter, KeyCode::NumpadEnter, KeyCode::Escape] {
+        keyboard_input.clear_just_pressed(key);
+    }
+
+    let confirm = confirm_button(&settings.swap_confirm);
+    let cancel = if confirm == GamepadButtonType::South {
+        GamepadButtonType::East
+    } else {
+        GamepadButtonType::South
+    };
+    for gamepad in gamepads.iter() {
+        let value = entry.percent().unwrap_or(0);
+        if gamepad_input.clear_just_pressed(GamepadButton::new(gamepad, confirm)) {
+            action = Some(EntryAction::Confirm);
+        } else if gamepad_input.clear_just_pressed(GamepadButton::new(gamepad, cancel)) {
+            action = Some(EntryAction::Cancel);
+        } else if gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadUp))
+        {
+            entry.text = (value + 1).min(MAX_PERCENT).to_string();
+        } else if gamepad_input
+            .just_pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadDown))
+        {
+            entry.text = value.saturating_sub(1).to_string();
+        }
+    }
+
+    // The slider is gone if its screen was left in the meantime.
+    if !slider_query.contains(entry.slider) {
+        action = Some(EntryAction::Cancel);
+    }
+    match action {
+        Some(EntryAction::Confirm) => {
+            if let Some(percent) = entry.percent() {
+                commands.trigger_targets(SliderEntered { percent }, entry.slider);
+            }
+        }
+        Some(EntryAction::Cancel) => (),
+        None => {
+            if entry.is_changed() {
+                for mut text in &mut text_query {
+                    text.sections[0].value = format!("{}_%", entry.text);
+                }
+            }
+            return;
+        }
+    }
+    commands.remove_resource::<NumericEntry>();
+    for popup in &popup_query {
+        commands.entity(popup).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_values_above_the_maximum_are_lowered_to_it() {
+        let entry = |text: &str| NumericEntry {
+            slider: Entity::PLACEHOLDER,
+            text: text.to_string(),
+        };
+        assert_eq!(entry("42").percent(), Some(42));
+        assert_eq!(entry("256").percent(), Some(MAX_PERCENT));
+        assert_eq!(entry("999").percent(), Some(MAX_PERCENT));
+        assert_eq!(entry("").percent(), None);
+    }
+}
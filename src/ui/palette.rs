@@ -1,4 +1,29 @@
+//! The colors of the UI, and the colorblind palette modes that every color which tells
+//! the player something goes through.
+//!
+//! The [`ColorblindSetting`] picks a [`ColorVision`], which [`GameplayPalette`] uses to
+//! remap colors so that they stay apart for players with that kind of color blindness.
+//! The UI theme is remapped as a whole, see `ui::theme`. Gameplay sprites are tinted with
+//! a [`GameplayTint`] instead of their sprite color, and other gameplay colors, like
+//! damage numbers, go through [`GameplayPalette::color`] when they are spawned.
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{BoundedU8, GameSettings, LevelSetting};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GameplayTint>();
+    app.init_resource::<GameplayPalette>();
+    app.add_systems(
+        Update,
+        (
+            select_palette.run_if(resource_changed::<GameSettings>),
+            apply_gameplay_tints,
+        )
+            .chain(),
+    );
+}
 
 pub const BUTTON_HOVERED_BACKGROUND: Color = Color::srgb(0.186, 0.328, 0.573);
 pub const BUTTON_PRESSED_BACKGROUND: Color = Color::srgb(0.286, 0.478, 0.773);
@@ -8,3 +33,164 @@ pub const LABEL_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 pub const HEADER_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 
 pub const NODE_BACKGROUND: Color = Color::srgb(0.286, 0.478, 0.773);
+
+pub const FOCUS_OUTLINE: Color = Color::srgb(0.925, 0.925, 0.925);
+
+pub const HIGH_CONTRAST_ACCENT: Color = Color::srgb(1.0, 0.85, 0.0);
+
+/// How a player sees colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum ColorVision {
+    #[default]
+    Normal,
+    /// No green cones, the most common kind.
+    Deuteranopia,
+    /// No red cones.
+    Protanopia,
+    /// No blue cones.
+    Tritanopia,
+}
+
+/// From linear RGB to the responses of the long, medium and short cones.
+const RGB_TO_LMS: [[f32; 3]; 3] = [
+    [17.8824, 43.5161, 4.11935],
+    [3.45565, 27.1554, 3.86714],
+    [0.0299566, 0.184309, 1.46709],
+];
+const LMS_TO_RGB: [[f32; 3]; 3] = [
+    [0.080_944_45, -0.130_504_4, 0.116_721_1],
+    [-0.010_248_53, 0.054_019_33, -0.113_614_7],
+    [-0.000_365_297, -0.004_121_615, 0.693_511_4],
+];
+
+/// Multiplies a matrix, given as rows, with a column vector.
+fn transform(rows: [[f32; 3]; 3], vector: Vec3) -> Vec3 {
+    Mat3::from_cols_array_2d(&rows).transpose() * vector
+}
+
+impl ColorVision {
+    /// How `color` looks with this color vision, with the missing cone's response made up
+    /// from the other two (Viénot, Brettel and Mollon, 1999).
+    pub fn simulate(self, color: Color) -> Color {
+        let rgb = color.to_linear();
+        let lms = transform(RGB_TO_LMS, Vec3::new(rgb.red, rgb.green, rgb.blue));
+        let lms = match self {
+            Self::Normal => return color,
+            Self::Deuteranopia => Vec3::new(lms.x, 0.494_207 * lms.x + 1.248_27 * lms.z, lms.z),
+            Self::Protanopia => Vec3::new(2.023_44 * lms.y - 2.525_81 * lms.z, lms.y, lms.z),
+            Self::Tritanopia => Vec3::new(lms.x, lms.y, -0.395_913 * lms.x + 0.801_109 * lms.y),
+        };
+        let simulated = transform(LMS_TO_RGB, lms).clamp(Vec3::ZERO, Vec3::ONE);
+        LinearRgba::new(simulated.x, simulated.y, simulated.z, rgb.alpha).into()
+    }
+
+    /// `color` with what this color vision can't see of it moved into channels it can
+    /// (daltonizing), so colors that would look alike stay apart.
+    pub fn remap(self, color: Color) -> Color {
+        if self == Self::Normal {
+            return color;
+        }
+        let rgb = color.to_linear();
+        let simulated = self.simulate(color).to_linear();
+        let (red, green, blue) = (
+            rgb.red - simulated.red,
+            rgb.green - simulated.green,
+            rgb.blue - simulated.blue,
+        );
+        let shifted = match self {
+            // Red and green are lost, so show their difference in green and blue.
+            Self::Deuteranopia | Self::Protanopia => {
+                Vec3::new(0.0, 0.7 * red + green, 0.7 * red + blue)
+            }
+            // Blue is lost, so show it in red and green.
+            _ => Vec3::new(red + 0.7 * blue, green + 0.7 * blue, 0.0),
+        };
+        let remapped =
+            (Vec3::new(rgb.red, rgb.green, rgb.blue) + shifted).clamp(Vec3::ZERO, Vec3::ONE);
+        LinearRgba::new(remapped.x, remapped.y, remapped.z, rgb.alpha).into()
+    }
+}
+
+/// Normal colors, or remapped for one kind of color blindness.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Default, Eq, PartialEq, Reflect)]
+pub(crate) struct ColorblindSetting(pub(crate) BoundedU8<0, 3>);
+
+impl LevelSetting for ColorblindSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl ColorblindSetting {
+    pub(crate) fn vision(&self) -> ColorVision {
+        match self.0 .0 {
+            1 => ColorVision::Deuteranopia,
+            2 => ColorVision::Protanopia,
+            3 => ColorVision::Tritanopia,
+            _ => ColorVision::Normal,
+        }
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        format!("{:?}", self.vision())
+    }
+}
+
+/// The colors that tell the player something, for the current [`ColorblindSetting`].
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub struct GameplayPalette(pub ColorVision);
+
+impl GameplayPalette {
+    /// What to draw instead of `color`.
+    pub fn color(&self, color: Color) -> Color {
+        self.0.remap(color)
+    }
+}
+
+/// The color a gameplay sprite is meant to be tinted, which its sprite color follows
+/// through the [`GameplayPalette`]. Spawn it instead of setting the sprite color.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct GameplayTint(pub Color);
+
+fn select_palette(settings: Res<GameSettings>, mut palette: ResMut<GameplayPalette>) {
+    palette.set_if_neq(GameplayPalette(settings.colorblind.vision()));
+}
+
+fn apply_gameplay_tints(
+    palette: Res<GameplayPalette>,
+    mut tint_query: Query<(Ref<GameplayTint>, &mut Sprite)>,
+) {
+    for (tint, mut sprite) in &mut tint_query {
+        if palette.is_changed() || tint.is_changed() {
+            sprite.color = palette.color(tint.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::health::DamageType;
+
+    #[test]
+    fn colorblind_palettes_keep_damage_types_apart() {
+        let (fire, poison) = (DamageType::Fire.color(), DamageType::Poison.color());
+        assert_eq!(ColorVision::Normal.remap(fire), fire);
+        let distance = |vision: ColorVision, a: Color, b: Color| {
+            let (a, b) = (
+                vision.simulate(a).to_linear(),
+                vision.simulate(b).to_linear(),
+            );
+            Vec3::new(a.red - b.red, a.green - b.green, a.blue - b.blue).length()
+        };
+        for vision in [
+            ColorVision::Deuteranopia,
+            ColorVision::Protanopia,
+            ColorVision::Tritanopia,
+        ] {
+            let remapped = distance(vision, vision.remap(fire), vision.remap(poison));
+            assert!(remapped > distance(vision, fire, poison), "{vision:?}");
+        }
+    }
+}
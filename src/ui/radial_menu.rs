@@ -0,0 +1,78 @@
+//! A ring of slots around a center, picked by direction, like with a stick.
+//! Spawn one with [`Widgets::radial_menu`](super::widgets::Widgets::radial_menu),
+//! and set [`RadialMenu::selected`], e.g. from [`radial_slot`]. Hovering a slot
+//! with the mouse selects it too.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use super::palette::{BUTTON_HOVERED_BACKGROUND, NODE_BACKGROUND};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(RadialMenu, RadialSlot)>();
+    app.add_systems(
+        Update,
+        (select_hovered_slot, highlight_selected_slot).chain(),
+    );
+}
+
+/// A ring of [`RadialSlot`]s, which are its children.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct RadialMenu {
+    pub slots: usize,
+    pub selected: Option<usize>,
+}
+
+/// A slot of a [`RadialMenu`], numbered clockwise from the top.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct RadialSlot(pub usize);
+
+/// The slot of a menu with `slots` slots that `direction` points at, with y up,
+/// or `None` for no direction.
+pub fn radial_slot(direction: Vec2, slots: usize) -> Option<usize> {
+    if direction == Vec2::ZERO || slots == 0 {
+        return None;
+    }
+    // Clockwise from the top.
+    let angle = direction.x.atan2(direction.y).rem_euclid(TAU);
+    Some((angle / (TAU / slots as f32)).round() as usize % slots)
+}
+
+/// Where `slot` goes, relative to the center, with y up.
+pub fn radial_offset(slot: usize, slots: usize, radius: f32) -> Vec2 {
+    let angle = slot as f32 * TAU / slots.max(1) as f32;
+    Vec2::new(angle.sin(), angle.cos()) * radius
+}
+
+fn select_hovered_slot(
+    slot_query: Query<(&Interaction, &RadialSlot, &Parent), Changed<Interaction>>,
+    mut menu_query: Query<&mut RadialMenu>,
+) {
+    for (interaction, slot, parent) in &slot_query {
+        if matches!(interaction, Interaction::None) {
+            continue;
+        }
+        if let Ok(mut menu) = menu_query.get_mut(parent.get()) {
+            menu.selected = Some(slot.0);
+        }
+    }
+}
+
+fn highlight_selected_slot(
+    menu_query: Query<(&RadialMenu, &Children), Changed<RadialMenu>>,
+    mut slot_query: Query<(&RadialSlot, &mut BackgroundColor)>,
+) {
+    for (menu, children) in &menu_query {
+        let mut slots = slot_query.iter_many_mut(children);
+        while let Some((slot, mut background)) = slots.fetch_next() {
+            background.0 = if menu.selected == Some(slot.0) {
+                BUTTON_HOVERED_BACKGROUND
+            } else {
+                NODE_BACKGROUND
+            };
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! A circular progress indicator, drawn by `shaders/ring.wgsl`.
+//! Spawn one with [`Widgets::ring`](super::widgets::Widgets::ring),
+//! and set [`ProgressRing::fraction`] to fill it clockwise from the top.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef, ShaderType},
+};
+
+use super::palette::{LABEL_TEXT, NODE_BACKGROUND};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(UiMaterialPlugin::<RingMaterial>::default());
+    app.register_type::<ProgressRing>();
+    app.add_systems(
+        PostUpdate,
+        (attach_ring_materials, update_ring_materials).chain(),
+    );
+}
+
+/// A ring that fills up with progress.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct ProgressRing {
+    /// How much of the ring is filled, from 0 to 1.
+    pub fraction: f32,
+    /// The width of the ring, as a fraction of its radius.
+    pub thickness: f32,
+    pub color: Color,
+    pub background: Color,
+}
+
+impl Default for ProgressRing {
+    fn default() -> Self {
+        Self {
+            fraction: 0.0,
+            thickness: 0.3,
+            color: LABEL_TEXT,
+            background: NODE_BACKGROUND,
+        }
+    }
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct RingMaterial {
+    #[uniform(0)]
+    params: RingParams,
+}
+
+impl UiMaterial for RingMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/ring.wgsl".into()
+    }
+}
+
+/// Colors are in linear space.
+#[derive(ShaderType, Debug, Clone, Copy, Default, PartialEq)]
+struct RingParams {
+    color: Vec4,
+    background: Vec4,
+    fraction: f32,
+    thickness: f32,
+}
+
+impl From<&ProgressRing> for RingParams {
+    fn from(ring: &ProgressRing) -> Self {
+        Self {
+            color: ring.color.to_linear().to_vec4(),
+            background: ring.background.to_linear().to_vec4(),
+            fraction: ring.fraction.clamp(0.0, 1.0),
+            thickness: ring.thickness.clamp(0.0, 1.0),
+        }
+    }
+}
+
+fn attach_ring_materials(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<RingMaterial>>,
+    ring_query: Query<(Entity, &ProgressRing), Without<Handle<RingMaterial>>>,
+) {
+    for (entity, ring) in &ring_query {
+        commands.entity(entity).insert(materials.add(RingMaterial {
+            params: ring.into(),
+        }));
+    }
+}
+
+fn update_ring_materials(
+    mut materials: ResMut<Assets<RingMaterial>>,
+    ring_query: Query<(&ProgressRing, &Handle<RingMaterial>), Changed<ProgressRing>>,
+) {
+    for (ring, handle) in &ring_query {
+        if let Some(material) = materials.get_mut(handle) {
+            material.params = ring.into();
+        }
+    }
+}
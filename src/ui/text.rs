@@ -0,0 +1,116 @@
+//! Text sizes and fonts for the widgets, following the text size and font settings.
+//! Text size is separate from any UI scaling: only text grows, and widgets let it wrap.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{game::assets::FontAssets, BoundedU8, GameSettings, LevelSetting};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TextPreset>();
+    app.init_resource::<UiFont>();
+    app.add_systems(
+        Update,
+        (
+            // The fonts start loading with the loading screen.
+            select_font.run_if(resource_exists::<FontAssets>),
+            apply_text_preset,
+        )
+            .chain(),
+    );
+}
+
+/// The kind of text an entity shows, which decides its size before scaling.
+/// Spawn text with [`TextPreset::style`] and keep this component on it.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum TextPreset {
+    Button,
+    Header,
+    Label,
+    Tab,
+    /// The value of a settings field.
+    Value,
+}
+
+impl TextPreset {
+    /// Font size at 100% text size.
+    pub fn base_size(self) -> f32 {
+        match self {
+            TextPreset::Button | TextPreset::Header => 40.0,
+            TextPreset::Label | TextPreset::Tab => 24.0,
+            TextPreset::Value => 16.0,
+        }
+    }
+
+    /// Style to spawn with. Size and font are corrected for the settings on the next update.
+    pub fn style(self, color: Color) -> TextStyle {
+        TextStyle {
+            font_size: self.base_size(),
+            color,
+            ..default()
+        }
+    }
+}
+
+/// Text size from 100% to 200%, in steps of 25%.
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, Default, Eq, PartialEq, Reflect)]
+pub(crate) struct TextSizeSetting(pub(crate) BoundedU8<0, 4>);
+
+impl LevelSetting for TextSizeSetting {
+    fn from_raw(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl TextSizeSetting {
+    pub(crate) fn scale(&self) -> f32 {
+        1.0 + self.0 .0 as f32 * 0.25
+    }
+
+    pub(crate) fn name_display(&self) -> String {
+        format!("{}%", (self.scale() * 100.0).round())
+    }
+}
+
+/// The font used for all [`TextPreset`] text.
+#[derive(Resource, Debug, Default, PartialEq)]
+pub struct UiFont(pub Handle<Font>);
+
+fn select_font(
+    settings: Res<GameSettings>,
+    asset_server: Res<AssetServer>,
+    fonts: Res<FontAssets>,
+    mut ui_font: ResMut<UiFont>,
+) {
+    let dyslexic = &fonts.dyslexic;
+    // Keep the default font until the dyslexic one has loaded, or if it failed to.
+    let font =
+        if settings.dyslexic_font.is_on() && asset_server.is_loaded_with_dependencies(dyslexic) {
+            dyslexic.clone_weak()
+        } else {
+            Handle::default()
+        };
+    ui_font.set_if_neq(UiFont(font));
+}
+
+fn apply_text_preset(
+    settings: Res<GameSettings>,
+    ui_font: Res<UiFont>,
+    mut applied_scale: Local<Option<f32>>,
+    mut text_query: Query<(Ref<TextPreset>, &mut Text)>,
+) {
+    let scale = settings.text_size.scale();
+    let changed = *applied_scale != Some(scale) || ui_font.is_changed();
+    *applied_scale = Some(scale);
+    for (preset, mut text) in &mut text_query {
+        if !changed && !preset.is_added() {
+            continue;
+        }
+        let font_size = preset.base_size() * scale;
+        for section in &mut text.sections {
+            section.style.font_size = font_size;
+            section.style.font = ui_font.0.clone();
+        }
+    }
+}
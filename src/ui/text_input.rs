@@ -0,0 +1,231 @@
+//! Single-line text fields, spawned by [`Widgets::text_input`](super::widgets::Widgets).
+//! Clicking or pressing a field starts editing it, which captures the keyboard:
+//! typed characters are inserted at the caret, Left / Right / Home / End move it,
+//! Backspace and Delete remove characters, and Enter or Escape stop editing.
+//! There is no on-screen keyboard, so gamepad players can't type.
+
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+    ui::UiSystem,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TextInput>();
+    // Before focus navigation, so the keys used here don't also press or move anything.
+    app.add_systems(
+        PreUpdate,
+        (start_editing, edit_text_input)
+            .chain()
+            .after(UiSystem::Focus)
+            .before(super::focus::NavigationSet),
+    );
+    app.add_systems(Update, show_text_input);
+}
+
+/// A text field's contents. Change `value` to set them from code.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct TextInput {
+    pub value: String,
+    /// The most characters that can be typed in.
+    pub max_chars: usize,
+    /// Position of the caret, in characters.
+    caret: usize,
+}
+
+impl TextInput {
+    pub fn new(value: impl Into<String>, max_chars: usize) -> Self {
+        let value = value.into();
+        Self {
+            caret: value.chars().count(),
+            value,
+            max_chars,
+        }
+    }
+
+    /// Insert typed text at the caret, dropping characters that don't fit.
+    pub fn insert(&mut self, text: &str) {
+        self.caret = self.caret();
+        for c in text.chars().filter(|c| !c.is_control()) {
+            if self.value.chars().count() >= self.max_chars {
+                break;
+            }
+            let index = self.byte_index(self.caret);
+            self.value.insert(index, c);
+            self.caret += 1;
+        }
+    }
+
+    /// Remove the character before the caret.
+    pub fn backspace(&mut self) {
+        self.caret = self.caret();
+        if self.caret > 0 {
+            self.caret -= 1;
+            self.value.remove(self.byte_index(self.caret));
+        }
+    }
+
+    /// Remove the character after the caret.
+    pub fn delete(&mut self) {
+        self.caret = self.caret();
+        if self.caret < self.value.chars().count() {
+            self.value.remove(self.byte_index(self.caret));
+        }
+    }
+
+    /// Move the caret by a number of characters, staying within the text.
+    pub fn move_caret(&mut self, by: isize) {
+        let len = self.value.chars().count();
+        self.caret = self.caret().saturating_add_signed(by).min(len);
+    }
+
+    pub fn caret(&self) -> usize {
+        // `value` may have been set from code since the caret last moved.
+        self.caret.min(self.value.chars().count())
+    }
+
+    fn byte_index(&self, chars: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(chars)
+            .map_or(self.value.len(), |(index, _)| index)
+    }
+}
+
+/// Triggered on a [`TextInput`] when editing it ends with Enter.
+#[derive(Event, Debug, Clone)]
+pub struct TextSubmitted(pub String);
+
+/// The text field being edited, if any. Focus navigation is paused while it exists.
+#[derive(Resource, Debug)]
+pub struct EditingText(pub Entity);
+
+/// The text of a [`TextInput`].
+#[derive(Component)]
+pub(super) struct TextInputText;
+
+fn start_editing(
+    mut commands: Commands,
+    editing: Option<Res<EditingText>>,
+    input_query: Query<(Entity, &Interaction), (With<TextInput>, Changed<Interaction>)>,
+) {
+    if editing.is_some() {
+        return;
+    }
+    if let Some((entity, _)) = input_query
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Pressed)
+    {
+        commands.insert_resource(EditingText(entity));
+    }
+}
+
+fn edit_text_input(
+    mut commands: Commands,
+    editing: Option<Res<EditingText>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut input_query: Query<(&mut TextInput, &Interaction)>,
+) {
+    let Some(editing) = editing else {
+        keyboard_events.clear();
+        return;
+    };
+    // The field is gone if its screen was left in the meantime.
+    let Ok((mut input, interaction)) = input_query.get_mut(editing.0) else {
+        commands.remove_resource::<EditingText>();
+        return;
+    };
+
+    let mut submitted = false;
+    // Clicking anywhere else stops editing, like Escape.
+    let mut done = mouse_input.just_pressed(MouseButton::Left)
+        && *interaction == Interaction::None
+        && !editing.is_added();
+    for event in keyboard_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(c) => input.insert(c.as_str()),
+            Key::Space => input.insert(" "),
+            Key::Backspace => input.backspace(),
+            Key::Delete => input.delete(),
+            Key::ArrowLeft => input.move_caret(-1),
+            Key::ArrowRight => input.move_caret(1),
+            Key::Home => input.move_caret(isize::MIN),
+            Key::End => input.move_caret(isize::MAX),
+            Key::Enter => {
+                submitted = true;
+                done = true;
+            }
+            Key::Escape => done = true,
+            _ => (),
+        }
+    }
+    // Keep these from pausing, going back, navigating or pressing the field again.
+    let captured = keyboard_input
+        .get_just_pressed()
+        .copied()
+        .collect::<Vec<_>>();
+    for key in captured {
+        keyboard_input.clear_just_pressed(key);
+    }
+
+    if submitted {
+        commands.trigger_targets(TextSubmitted(input.value.clone()), editing.0);
+    }
+    if done {
+        commands.remove_resource::<EditingText>();
+    }
+}
+
+fn show_text_input(
+    editing: Option<Res<EditingText>>,
+    mut shown_editing: Local<Option<Entity>>,
+    input_query: Query<(Entity, Ref<TextInput>, &Children)>,
+    mut text_query: Query<&mut Text, With<TextInputText>>,
+) {
+    let editing = editing.map(|editing| editing.0);
+    let editing_changed = *shown_editing != editing;
+    *shown_editing = editing;
+    for (entity, input, children) in &input_query {
+        if !input.is_changed() && !editing_changed {
+            continue;
+        }
+        let is_editing = editing == Some(entity);
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = if is_editing {
+                let mut value = input.value.clone();
+                value.insert(input.byte_index(input.caret()), '|');
+                value
+            } else {
+                input.value.clone()
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_input_edits_at_the_caret() {
+        let mut input = TextInput::new("dck", 5);
+        input.move_caret(-2);
+        input.insert("u");
+        assert_eq!(input.value, "duck");
+        input.move_caret(isize::MAX);
+        input.insert("ies!");
+        assert_eq!(input.value, "ducki");
+        input.move_caret(isize::MIN);
+        input.delete();
+        input.backspace();
+        assert_eq!(input.value, "ucki");
+        assert_eq!(input.caret(), 0);
+    }
+}
@@ -0,0 +1,185 @@
+//! Switchable UI themes, currently the default and a high-contrast theme,
+//! with their colors remapped for the colorblind setting, see `ui::palette`.
+//! Widgets mark their parts with [`Themed`] so they can be restyled at runtime,
+//! and world entities with a [`WorldOutline`] get an outline in high contrast.
+
+use bevy::prelude::*;
+
+use super::{interaction::InteractionPalette, palette::*};
+use crate::GameSettings;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Themed, WorldOutline)>();
+    app.insert_resource(UiTheme::DEFAULT);
+    app.add_systems(
+        Update,
+        (
+            select_theme.run_if(resource_changed::<GameSettings>),
+            apply_theme,
+            draw_world_outlines,
+        )
+            .chain(),
+    );
+}
+
+/// Colors and sizes used by the widgets.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct UiTheme {
+    pub node_background: Color,
+    pub button_hovered: Color,
+    pub button_pressed: Color,
+    pub button_text: Color,
+    pub header_text: Color,
+    pub label_text: Color,
+    /// Text showing the value of a setting.
+    pub value_text: Color,
+    pub border: Color,
+    pub border_width: f32,
+    pub focus_outline: Color,
+    pub focus_outline_width: f32,
+    /// Outline around [`WorldOutline`] entities, if any.
+    pub world_outline: Option<Color>,
+}
+
+impl UiTheme {
+    pub const DEFAULT: Self = Self {
+        node_background: NODE_BACKGROUND,
+        button_hovered: BUTTON_HOVERED_BACKGROUND,
+        button_pressed: BUTTON_PRESSED_BACKGROUND,
+        button_text: BUTTON_TEXT,
+        header_text: HEADER_TEXT,
+        label_text: LABEL_TEXT,
+        value_text: Color::WHITE,
+        border: Color::NONE,
+        border_width: 0.0,
+        focus_outline: FOCUS_OUTLINE,
+        focus_outline_width: 3.0,
+        world_outline: None,
+    };
+
+    /// Black and white with yellow accents, and borders so that no widget relies on
+    /// its background color to stand out.
+    pub const HIGH_CONTRAST: Self = Self {
+        node_background: Color::BLACK,
+        button_hovered: Color::srgb(0.25, 0.25, 0.25),
+        button_pressed: Color::srgb(0.4, 0.4, 0.4),
+        button_text: Color::WHITE,
+        header_text: HIGH_CONTRAST_ACCENT,
+        label_text: Color::WHITE,
+        value_text: HIGH_CONTRAST_ACCENT,
+        border: Color::WHITE,
+        border_width: 3.0,
+        focus_outline: HIGH_CONTRAST_ACCENT,
+        focus_outline_width: 6.0,
+        world_outline: Some(HIGH_CONTRAST_ACCENT),
+    };
+
+    /// This theme with every color remapped for `vision`.
+    pub fn remapped(&self, vision: ColorVision) -> Self {
+        let remap = |color| vision.remap(color);
+        Self {
+            node_background: remap(self.node_background),
+            button_hovered: remap(self.button_hovered),
+            button_pressed: remap(self.button_pressed),
+            button_text: remap(self.button_text),
+            header_text: remap(self.header_text),
+            label_text: remap(self.label_text),
+            value_text: remap(self.value_text),
+            border: remap(self.border),
+            focus_outline: remap(self.focus_outline),
+            world_outline: self.world_outline.map(remap),
+            ..self.clone()
+        }
+    }
+}
+
+/// The part a themed entity plays in its widget, which decides how it is styled.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum Themed {
+    Button,
+    Header,
+    ButtonText,
+    HeaderText,
+    LabelText,
+    ValueText,
+}
+
+/// Outlines a gameplay-relevant sprite of the given size when the theme asks for it.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct WorldOutline(pub Vec2);
+
+fn select_theme(settings: Res<GameSettings>, mut theme: ResMut<UiTheme>) {
+    let selected = if settings.high_contrast.is_on() {
+        UiTheme::HIGH_CONTRAST
+    } else {
+        UiTheme::DEFAULT
+    };
+    theme.set_if_neq(selected.remapped(settings.colorblind.vision()));
+}
+
+fn apply_theme(
+    theme: Res<UiTheme>,
+    mut themed_query: Query<(
+        Ref<Themed>,
+        Option<&Interaction>,
+        Option<&mut InteractionPalette>,
+        Option<&mut BackgroundColor>,
+        Option<&mut BorderColor>,
+        Option<&mut Style>,
+        Option<&mut Text>,
+    )>,
+) {
+    for (themed, interaction, palette, background, border, style, text) in &mut themed_query {
+        // Widgets are spawned with the default theme, so new ones need restyling too.
+        if !theme.is_changed() && !themed.is_added() {
+            continue;
+        }
+        if let Some(mut palette) = palette {
+            *palette = InteractionPalette {
+                none: theme.node_background,
+                hovered: theme.button_hovered,
+                pressed: theme.button_pressed,
+            };
+        }
+        if let Some(mut background) = background {
+            background.0 = match interaction {
+                Some(Interaction::Hovered) => theme.button_hovered,
+                Some(Interaction::Pressed) => theme.button_pressed,
+                _ => theme.node_background,
+            };
+        }
+        if let (Some(mut border), Some(mut style)) = (border, style) {
+            border.0 = theme.border;
+            style.border = UiRect::all(Val::Px(theme.border_width));
+        }
+        if let Some(mut text) = text {
+            let color = match *themed {
+                Themed::ButtonText => theme.button_text,
+                Themed::HeaderText => theme.header_text,
+                Themed::LabelText => theme.label_text,
+                Themed::ValueText => theme.value_text,
+                Themed::Button | Themed::Header => continue,
+            };
+            for section in &mut text.sections {
+                section.style.color = color;
+            }
+        }
+    }
+}
+
+fn draw_world_outlines(
+    theme: Res<UiTheme>,
+    mut gizmos: Gizmos,
+    outline_query: Query<(&GlobalTransform, &WorldOutline, &InheritedVisibility)>,
+) {
+    let Some(color) = theme.world_outline else {
+        return;
+    };
+    for (transform, outline, visibility) in &outline_query {
+        if visibility.get() {
+            gizmos.rect_2d(transform.translation().truncate(), 0.0, outline.0, color);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Simple tweens for UI nodes. A [`UiTween`] slides its node in from an offset,
+//! following an [`Ease`] curve, and removes itself once the node has arrived.
+//! Tweens move the node's `left` and `top`, so they only suit nodes that don't set those.
+
+use bevy::{prelude::*, ui::UiSystem};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(UiTween, Ease)>();
+    // After all gameplay and UI logic, so a tween skipped this frame still lands before layout.
+    app.add_systems(PostUpdate, animate_ui_tweens.before(UiSystem::Layout));
+}
+
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct UiTween {
+    /// Offset from the node's place in the layout at the start, in pixels.
+    pub from: Vec2,
+    /// Seconds to wait before moving.
+    pub delay: f32,
+    /// Seconds to move for.
+    pub duration: f32,
+    pub ease: Ease,
+    elapsed: f32,
+}
+
+impl UiTween {
+    pub fn slide_in(from: Vec2, duration: f32, ease: Ease) -> Self {
+        Self {
+            from,
+            delay: 0.0,
+            duration,
+            ease,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Jump to the end, for skipping animations.
+    pub fn finish(&mut self) {
+        self.elapsed = self.delay + self.duration;
+    }
+
+    /// How far along the tween is, from 0 to 1, before easing.
+    fn progress(&self) -> f32 {
+        ((self.elapsed - self.delay) / self.duration.max(f32::EPSILON)).clamp(0.0, 1.0)
+    }
+}
+
+/// Easing curves, mapping progress from 0 to 1 onto distance traveled from 0 to 1.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+pub enum Ease {
+    Linear,
+    /// Fast at first, slowing down towards the end.
+    OutCubic,
+    /// Overshoots and bounces back a few times, like something dropped.
+    OutBounce,
+}
+
+impl Ease {
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Self::Linear => t,
+            Self::OutCubic => 1.0 - (1.0 - t).powi(3),
+            Self::OutBounce => {
+                // The usual piecewise parabolas, see https://easings.net/#easeOutBounce.
+                const N: f32 = 7.5625;
+                const D: f32 = 2.75;
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+        }
+    }
+}
+
+fn animate_ui_tweens(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tween_query: Query<(Entity, &mut UiTween, &mut Style)>,
+) {
+    for (entity, mut tween, mut style) in &mut tween_query {
+        tween.elapsed += time.delta_seconds();
+        let progress = tween.progress();
+        let offset = tween.from * (1.0 - tween.ease.apply(progress));
+        style.left = Val::Px(offset.x);
+        style.top = Val::Px(offset.y);
+        if progress >= 1.0 {
+            commands.entity(entity).remove::<UiTween>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eases_start_and_end_in_place() {
+        for ease in [Ease::Linear, Ease::OutCubic, Ease::OutBounce] {
+            assert!(ease.apply(0.0).abs() < 1e-5, "{ease:?}");
+            assert!((ease.apply(1.0) - 1.0).abs() < 1e-5, "{ease:?}");
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Text that appears a character at a time, like in the
+//! [`Widgets::dialogue_box`](super::widgets::Widgets). Call [`Typewriter::set`] to show
+//! new text, and [`Typewriter::finish`] to show the rest of it right away.
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Typewriter>();
+    app.add_systems(Update, reveal_text);
+}
+
+/// The full text of a text entity, of which the first `shown` characters are visible.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct Typewriter {
+    text: String,
+    /// Characters revealed so far, including partly.
+    shown: f32,
+}
+
+/// How quickly text is revealed.
+const CHARS_PER_SECOND: f32 = 45.0;
+
+impl Typewriter {
+    /// Start revealing `text` from the beginning.
+    pub fn set(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.shown = 0.0;
+    }
+
+    pub fn finish(&mut self) {
+        self.shown = self.text.chars().count() as f32;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.shown as usize >= self.text.chars().count()
+    }
+
+    /// The part of the text that is revealed.
+    pub fn visible(&self) -> &str {
+        let end = self
+            .text
+            .char_indices()
+            .nth(self.shown as usize)
+            .map_or(self.text.len(), |(index, _)| index);
+        &self.text[..end]
+    }
+
+    /// Reveal more of the text, as `delta_seconds` passed.
+    pub fn step(&mut self, delta_seconds: f32) {
+        if !self.is_finished() {
+            self.shown += delta_seconds * CHARS_PER_SECOND;
+        }
+    }
+}
+
+fn reveal_text(time: Res<Time>, mut typewriter_query: Query<(&mut Typewriter, &mut Text)>) {
+    for (mut typewriter, mut text) in &mut typewriter_query {
+        if !typewriter.is_finished() {
+            typewriter.step(time.delta_seconds());
+        }
+        let visible = typewriter.visible();
+        if text.sections[0].value != visible {
+            text.sections[0].value = visible.to_string();
+        }
+    }
+}
@@ -1,6 +1,18 @@
 //! Helper traits for creating common widgets.
 
-use super::{interaction::InteractionPalette, palette::*};
+use super::{
+    counter::Counter,
+    focus::{Focusable, TabBar, TabButton},
+    interaction::{InteractionPalette, RepeatButton},
+    numeric_entry::SliderValue,
+    palette::*,
+    radial_menu::{radial_offset, RadialMenu, RadialSlot},
+    ring::ProgressRing,
+    text::TextPreset,
+    text_input::{TextInput, TextInputText},
+    theme::Themed,
+    typewriter::Typewriter,
+};
 use crate::{BinaryAdjustment, LevelSettingAction};
 use bevy::{ecs::system::EntityCommands, prelude::*, ui::Val::*};
 
@@ -15,6 +27,12 @@ pub trait Widgets {
     /// Spawn a simple text label.
     fn label(&mut self, text: impl Into<String>) -> EntityCommands;
 
+    /// Spawn a text field holding `value`, see [`TextInput`].
+    fn text_input(&mut self, value: impl Into<String>, max_chars: usize) -> EntityCommands;
+
+    /// Spawn a number that counts up or down to new values, see [`Counter`].
+    fn counter(&mut self, value: u64) -> EntityCommands;
+
     /// Extra: Level-based settings field
     fn settings_field(
         &mut self,
@@ -22,6 +40,47 @@ pub trait Widgets {
         field_text: impl Into<String>,
         scope: impl Component + Copy,
     ) -> EntityCommands;
+
+    /// Extra: [`Widgets::settings_field`] for settings with many levels.
+    /// Holding Shift or a trigger adjusts it in finer steps,
+    /// and clicking the value opens a popup to type it in, see [`SliderValue`].
+    fn slider(
+        &mut self,
+        field_title: impl Into<String>,
+        field_text: impl Into<String>,
+        scope: impl Component + Copy,
+    ) -> EntityCommands;
+
+    /// Spawn a horizontal level meter, with `scope` on its parts.
+    /// Use [`set_meter_level`] to move them.
+    fn meter(&mut self, scope: impl Component + Copy) -> EntityCommands;
+
+    /// Spawn a horizontal progress bar. Use [`set_progress`] on its [`ProgressFill`].
+    fn progress_bar(&mut self) -> EntityCommands;
+
+    /// Spawn a ring `size` pixels across that fills up clockwise, see [`ProgressRing`].
+    fn ring(&mut self, size: f32) -> EntityCommands;
+
+    /// Spawn a square slot `size` pixels across for a stack of items, with `scope` on its
+    /// parts. It starts out empty. Use [`set_slot_part`] to show a stack in it.
+    fn item_slot(&mut self, size: f32, scope: impl Component + Copy) -> EntityCommands;
+
+    /// Spawn a ring of slots `radius` pixels from its center, one per label,
+    /// clockwise from the top. See [`RadialMenu`].
+    fn radial_menu(&mut self, labels: &[String], radius: f32) -> EntityCommands;
+
+    /// Spawn a row of tab buttons, each with its `tab` component inserted.
+    /// Tabs are smaller than [`Widgets::button`] so that a few fit in a row.
+    /// The first tab starts out selected, and Q / E or the bumpers switch tabs.
+    fn tab_bar<C: Component + Copy>(&mut self, tabs: &[(&str, C)]) -> EntityCommands;
+
+    /// Spawn a column for the content of one tab, hidden unless `visible`.
+    /// Use [`set_tab_panel_visible`] to switch tabs.
+    fn tab_panel(&mut self, tab: impl Component, visible: bool) -> EntityCommands;
+
+    /// Spawn a box along the bottom of the screen for a line of dialogue, with its
+    /// [`DialoguePart`]s empty. The box is a button, so clicking it can advance the dialogue.
+    fn dialogue_box(&mut self) -> EntityCommands;
 }
 
 impl<T: Spawn> Widgets for T {
@@ -31,7 +90,9 @@ impl<T: Spawn> Widgets for T {
             ButtonBundle {
                 style: Style {
                     width: Px(200.0),
-                    height: Px(65.0),
+                    // Grow instead of clipping when large text wraps.
+                    min_height: Px(65.0),
+                    padding: UiRect::all(Px(5.0)),
                     justify_content: JustifyContent::Center,
                     align_items: AlignItems::Center,
                     ..default()
@@ -44,30 +105,79 @@ impl<T: Spawn> Widgets for T {
                 hovered: BUTTON_HOVERED_BACKGROUND,
                 pressed: BUTTON_PRESSED_BACKGROUND,
             },
+            Focusable,
+            Themed::Button,
         ));
         entity.with_children(|children| {
             children.spawn((
                 Name::new("Button Text"),
+                TextBundle::from_section(text, TextPreset::Button.style(BUTTON_TEXT))
+                    .with_text_justify(JustifyText::Center),
+                TextPreset::Button,
+                Themed::ButtonText,
+            ));
+        });
+        entity
+    }
+
+    fn text_input(&mut self, value: impl Into<String>, max_chars: usize) -> EntityCommands {
+        let input = TextInput::new(value, max_chars);
+        let mut entity = self.spawn((
+            Name::new("Text Input"),
+            ButtonBundle {
+                style: Style {
+                    width: Px(500.0),
+                    min_height: Px(65.0),
+                    padding: UiRect::all(Px(5.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(NODE_BACKGROUND),
+                ..default()
+            },
+            InteractionPalette {
+                none: NODE_BACKGROUND,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_PRESSED_BACKGROUND,
+            },
+            Focusable,
+            Themed::Button,
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Text Input Text"),
                 TextBundle::from_section(
-                    text,
-                    TextStyle {
-                        font_size: 40.0,
-                        color: BUTTON_TEXT,
-                        ..default()
-                    },
+                    input.value.clone(),
+                    TextPreset::Value.style(Color::WHITE),
                 ),
+                TextPreset::Value,
+                Themed::ValueText,
+                TextInputText,
             ));
         });
+        entity.insert(input);
         entity
     }
 
+    fn counter(&mut self, value: u64) -> EntityCommands {
+        self.spawn((
+            Name::new("Counter"),
+            TextBundle::from_section(value.to_string(), TextPreset::Label.style(LABEL_TEXT)),
+            TextPreset::Label,
+            Themed::LabelText,
+            Counter::new(value),
+        ))
+    }
+
     fn header(&mut self, text: impl Into<String>) -> EntityCommands {
         let mut entity = self.spawn((
             Name::new("Header"),
             NodeBundle {
                 style: Style {
                     width: Px(500.0),
-                    height: Px(65.0),
+                    min_height: Px(65.0),
+                    padding: UiRect::all(Px(5.0)),
                     justify_content: JustifyContent::Center,
                     align_items: AlignItems::Center,
                     ..default()
@@ -75,18 +185,15 @@ impl<T: Spawn> Widgets for T {
                 background_color: BackgroundColor(NODE_BACKGROUND),
                 ..default()
             },
+            Themed::Header,
         ));
         entity.with_children(|children| {
             children.spawn((
                 Name::new("Header Text"),
-                TextBundle::from_section(
-                    text,
-                    TextStyle {
-                        font_size: 40.0,
-                        color: HEADER_TEXT,
-                        ..default()
-                    },
-                ),
+                TextBundle::from_section(text, TextPreset::Header.style(HEADER_TEXT))
+                    .with_text_justify(JustifyText::Center),
+                TextPreset::Header,
+                Themed::HeaderText,
             ));
         });
         entity
@@ -108,14 +215,10 @@ impl<T: Spawn> Widgets for T {
         entity.with_children(|children| {
             children.spawn((
                 Name::new("Label Text"),
-                TextBundle::from_section(
-                    text,
-                    TextStyle {
-                        font_size: 24.0,
-                        color: LABEL_TEXT,
-                        ..default()
-                    },
-                ),
+                TextBundle::from_section(text, TextPreset::Label.style(LABEL_TEXT))
+                    .with_text_justify(JustifyText::Center),
+                TextPreset::Label,
+                Themed::LabelText,
             ));
         });
         entity
@@ -127,41 +230,434 @@ impl<T: Spawn> Widgets for T {
         field_text: impl Into<String>,
         scope: impl Component + Copy,
     ) -> EntityCommands {
-        let mut label = self.label(field_title);
-        label.with_children(|field| {
-            field
-                .spawn(NodeBundle {
+        level_field(self, field_title, field_text, scope, false)
+    }
+
+    fn slider(
+        &mut self,
+        field_title: impl Into<String>,
+        field_text: impl Into<String>,
+        scope: impl Component + Copy,
+    ) -> EntityCommands {
+        level_field(self, field_title, field_text, scope, true)
+    }
+
+    fn meter(&mut self, scope: impl Component + Copy) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Meter"),
+            NodeBundle {
+                style: Style {
+                    width: Px(500.0),
+                    height: Px(8.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(NODE_BACKGROUND),
+                ..default()
+            },
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Meter Fill"),
+                NodeBundle {
                     style: Style {
-                        width: Px(500.0),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
+                        width: Percent(0.0),
+                        height: Percent(100.0),
                         ..default()
                     },
+                    background_color: BackgroundColor(LABEL_TEXT),
                     ..default()
-                })
-                .with_children(|volume_text| {
-                    volume_text.spawn((
-                        TextBundle::from_section(
-                            field_text,
-                            TextStyle {
-                                font_size: 16.0,
-                                color: Color::WHITE,
-                                ..default()
-                            },
-                        ),
-                        scope,
-                    ));
-                });
-            field.button("-").insert(LevelSettingAction {
+                },
+                MeterPart::Fill,
                 scope,
-                adjustment: BinaryAdjustment::Down,
-            });
-            field.button("+").insert(LevelSettingAction {
+            ));
+            children.spawn((
+                Name::new("Meter Peak"),
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Percent(0.0),
+                        width: Px(2.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(BUTTON_TEXT),
+                    ..default()
+                },
+                MeterPart::Peak,
                 scope,
-                adjustment: BinaryAdjustment::Up,
-            });
+            ));
+        });
+        entity
+    }
+
+    fn progress_bar(&mut self) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Progress Bar"),
+            NodeBundle {
+                style: Style {
+                    width: Px(500.0),
+                    height: Px(20.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(NODE_BACKGROUND),
+                ..default()
+            },
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Progress Fill"),
+                NodeBundle {
+                    style: Style {
+                        width: Percent(0.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(LABEL_TEXT),
+                    ..default()
+                },
+                ProgressFill,
+            ));
+        });
+        entity
+    }
+
+    fn ring(&mut self, size: f32) -> EntityCommands {
+        self.spawn((
+            Name::new("Progress Ring"),
+            NodeBundle {
+                style: Style {
+                    width: Px(size),
+                    height: Px(size),
+                    ..default()
+                },
+                ..default()
+            },
+            ProgressRing::default(),
+        ))
+    }
+
+    fn item_slot(&mut self, size: f32, scope: impl Component + Copy) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Item Slot"),
+            NodeBundle {
+                style: Style {
+                    width: Px(size),
+                    height: Px(size),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(NODE_BACKGROUND),
+                ..default()
+            },
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Item Slot Icon"),
+                NodeBundle {
+                    style: Style {
+                        width: Percent(60.0),
+                        height: Percent(60.0),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                SlotPart::Icon,
+                scope,
+            ));
+            children.spawn((
+                Name::new("Item Slot Count"),
+                TextBundle::from_section("", TextPreset::Label.style(LABEL_TEXT)).with_style(
+                    Style {
+                        position_type: PositionType::Absolute,
+                        right: Px(2.0),
+                        bottom: Px(0.0),
+                        ..default()
+                    },
+                ),
+                TextPreset::Label,
+                Themed::LabelText,
+                SlotPart::Count,
+                scope,
+            ));
+        });
+        entity
+    }
+
+    fn radial_menu(&mut self, labels: &[String], radius: f32) -> EntityCommands {
+        const SLOT_SIZE: f32 = 120.0;
+        let center = radius + SLOT_SIZE / 2.0;
+        let mut entity = self.spawn((
+            Name::new("Radial Menu"),
+            NodeBundle {
+                style: Style {
+                    width: Px(center * 2.0),
+                    height: Px(center * 2.0),
+                    ..default()
+                },
+                ..default()
+            },
+            RadialMenu {
+                slots: labels.len(),
+                selected: None,
+            },
+        ));
+        entity.with_children(|children| {
+            for (i, label) in labels.iter().enumerate() {
+                let offset = radial_offset(i, labels.len(), radius);
+                children
+                    .spawn((
+                        Name::new("Radial Slot"),
+                        ButtonBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                left: Px(center + offset.x - SLOT_SIZE / 2.0),
+                                // UI coordinates have y down.
+                                top: Px(center - offset.y - SLOT_SIZE / 2.0),
+                                width: Px(SLOT_SIZE),
+                                height: Px(SLOT_SIZE),
+                                padding: UiRect::all(Px(5.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: BackgroundColor(NODE_BACKGROUND),
+                            border_radius: BorderRadius::MAX,
+                            ..default()
+                        },
+                        RadialSlot(i),
+                    ))
+                    .with_children(|children| {
+                        children.spawn((
+                            Name::new("Radial Slot Text"),
+                            TextBundle::from_section(
+                                label.clone(),
+                                TextPreset::Button.style(BUTTON_TEXT),
+                            )
+                            .with_text_justify(JustifyText::Center),
+                            TextPreset::Button,
+                            Themed::ButtonText,
+                        ));
+                    });
+            }
+        });
+        entity
+    }
+
+    fn tab_bar<C: Component + Copy>(&mut self, tabs: &[(&str, C)]) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Tab Bar"),
+            NodeBundle {
+                style: Style {
+                    column_gap: Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+            TabBar::default(),
+        ));
+        entity.with_children(|children| {
+            for &(text, tab) in tabs {
+                children
+                    .spawn((
+                        Name::new("Tab"),
+                        ButtonBundle {
+                            style: Style {
+                                width: Px(180.0),
+                                min_height: Px(45.0),
+                                padding: UiRect::all(Px(5.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: BackgroundColor(NODE_BACKGROUND),
+                            ..default()
+                        },
+                        InteractionPalette {
+                            none: NODE_BACKGROUND,
+                            hovered: BUTTON_HOVERED_BACKGROUND,
+                            pressed: BUTTON_PRESSED_BACKGROUND,
+                        },
+                        Focusable,
+                        TabButton,
+                        Themed::Button,
+                        tab,
+                    ))
+                    .with_children(|children| {
+                        children.spawn((
+                            Name::new("Tab Text"),
+                            TextBundle::from_section(text, TextPreset::Tab.style(BUTTON_TEXT))
+                                .with_text_justify(JustifyText::Center),
+                            TextPreset::Tab,
+                            Themed::ButtonText,
+                        ));
+                    });
+            }
         });
-        label
+        entity
+    }
+
+    fn tab_panel(&mut self, tab: impl Component, visible: bool) -> EntityCommands {
+        let mut style = Style {
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            row_gap: Px(10.0),
+            ..default()
+        };
+        let mut visibility = Visibility::Inherited;
+        set_tab_panel_visible(&mut style, &mut visibility, visible);
+        self.spawn((
+            Name::new("Tab Panel"),
+            NodeBundle {
+                style,
+                visibility,
+                ..default()
+            },
+            tab,
+        ))
+    }
+
+    fn dialogue_box(&mut self) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Dialogue Box"),
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Percent(10.0),
+                    right: Percent(10.0),
+                    bottom: Px(20.0),
+                    min_height: Px(160.0),
+                    padding: UiRect::all(Px(15.0)),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Px(10.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(NODE_BACKGROUND),
+                ..default()
+            },
+            Themed::Header,
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Dialogue Speaker"),
+                TextBundle::from_section("", TextPreset::Label.style(HEADER_TEXT)),
+                TextPreset::Label,
+                Themed::HeaderText,
+                DialoguePart::Speaker,
+            ));
+            children.spawn((
+                Name::new("Dialogue Text"),
+                TextBundle::from_section("", TextPreset::Label.style(BUTTON_TEXT)),
+                TextPreset::Label,
+                Themed::ButtonText,
+                Typewriter::default(),
+                DialoguePart::Text,
+            ));
+            children.spawn((
+                Name::new("Dialogue Choices"),
+                NodeBundle {
+                    style: Style {
+                        flex_wrap: FlexWrap::Wrap,
+                        column_gap: Px(10.0),
+                        row_gap: Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                DialoguePart::Choices,
+            ));
+        });
+        entity
+    }
+}
+
+/// The parts of a [`Widgets::dialogue_box`].
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum DialoguePart {
+    /// Who is talking.
+    Speaker,
+    /// What they say, revealed by its [`Typewriter`].
+    Text,
+    /// A row to spawn buttons in for the choices of the line, if it has any.
+    Choices,
+}
+
+/// Show or hide a panel spawned with [`Widgets::tab_panel`].
+/// Hidden panels take up no space, and their widgets can't be focused.
+pub fn set_tab_panel_visible(style: &mut Style, visibility: &mut Visibility, visible: bool) {
+    if visible {
+        style.display = Display::Flex;
+        *visibility = Visibility::Inherited;
+    } else {
+        style.display = Display::None;
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// The moving parts of a [`Widgets::meter`].
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum MeterPart {
+    /// Grows with the level.
+    Fill,
+    /// Marks the highest recent level.
+    Peak,
+}
+
+/// Move a part of a [`Widgets::meter`] to `rms` or `peak`, both fractions from 0 to 1.
+pub fn set_meter_level(style: &mut Style, part: MeterPart, rms: f32, peak: f32) {
+    match part {
+        MeterPart::Fill => style.width = Percent(rms * 100.0),
+        MeterPart::Peak => style.left = Percent(peak * 100.0),
+    }
+}
+
+/// The part of a [`Widgets::progress_bar`] that grows with progress.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct ProgressFill;
+
+/// Fill a [`Widgets::progress_bar`] up to `fraction`, from 0 to 1.
+pub fn set_progress(style: &mut Style, fraction: f32) {
+    style.width = Percent(fraction.clamp(0.0, 1.0) * 100.0);
+}
+
+/// The parts of a [`Widgets::item_slot`].
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum SlotPart {
+    /// A square in the item's color.
+    Icon,
+    /// How many are stacked, when more than one.
+    Count,
+}
+
+/// Show a stack of `count` items of `color` in a part of a [`Widgets::item_slot`],
+/// or nothing for `None`. Only the count has `text`.
+pub fn set_slot_part(
+    part: SlotPart,
+    stack: Option<(Color, u32)>,
+    background: &mut BackgroundColor,
+    visibility: &mut Visibility,
+    text: Option<&mut Text>,
+) {
+    match (part, stack) {
+        (SlotPart::Icon, Some((color, _))) => {
+            background.0 = color;
+            *visibility = Visibility::Inherited;
+        }
+        (SlotPart::Icon, None) => *visibility = Visibility::Hidden,
+        (SlotPart::Count, stack) => {
+            if let Some(text) = text {
+                text.sections[0].value = match stack {
+                    Some((_, count)) if count > 1 => count.to_string(),
+                    _ => String::new(),
+                };
+            }
+        }
     }
 }
 
@@ -193,6 +689,67 @@ impl Containers for Commands<'_, '_> {
     }
 }
 
+/// A label with the value of a level setting and buttons to adjust it.
+/// With `slider`, the value is a button that opens a [`SliderValue`] popup.
+fn level_field<'a, T: Spawn>(
+    spawner: &'a mut T,
+    field_title: impl Into<String>,
+    field_text: impl Into<String>,
+    scope: impl Component + Copy,
+    slider: bool,
+) -> EntityCommands<'a> {
+    let mut label = spawner.label(field_title);
+    label.with_children(|field| {
+        let mut value = field.spawn(NodeBundle {
+            style: Style {
+                width: Px(500.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        });
+        if slider {
+            value.insert((
+                Name::new("Slider Value"),
+                Button,
+                Interaction::default(),
+                InteractionPalette {
+                    none: Color::NONE,
+                    hovered: BUTTON_HOVERED_BACKGROUND,
+                    pressed: BUTTON_PRESSED_BACKGROUND,
+                },
+                Focusable,
+                SliderValue,
+                scope,
+            ));
+        }
+        value.with_children(|volume_text| {
+            volume_text.spawn((
+                TextBundle::from_section(field_text, TextPreset::Value.style(Color::WHITE)),
+                scope,
+                TextPreset::Value,
+                Themed::ValueText,
+            ));
+        });
+        field.button("-").insert((
+            LevelSettingAction {
+                scope,
+                adjustment: BinaryAdjustment::Down,
+            },
+            RepeatButton::default(),
+        ));
+        field.button("+").insert((
+            LevelSettingAction {
+                scope,
+                adjustment: BinaryAdjustment::Up,
+            },
+            RepeatButton::default(),
+        ));
+    });
+    label
+}
+
 /// An internal trait for types that can spawn entities.
 /// This is here so that [`Widgets`] can be implemented on all types that
 /// are able to spawn entities.
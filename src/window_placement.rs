@@ -0,0 +1,276 @@
+//! Remembering where the window was on native builds, so it opens there again.
+//!
+//! The window's size, position and monitor are kept in [`GameSettings`] as a
+//! [`WindowPlacement`], and restored once the monitors are known at startup. The monitors
+//! may have changed since, so the window is moved onto one that still exists and kept
+//! within it. Whether the window is fullscreen, and on which monitor, is the display
+//! settings' business.
+
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    window::{PrimaryWindow, WindowMode, WindowMoved, WindowResized},
+    winit::WinitWindows,
+};
+use serde::{Deserialize, Serialize};
+use winit::monitor::MonitorHandle;
+
+use crate::{
+    display::{go_fullscreen_on, MonitorChoice},
+    GameSettings,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<WindowPlacement>();
+    // Browsers decide where the canvas goes.
+    if cfg!(not(target_family = "wasm")) {
+        app.add_systems(
+            Update,
+            (
+                restore_window_placement.run_if(not(resource_exists::<PlacementRestored>)),
+                remember_window_placement.run_if(resource_exists::<PlacementRestored>),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Where the window was last seen while windowed.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, Reflect)]
+pub(crate) struct WindowPlacement {
+    /// Size in logical pixels.
+    pub(crate) size: Option<UVec2>,
+    /// Top left corner in physical pixels, relative to the monitor's top left corner.
+    pub(crate) position: Option<IVec2>,
+    /// Name of the monitor the window was on.
+    pub(crate) monitor: Option<String>,
+}
+
+/// The part of the desktop a monitor covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct MonitorArea {
+    /// Top left corner and size in physical pixels.
+    pub(crate) position: IVec2,
+    pub(crate) size: UVec2,
+    pub(crate) scale_factor: f32,
+}
+
+impl From<&MonitorHandle> for MonitorArea {
+    fn from(monitor: &MonitorHandle) -> Self {
+        let position = monitor.position();
+        let size = monitor.size();
+        Self {
+            position: IVec2::new(position.x, position.y),
+            size: UVec2::new(size.width, size.height),
+            scale_factor: monitor.scale_factor() as f32,
+        }
+    }
+}
+
+impl MonitorArea {
+    fn contains(&self, point: IVec2) -> bool {
+        let max = self.position + self.size.as_ivec2();
+        point.cmpge(self.position).all() && point.cmplt(max).all()
+    }
+}
+
+/// A connected monitor.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MonitorInfo {
+    pub(crate) name: Option<String>,
+    pub(crate) area: MonitorArea,
+    pub(crate) primary: bool,
+}
+
+/// The connected monitors, which winit knows once it has created a window.
+#[derive(SystemParam)]
+pub(crate) struct Monitors<'w> {
+    winit_windows: Option<NonSend<'w, WinitWindows>>,
+}
+
+impl Monitors<'_> {
+    pub(crate) fn all(&self) -> Vec<MonitorInfo> {
+        let Some(window) = self
+            .winit_windows
+            .as_ref()
+            .and_then(|winit_windows| winit_windows.windows.values().next())
+        else {
+            return Vec::new();
+        };
+        let primary = window.primary_monitor();
+        window
+            .available_monitors()
+            .map(|monitor| MonitorInfo {
+                name: monitor.name(),
+                area: MonitorArea::from(&monitor),
+                primary: primary.as_ref() == Some(&monitor),
+            })
+            .collect()
+    }
+}
+
+impl WindowPlacement {
+    /// The logical size and absolute physical position to put the window at on `monitor`,
+    /// shrunk and moved as needed to fit on it entirely.
+    pub(crate) fn fitted(&self, monitor: MonitorArea) -> (Option<UVec2>, IVec2) {
+        let logical_monitor = (monitor.size.as_vec2() / monitor.scale_factor).as_uvec2();
+        let size = self
+            .size
+            .map(|size| size.min(logical_monitor).max(UVec2::ONE));
+        let physical_size = size.map_or(IVec2::ZERO, |size| {
+            (size.as_vec2() * monitor.scale_factor).as_ivec2()
+        });
+        let room = (monitor.size.as_ivec2() - physical_size).max(IVec2::ZERO);
+        let offset = self.position.unwrap_or(room / 2).clamp(IVec2::ZERO, room);
+        (size, monitor.position + offset)
+    }
+}
+
+/// Marks that the remembered placement was applied, after which changes are remembered.
+#[derive(Resource)]
+struct PlacementRestored;
+
+/// Seconds the window has to stay put before its placement is saved,
+/// so dragging it around doesn't write the settings every frame.
+const SETTLE_SECONDS: f32 = 0.5;
+
+fn restore_window_placement(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    monitors: Monitors,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    // Monitors are only known once the event loop is running.
+    let monitors = monitors.all();
+    if monitors.is_empty() {
+        return;
+    }
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+    commands.insert_resource(PlacementRestored);
+    // Monitors weren't known yet when the display settings were first applied, and going
+    // fullscreen on the chosen one leaves the windowed placement out of it.
+    let choices = MonitorChoice::all(&monitors);
+    if let Some(monitor) = settings.display.monitor.find(&choices) {
+        if settings.display.fullscreen.is_on() {
+            go_fullscreen_on(&mut commands, &mut window, monitor);
+            return;
+        }
+    }
+    let placement = &settings.window;
+    if placement == &WindowPlacement::default() {
+        return;
+    }
+    let named = monitors
+        .iter()
+        .find(|monitor| placement.monitor.is_some() && monitor.name == placement.monitor);
+    let Some(monitor) = named
+        .or_else(|| monitors.iter().find(|monitor| monitor.primary))
+        .or_else(|| monitors.first())
+    else {
+        return;
+    };
+    let (size, position) = placement.fitted(monitor.area);
+    info!("Restoring the window at {position} on {:?}.", monitor.name);
+    if window.mode == WindowMode::Windowed {
+        if let Some(size) = size {
+            window.resolution.set(size.x as f32, size.y as f32);
+        }
+    }
+    // Also decides which monitor fullscreen goes to, without a chosen one.
+    window.position = WindowPosition::At(position);
+}
+
+fn remember_window_placement(
+    time: Res<Time<Real>>,
+    mut moved_events: EventReader<WindowMoved>,
+    mut resized_events: EventReader<WindowResized>,
+    mut unsettled: Local<Option<f32>>,
+    settings: ResMut<GameSettings>,
+    monitors: Monitors,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    if moved_events.read().count() + resized_events.read().count() > 0 {
+        *unsettled = Some(0.0);
+    }
+    let Some(seconds) = unsettled.as_mut() else {
+        return;
+    };
+    *seconds += time.delta_seconds();
+    if *seconds < SETTLE_SECONDS {
+        return;
+    }
+    *unsettled = None;
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    // Fullscreen windows cover their monitor, which says nothing about the windowed placement.
+    let WindowPosition::At(position) = window.position else {
+        return;
+    };
+    if window.mode != WindowMode::Windowed {
+        return;
+    }
+    let center = position + window.physical_size().as_ivec2() / 2;
+    let monitor = monitors
+        .all()
+        .into_iter()
+        .find(|monitor| monitor.area.contains(center));
+    let placement = WindowPlacement {
+        size: Some(
+            Vec2::new(window.width(), window.height())
+                .round()
+                .as_uvec2(),
+        ),
+        position: monitor
+            .as_ref()
+            .map(|monitor| position - monitor.area.position),
+        monitor: monitor.and_then(|monitor| monitor.name),
+    };
+    // Only writes the settings when something changed.
+    settings
+        .map_unchanged(|settings| &mut settings.window)
+        .set_if_neq(placement);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_placement_is_kept_on_the_monitor() {
+        let monitor = MonitorArea {
+            position: IVec2::new(1920, 0),
+            size: UVec2::new(2560, 1440),
+            scale_factor: 2.0,
+        };
+        let placement = WindowPlacement {
+            size: Some(UVec2::new(1000, 600)),
+            position: Some(IVec2::new(100, 50)),
+            monitor: Some("Second".to_string()),
+        };
+        assert_eq!(
+            placement.fitted(monitor),
+            (Some(UVec2::new(1000, 600)), IVec2::new(2020, 50))
+        );
+
+        // Too big for the monitor, and partly off of it.
+        let placement = WindowPlacement {
+            size: Some(UVec2::new(1600, 900)),
+            position: Some(IVec2::new(-300, 2000)),
+            monitor: None,
+        };
+        assert_eq!(
+            placement.fitted(monitor),
+            (Some(UVec2::new(1280, 720)), IVec2::new(1920, 0))
+        );
+
+        // Without a position, the window is centered.
+        let placement = WindowPlacement {
+            size: Some(UVec2::new(640, 360)),
+            ..default()
+        };
+        assert_eq!(placement.fitted(monitor).1, IVec2::new(1920 + 640, 360));
+    }
+}